@@ -9,6 +9,7 @@ pub(crate) mod new;
 pub(crate) mod package;
 pub(crate) mod run;
 pub(crate) mod schema;
+pub(crate) mod schema_diff;
 pub(crate) mod start;
 pub(crate) mod status;
 pub(crate) mod stop;
@@ -3,12 +3,14 @@
 
 use crate::commands::get::get_property;
 use pgx_utils::{
-    categorize_type, exit_with_error, get_named_capture, handle_result, CategorizedType, ExternArgs,
+    categorize_type, exit_with_error, get_named_capture, handle_result, parse_composite_field_args,
+    parse_requires_list, unquote_string_literal, validate_extern_attributes, CategorizedType,
+    ExternArgs,
 };
 use proc_macro2::{Ident, Span, TokenTree};
 use quote::quote;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::DirEntry;
 use std::io::{BufRead, Write};
 use std::ops::Deref;
@@ -29,6 +31,8 @@ enum OperatorOptions {
     Join(String),
     Hashes,
     Merges,
+    /// `(index access method, strategy number)` from `#[opclass_operator]`
+    OpClass(String, String),
 }
 
 #[derive(Debug)]
@@ -38,12 +42,128 @@ enum CategorizedAttribute {
     RustTest(Span),
     PgExtern((Span, HashSet<ExternArgs>)),
     PgOperator(Span, HashSet<ExternArgs>, Vec<OperatorOptions>),
+    PgProc((Span, HashSet<ExternArgs>)),
+    PgEventTrigger(Span),
+    /// `(index access method, support function number)` from `#[opclass_function]`
+    OpClassFunction(String, String),
     Sql(Vec<String>),
     SqlFunctionName(String),
     SqlFunctionArgs(String),
     Other(Vec<(Span, String)>),
 }
 
+/// The SQL statements generated for (or provided by) a single item, along with the names of the
+/// items that must be created before it
+#[derive(Debug, Default)]
+struct SqlEntity {
+    name: Option<String>,
+    requires: Vec<String>,
+    /// the Rust type names this item's signature uses, which become requirements if they turn out
+    /// to be types this extension creates
+    references: Vec<String>,
+    is_type: bool,
+    finalize: bool,
+    statements: Vec<String>,
+}
+
+/// The members of a `CREATE OPERATOR CLASS` statement, collected from the `#[opclass_operator]`
+/// and `#[opclass_function]` attributes of the operators and functions for a type
+#[derive(Debug, Default)]
+struct OpClass {
+    method: String,
+    type_name: String,
+    members: Vec<String>,
+    requires: Vec<String>,
+}
+
+impl OpClass {
+    /// The operator class for `type_name` using the index access method `method`
+    fn find<'a>(opclasses: &'a mut Vec<OpClass>, method: &str, type_name: &str) -> &'a mut OpClass {
+        match opclasses
+            .iter()
+            .position(|opclass| opclass.method == method && opclass.type_name == type_name)
+        {
+            Some(idx) => &mut opclasses[idx],
+            None => {
+                opclasses.push(OpClass {
+                    method: method.to_string(),
+                    type_name: type_name.to_string(),
+                    ..Default::default()
+                });
+                opclasses.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// The `CREATE OPERATOR CLASS` statement, which names the class `<type>_<method>_ops` and
+    /// makes it the default for the type
+    fn into_entity(self, schema: &str) -> SqlEntity {
+        let type_name = self
+            .type_name
+            .rsplit('.')
+            .next()
+            .unwrap()
+            .trim_matches('"')
+            .to_lowercase();
+        let name = format!("{}_{}_ops", type_name, self.method);
+
+        SqlEntity {
+            requires: self.requires,
+            statements: vec![format!(
+                "CREATE OPERATOR CLASS {} DEFAULT FOR TYPE {} USING {} AS\n   {};",
+                qualify_name(schema, &name),
+                self.type_name,
+                self.method,
+                self.members.join(",\n   ")
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds the `CREATE TYPE ... AS (...)` statement for a `#[composite]` type, whose attributes
+/// are declared in the order they're added
+struct CompositeType {
+    name: String,
+    attributes: Vec<(String, String)>,
+}
+
+impl CompositeType {
+    fn new(name: String) -> Self {
+        CompositeType {
+            name,
+            attributes: Vec::new(),
+        }
+    }
+
+    fn attribute(&mut self, name: String, sql_type: String) -> &mut Self {
+        self.attributes.push((name, sql_type));
+        self
+    }
+
+    fn to_sql(&self) -> String {
+        format!(
+            "CREATE TYPE {} AS (\n{}\n);",
+            self.name,
+            self.attributes
+                .iter()
+                .map(|(name, sql_type)| format!("    {} {}", name, sql_type))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        )
+    }
+}
+
+impl SqlEntity {
+    fn new(name: Option<String>, statements: Vec<String>) -> Self {
+        SqlEntity {
+            name,
+            statements,
+            ..Default::default()
+        }
+    }
+}
+
 pub(crate) fn generate_schema() -> Result<(), std::io::Error> {
     let path = PathBuf::from_str("./src").unwrap();
     let files = find_rs_files(&path, Vec::new());
@@ -51,26 +171,186 @@ pub(crate) fn generate_schema() -> Result<(), std::io::Error> {
 
     delete_generated_sql();
 
-    let mut created = Vec::new();
+    let mut generated = Vec::new();
+    let mut finalizers = Vec::new();
+    let mut defined_in = HashMap::<String, Vec<String>>::new();
     files.iter().for_each(|f: &DirEntry| {
-        let statemets = generate_sql(f, default_schema.clone());
-        let (did_write, filename) = write_sql_file(f, statemets);
+        let filename = make_sql_filename(f);
+        let (entities, mut finalize): (Vec<_>, Vec<_>) = generate_sql(f, default_schema.clone())
+            .into_iter()
+            .partition(|entity| !entity.finalize);
+
+        for entity in &entities {
+            if let Some(name) = &entity.name {
+                defined_in
+                    .entry(name.clone())
+                    .or_default()
+                    .push(strip_sql_dir(&filename));
+            }
+        }
+
+        finalizers.append(&mut finalize);
+        generated.push((filename, entities));
+    });
+
+    // functions must be created after the types they use, wherever those types are defined
+    let type_names = generated
+        .iter()
+        .flat_map(|(_, entities)| entities.iter())
+        .chain(finalizers.iter())
+        .filter(|entity| entity.is_type)
+        .filter_map(|entity| entity.name.clone())
+        .collect::<HashSet<_>>();
+    for entity in generated
+        .iter_mut()
+        .flat_map(|(_, entities)| entities.iter_mut())
+        .chain(finalizers.iter_mut())
+    {
+        for referenced in std::mem::take(&mut entity.references) {
+            if type_names.contains(&referenced)
+                && entity.name.as_ref() != Some(&referenced)
+                && !entity.requires.contains(&referenced)
+            {
+                entity.requires.push(referenced);
+            }
+        }
+    }
+
+    let mut created = Vec::new();
+    let mut dependencies = HashMap::new();
+    for (filename, entities) in generated {
+        let (statements, external) = sort_entities(entities, &defined_in);
+        let (did_write, filename) = write_sql_file(filename, statements);
 
         // strip the leading ./sql/ from the filenames we generated
-        let mut filename = filename.display().to_string();
-        filename = filename.trim_start_matches("./sql/").to_string();
+        let filename = strip_sql_dir(&filename);
 
         if did_write {
+            let mut required_files = external
+                .iter()
+                .flat_map(|name| defined_in.get(name).unwrap().iter().cloned())
+                .filter(|required| required != &filename)
+                .collect::<Vec<_>>();
+            required_files.sort();
+            required_files.dedup();
+
+            dependencies.insert(filename.clone(), required_files);
             created.push(filename);
         }
-    });
+    }
+
+    // finalizers, from all files, run after everything else
+    let finalize_filename = PathBuf::from_str(FINALIZE_SQL_FILENAME).unwrap();
+    let (statements, _) = sort_entities(finalizers, &defined_in);
+    let (did_write, _) = write_sql_file(finalize_filename, statements);
 
-    process_schema_load_order(created);
+    process_schema_load_order(created, dependencies, did_write);
 
     Ok(())
 }
 
-fn process_schema_load_order(mut created: Vec<String>) {
+const FINALIZE_SQL_FILENAME: &str = "./sql/pgx-finalize.generated.sql";
+
+fn strip_sql_dir(filename: &PathBuf) -> String {
+    filename
+        .display()
+        .to_string()
+        .trim_start_matches("./sql/")
+        .to_string()
+}
+
+/// Order `entities` so that each comes after the entities it requires, otherwise preserving their
+/// declared order, and flatten them into a list of statements.
+///
+/// Requirements not satisfied by `entities` must be defined elsewhere (in `defined_in`), and are
+/// returned so the caller can order the files that contain them.
+fn sort_entities(
+    mut entities: Vec<SqlEntity>,
+    defined_in: &HashMap<String, Vec<String>>,
+) -> (Vec<String>, Vec<String>) {
+    let local = entities
+        .iter()
+        .filter_map(|entity| entity.name.clone())
+        .collect::<HashSet<_>>();
+    let mut external = Vec::new();
+    for entity in &entities {
+        for required in &entity.requires {
+            if !local.contains(required) {
+                if !defined_in.contains_key(required) {
+                    exit_with_error!(
+                        "{} requires `{}`, which isn't defined by this extension",
+                        entity.name.as_deref().unwrap_or("extension_sql!()"),
+                        required
+                    );
+                }
+                external.push(required.clone());
+            }
+        }
+    }
+
+    let mut created = HashSet::new();
+    let mut statements = Vec::new();
+    while !entities.is_empty() {
+        let ready = entities.iter().position(|entity| {
+            entity
+                .requires
+                .iter()
+                .all(|required| created.contains(required) || !local.contains(required))
+        });
+
+        match ready {
+            Some(idx) => {
+                let mut entity = entities.remove(idx);
+                if let Some(name) = entity.name {
+                    created.insert(name);
+                }
+                statements.append(&mut entity.statements);
+            }
+            None => exit_with_error!(
+                "circular requirements between: {}",
+                find_cycle(&entities).join(" -> ")
+            ),
+        }
+    }
+
+    (statements, external)
+}
+
+/// Find the names of the entities that form a requirement cycle, given entities that are all
+/// blocked waiting on each other.  The first name is repeated at the end, to close the cycle.
+fn find_cycle(blocked: &[SqlEntity]) -> Vec<String> {
+    let by_name = blocked
+        .iter()
+        .filter_map(|entity| entity.name.as_ref().map(|name| (name.clone(), entity)))
+        .collect::<HashMap<_, _>>();
+
+    // every blocked entity requires another blocked entity, so following those requirements from
+    // anywhere must eventually revisit one of them
+    let mut path = Vec::<String>::new();
+    let mut current = &blocked[0];
+    loop {
+        let next = current
+            .requires
+            .iter()
+            .find(|required| by_name.contains_key(*required))
+            .expect("blocked entity has no blocked requirements");
+
+        if let Some(start) = path.iter().position(|name| name == next) {
+            let mut cycle = path.split_off(start);
+            cycle.push(next.clone());
+            return cycle;
+        }
+
+        path.push(next.clone());
+        current = by_name[next];
+    }
+}
+
+fn process_schema_load_order(
+    mut created: Vec<String>,
+    dependencies: HashMap<String, Vec<String>>,
+    has_finalize: bool,
+) {
     let filename = PathBuf::from_str("./sql/load-order.txt").unwrap();
     let mut load_order = read_load_order(&filename);
 
@@ -87,6 +367,25 @@ fn process_schema_load_order(mut created: Vec<String>) {
     created.sort();
     load_order.append(&mut created);
 
+    // move files after the files whose items they require
+    let mut moves = 0;
+    while let Some((idx, after)) = find_misordered(&load_order, &dependencies) {
+        moves += 1;
+        if moves > load_order.len() * load_order.len() {
+            exit_with_error!("circular requirements between generated sql files");
+        }
+
+        let file = load_order.remove(idx);
+        load_order.insert(after, file);
+    }
+
+    // and the finalize file always goes last
+    let finalize_filename = strip_sql_dir(&PathBuf::from_str(FINALIZE_SQL_FILENAME).unwrap());
+    load_order.retain(|v| v != &finalize_filename);
+    if has_finalize {
+        load_order.push(finalize_filename);
+    }
+
     // rewrite the load_order file
     let mut file = handle_result!(
         format!("failed to create {}", filename.display()),
@@ -106,6 +405,30 @@ fn process_schema_load_order(mut created: Vec<String>) {
     });
 }
 
+/// Find a file in `load_order` that appears before a file it depends on, returning its index and
+/// the index it should be moved to
+fn find_misordered(
+    load_order: &[String],
+    dependencies: &HashMap<String, Vec<String>>,
+) -> Option<(usize, usize)> {
+    for (idx, file) in load_order.iter().enumerate() {
+        if let Some(required_files) = dependencies.get(file) {
+            let last = required_files
+                .iter()
+                .filter_map(|required| load_order.iter().position(|v| v == required))
+                .max();
+
+            if let Some(last) = last {
+                if last > idx {
+                    return Some((idx, last));
+                }
+            }
+        }
+    }
+
+    None
+}
+
 pub(crate) fn read_load_order(filename: &PathBuf) -> Vec<String> {
     let mut load_order = Vec::new();
 
@@ -119,9 +442,7 @@ pub(crate) fn read_load_order(filename: &PathBuf) -> Vec<String> {
     load_order
 }
 
-fn write_sql_file(f: &DirEntry, statements: Vec<String>) -> (bool, PathBuf) {
-    let filename = make_sql_filename(f);
-
+fn write_sql_file(filename: PathBuf, statements: Vec<String>) -> (bool, PathBuf) {
     if statements.is_empty() {
         // delete existing sql file if it exists
         if filename.exists() {
@@ -211,36 +532,96 @@ fn parse_extern_args(att: &Attribute) -> HashSet<ExternArgs> {
     pgx_utils::parse_extern_attributes(att.tokens.clone())
 }
 
-fn generate_sql(rs_file: &DirEntry, default_schema: String) -> Vec<String> {
+fn generate_sql(rs_file: &DirEntry, default_schema: String) -> Vec<SqlEntity> {
     let mut sql = Vec::new();
     let file = std::fs::read_to_string(rs_file.path()).unwrap();
     let ast = syn::parse_file(file.as_str()).unwrap();
 
     let mut schema_stack = Vec::new();
+    let mut type_schemas = HashMap::new();
 
     schema_stack.push(default_schema.clone());
+    collect_type_schemas(&ast.items, &default_schema, None, &mut type_schemas);
     walk_items(
         rs_file,
         &mut sql,
         ast.items,
         &mut schema_stack,
         &default_schema,
+        &type_schemas,
     );
 
     sql
 }
 
+/// Find the `#[derive(PostgresType)]` and `#[derive(PostgresEnum)]` types declared in modules,
+/// which are created in the module's schema, so references to them can be schema-qualified
+fn collect_type_schemas(
+    items: &[Item],
+    default_schema: &str,
+    schema: Option<&str>,
+    type_schemas: &mut HashMap<String, String>,
+) {
+    let is_postgres_type = |attrs: &[Attribute]| {
+        attrs.iter().any(|a| {
+            let string = a.to_token_stream().to_string();
+            string.contains("PostgresType") || string.contains("PostgresEnum")
+        })
+    };
+
+    for item in items {
+        let ident = match item {
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    let schema = module.ident.to_string();
+                    collect_type_schemas(items, default_schema, Some(&schema), type_schemas);
+                }
+                continue;
+            }
+            Item::Struct(strct) if is_postgres_type(&strct.attrs) => &strct.ident,
+            Item::Enum(enm) if is_postgres_type(&enm.attrs) => &enm.ident,
+            _ => continue,
+        };
+
+        if let Some(schema) = schema.filter(|schema| *schema != default_schema) {
+            type_schemas.insert(ident.to_string(), schema.to_string());
+        }
+    }
+}
+
+/// Qualify the name of a type from `collect_type_schemas()` with its schema, including when
+/// it's an array or a `SETOF`
+fn qualify_type(type_name: String, type_schemas: &HashMap<String, String>) -> String {
+    let (prefix, rest) = match type_name.strip_prefix("SETOF ") {
+        Some(rest) => ("SETOF ", rest),
+        None => ("", type_name.as_str()),
+    };
+    let base = rest.trim_end_matches("[]");
+
+    match type_schemas.get(base) {
+        Some(schema) => format!(
+            "{}{}{}",
+            prefix,
+            qualify_name(schema, base),
+            &rest[base.len()..]
+        ),
+        None => type_name,
+    }
+}
+
 #[allow(clippy::cognitive_complexity)]
 fn walk_items(
     rs_file: &DirEntry,
-    sql: &mut Vec<String>,
+    sql: &mut Vec<SqlEntity>,
     items: Vec<Item>,
     schema_stack: &mut Vec<String>,
     default_schema: &str,
+    type_schemas: &HashMap<String, String>,
 ) {
     let statement_cnt = sql.len();
     let mut postgres_types = Vec::new();
     let mut operator_sql = Vec::new();
+    let mut opclasses = Vec::new();
     let current_schema = schema_stack
         .last()
         .expect("couldn't determine the current schema")
@@ -249,26 +630,74 @@ fn walk_items(
         if let Item::Mod(module) = item {
             if let Some((_, items)) = module.content {
                 schema_stack.push(module.ident.to_string());
-                walk_items(rs_file, sql, items, schema_stack, default_schema);
+                walk_items(
+                    rs_file,
+                    sql,
+                    items,
+                    schema_stack,
+                    default_schema,
+                    type_schemas,
+                );
                 schema_stack.pop();
             }
         } else if let Item::Struct(strct) = item {
             let mut found_postgres_type = false;
-            for a in strct.attrs {
+            let mut is_composite = false;
+            for a in &strct.attrs {
                 let string = a.to_token_stream().to_string();
 
                 if string.contains("PostgresType") {
                     found_postgres_type = true;
+                } else if a.path.is_ident("composite") {
+                    is_composite = true;
                 }
             }
 
-            if found_postgres_type {
+            if found_postgres_type && is_composite {
                 let name = strct.ident.to_string().to_lowercase();
-                sql.push(format!("CREATE TYPE {}.{};", current_schema, name));
+                let mut composite = CompositeType::new(qualify_name(&current_schema, &name));
+                let mut references = Vec::new();
+                for field in &strct.fields {
+                    let field_name = match &field.ident {
+                        Some(ident) => ident.to_string(),
+                        None => exit_with_error!("#[composite] types must have named fields"),
+                    };
+                    let args = match parse_composite_field_args(field) {
+                        Ok(args) => args,
+                        Err(e) => exit_with_error!("{}.{}: {}", strct.ident, field_name, e),
+                    };
+                    let type_name = match (args.sql_type, translate_type(rs_file, &field.ty)) {
+                        (Some(sql_type), _) => sql_type,
+                        (None, Some((type_name, _, _, _))) => qualify_type(type_name, type_schemas),
+                        (None, None) => exit_with_error!(
+                            "{}.{} has a type that can't be used in a composite type",
+                            strct.ident,
+                            field_name
+                        ),
+                    };
+
+                    collect_idents(field.ty.to_token_stream(), &mut references);
+                    composite.attribute(field_name, type_name);
+                }
 
-                postgres_types.push(format!("CREATE OR REPLACE FUNCTION {qualified_name}_in(cstring) RETURNS {qualified_name} IMMUTABLE STRICT LANGUAGE C AS 'MODULE_PATHNAME', '{name}_in_wrapper';", qualified_name = qualify_name(&current_schema, &name), name = name));
-                postgres_types.push(format!("CREATE OR REPLACE FUNCTION {qualified_name}_out({qualified_name}) RETURNS cstring IMMUTABLE STRICT LANGUAGE C AS 'MODULE_PATHNAME', '{name}_out_wrapper';", qualified_name = qualify_name(&current_schema, &name), name = name));
-                postgres_types.push(format!(
+                postgres_types.push(SqlEntity {
+                    name: Some(strct.ident.to_string()),
+                    references,
+                    is_type: true,
+                    statements: vec![composite.to_sql()],
+                    ..Default::default()
+                });
+            } else if found_postgres_type {
+                let name = strct.ident.to_string().to_lowercase();
+                sql.push(SqlEntity::new(
+                    None,
+                    vec![format!("CREATE TYPE {}.{};", current_schema, name)],
+                ));
+
+                let mut type_sql = Vec::new();
+                type_sql.push(format!("CREATE OR REPLACE FUNCTION {qualified_name}_in(cstring) RETURNS {qualified_name} IMMUTABLE STRICT LANGUAGE C AS 'MODULE_PATHNAME', '{name}_in_wrapper';", qualified_name = qualify_name(&current_schema, &name), name = name));
+                type_sql.push(format!("CREATE OR REPLACE FUNCTION {qualified_name}_out({qualified_name}) RETURNS cstring IMMUTABLE STRICT LANGUAGE C AS 'MODULE_PATHNAME', '{name}_out_wrapper';", qualified_name = qualify_name(&current_schema, &name), name = name));
+                type_sql.push(format!(
                     "CREATE TYPE {qualified_name} (
                         INTERNALLENGTH = variable,
                         INPUT = {qualified_name}_in,
@@ -277,6 +706,12 @@ fn walk_items(
                     );",
                     qualified_name = qualify_name(&current_schema, &name)
                 ));
+                postgres_types.push(SqlEntity {
+                    name: Some(strct.ident.to_string()),
+                    is_type: true,
+                    statements: type_sql,
+                    ..Default::default()
+                });
             }
         } else if let Item::Enum(enm) = item {
             let mut found_postgres_enum = false;
@@ -290,7 +725,8 @@ fn walk_items(
 
             if found_postgres_enum {
                 let name = enm.ident.to_string().to_lowercase();
-                sql.push(format!(
+                let mut enum_sql = Vec::new();
+                enum_sql.push(format!(
                     "CREATE TYPE {qualified_name} AS ENUM (",
                     qualified_name = qualify_name(&current_schema, &name)
                 ));
@@ -302,9 +738,15 @@ fn walk_items(
                         line.push(',');
                     }
 
-                    sql.push(line);
+                    enum_sql.push(line);
                 }
-                sql.push(");".to_string());
+                enum_sql.push(");".to_string());
+                sql.push(SqlEntity {
+                    name: Some(enm.ident.to_string()),
+                    is_type: true,
+                    statements: enum_sql,
+                    ..Default::default()
+                });
             }
         } else if let Item::Macro(makro) = item {
             let name = match makro.mac.path.get_ident() {
@@ -313,29 +755,25 @@ fn walk_items(
             };
 
             if name.ends_with("extension_sql") {
-                let string = makro.mac.tokens.to_string();
-                let string = string.trim();
-
-                if !string.starts_with("r#\"") || !string.ends_with("\"#") {
-                    exit_with_error!("extension_sql!{{}} value isn't ia raw string");
-                }
-
-                // remove the raw string quotes
-                let string = string.trim_start_matches("r#\"");
-                let string = string.trim_end_matches("\"#");
-
-                // trim off leading/trailing new lines, but preserve other whitespace
-                let string = string.trim_start_matches('\n');
-                let string = string.trim_end_matches('\n');
-
-                // and remember this sql block
-                sql.push(string.to_string());
+                sql.push(parse_extension_sql(rs_file, makro.mac.tokens, false));
+            } else if name.ends_with("extension_sql_file") {
+                sql.push(parse_extension_sql(rs_file, makro.mac.tokens, true));
             }
         } else if let Item::Fn(func) = item {
             let attributes = collect_attributes(rs_file, &func.sig.ident, &func.attrs);
             let is_test_mode = std::env::var("PGX_TEST_MODE_VERSION").is_ok();
             let mut function_sql = Vec::new();
+            let mut requires = Vec::new();
             let sql_func_args = extract_funcargs_attribute(&attributes);
+            let opclass_functions = attributes
+                .iter()
+                .filter_map(|attribute| match attribute {
+                    CategorizedAttribute::OpClassFunction(method, number) => {
+                        Some((method.clone(), number.clone()))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
 
             for attribute in attributes {
                 match attribute {
@@ -349,6 +787,7 @@ fn walk_items(
                             rs_file,
                             None,
                             &current_schema,
+                            type_schemas,
                         ) {
                             function_sql.push(location_comment(rs_file, &span));
                             function_sql.push(statement);
@@ -358,15 +797,40 @@ fn walk_items(
                     // for #[pg_extern] attributes, we only want to programatically generate
                     // a CREATE FUNCTION statement if we don't already have some
                     CategorizedAttribute::PgExtern((span, args)) if function_sql.is_empty() => {
-                        if let (Some(statement), _, _) = make_create_function_statement(
-                            &func,
-                            Some(args),
-                            rs_file,
-                            sql_func_args.clone(),
-                            &current_schema,
-                        ) {
+                        requires.append(&mut extract_requires_arg(&args));
+
+                        // unless the user provided the complete statement themselves
+                        if let Some(statement) = extract_sql_arg(&args, &func.sig.ident) {
                             function_sql.push(location_comment(rs_file, &span));
                             function_sql.push(statement);
+                        } else if let (Some(statement), Some(func_name), Some(type_names)) =
+                            make_create_function_statement(
+                                &func,
+                                Some(args),
+                                rs_file,
+                                sql_func_args.clone(),
+                                &current_schema,
+                                type_schemas,
+                            )
+                        {
+                            function_sql.push(location_comment(rs_file, &span));
+                            function_sql.push(statement);
+
+                            for (method, number) in &opclass_functions {
+                                let type_name = type_names.get(0).unwrap_or_else(|| {
+                                    exit_with_error!(
+                                        "#[opclass_function] functions must have an argument of the operator class's type"
+                                    )
+                                });
+                                let opclass = OpClass::find(&mut opclasses, method, type_name);
+                                opclass.members.push(format!(
+                                    "FUNCTION {} {}({})",
+                                    number,
+                                    qualify_name(&current_schema, &func_name),
+                                    type_names.join(", ")
+                                ));
+                                opclass.requires.push(func.sig.ident.to_string());
+                            }
                         }
                     }
 
@@ -375,6 +839,8 @@ fn walk_items(
                     CategorizedAttribute::PgOperator(span, args, options)
                         if function_sql.is_empty() =>
                     {
+                        requires.append(&mut extract_requires_arg(&args));
+
                         if let (Some(statement), Some(func_name), Some(type_names)) =
                             make_create_function_statement(
                                 &func,
@@ -382,6 +848,7 @@ fn walk_items(
                                 rs_file,
                                 sql_func_args.clone(),
                                 &current_schema,
+                                type_schemas,
                             )
                         {
                             if type_names.len() > 2 {
@@ -408,9 +875,10 @@ fn walk_items(
                                 )
                             }
 
+                            let qualified_opname = qualify_name(&current_schema, &name.unwrap());
                             let mut sql = String::new();
                             sql.push_str("CREATE OPERATOR ");
-                            sql.push_str(&qualify_name(&current_schema, &name.unwrap()));
+                            sql.push_str(&qualified_opname);
                             sql.push_str(" (");
                             sql.push_str("\n   FUNCTION=");
                             sql.push_str(&qualify_name(&current_schema, &func_name));
@@ -444,16 +912,63 @@ fn walk_items(
                                     }
                                     OperatorOptions::Hashes => sql.push_str(",\n   HASHES"),
                                     OperatorOptions::Merges => sql.push_str(",\n   MERGES"),
+                                    OperatorOptions::OpClass(method, strategy) => {
+                                        if type_names.len() != 2 {
+                                            exit_with_error!(
+                                                "#[opclass_operator] requires an operator with 2 arguments"
+                                            )
+                                        }
+
+                                        let opclass =
+                                            OpClass::find(&mut opclasses, &method, &type_names[0]);
+                                        opclass.members.push(format!(
+                                            "OPERATOR {} {}",
+                                            strategy, qualified_opname
+                                        ));
+                                        opclass.requires.push(func.sig.ident.to_string());
+                                    }
                                     _ => {}
                                 }
                             }
 
                             sql.push_str("\n);");
 
-                            operator_sql.push(sql);
+                            operator_sql.push(SqlEntity {
+                                requires: vec![func.sig.ident.to_string()],
+                                statements: vec![sql],
+                                ..Default::default()
+                            });
                         }
                     }
 
+                    // #[pg_proc] functions are procedures, which don't return anything
+                    CategorizedAttribute::PgProc((span, args)) if function_sql.is_empty() => {
+                        requires.append(&mut extract_requires_arg(&args));
+
+                        let statement =
+                            extract_sql_arg(&args, &func.sig.ident).unwrap_or_else(|| {
+                                make_create_procedure_statement(
+                                    &func,
+                                    rs_file,
+                                    sql_func_args.clone(),
+                                    &current_schema,
+                                    type_schemas,
+                                )
+                            });
+                        function_sql.push(location_comment(rs_file, &span));
+                        function_sql.push(statement);
+                    }
+
+                    // #[pg_eventtrigger] functions take no SQL arguments and return `event_trigger`
+                    CategorizedAttribute::PgEventTrigger(span) if function_sql.is_empty() => {
+                        function_sql.push(location_comment(rs_file, &span));
+                        function_sql.push(format!(
+                            "CREATE OR REPLACE FUNCTION {}() RETURNS event_trigger LANGUAGE c AS 'MODULE_PATHNAME', '{}_wrapper';",
+                            qualify_name(&current_schema, &quote_ident(&func.sig.ident)),
+                            func.sig.ident
+                        ));
+                    }
+
                     // it's user-provided SQL from doc comment blocks
                     CategorizedAttribute::Sql(mut sql_lines) => function_sql.append(&mut sql_lines),
 
@@ -462,12 +977,23 @@ fn walk_items(
                 }
             }
 
-            sql.append(&mut function_sql);
+            if !function_sql.is_empty() {
+                sql.push(SqlEntity {
+                    name: Some(func.sig.ident.to_string()),
+                    requires,
+                    references: referenced_type_names(&func),
+                    statements: function_sql,
+                    ..Default::default()
+                });
+            }
         }
     }
 
     sql.append(&mut postgres_types);
     sql.append(&mut operator_sql);
+    for opclass in opclasses {
+        sql.push(opclass.into_entity(&current_schema));
+    }
 
     if sql.len() != statement_cnt {
         // we added some statements, so inject a CREATE SCHEMA statement ahead of the statements
@@ -477,9 +1003,12 @@ fn walk_items(
             if current_schema != "pg_catalog" {
                 sql.insert(
                     statement_cnt,
-                    format!(
-                        "CREATE SCHEMA IF NOT EXISTS {};",
-                        quote_ident_string(current_schema)
+                    SqlEntity::new(
+                        None,
+                        vec![format!(
+                            "CREATE SCHEMA IF NOT EXISTS {};",
+                            quote_ident_string(current_schema)
+                        )],
                     ),
                 );
             }
@@ -487,6 +1016,107 @@ fn walk_items(
     }
 }
 
+/// Parse the tokens of an `extension_sql!` invocation, or if `from_file`, an
+/// `extension_sql_file!` invocation
+fn parse_extension_sql(rs_file: &DirEntry, tokens: TokenStream2, from_file: bool) -> SqlEntity {
+    let mut itr = tokens.into_iter();
+    let literal = match itr.next() {
+        Some(TokenTree::Literal(literal)) => literal.to_string(),
+        _ => exit_with_error!("extension_sql!() must start with a string literal"),
+    };
+
+    let sql = if from_file {
+        let path = rs_file
+            .path()
+            .parent()
+            .expect("source file has no parent directory")
+            .join(unquote_string_literal(&literal));
+        handle_result!(
+            format!("failed to read {}", path.display()),
+            std::fs::read_to_string(&path)
+        )
+    } else {
+        if !literal.starts_with("r#\"") || !literal.ends_with("\"#") {
+            exit_with_error!("extension_sql!() value isn't a raw string");
+        }
+        unquote_string_literal(&literal)
+    };
+
+    // trim off leading/trailing new lines, but preserve other whitespace
+    let sql = sql.trim_start_matches('\n').trim_end_matches('\n');
+    let mut entity = SqlEntity::new(None, vec![sql.to_string()]);
+
+    // and then the ordering hints
+    while let Some(token) = itr.next() {
+        match token {
+            TokenTree::Punct(_) => {}
+            TokenTree::Ident(ident) => match ident.to_string().as_str() {
+                "name" => match (itr.next(), itr.next()) {
+                    (_, Some(TokenTree::Literal(name))) => {
+                        entity.name = Some(unquote_string_literal(&name.to_string()))
+                    }
+                    _ => exit_with_error!("extension_sql!() `name` must be a string"),
+                },
+                "requires" => match (itr.next(), itr.next()) {
+                    (_, Some(TokenTree::Group(group))) => {
+                        entity.requires = parse_requires_list(group.stream())
+                    }
+                    _ => exit_with_error!("extension_sql!() `requires` must be a list"),
+                },
+                "finalize" => entity.finalize = true,
+                other => exit_with_error!("unrecognized extension_sql!() option: {}", other),
+            },
+            other => exit_with_error!("unexpected extension_sql!() token: {}", other),
+        }
+    }
+
+    entity
+}
+
+/// The items named by `#[pg_extern(requires = [...])]`, if any
+fn extract_requires_arg(args: &HashSet<ExternArgs>) -> Vec<String> {
+    args.iter()
+        .find_map(|arg| match arg {
+            ExternArgs::Requires(requires) => Some(requires.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Every identifier used in the argument and return types of `func`, which includes the names of
+/// any types it references, such as `MyType` in `Option<PgBox<MyType>>`
+fn referenced_type_names(func: &ItemFn) -> Vec<String> {
+    let mut names = Vec::new();
+    for arg in &func.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            collect_idents(pat_type.ty.to_token_stream(), &mut names);
+        }
+    }
+    if let ReturnType::Type(_, ty) = &func.sig.output {
+        collect_idents(ty.to_token_stream(), &mut names);
+    }
+    names
+}
+
+/// Collect every identifier in `tokens`, including those nested in groups
+fn collect_idents(tokens: TokenStream2, names: &mut Vec<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => names.push(ident.to_string()),
+            TokenTree::Group(group) => collect_idents(group.stream(), names),
+            _ => {}
+        }
+    }
+}
+
+/// The complete `CREATE FUNCTION` statement from `#[pg_extern(sql = "...")]`, if provided
+fn extract_sql_arg(args: &HashSet<ExternArgs>, ident: &Ident) -> Option<String> {
+    args.iter().find_map(|arg| match arg {
+        ExternArgs::Sql(sql) => Some(sql.replace("@FUNCTION_NAME@", &format!("{}_wrapper", ident))),
+        _ => None,
+    })
+}
+
 fn qualify_name(schema: &str, name: &str) -> String {
     if "public" == schema {
         name.to_owned()
@@ -501,6 +1131,7 @@ fn make_create_function_statement(
     rs_file: &DirEntry,
     sql_func_arg: Option<String>,
     schema: &str,
+    type_schemas: &HashMap<String, String>,
 ) -> (Option<String>, Option<String>, Option<Vec<String>>) {
     let exported_func_name = format!("{}_wrapper", func.sig.ident.to_string());
     let mut statement = String::new();
@@ -510,6 +1141,18 @@ fn make_create_function_statement(
         extract_funcname_attribute(&attributes).unwrap_or_else(|| quote_ident(&func.sig.ident));
     let mut sql_argument_type_names = Vec::new();
 
+    // a function returning a RecordType<(...)> of name!() members returns a single record,
+    // which we describe with OUT parameters
+    let out_params = match &func.sig.output {
+        ReturnType::Type(_, ty) => match categorize_type(ty) {
+            CategorizedType::Record(types) => {
+                Some(named_columns(types, rs_file, &ty.span(), 0, None, false))
+            }
+            _ => None,
+        },
+        ReturnType::Default => None,
+    };
+
     statement.push_str(&format!(
         "CREATE OR REPLACE FUNCTION {}",
         qualify_name(schema, &sql_func_name)
@@ -529,6 +1172,7 @@ fn make_create_function_statement(
                 }
                 FnArg::Typed(ty) => match translate_type(rs_file, &ty.ty) {
                     Some((type_name, _, default_value, variadic)) => {
+                        let type_name = qualify_type(type_name, type_schemas);
                         sql_argument_type_names.push(type_name.to_string());
 
                         if i > 0 {
@@ -542,22 +1186,11 @@ fn make_create_function_statement(
                         }
                         statement.push_str(&type_name);
 
-                        if let Some(mut default_value) = default_value {
-                            default_value = default_value.trim_start_matches('"').to_string();
-                            default_value = default_value.trim_end_matches('"').to_string();
-                            default_value = default_value.trim().to_string();
-                            default_value = default_value.trim_start_matches('\'').to_string();
-                            default_value = default_value.trim_end_matches('\'').to_string();
-
-                            // quote default values, except those that match certain patterns
-                            if !default_value.eq_ignore_ascii_case("NULL")
-                                && !default_value.to_uppercase().starts_with("ARRAY")
-                                && !default_value.contains("(")
-                            {
-                                default_value = format!("'{}'", default_value);
-                            }
-
-                            statement.push_str(&format!(" DEFAULT {}", default_value));
+                        if let Some(default_value) = default_value {
+                            statement.push_str(&format!(
+                                " DEFAULT {}",
+                                sql_default_value(default_value)
+                            ));
                         }
 
                         i += 1;
@@ -578,6 +1211,20 @@ fn make_create_function_statement(
             );
             return (None, None, None);
         }
+
+        if let Some(out_params) = &out_params {
+            for (name, type_name) in out_params {
+                if i > 0 {
+                    statement.push_str(", ");
+                }
+                statement.push_str(&format!(
+                    "OUT {} {}",
+                    name,
+                    qualify_type(type_name.clone(), type_schemas)
+                ));
+                i += 1;
+            }
+        }
         statement.push(')');
     }
 
@@ -591,19 +1238,29 @@ fn make_create_function_statement(
     // append RETURNS clause
     match match &func.sig.output {
         ReturnType::Default => Some(("void".to_string(), false, None, false)),
+        ReturnType::Type(_, _) if out_params.is_some() => {
+            Some(("record".to_string(), false, None, false))
+        }
         ReturnType::Type(_, ty) => translate_type(rs_file, ty),
     } {
-        Some((return_type, _is_option, _, _)) => {
-            statement.push_str(&format!(" RETURNS {}", return_type))
-        }
+        Some((return_type, _is_option, _, _)) => statement.push_str(&format!(
+            " RETURNS {}",
+            qualify_type(return_type, type_schemas)
+        )),
         None => exit_with_error!(
             "could not determine return type for function: {}",
             func.sig.ident
         ),
     }
 
-    // modifiers
+    // modifiers, in a stable order so the generated sql doesn't churn between runs
     if let Some(extern_args) = extern_args {
+        if let Err(message) = validate_extern_attributes(&extern_args) {
+            exit_with_error!("function {} {}", func.sig.ident, message);
+        }
+
+        let mut extern_args = extern_args.into_iter().collect::<Vec<_>>();
+        extern_args.sort();
         for extern_arg in extern_args {
             match extern_arg {
                 ExternArgs::Immutable => statement.push_str(" IMMUTABLE"),
@@ -615,7 +1272,10 @@ fn make_create_function_statement(
                 ExternArgs::ParallelUnsafe => statement.push_str(" PARALLEL UNSAFE"),
                 ExternArgs::ParallelRestricted => statement.push_str(" PARALLEL RESTRICTED"),
                 ExternArgs::Error(_) => { /* noop */ }
+                ExternArgs::Transactional => { /* noop */ }
                 ExternArgs::NoGuard => {}
+                ExternArgs::Sql(_) => { /* noop */ }
+                ExternArgs::Requires(_) => { /* noop */ }
             }
         }
     }
@@ -632,6 +1292,83 @@ fn make_create_function_statement(
     )
 }
 
+/// Quote the value of a `default!()` argument, unless it's NULL, an ARRAY, or a function call
+fn sql_default_value(mut default_value: String) -> String {
+    default_value = default_value.trim_start_matches('"').to_string();
+    default_value = default_value.trim_end_matches('"').to_string();
+    default_value = default_value.trim().to_string();
+    default_value = default_value.trim_start_matches('\'').to_string();
+    default_value = default_value.trim_end_matches('\'').to_string();
+
+    // quote default values, except those that match certain patterns
+    if !default_value.eq_ignore_ascii_case("NULL")
+        && !default_value.to_uppercase().starts_with("ARRAY")
+        && !default_value.contains("(")
+    {
+        default_value = format!("'{}'", default_value);
+    }
+
+    default_value
+}
+
+/// A `CREATE PROCEDURE` statement for a `#[pg_proc]` function, whose `&mut` arguments are
+/// `INOUT` parameters
+fn make_create_procedure_statement(
+    func: &ItemFn,
+    rs_file: &DirEntry,
+    sql_func_arg: Option<String>,
+    schema: &str,
+    type_schemas: &HashMap<String, String>,
+) -> String {
+    let attributes = collect_attributes(rs_file, &func.sig.ident, &func.attrs);
+    let sql_func_name =
+        extract_funcname_attribute(&attributes).unwrap_or_else(|| quote_ident(&func.sig.ident));
+    let mut statement = format!(
+        "CREATE OR REPLACE PROCEDURE {}",
+        qualify_name(schema, &sql_func_name)
+    );
+
+    if let Some(sql_func_arg) = sql_func_arg {
+        statement.push_str(sql_func_arg.as_str());
+    } else {
+        let mut args = Vec::new();
+        for arg in &func.sig.inputs {
+            let ty = match arg {
+                FnArg::Typed(pat_type) => pat_type.ty.deref(),
+                FnArg::Receiver(_) => {
+                    exit_with_error!("functions that take 'self' are not supported")
+                }
+            };
+            let (ty, mode) = match ty {
+                Type::Reference(tref) if tref.mutability.is_some() => (tref.elem.deref(), "INOUT "),
+                _ => (ty, ""),
+            };
+
+            // pg_sys::FunctionCallInfo and FcInfo arguments don't translate to a SQL type
+            if let Some((type_name, _, default_value, variadic)) = translate_type(rs_file, ty) {
+                let mut sql_arg = format!(
+                    "{}{} {}{}",
+                    mode,
+                    arg_name(arg),
+                    if variadic { "VARIADIC " } else { "" },
+                    qualify_type(type_name, type_schemas)
+                );
+                if let Some(default_value) = default_value {
+                    sql_arg.push_str(&format!(" DEFAULT {}", sql_default_value(default_value)));
+                }
+                args.push(sql_arg);
+            }
+        }
+        statement.push_str(&format!("({})", args.join(", ")));
+    }
+
+    statement.push_str(&format!(
+        " LANGUAGE c AS 'MODULE_PATHNAME', '{}_wrapper';",
+        func.sig.ident
+    ));
+    statement
+}
+
 fn func_args_have_option(func: &ItemFn, rs_file: &DirEntry) -> bool {
     for arg in &func.sig.inputs {
         if let FnArg::Typed(ty) = arg {
@@ -718,7 +1455,7 @@ fn translate_type(filename: &DirEntry, ty: &Type) -> Option<(String, bool, Optio
         }
 
         Type::ImplTrait(_) | Type::Tuple(_) => match categorize_type(ty) {
-            CategorizedType::Default => {
+            CategorizedType::Default | CategorizedType::Record(_) => {
                 exit_with_error!("{:?} isn't an 'impl Trait' type or a Rust Tuple", ty)
             }
             CategorizedType::Iterator(types)
@@ -781,6 +1518,7 @@ fn translate_type_string(
         "i16" => Some(("smallint".to_string(), false, default_value, variadic)),
         "i32" => Some(("integer".to_string(), false, default_value, variadic)),
         "i64" => Some(("bigint".to_string(), false, default_value, variadic)),
+        "i128" => Some(("numeric".to_string(), false, default_value, variadic)),
         "bool" => Some(("bool".to_string(), false, default_value, variadic)),
         "char" => Some(("varchar".to_string(), false, default_value, variadic)), // a Rust "char" is 4 bytes, so we need to represent that as a varchar
         "f32" => Some(("real".to_string(), false, default_value, variadic)),
@@ -791,17 +1529,34 @@ fn translate_type_string(
             variadic,
         )),
         "PgRelation" => Some(("regclass".to_string(), false, default_value, variadic)),
+        "PgOid" => Some(("oid".to_string(), false, default_value, variadic)),
         "Numeric" => Some(("numeric".to_string(), false, default_value, variadic)),
+        "Name" => Some(("name".to_string(), false, default_value, variadic)),
+        "Int2Vector" => Some(("int2vector".to_string(), false, default_value, variadic)),
+        "OidVector" => Some(("oidvector".to_string(), false, default_value, variadic)),
+        "Materialize" | "pgx :: Materialize" => {
+            Some(("SETOF record".to_string(), false, default_value, variadic))
+        }
         "Inet" => Some(("inet".to_string(), false, default_value, variadic)),
+        "Interval" => Some(("interval".to_string(), false, default_value, variadic)),
+        "Money" => Some(("money".to_string(), false, default_value, variadic)),
+        "Tid" => Some(("tid".to_string(), false, default_value, variadic)),
+        "Record" => Some(("record".to_string(), false, default_value, variadic)),
+        "Lsn" => Some(("pg_lsn".to_string(), false, default_value, variadic)),
+        "VarBit" => Some(("varbit".to_string(), false, default_value, variadic)),
         "Json" => Some(("json".to_string(), false, default_value, variadic)),
-        "Jsonb" => Some(("jsonb".to_string(), false, default_value, variadic)),
+        "Jsonb" | "JsonB" | "JsonBValue" => {
+            Some(("jsonb".to_string(), false, default_value, variadic))
+        }
         "& str" | "& 'static str" | "&'static str" | "String" | "& 'static String" | "& String" => {
             Some(("text".to_string(), false, default_value, variadic))
         }
         "& [ u8 ]" | "& 'static [ u8 ]" | "&'static [ u8 ]" | "Vec < u8 >" => {
             Some(("bytea".to_string(), false, default_value, variadic))
         }
-        "& std :: ffi :: CStr" => Some(("cstring".to_string(), false, default_value, variadic)),
+        "& std :: ffi :: CStr" | "& CStr" | "std :: ffi :: CString" | "CString" => {
+            Some(("cstring".to_string(), false, default_value, variadic))
+        }
         "AnyElement" => Some(("anyelement".to_string(), false, default_value, variadic)),
         "AnyArray" => Some(("anyarray".to_string(), false, default_value, variadic)),
         "time :: Time" | "Time" => Some(("time".to_string(), false, default_value, variadic)),
@@ -833,7 +1588,7 @@ fn translate_type_string(
         "pg_sys :: Point" | "Point" => Some(("point".to_string(), false, default_value, variadic)),
         "pg_sys :: Oid" => Some(("oid".to_string(), false, default_value, variadic)),
         "pg_sys :: ItemPointerData" => Some(("tid".to_string(), false, default_value, variadic)),
-        "pg_sys :: FunctionCallInfo" => None,
+        "pg_sys :: FunctionCallInfo" | "FcInfo" | "pgx :: FcInfo" => None,
         "pg_sys :: IndexAmRoutine" => Some((
             "index_am_handler".to_string(),
             false,
@@ -865,77 +1620,18 @@ fn translate_type_string(
             ))
         }
         "Iterator" => {
-            let mut composite_def = String::new();
-            for ty in subtypes
-                .expect("no iterator subtypes where len != 1")
-                .into_iter()
-            {
-                let parsed_type_with_name =
-                    proc_macro2::TokenStream::from_str(&ty).expect("failed to parse type name");
-                let mut iter = parsed_type_with_name.into_iter();
-                let (name, ty) = if let Some(tree) = iter.next() {
-                    match tree {
-                        TokenTree::Ident(_ident) if ty.starts_with("name !") => {
-                            let _bang = iter.next();
-                            let open_paren = iter.next();
-                            match open_paren.unwrap() {
-                                TokenTree::Group(group) => {
-                                    let mut iter = group.stream().into_iter();
-
-                                    let name = iter.next();
-                                    let _comma = iter.next();
-                                    let mut ty = proc_macro2::TokenStream::new();
-                                    for tree in iter {
-                                        match tree {
-                                            TokenTree::Punct(punc) => {
-                                                if punc.as_char() != ')' {
-                                                    ty.extend(quote! {#punc})
-                                                }
-                                            }
-                                            other => ty.extend(quote! {#other}),
-                                        }
-                                    }
-
-                                    (name.unwrap().to_string(), ty.to_string())
-                                }
-                                _ => exit_with_error!("malformed name!() macro"),
-                            }
-                        }
-                        _ => exit_with_error!(
-                            "No name!() macro specified for tuple member of type: {} at {}",
-                            ty,
-                            location_comment(filename, span)
-                        ),
-                    }
-                } else {
-                    exit_with_error!("malformed name!() macro")
-                };
-
-                let translated = translate_type_string(
-                    ty,
-                    filename,
-                    span,
-                    depth + 1,
-                    default_value.clone(),
-                    variadic,
-                    None,
-                )
-                .unwrap();
-
-                if !composite_def.is_empty() {
-                    composite_def.push_str(", ");
-                }
-                if !name.starts_with('"') {
-                    composite_def.push('"');
-                }
-                composite_def.push_str(&name);
-                if !name.ends_with('"') {
-                    composite_def.push('"');
-                }
-
-                composite_def.push(' ');
-                composite_def.push_str(&translated.0);
-            }
+            let composite_def = named_columns(
+                subtypes.expect("no iterator subtypes where len != 1"),
+                filename,
+                span,
+                depth,
+                default_value.clone(),
+                variadic,
+            )
+            .into_iter()
+            .map(|(name, type_name)| format!("{} {}", name, type_name))
+            .collect::<Vec<_>>()
+            .join(", ");
 
             Some((
                 format!("TABLE ({})", composite_def),
@@ -944,7 +1640,11 @@ fn translate_type_string(
                 variadic,
             ))
         }
-        _array if rust_type.starts_with("Array <") | rust_type.starts_with("Vec <") => {
+        _array
+            if rust_type.starts_with("Array <")
+                | rust_type.starts_with("Vec <")
+                | rust_type.starts_with("ArrayBuilder <") =>
+        {
             let rc = translate_type_string(
                 extract_type(&rust_type),
                 filename,
@@ -972,9 +1672,21 @@ fn translate_type_string(
             type_string.push_str("[]");
             Some((type_string, false, default_value, true))
         }
+        _jsonb if rust_type.starts_with("PgJsonB <") => {
+            Some(("jsonb".to_string(), false, default_value, variadic))
+        }
         _internal if rust_type.starts_with("Internal <") => {
             Some(("internal".to_string(), false, default_value, variadic))
         }
+        _varlena if rust_type.starts_with("PgVarlena <") => translate_type_string(
+            extract_type(&rust_type),
+            filename,
+            span,
+            depth + 1,
+            default_value,
+            variadic,
+            subtypes,
+        ),
         _boxed if rust_type.starts_with("PgBox <") => translate_type_string(
             extract_type(&rust_type),
             filename,
@@ -984,6 +1696,15 @@ fn translate_type_string(
             variadic,
             subtypes,
         ),
+        _result if rust_type.starts_with("Result <") => translate_type_string(
+            first_type_arg(&extract_type(&rust_type)),
+            filename,
+            span,
+            depth + 1,
+            default_value,
+            variadic,
+            subtypes,
+        ),
         _option if rust_type.starts_with("Option <") => {
             let mut extraced_type = extract_type(&rust_type);
             if let Some((rt, dv, v)) = deconstruct_macro(&extraced_type) {
@@ -1021,9 +1742,97 @@ fn translate_type_string(
                 Some(idx) => unknown[0..idx].trim(),
                 None => unknown,
             };
-            Some((unknown.to_string(), false, default_value, variadic))
+
+            // a type from another module, such as `my_schema::MyType`, is in that module's schema
+            let mut segments = unknown.rsplit(" :: ");
+            let name = segments.next().unwrap_or(unknown);
+            let unknown = match segments.next() {
+                Some(module) if !["crate", "self", "super", "pgx"].contains(&module) => {
+                    format!("{}.{}", module, name)
+                }
+                _ => name.to_string(),
+            };
+            Some((unknown, false, default_value, variadic))
+        }
+    }
+}
+
+/// Translate the `name!(ident, Type)` members of a returned tuple into (quoted column name, SQL type)
+/// pairs
+fn named_columns(
+    subtypes: Vec<String>,
+    filename: &DirEntry,
+    span: &proc_macro2::Span,
+    depth: i32,
+    default_value: Option<String>,
+    variadic: bool,
+) -> Vec<(String, String)> {
+    let mut columns = Vec::new();
+    for ty in subtypes.into_iter() {
+        let parsed_type_with_name =
+            proc_macro2::TokenStream::from_str(&ty).expect("failed to parse type name");
+        let mut iter = parsed_type_with_name.into_iter();
+        let (name, ty) = if let Some(tree) = iter.next() {
+            match tree {
+                TokenTree::Ident(_ident) if ty.starts_with("name !") => {
+                    let _bang = iter.next();
+                    let open_paren = iter.next();
+                    match open_paren.unwrap() {
+                        TokenTree::Group(group) => {
+                            let mut iter = group.stream().into_iter();
+
+                            let name = iter.next();
+                            let _comma = iter.next();
+                            let mut ty = proc_macro2::TokenStream::new();
+                            for tree in iter {
+                                match tree {
+                                    TokenTree::Punct(punc) => {
+                                        if punc.as_char() != ')' {
+                                            ty.extend(quote! {#punc})
+                                        }
+                                    }
+                                    other => ty.extend(quote! {#other}),
+                                }
+                            }
+
+                            (name.unwrap().to_string(), ty.to_string())
+                        }
+                        _ => exit_with_error!("malformed name!() macro"),
+                    }
+                }
+                _ => exit_with_error!(
+                    "No name!() macro specified for tuple member of type: {} at {}",
+                    ty,
+                    location_comment(filename, span)
+                ),
+            }
+        } else {
+            exit_with_error!("malformed name!() macro")
+        };
+
+        let translated = translate_type_string(
+            ty,
+            filename,
+            span,
+            depth + 1,
+            default_value.clone(),
+            variadic,
+            None,
+        )
+        .unwrap();
+
+        let mut quoted_name = String::new();
+        if !name.starts_with('"') {
+            quoted_name.push('"');
         }
+        quoted_name.push_str(&name);
+        if !name.ends_with('"') {
+            quoted_name.push('"');
+        }
+
+        columns.push((quoted_name, translated.0));
     }
+    columns
 }
 
 fn extract_type(type_name: &str) -> String {
@@ -1035,6 +1844,20 @@ fn extract_type(type_name: &str) -> String {
     capture.unwrap().as_str().to_string().trim().to_string()
 }
 
+/// The first of a comma-separated list of type arguments, such as `T` in `T , E`
+fn first_type_arg(type_args: &str) -> String {
+    let mut depth = 0;
+    for (i, c) in type_args.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => return type_args[..i].trim().to_string(),
+            _ => {}
+        }
+    }
+    type_args.trim().to_string()
+}
+
 fn extract_funcargs_attribute(attrs: &[CategorizedAttribute]) -> Option<String> {
     for a in attrs {
         if let CategorizedAttribute::SqlFunctionArgs(sql) = a {
@@ -1078,6 +1901,11 @@ fn collect_attributes(
                 span,
                 parse_extern_args(&a),
             )));
+        } else if as_string.starts_with("# [ pg_proc") {
+            categorized_attributes
+                .push(CategorizedAttribute::PgProc((span, parse_extern_args(&a))));
+        } else if as_string.starts_with("# [ pg_eventtrigger") {
+            categorized_attributes.push(CategorizedAttribute::PgEventTrigger(span));
         } else if as_string.starts_with("# [ pg_operator") {
             operator = Some(CategorizedAttribute::PgOperator(
                 span,
@@ -1118,6 +1946,14 @@ fn collect_attributes(
             if let Some(CategorizedAttribute::PgOperator(_, _, options)) = operator.as_mut() {
                 options.push(OperatorOptions::Merges);
             }
+        } else if as_string.starts_with("# [ opclass_operator") {
+            if let Some(CategorizedAttribute::PgOperator(_, _, options)) = operator.as_mut() {
+                let (method, strategy) = extract_opclass_args(a.tokens.clone());
+                options.push(OperatorOptions::OpClass(method, strategy));
+            }
+        } else if as_string.starts_with("# [ opclass_function") {
+            let (method, number) = extract_opclass_args(a.tokens.clone());
+            categorized_attributes.push(CategorizedAttribute::OpClassFunction(method, number));
         } else if as_string.starts_with("# [ pg_test") {
             categorized_attributes
                 .push(CategorizedAttribute::PgTest((span, parse_extern_args(&a))));
@@ -1215,6 +2051,22 @@ fn extract_single_arg(attr: TokenStream2) -> String {
     arg.trim_start_matches('(').trim_end_matches(')').into()
 }
 
+/// The `(index access method, number)` arguments of `#[opclass_operator]` and
+/// `#[opclass_function]`
+fn extract_opclass_args(attr: TokenStream2) -> (String, String) {
+    let arg = extract_single_arg(attr);
+    let mut args = arg.split(',').map(|arg| arg.trim().to_string());
+    match (args.next(), args.next(), args.next()) {
+        (Some(method), Some(number), None) if !method.is_empty() && !number.is_empty() => {
+            (method, number)
+        }
+        _ => exit_with_error!(
+            "expected an index access method and a number, such as (btree, 1), but got ({})",
+            arg
+        ),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn collect_doc(
     rs_file: &DirEntry,
@@ -1274,3 +2126,45 @@ fn location_comment(rs_file: &DirEntry, span: &Span) -> String {
         span.start().column,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::make_create_procedure_statement;
+    use std::collections::HashMap;
+    use std::fs::DirEntry;
+    use syn::ItemFn;
+
+    fn rs_file() -> DirEntry {
+        std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))
+            .expect("failed to read src/")
+            .next()
+            .expect("src/ is empty")
+            .expect("failed to read src/ entry")
+    }
+
+    #[test]
+    fn create_procedure_with_inout_args() {
+        let func: ItemFn = syn::parse_str(
+            "#[pg_proc] fn split(name: &mut String, suffix: &mut Option<String>, sep: i32) {}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            make_create_procedure_statement(&func, &rs_file(), None, "public", &HashMap::new()),
+            "CREATE OR REPLACE PROCEDURE \"split\"(INOUT \"name\" text, INOUT \"suffix\" text, \"sep\" integer) LANGUAGE c AS 'MODULE_PATHNAME', 'split_wrapper';"
+        );
+    }
+
+    #[test]
+    fn create_procedure_skips_fcinfo() {
+        let func: ItemFn = syn::parse_str(
+            "#[pg_proc] fn batches(count: i32, fcinfo: pg_sys::FunctionCallInfo) {}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            make_create_procedure_statement(&func, &rs_file(), None, "tests", &HashMap::new()),
+            "CREATE OR REPLACE PROCEDURE tests.\"batches\"(\"count\" integer) LANGUAGE c AS 'MODULE_PATHNAME', 'batches_wrapper';"
+        );
+    }
+}
@@ -0,0 +1,694 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::commands::get::get_property;
+use crate::commands::schema::read_load_order;
+use colored::Colorize;
+use pgx_utils::{exit_with_error, handle_result, run_pg_config};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The kind of schema object a top-level SQL statement creates, along with the identity
+/// we use to match it up between two versions of the extension schema
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SchemaObject {
+    Function { name: String, args: String },
+    Type(String),
+    Operator { name: String, args: String },
+    Other(String),
+}
+
+#[derive(Debug)]
+struct SchemaStatement {
+    object: SchemaObject,
+    returns: Option<String>,
+    sql: String,
+    normalized: String,
+}
+
+/// Compare the freshly generated schema against the full install script of version `against`
+/// and write the delta to `./sql/<extname>--<against>--<default_version>.sql`
+///
+/// The previous install script is read from `./sql/<extname>--<against>.sql` if it exists,
+/// otherwise from the extension directory of whatever "pg_config" is currently on your $PATH
+pub(crate) fn generate_upgrade_script(against: &str) {
+    let extname = get_property("extname").expect("could not determine extension name");
+    let version = match get_property("default_version") {
+        Some(v) => v,
+        None => exit_with_error!("cannot determine extension version number.  Is the `default_version` property declared in the control file?"),
+    };
+
+    if version == against {
+        exit_with_error!(
+            "cannot generate an upgrade script from version {} to itself",
+            against
+        );
+    }
+
+    let old_sql = read_previous_schema(&extname, against);
+    let new_sql = read_current_schema();
+
+    let old_statements = split_statements(&old_sql)
+        .into_iter()
+        .map(|s| classify_statement(&s))
+        .collect::<Vec<_>>();
+    let new_statements = split_statements(&new_sql)
+        .into_iter()
+        .map(|s| classify_statement(&s))
+        .collect::<Vec<_>>();
+
+    let delta = diff_statements(&old_statements, &new_statements);
+
+    let filename =
+        PathBuf::from_str(&format!("./sql/{}--{}--{}.sql", extname, against, version)).unwrap();
+    let mut file = handle_result!(
+        format!("failed to create {}", filename.display()),
+        std::fs::File::create(&filename)
+    );
+    let header = format!(
+        "-- upgrade script from version {} to {}\n-- generated by `cargo pgx schema --against {}`\n\n",
+        against, version, against
+    );
+    handle_result!(
+        format!("failed to write to {}", filename.display()),
+        file.write_all(header.as_bytes())
+    );
+    for statement in &delta {
+        handle_result!(
+            format!("failed to write to {}", filename.display()),
+            file.write_all(statement.as_bytes())
+        );
+        handle_result!(
+            format!("failed to write to {}", filename.display()),
+            file.write_all(b"\n\n")
+        );
+    }
+
+    println!(
+        "{} upgrade script to `{}` ({} statements)",
+        "     Writing".bold().green(),
+        filename.display(),
+        delta.len()
+    );
+}
+
+fn read_previous_schema(extname: &str, against: &str) -> String {
+    let script_name = format!("{}--{}.sql", extname, against);
+    let mut local = PathBuf::from_str("./sql").unwrap();
+    local.push(&script_name);
+
+    if local.exists() {
+        return handle_result!(
+            format!("failed to read {}", local.display()),
+            std::fs::read_to_string(&local)
+        );
+    }
+
+    let mut installed: PathBuf = run_pg_config(&None, "--sharedir").into();
+    installed.push("extension");
+    installed.push(&script_name);
+
+    if installed.exists() {
+        return handle_result!(
+            format!("failed to read {}", installed.display()),
+            std::fs::read_to_string(&installed)
+        );
+    }
+
+    exit_with_error!(
+        "could not find the install script for version {}.  Looked for `{}` and `{}`",
+        against,
+        local.display(),
+        installed.display()
+    )
+}
+
+fn read_current_schema() -> String {
+    let load_order = read_load_order(&PathBuf::from_str("./sql/load-order.txt").unwrap());
+    let mut sql = String::new();
+
+    for file in load_order {
+        let file = PathBuf::from_str(&format!("./sql/{}", file)).unwrap();
+        let contents = handle_result!(
+            format!("failed to read {}", file.display()),
+            std::fs::read_to_string(&file)
+        );
+        sql.push_str(&contents);
+        sql.push('\n');
+    }
+
+    sql
+}
+
+/// Produce the statements necessary to move from `old` to `new`.  DROPs for objects that no
+/// longer exist come first (operators, then types, then functions), followed
+/// by the added and changed objects in the order they appear in the new schema
+fn diff_statements(old: &[SchemaStatement], new: &[SchemaStatement]) -> Vec<String> {
+    let old_by_object = group_by_object(old);
+    let new_by_object = group_by_object(new);
+    let mut drops = Vec::new();
+    let mut creates = Vec::new();
+
+    for statement in old {
+        let object = &statement.object;
+        if new_by_object.contains_key(object) {
+            continue;
+        }
+
+        let drop = match object {
+            SchemaObject::Operator { name, args } => {
+                (0, format!("DROP OPERATOR IF EXISTS {} ({});", name, args))
+            }
+            SchemaObject::Type(name) => (
+                // a base type and its input/output functions depend on each other, so
+                // only CASCADE can remove them.  this also drops any columns of this type
+                1,
+                format!(
+                    "-- NOTE:  this also drops any columns of type {}\nDROP TYPE IF EXISTS {} CASCADE;",
+                    name, name
+                ),
+            ),
+            SchemaObject::Function { name, args } => {
+                (2, format!("DROP FUNCTION IF EXISTS {}({});", name, args))
+            }
+            SchemaObject::Other(sql) => (
+                3,
+                format!(
+                    "-- the following statement no longer exists and must be reverted by hand:\n{}",
+                    comment_out(sql)
+                ),
+            ),
+        };
+
+        if !drops.contains(&drop) {
+            drops.push(drop);
+        }
+    }
+    drops.sort_by_key(|(order, _)| *order);
+
+    let mut seen = Vec::new();
+    for statement in new {
+        let object = &statement.object;
+        if seen.contains(&object) {
+            continue;
+        }
+        seen.push(object);
+
+        let new_group = new_by_object.get(object).unwrap();
+        match old_by_object.get(object) {
+            None => creates.extend(new_group.iter().map(|s| s.sql.clone())),
+            Some(old_group) if normalized(old_group) == normalized(new_group) => { /* unchanged */ }
+            Some(old_group) => match object {
+                SchemaObject::Function { name, args } => {
+                    if old_group.last().unwrap().returns != new_group.last().unwrap().returns {
+                        // CREATE OR REPLACE can't change a function's return type, and only
+                        // CASCADE can drop a function that views or operators depend on
+                        creates.push(format!(
+                            "-- NOTE:  the return type of {} has changed.  this also drops any views and operators that depend on it,\n-- which must be recreated by hand\nDROP FUNCTION IF EXISTS {}({}) CASCADE;",
+                            name, name, args
+                        ));
+                    }
+                    creates.extend(new_group.iter().map(|s| s.sql.clone()));
+                }
+                SchemaObject::Operator { name, args } => {
+                    creates.push(format!("DROP OPERATOR IF EXISTS {} ({});", name, args));
+                    creates.extend(new_group.iter().map(|s| s.sql.clone()));
+                }
+                SchemaObject::Type(name) => {
+                    let old_attributes =
+                        composite_attributes(&old_group.last().unwrap().normalized);
+                    let new_attributes =
+                        composite_attributes(&new_group.last().unwrap().normalized);
+                    let change = match (old_attributes, new_attributes) {
+                        // the same attributes in a different order, so the fields of existing
+                        // values of the type would be swapped around
+                        (Some(old_attributes), Some(new_attributes))
+                            if is_reordering(&old_attributes, &new_attributes) =>
+                        {
+                            "has had its attributes reordered"
+                        }
+                        _ => "has changed",
+                    };
+
+                    creates.push(format!(
+                        "-- the definition of type {} {} and must be migrated by hand:\n{}",
+                        name,
+                        change,
+                        comment_out(
+                            &new_group
+                                .iter()
+                                .map(|s| s.sql.clone())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        )
+                    ))
+                }
+                SchemaObject::Other(_) => {
+                    // these are keyed by their text, so they only differ in how often they repeat
+                }
+            },
+        }
+    }
+
+    drops
+        .into_iter()
+        .map(|(_, sql)| sql)
+        .chain(creates.into_iter())
+        .collect()
+}
+
+fn group_by_object(
+    statements: &[SchemaStatement],
+) -> HashMap<&SchemaObject, Vec<&SchemaStatement>> {
+    let mut map: HashMap<&SchemaObject, Vec<&SchemaStatement>> = HashMap::new();
+    for statement in statements {
+        map.entry(&statement.object).or_default().push(statement);
+    }
+    map
+}
+
+fn normalized<'a>(group: &'a [&SchemaStatement]) -> Vec<&'a str> {
+    group.iter().map(|s| s.normalized.as_str()).collect()
+}
+
+fn comment_out(sql: &str) -> String {
+    sql.lines()
+        .map(|line| format!("-- {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split a SQL script into its top-level statements, discarding comments.  Semicolons inside
+/// quoted strings, quoted identifiers, and dollar-quoted bodies don't terminate a statement
+fn split_statements(sql: &str) -> Vec<String> {
+    let chars = sql.chars().collect::<Vec<_>>();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).cloned();
+
+        if c == '-' && next == Some('-') {
+            // line comment
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && next == Some('*') {
+            // block comment
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == '\'' || c == '"' {
+            // quoted string or identifier.  doubled quotes are handled by simply re-entering
+            current.push(c);
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                current.push(chars[i]);
+                i += 1;
+            }
+            if i < chars.len() {
+                current.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        } else if c == '$' {
+            if let Some(tag) = dollar_quote_tag(&chars[i..]) {
+                let tag_chars = tag.chars().collect::<Vec<_>>();
+                current.push_str(&tag);
+                i += tag_chars.len();
+                while i < chars.len() && !chars[i..].starts_with(&tag_chars) {
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                if i < chars.len() {
+                    current.push_str(&tag);
+                    i += tag_chars.len();
+                }
+                continue;
+            }
+        } else if c == ';' {
+            current.push(c);
+            let statement = current.trim().to_string();
+            if statement != ";" {
+                statements.push(statement);
+            }
+            current.clear();
+            i += 1;
+            continue;
+        }
+
+        current.push(c);
+        i += 1;
+    }
+
+    let remainder = current.trim();
+    if !remainder.is_empty() {
+        statements.push(remainder.to_string());
+    }
+
+    statements
+}
+
+fn dollar_quote_tag(chars: &[char]) -> Option<String> {
+    let mut tag = String::from("$");
+    for c in chars.iter().skip(1) {
+        if *c == '$' {
+            tag.push('$');
+            return Some(tag);
+        } else if c.is_alphanumeric() || *c == '_' {
+            tag.push(*c);
+        } else {
+            return None;
+        }
+    }
+    None
+}
+
+fn classify_statement(sql: &str) -> SchemaStatement {
+    let normalized = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    let upper = normalized.to_uppercase();
+    let mut returns = None;
+
+    let object = if upper.starts_with("CREATE OR REPLACE FUNCTION ")
+        || upper.starts_with("CREATE FUNCTION ")
+    {
+        let start = upper.find("FUNCTION ").unwrap() + "FUNCTION ".len();
+        let rest = &normalized[start..];
+        match (rest.find('('), find_matching_paren(rest)) {
+            (Some(open), Some(close)) => {
+                let after = &rest[close + 1..];
+                if let Some(idx) = after.to_uppercase().find("RETURNS ") {
+                    returns = Some(
+                        after[idx + "RETURNS ".len()..]
+                            .split_whitespace()
+                            .take_while(|w| !is_function_option(w))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    );
+                }
+                SchemaObject::Function {
+                    name: unqualify_public(rest[..open].trim()),
+                    args: strip_arg_defaults(&rest[open + 1..close]),
+                }
+            }
+            _ => SchemaObject::Other(normalized.clone()),
+        }
+    } else if upper.starts_with("CREATE TYPE ") {
+        let rest = &normalized["CREATE TYPE ".len()..];
+        let name = rest
+            .split(|c: char| c.is_whitespace() || c == '(' || c == ';')
+            .next()
+            .unwrap();
+        SchemaObject::Type(unqualify_public(name))
+    } else if upper.starts_with("CREATE OPERATOR ") && !upper.starts_with("CREATE OPERATOR CLASS") {
+        let rest = &normalized["CREATE OPERATOR ".len()..];
+        let name = rest
+            .split(|c: char| c.is_whitespace() || c == '(')
+            .next()
+            .unwrap();
+        let leftarg = operator_option(rest, "LEFTARG").unwrap_or_else(|| "NONE".to_string());
+        let rightarg = operator_option(rest, "RIGHTARG").unwrap_or_else(|| "NONE".to_string());
+        SchemaObject::Operator {
+            name: unqualify_public(name),
+            args: format!("{}, {}", leftarg, rightarg),
+        }
+    } else {
+        SchemaObject::Other(normalized.clone())
+    };
+
+    SchemaStatement {
+        object,
+        returns,
+        sql: sql.to_string(),
+        normalized,
+    }
+}
+
+/// Shell types are created as `public.name` while everything else in the public schema is
+/// unqualified, so treat both spellings as the same object
+fn unqualify_public(name: &str) -> String {
+    name.trim_start_matches("public.").to_string()
+}
+
+fn is_function_option(word: &str) -> bool {
+    match word.to_uppercase().trim_end_matches(';') {
+        "IMMUTABLE" | "STABLE" | "VOLATILE" | "STRICT" | "PARALLEL" | "LANGUAGE" | "AS"
+        | "COST" | "ROWS" | "SECURITY" | "CALLED" | "RETURNS" | "SET" | "LEAKPROOF" => true,
+        _ => false,
+    }
+}
+
+fn find_matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `(name, type)` attributes of a `CREATE TYPE name AS (...)` statement, in order, or `None`
+/// if it creates some other kind of type
+fn composite_attributes(type_sql: &str) -> Option<Vec<(String, String)>> {
+    let idx = type_sql.to_uppercase().find(" AS (")?;
+    let definition = &type_sql[idx + " AS ".len()..];
+    let close = find_matching_paren(definition)?;
+
+    let mut attributes = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in definition[1..close].chars().chain(std::iter::once(',')) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                let attribute = current.trim();
+                if let Some(space) = attribute.find(' ') {
+                    attributes.push((
+                        attribute[..space].to_string(),
+                        attribute[space + 1..].trim().to_string(),
+                    ));
+                }
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+
+    Some(attributes)
+}
+
+fn is_reordering(old: &[(String, String)], new: &[(String, String)]) -> bool {
+    let mut old_sorted = old.to_vec();
+    let mut new_sorted = new.to_vec();
+    old_sorted.sort();
+    new_sorted.sort();
+    old != new && old_sorted == new_sorted
+}
+
+/// DROP FUNCTION doesn't accept DEFAULT clauses in its argument list
+fn strip_arg_defaults(args: &str) -> String {
+    let mut stripped = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+
+    for c in args.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                stripped.push(current.clone());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    stripped.push(current);
+
+    stripped
+        .iter()
+        .map(|arg| {
+            let upper = arg.to_uppercase();
+            match upper.find(" DEFAULT ") {
+                Some(idx) => arg[..idx].trim().to_string(),
+                None => arg.trim().to_string(),
+            }
+        })
+        .filter(|arg| !arg.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn operator_option(operator_sql: &str, option: &str) -> Option<String> {
+    let upper = operator_sql.to_uppercase();
+    let idx = upper.find(option)?;
+    let value = operator_sql[idx + option.len()..]
+        .trim_start()
+        .trim_start_matches('=')
+        .trim_start();
+    let value = value
+        .split(|c: char| c == ',' || c == ')')
+        .next()
+        .unwrap()
+        .trim();
+    Some(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        classify_statement, diff_statements, split_statements, strip_arg_defaults, SchemaObject,
+        SchemaStatement,
+    };
+
+    fn classify_all(sql: &str) -> Vec<SchemaStatement> {
+        split_statements(sql)
+            .into_iter()
+            .map(|s| classify_statement(&s))
+            .collect()
+    }
+
+    #[test]
+    fn split_ignores_comments_and_quoted_semicolons() {
+        let sql = "-- a comment; with a semicolon\n\
+                   SELECT 'a;b', \"c;d\";\n\
+                   /* another; comment */\n\
+                   CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql;\n\
+                   ;\n\
+                   SELECT 2";
+
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "SELECT 'a;b', \"c;d\";",
+                "CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql;",
+                "SELECT 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn classify_function() {
+        let statement = classify_statement(
+            "CREATE OR REPLACE FUNCTION public.add(a integer, b integer DEFAULT 1)\n\
+             RETURNS integer IMMUTABLE STRICT LANGUAGE c AS 'MODULE_PATHNAME', 'add_wrapper';",
+        );
+
+        assert_eq!(
+            statement.object,
+            SchemaObject::Function {
+                name: "add".to_string(),
+                args: "a integer, b integer".to_string()
+            }
+        );
+        assert_eq!(statement.returns, Some("integer".to_string()));
+    }
+
+    #[test]
+    fn classify_type_and_operator() {
+        assert_eq!(
+            classify_statement("CREATE TYPE public.animals;").object,
+            SchemaObject::Type("animals".to_string())
+        );
+        assert_eq!(
+            classify_statement("CREATE TYPE animals AS (name text, age int);").object,
+            SchemaObject::Type("animals".to_string())
+        );
+        assert_eq!(
+            classify_statement(
+                "CREATE OPERATOR == (PROCEDURE = animals_eq, LEFTARG = animals, RIGHTARG = animals);"
+            )
+            .object,
+            SchemaObject::Operator {
+                name: "==".to_string(),
+                args: "animals, animals".to_string()
+            }
+        );
+        assert_eq!(
+            classify_statement("GRANT ALL ON animals TO public;").object,
+            SchemaObject::Other("GRANT ALL ON animals TO public;".to_string())
+        );
+    }
+
+    #[test]
+    fn strip_defaults() {
+        assert_eq!(
+            strip_arg_defaults("a integer DEFAULT 99, b numeric(10, 2) default 1.0, c text"),
+            "a integer, b numeric(10, 2), c text"
+        );
+        assert_eq!(strip_arg_defaults(""), "");
+    }
+
+    #[test]
+    fn diff_adds_and_drops() {
+        let old = classify_all(
+            "CREATE FUNCTION kept() RETURNS int LANGUAGE c AS 'MODULE_PATHNAME', 'kept_wrapper';\n\
+             CREATE FUNCTION removed(a int) RETURNS int LANGUAGE c AS 'MODULE_PATHNAME', 'removed_wrapper';",
+        );
+        let new = classify_all(
+            "CREATE FUNCTION kept() RETURNS int LANGUAGE c AS 'MODULE_PATHNAME', 'kept_wrapper';\n\
+             CREATE FUNCTION added() RETURNS text LANGUAGE c AS 'MODULE_PATHNAME', 'added_wrapper';",
+        );
+
+        assert_eq!(
+            diff_statements(&old, &new),
+            vec![
+                "DROP FUNCTION IF EXISTS removed(a int);",
+                "CREATE FUNCTION added() RETURNS text LANGUAGE c AS 'MODULE_PATHNAME', 'added_wrapper';",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_changed_return_type_cascades() {
+        let old = classify_all(
+            "CREATE FUNCTION f(a int) RETURNS int LANGUAGE c AS 'MODULE_PATHNAME', 'f_wrapper';",
+        );
+        let new = classify_all(
+            "CREATE FUNCTION f(a int) RETURNS bigint LANGUAGE c AS 'MODULE_PATHNAME', 'f_wrapper';",
+        );
+
+        let diff = diff_statements(&old, &new);
+        assert_eq!(diff.len(), 2);
+        assert!(diff[0].starts_with("-- NOTE:"));
+        assert!(diff[0].ends_with("\nDROP FUNCTION IF EXISTS f(a int) CASCADE;"));
+        assert_eq!(
+            diff[1],
+            "CREATE FUNCTION f(a int) RETURNS bigint LANGUAGE c AS 'MODULE_PATHNAME', 'f_wrapper';"
+        );
+    }
+
+    #[test]
+    fn diff_changed_type_is_migrated_by_hand() {
+        let old = classify_all("CREATE TYPE animals AS (name text, age int);");
+        let new = classify_all("CREATE TYPE animals AS (age int, name text);");
+
+        assert_eq!(
+            diff_statements(&old, &new),
+            vec![
+                "-- the definition of type animals has had its attributes reordered and must be migrated by hand:\n\
+                 -- CREATE TYPE animals AS (age int, name text);"
+            ]
+        );
+    }
+}
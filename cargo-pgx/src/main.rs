@@ -13,13 +13,14 @@ use crate::commands::new::create_crate_template;
 use crate::commands::package::package_extension;
 use crate::commands::run::run_psql;
 use crate::commands::schema::generate_schema;
+use crate::commands::schema_diff::generate_upgrade_script;
 use crate::commands::start::start_postgres;
 use crate::commands::status::status_postgres;
 use crate::commands::stop::stop_postgres;
 use crate::commands::test::test_extension;
 use clap::App;
 use colored::Colorize;
-use pgx_utils::{exit, exit_with_error, get_pg_config};
+use pgx_utils::{exit, exit_with_error, get_pg_config, override_pg_config};
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -109,17 +110,33 @@ fn main() -> std::result::Result<(), std::io::Error> {
                     |v| v.to_string(),
                 );
                 let is_release = run.is_present("release");
-                run_psql(make_pg_major_version(pgver)[0], &dbname, is_release);
+                let major_version = make_pg_major_version(pgver)[0];
+                if let Some(pg_config) = run.value_of("pg_config") {
+                    override_pg_config(major_version, pg_config);
+                }
+                run_psql(major_version, &dbname, is_release);
                 Ok(())
             }
             ("test", Some(test)) => {
                 let pgver = test.value_of("pg_version").unwrap_or("all");
+                if let Some(pg_config) = test.value_of("pg_config") {
+                    if pgver == "all" {
+                        exit_with_error!("--pg-config requires a specific PG_VERSION")
+                    }
+                    override_pg_config(make_pg_major_version(pgver)[0], pg_config);
+                }
                 for major_version in make_pg_major_version(pgver) {
                     test_extension(*major_version);
                 }
                 Ok(())
             }
-            ("schema", Some(_schema)) => generate_schema(),
+            ("schema", Some(schema)) => {
+                generate_schema()?;
+                if let Some(against) = schema.value_of("against") {
+                    generate_upgrade_script(against);
+                }
+                Ok(())
+            }
             ("get", Some(get)) => {
                 let name = get.value_of("name").expect("no property name specified");
                 if let Some(value) = get_property(name) {
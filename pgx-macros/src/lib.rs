@@ -41,18 +41,38 @@ pub fn pg_guard(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
 /// `#[pg_test]` functions are test functions (akin to `#[test]`), but they run in-process inside
 /// Postgres during `cargo pgx test`.
+///
+/// Each test already runs in a transaction that is aborted once it finishes.  Use
+/// `#[pg_test(transactional)]` to also run the test body inside a subtransaction that is rolled
+/// back as soon as the body returns, via a `pgx::RolledBackSubtransaction`.  The body isn't
+/// wrapped in a closure, so `return` still returns from the test function.
 #[proc_macro_attribute]
 pub fn pg_test(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut stream = proc_macro2::TokenStream::new();
     let args = parse_extern_attributes(TokenStream2::from(attr.clone()));
 
     let mut expected_error = None;
-    args.into_iter().for_each(|v| {
-        if let ExternArgs::Error(message) = v {
-            expected_error = Some(message)
-        }
+    let mut is_transactional = false;
+    args.into_iter().for_each(|v| match v {
+        ExternArgs::Error(message) => expected_error = Some(message),
+        ExternArgs::Transactional => is_transactional = true,
+        _ => {}
     });
 
+    let item = if is_transactional {
+        let mut func = parse_macro_input!(item as ItemFn);
+        let block = func.block;
+        func.block = Box::new(syn::parse_quote! {
+            {
+                let _subxact = pgx::RolledBackSubtransaction::begin();
+                #block
+            }
+        });
+        TokenStream::from(func.into_token_stream())
+    } else {
+        item
+    };
+
     stream.extend(proc_macro2::TokenStream::from(pg_extern(
         attr,
         item.clone(),
@@ -154,16 +174,106 @@ pub fn merges(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Used with `#[pg_operator]`.  2 values: an index access method, such as `btree` or `hash`, and
+/// the operator's strategy number within that method.
+///
+/// The operators and `#[opclass_function]` support functions for the same type and access method
+/// become a `CREATE OPERATOR CLASS <type>_<method>_ops DEFAULT FOR TYPE <type>`, which lets the
+/// type be indexed.  They must all be in the same module.
+///
+/// A `btree` class needs the `<`, `<=`, `=`, `>=`, and `>` operators as strategies 1 through 5,
+/// and a comparison function returning an `i32` as support function 1.  A `hash` class needs the
+/// `=` operator as strategy 1, and a function hashing the type to an `i32` as support function 1.
+///
+/// A hash index stores the values the hash function returns, so they must never change.  Don't
+/// use `std::collections::hash_map::DefaultHasher`, whose algorithm may change in any Rust
+/// release, which would silently break existing indexes.  Postgres' own hash functions, such as
+/// `pg_sys::hashint8`, are stable.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(PostgresType, Serialize, Deserialize, PartialEq, Eq)]
+/// pub struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// #[pg_operator(immutable)]
+/// #[opname(=)]
+/// #[opclass_operator(hash, 1)]
+/// fn point_eq(left: Point, right: Point) -> bool {
+///     left == right
+/// }
+///
+/// #[pg_extern(immutable)]
+/// #[opclass_function(hash, 1)]
+/// fn point_hash(point: Point) -> i32 {
+///     let key = ((point.x as i64) << 32) | (point.y as u32 as i64);
+///     unsafe { direct_function_call::<i32>(pg_sys::hashint8, vec![key.into_datum()]) }
+///         .expect("hashint8() returned NULL")
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn opclass_operator(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// Used with `#[pg_extern]`.  2 values: an index access method, such as `btree` or `hash`, and the
+/// function's support function number within that method.  See `#[opclass_operator]`.
+#[proc_macro_attribute]
+pub fn opclass_function(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /// Declare a function as `#[pg_extern]` to indicate that it can be used by Postgres as a UDF
+///
+/// The generated `CREATE FUNCTION` statement can be decorated with the volatility markers
+/// `immutable`, `stable`, or `volatile` (the default), and the parallel-safety markers
+/// `parallel_safe`, `parallel_restricted`, or `parallel_unsafe` (the default), for example
+/// `#[pg_extern(immutable, parallel_safe)]`.  At most one of each may be specified.
+///
+/// Arguments of type `Option<T>` receive `None` for a SQL NULL, and the function decides what to
+/// do with it.  When no argument is an `Option<T>`, the function is declared `STRICT`, so Postgres
+/// returns NULL without calling it if any argument is NULL.  When only some are, the function
+/// isn't `STRICT`, but a NULL for any of the others still returns NULL without calling it (or no
+/// rows, for a set-returning function).
+///
+/// A function returning `impl Iterator<Item = T>` is a set-returning function, and each item is a
+/// row.  It may also return `Option<impl Iterator<Item = T>>`, where `None` returns zero rows, just
+/// as an empty iterator does.
+///
+/// For cases the generated statement can't express (`SECURITY DEFINER`, a custom `COST`, etc),
+/// `#[pg_extern(sql = "...")]` replaces it entirely.  `@FUNCTION_NAME@` in the provided SQL is
+/// replaced with the name of the function's exported symbol.
+///
+/// An argument declared as `default!(type, value)`, such as `b: default!(i32, 10)`, is created
+/// with `DEFAULT 10` so callers may omit it.  Use `Option<default!(type, "NULL")>` for a NULL
+/// default.  As in SQL, every argument after one with a default must also have one.
+///
+/// The function is created after any `#[derive(PostgresType)]` or `#[derive(PostgresEnum)]` types
+/// its signature uses.  Other items it depends on, such as a named `extension_sql!` block, can be
+/// listed with `#[pg_extern(requires = [...])]`.
 #[proc_macro_attribute]
 pub fn pg_extern(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_extern_attributes(TokenStream2::from(attr));
+    if let Err(message) = validate_extern_attributes(&args) {
+        panic!("#[pg_extern] {}", message);
+    }
     let is_raw = args.contains(&ExternArgs::Raw);
     let no_guard = args.contains(&ExternArgs::NoGuard);
 
     let ast = parse_macro_input!(item as syn::Item);
     match ast {
-        Item::Fn(func) => rewrite_item_fn(func, is_raw, no_guard).into(),
+        Item::Fn(func) => {
+            if let Err(message) = validate_default_args(&func) {
+                panic!("#[pg_extern] {}", message);
+            }
+            rewrite_item_fn(func, is_raw, no_guard).into()
+        }
         _ => panic!("#[pg_extern] can only be applied to top-level functions"),
     }
 }
@@ -199,7 +309,334 @@ fn rewrite_item_fn(mut func: ItemFn, is_raw: bool, no_guard: bool) -> proc_macro
     }
 }
 
-#[proc_macro_derive(PostgresEnum)]
+/// Declare a function as `#[pg_proc]` to indicate that it can be used by Postgres as a procedure,
+/// via `CALL`.  Procedures require Postgres 11 or later.
+///
+/// `cargo pgx schema` generates a `CREATE PROCEDURE` statement for it.  A procedure can't return
+/// a value, but its `&mut` arguments are `INOUT` parameters, and the values they hold when the
+/// procedure returns are returned to the caller.  Use `&mut Option<T>` for one that may be NULL.
+///
+/// When `CALL`ed outside of a transaction block, a procedure may commit and roll back.  Take a
+/// `pg_sys::FunctionCallInfo` as the last argument and pass it to `Spi::connect_nonatomic()`.
+///
+/// Procedures have no volatility or parallel-safety, so `#[pg_proc]` only accepts the `no_guard`,
+/// `sql`, and `requires` options of `#[pg_extern]`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_proc]
+/// fn add_to(value: i32, total: &mut i32) {
+///     *total += value;
+/// }
+///
+/// #[pg_proc]
+/// fn insert_in_batches(batches: i32, fcinfo: pg_sys::FunctionCallInfo) {
+///     Spi::connect_nonatomic(fcinfo, |client| {
+///         for batch in 0..batches {
+///             client.update(&format!("INSERT INTO t VALUES ({})", batch), None, None);
+///             client.commit();
+///         }
+///     });
+/// }
+/// ```
+///
+/// ```sql
+/// CALL add_to(1, 41);  -- returns a row with total = 42
+/// CALL insert_in_batches(10);
+/// ```
+#[proc_macro_attribute]
+pub fn pg_proc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_extern_attributes(TokenStream2::from(attr));
+    for arg in &args {
+        match arg {
+            ExternArgs::NoGuard | ExternArgs::Sql(_) | ExternArgs::Requires(_) => {}
+            other => panic!(
+                "#[pg_proc] only accepts `no_guard`, `sql`, and `requires`, not {:?}",
+                other
+            ),
+        }
+    }
+    let no_guard = args.contains(&ExternArgs::NoGuard);
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => {
+            if let Err(message) = validate_default_args(&func) {
+                panic!("#[pg_proc] {}", message);
+            }
+            rewrite_procedure_fn(func, no_guard).into()
+        }
+        _ => panic!("#[pg_proc] can only be applied to top-level functions"),
+    }
+}
+
+fn rewrite_procedure_fn(func: ItemFn, no_guard: bool) -> proc_macro2::TokenStream {
+    if let syn::ReturnType::Type(_, ty) = &func.sig.output {
+        if quote! {#ty}.to_string() != "( )" {
+            panic!("#[pg_proc] functions must not return a value.  Use `&mut` arguments for INOUT parameters");
+        }
+    }
+
+    let func_name = &func.sig.ident;
+    let vis = &func.vis;
+    let func_span = func.span();
+    let func_name_wrapper = Ident::new(&format!("{}_wrapper", func_name), func_span);
+    let finfo_name = Ident::new(
+        &format!("pg_finfo_{}_wrapper", func_name),
+        Span::call_site(),
+    );
+
+    let mut get_args = Vec::new();
+    let mut arg_list = Vec::new();
+    let mut null_checks = Vec::new();
+    let mut inout_args = Vec::new();
+    for (i, arg) in func.sig.inputs.iter().enumerate() {
+        let (ident, ty) = match arg {
+            syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => (&pat_ident.ident, pat_type.ty.as_ref()),
+                _ => panic!("Unrecognized function arg type"),
+            },
+            syn::FnArg::Receiver(_) => panic!("Functions that take self are not supported"),
+        };
+        let name = Ident::new(&format!("{}_", ident), ident.span());
+
+        // `&mut T` arguments are INOUT parameters
+        let (ty, is_inout) = match ty {
+            syn::Type::Reference(tref) if tref.mutability.is_some() => (tref.elem.as_ref(), true),
+            _ => (ty, false),
+        };
+
+        let value = if type_matches(ty, "pg_sys :: FunctionCallInfo") {
+            quote! { fcinfo }
+        } else if is_fcinfo_handle(ty) {
+            quote! { pgx::FcInfo::from_ptr(fcinfo) }
+        } else if type_matches(ty, "Option") {
+            let option_type = extract_option_type(ty);
+            quote! { pgx::pg_getarg::<#option_type>(fcinfo, #i) }
+        } else {
+            null_checks.push(quote! { pgx::pg_arg_is_null(fcinfo, #i) });
+            quote! { pgx::pg_getarg::<#ty>(fcinfo, #i).unwrap_or_else(|| panic!("{} is null", stringify!{#ident})) }
+        };
+
+        if is_inout {
+            get_args.push(quote_spanned! {ident.span()=> let mut #name = #value; });
+            arg_list.push(quote! { &mut #name });
+            inout_args.push(name);
+        } else {
+            get_args.push(quote_spanned! {ident.span()=> let #name = #value; });
+            arg_list.push(quote! { #name });
+        }
+    }
+
+    let num_inout = inout_args.len();
+    let call = quote! {
+        #(#get_args)*
+        #func_name(#(#arg_list),*);
+        [#(#inout_args.into_datum()),*]
+    };
+    let call = if no_guard {
+        quote! { { #call } }
+    } else {
+        quote! { pg_sys::guard::guard(|| { #call }) }
+    };
+
+    // like a function, a NULL for an argument that isn't an Option<T> means the procedure isn't
+    // called, and its INOUT parameters are all NULL
+    let call = if null_checks.is_empty() {
+        call
+    } else {
+        quote! {
+            if #(#null_checks)||* {
+                [None; #num_inout]
+            } else {
+                #call
+            }
+        }
+    };
+
+    // a procedure with INOUT parameters returns a record of them, even if there's only one
+    let result = match num_inout {
+        0 => quote! { pgx::pg_return_void() },
+        _ => quote! {
+            let mut tupdesc: *mut pgx::pg_sys::TupleDescData = std::ptr::null_mut();
+            if pgx::pg_sys::get_call_result_type(fcinfo, std::ptr::null_mut(), &mut tupdesc) != pgx::pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE {
+                pgx::error!("return type must be a row type");
+            }
+            let tupdesc = pgx::pg_sys::BlessTupleDesc(tupdesc);
+
+            let mut datums = values.iter().map(|v| v.unwrap_or(0)).collect::<Vec<_>>();
+            let mut nulls = values.iter().map(|v| v.is_none()).collect::<Vec<_>>();
+            let heap_tuple = pgx::pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+            pgx::heap_tuple_get_datum(heap_tuple)
+        },
+    };
+
+    quote_spanned! {func_span=>
+        #func
+
+        #[no_mangle]
+        pub extern "C" fn #finfo_name() -> &'static pg_sys::Pg_finfo_record {
+            const V1_API: pg_sys::Pg_finfo_record = pg_sys::Pg_finfo_record { api_version: 1 };
+            &V1_API
+        }
+
+        #[allow(clippy::missing_safety_doc)]
+        #[allow(clippy::redundant_closure)]
+        #[allow(unused_variables)]
+        #[pg_guard]
+        #vis unsafe extern "C" fn #func_name_wrapper(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+            let values: [Option<pg_sys::Datum>; #num_inout] = #call;
+
+            #result
+        }
+    }
+}
+
+/// Declare a function as `#[pg_eventtrigger]` to indicate that it can be used by Postgres as an
+/// event trigger function, via `CREATE EVENT TRIGGER ... EXECUTE PROCEDURE`.
+///
+/// The function must take a single `&PgEventTrigger` argument, describing the event and the
+/// command that fired it, and must not return a value.  `cargo pgx schema` generates a
+/// `CREATE FUNCTION ... RETURNS event_trigger` statement for it.
+#[proc_macro_attribute]
+pub fn pg_eventtrigger(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => rewrite_event_trigger_fn(func).into(),
+        _ => panic!("#[pg_eventtrigger] can only be applied to top-level functions"),
+    }
+}
+
+fn rewrite_event_trigger_fn(func: ItemFn) -> proc_macro2::TokenStream {
+    if func.sig.inputs.len() != 1 {
+        panic!("#[pg_eventtrigger] functions must take a single `&PgEventTrigger` argument");
+    }
+    if let syn::ReturnType::Type(..) = func.sig.output {
+        panic!("#[pg_eventtrigger] functions must not return a value");
+    }
+
+    let func_name = &func.sig.ident;
+    let vis = &func.vis;
+    let func_span = func.span();
+    let func_name_wrapper = Ident::new(&format!("{}_wrapper", func_name), func_span);
+    let finfo_name = Ident::new(
+        &format!("pg_finfo_{}_wrapper", func_name),
+        Span::call_site(),
+    );
+
+    quote_spanned! {func_span=>
+        #func
+
+        #[no_mangle]
+        pub extern "C" fn #finfo_name() -> &'static pg_sys::Pg_finfo_record {
+            const V1_API: pg_sys::Pg_finfo_record = pg_sys::Pg_finfo_record { api_version: 1 };
+            &V1_API
+        }
+
+        #[allow(clippy::missing_safety_doc)]
+        #[pg_guard]
+        #vis unsafe extern "C" fn #func_name_wrapper(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+            let trigger = PgEventTrigger::from_fcinfo(fcinfo);
+            #func_name(&trigger);
+
+            // event trigger functions return nothing
+            pg_return_void()
+        }
+    }
+}
+
+/// Declare an inline module as `#[pg_schema]` to create the functions and types within it in a
+/// schema named after the module, which `cargo pgx schema` creates with
+/// `CREATE SCHEMA IF NOT EXISTS`.
+///
+/// Functions elsewhere whose signatures use its `#[derive(PostgresType)]` or
+/// `#[derive(PostgresEnum)]` types, whether as `my_schema::MyType` or imported, are declared with
+/// the schema-qualified type name, and the types look themselves up by that name at runtime, so
+/// the schema needn't be on the `search_path`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_schema]
+/// mod geo {
+///     use pgx::*;
+///
+///     #[derive(PostgresEnum)]
+///     pub enum Direction {
+///         North,
+///         South,
+///     }
+///
+///     #[pg_extern]
+///     fn reverse(direction: Direction) -> Direction {
+///         match direction {
+///             Direction::North => Direction::South,
+///             Direction::South => Direction::North,
+///         }
+///     }
+/// }
+///
+/// // created as `public.north() RETURNS geo.Direction`
+/// #[pg_extern]
+/// fn north() -> geo::Direction {
+///     geo::Direction::North
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn pg_schema(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut module = parse_macro_input!(item as syn::ItemMod);
+    let schema = module.ident.to_string();
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => panic!("#[pg_schema] can only be applied to inline modules"),
+    };
+
+    // tell the types' derives which schema they're in
+    let is_postgres_type = |attrs: &[Attribute]| {
+        attrs.iter().any(|a| {
+            let string = a.to_token_stream().to_string();
+            a.path.is_ident("derive")
+                && (string.contains("PostgresType") || string.contains("PostgresEnum"))
+        })
+    };
+    let schema_attr: Attribute = syn::parse_quote! { #[pgx_schema = #schema] };
+    for item in items.iter_mut() {
+        match item {
+            Item::Struct(strct) if is_postgres_type(&strct.attrs) => {
+                strct.attrs.push(schema_attr.clone())
+            }
+            Item::Enum(enm) if is_postgres_type(&enm.attrs) => enm.attrs.push(schema_attr.clone()),
+            _ => {}
+        }
+    }
+
+    module.into_token_stream().into()
+}
+
+/// The name a derived type is looked up by at runtime, which is qualified with its schema when
+/// it's declared in a `#[pg_schema]` module
+fn type_lookup_name(ident: &Ident, attrs: &[Attribute]) -> String {
+    for attr in attrs {
+        if attr.path.is_ident("pgx_schema") {
+            if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(schema),
+                ..
+            })) = attr.parse_meta()
+            {
+                return format!("{}.{}", schema.value(), ident);
+            }
+        }
+    }
+
+    ident.to_string()
+}
+
+#[proc_macro_derive(PostgresEnum, attributes(pgx_schema))]
 pub fn postgres_enum(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -208,8 +645,8 @@ pub fn postgres_enum(input: TokenStream) -> TokenStream {
 
 fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     let mut stream = proc_macro2::TokenStream::new();
+    let enum_name = type_lookup_name(&ast.ident, &ast.attrs);
     let enum_ident = ast.ident;
-    let enum_name = enum_ident.to_string();
 
     // validate that we're only operating on an enum
     let enum_data = match ast.data {
@@ -265,7 +702,33 @@ fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     stream
 }
 
-#[proc_macro_derive(PostgresType, attributes(inoutfuncs))]
+/// Derive a Postgres type for a struct, which by default is a varlena type whose text
+/// representation is the struct serialized as JSON
+///
+/// Use `#[inoutfuncs = "Custom"]` to provide your own `InOutFuncs` implementation instead, or
+/// `#[composite]` to make it a composite (row) type whose attributes are the struct's fields, in
+/// declaration order.  A composite can be returned from a set-returning function as
+/// `impl Iterator<Item = MyComposite>`, which is declared `RETURNS SETOF mycomposite`.  Each
+/// field's type must implement `IntoDatum` and `FromDatum`, and `Option<T>` fields are nullable.
+///
+/// A composite's fields can be annotated with `#[pgx(sql_type = "numeric(10, 2)")]` to declare
+/// the attribute with a specific SQL type, rather than the one its Rust type translates to, and
+/// with `#[pgx(not_null)]` to make converting a field that's NULL into a Datum an error.
+/// Postgres doesn't allow constraints on a composite's attributes, so `not_null` isn't part of
+/// the generated `CREATE TYPE`.
+///
+/// ```rust,no_run
+/// # use pgx::*;
+/// #[derive(PostgresType)]
+/// #[composite]
+/// pub struct LineItem {
+///     sku: String,
+///     #[pgx(sql_type = "numeric(10, 2)", not_null)]
+///     price: Numeric,
+///     note: Option<String>,
+/// }
+/// ```
+#[proc_macro_derive(PostgresType, attributes(inoutfuncs, composite, pgx_schema, pgx))]
 pub fn postgres_type(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -276,16 +739,33 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
     let name = &ast.ident;
     let generics = &ast.generics;
     let has_lifetimes = generics.lifetimes().next();
-    let name_string = format!("{}", name);
+    let name_string = type_lookup_name(name, &ast.attrs);
     let funcname_in = Ident::new(&format!("{}_in", name).to_lowercase(), name.span());
     let funcname_out = Ident::new(&format!("{}_out", name).to_lowercase(), name.span());
     let mut args = parse_postgres_type_args(&ast.attrs);
     let mut stream = proc_macro2::TokenStream::new();
 
     // validate that we're only operating on a struct
-    match ast.data {
-        Data::Struct(_) => { /* this is okay */ }
+    let struct_data = match ast.data {
+        Data::Struct(s) => s,
         _ => panic!("#[derive(PostgresType)] can only be applied to structs"),
+    };
+
+    if args.contains(&PostgresTypeAttribute::Composite) {
+        if args.len() > 1 {
+            panic!("#[composite] types can't also have #[inoutfuncs]");
+        } else if generics.params.iter().next().is_some() {
+            panic!("#[composite] types can't be generic");
+        }
+        return impl_postgres_composite_type(name, &name_string, struct_data);
+    }
+
+    if struct_data
+        .fields
+        .iter()
+        .any(|field| field.attrs.iter().any(|attr| attr.path.is_ident("pgx")))
+    {
+        panic!("#[pgx] field attributes are only supported on #[composite] types");
     }
 
     if args.is_empty() {
@@ -445,10 +925,110 @@ fn impl_guc_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     stream
 }
 
+/// A `#[composite]` type converts to and from a row of its fields, using the row type's
+/// `TupleDesc` from the type cache
+fn impl_postgres_composite_type(
+    name: &Ident,
+    lookup_name: &str,
+    data: syn::DataStruct,
+) -> proc_macro2::TokenStream {
+    let name_string = name.to_string();
+    let fields = match data.fields {
+        syn::Fields::Named(fields) => fields.named,
+        _ => panic!("#[composite] types must have named fields"),
+    };
+    let numfields = fields.len();
+    let idents = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+    let i = (0..numfields).map(syn::Index::from);
+    let attnos = 1..=numfields;
+    let field_args = fields
+        .iter()
+        .map(|field| {
+            parse_composite_field_args(field).unwrap_or_else(|e| {
+                panic!("{}.{}: {}", name_string, field.ident.as_ref().unwrap(), e)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // a `not_null` field that converts to NULL is an error, rather than a value we can't read back
+    let on_null = fields.iter().zip(&field_args).map(|(field, args)| {
+        if args.not_null {
+            let field_name = format!("{}.{}", name_string, field.ident.as_ref().unwrap());
+            quote! { panic!("{} is NOT NULL", #field_name) }
+        } else {
+            quote! {}
+        }
+    });
+
+    // NULL attributes are only allowed for `Option<T>` fields
+    let getters = fields.iter().zip(attnos).map(|(field, attno)| {
+        let is_option = match &field.ty {
+            syn::Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "Option"),
+            _ => false,
+        };
+
+        if is_option {
+            quote! { tuple.get_by_index(#attno) }
+        } else {
+            let field_name = format!("{}.{}", name_string, field.ident.as_ref().unwrap());
+            quote! { tuple.get_by_index(#attno).unwrap_or_else(|| panic!("{} is NULL", #field_name)) }
+        }
+    });
+
+    quote! {
+        impl pgx::FromDatum for #name {
+            unsafe fn from_datum(datum: pgx::pg_sys::Datum, is_null: bool, typoid: pgx::pg_sys::Oid) -> Option<#name> {
+                let tuple = <pgx::PgHeapTuple as pgx::FromDatum>::from_datum(datum, is_null, typoid)?;
+                if tuple.len() != #numfields {
+                    panic!("{} has {} fields, but the row has {} attributes", #name_string, #numfields, tuple.len());
+                }
+
+                Some(#name {
+                    #( #idents: #getters, )*
+                })
+            }
+        }
+
+        impl pgx::IntoDatum for #name {
+            fn into_datum(self) -> Option<pgx::pg_sys::Datum> {
+                let mut datums: [usize; #numfields] = [0; #numfields];
+                let mut nulls: [bool; #numfields] = [false; #numfields];
+                #(
+                    match pgx::IntoDatum::into_datum(self.#idents) {
+                        Some(datum) => { datums[#i] = datum as usize; },
+                        None => { #on_null nulls[#i] = true; }
+                    }
+                )*
+
+                unsafe {
+                    let tupdesc = pgx::pg_sys::lookup_rowtype_tupdesc(Self::type_oid(), -1);
+                    let heap_tuple = pgx::pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+                    pgx::release_tupdesc(tupdesc);
+                    Some(pgx::heap_tuple_get_datum(heap_tuple))
+                }
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                // it's looked up for every row, so only resolve it once
+                static TYPE_OID: pgx::TypeOidCache = pgx::TypeOidCache::new();
+                pgx::extension_type_oid(#lookup_name, &TYPE_OID)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 enum PostgresTypeAttribute {
     Custom,
     Default,
+    Composite,
 }
 
 fn parse_postgres_type_args(attributes: &[Attribute]) -> HashSet<PostgresTypeAttribute> {
@@ -463,6 +1043,7 @@ fn parse_postgres_type_args(attributes: &[Attribute]) -> HashSet<PostgresTypeAtt
 
                 _ => panic!("unrecognized PostgresType property: {}", a.tokens),
             },
+            "composite" => categorized_attributes.insert(PostgresTypeAttribute::Composite),
             _ => panic!(
                 "unrecognized PostgresType attribute: {}",
                 a.path.to_token_stream().to_string()
@@ -473,8 +1054,38 @@ fn parse_postgres_type_args(attributes: &[Attribute]) -> HashSet<PostgresTypeAtt
     categorized_attributes
 }
 
+/// Declare raw SQL to be included in the extension's schema by `cargo pgx schema`.
+///
+/// The SQL must be a raw string, optionally followed by ordering hints:
+///
+/// - `name = "..."`: a name other `extension_sql!` blocks can `require`
+/// - `requires = [...]`: items (functions, types, or named `extension_sql!` blocks) that must
+///   be created before this SQL runs, even if they're declared in another file
+/// - `finalize`: run this SQL after everything else in the extension's schema
+///
+/// ```rust,no_run
+/// # use pgx_macros::extension_sql;
+/// extension_sql!(
+///     r#"CREATE TABLE answers (value my_type);"#,
+///     name = "answers_table",
+///     requires = [MyType]
+/// );
+/// ```
 #[proc_macro]
 pub fn extension_sql(_: TokenStream) -> TokenStream {
     // we don't want to output anything here
     TokenStream::new()
 }
+
+/// Same as `extension_sql!`, but the SQL is read from a file whose path is relative to the
+/// source file containing the macro, like `include_str!`
+///
+/// ```rust,no_run
+/// # use pgx_macros::extension_sql_file;
+/// extension_sql_file!("../sql/views.sql", requires = [answers_table], finalize);
+/// ```
+#[proc_macro]
+pub fn extension_sql_file(_: TokenStream) -> TokenStream {
+    // we don't want to output anything here
+    TokenStream::new()
+}
@@ -65,6 +65,7 @@ impl PgGuardRewriter {
         let func_span = func.span();
         let rewritten_args = self.rewrite_args(func.clone(), is_raw);
         let rewritten_return_type = self.rewrite_return_type(func.clone());
+        let null_args = self.rewrite_null_args(func.clone(), is_raw);
         let generics = &func.sig.generics;
         let func_name_wrapper = Ident::new(
             &format!("{}_wrapper", &func.sig.ident.to_string()),
@@ -98,6 +99,25 @@ impl PgGuardRewriter {
             }
         };
 
+        // a NULL argument that isn't an Option<T> means the function isn't called at all.  It
+        // returns NULL instead, or no rows if it's a set-returning function
+        let (null_check, srf_null_check) = match null_args {
+            Some(null_args) => (
+                quote! {
+                    if #null_args {
+                        return pgx::pg_return_null(fcinfo);
+                    }
+                },
+                quote! {
+                    if #null_args {
+                        pgx::srf_return_done(fcinfo, &mut funcctx);
+                        return pgx::pg_return_null(fcinfo);
+                    }
+                },
+            ),
+            None => (quote! {}, quote! {}),
+        };
+
         let prolog = quote! {
             #func
 
@@ -111,7 +131,7 @@ impl PgGuardRewriter {
                     vis,
                     func_name_wrapper,
                     generics,
-                    func_call,
+                    quote! { #null_check #func_call },
                     rewritten_return_type,
                 ),
                 true,
@@ -119,6 +139,19 @@ impl PgGuardRewriter {
 
             CategorizedType::Tuple(_types) => (PgGuardRewriter::impl_tuple_udf(func), false),
 
+            CategorizedType::Record(types) => (
+                PgGuardRewriter::impl_record_udf(
+                    types,
+                    func_span,
+                    prolog,
+                    vis,
+                    func_name_wrapper,
+                    generics,
+                    quote! { #null_check #func_call },
+                ),
+                true,
+            ),
+
             CategorizedType::Iterator(types) if types.len() == 1 => (
                 PgGuardRewriter::impl_setof_srf(
                     types,
@@ -128,6 +161,7 @@ impl PgGuardRewriter {
                     func_name_wrapper,
                     generics,
                     func_call,
+                    srf_null_check.clone(),
                     false,
                 ),
                 true,
@@ -142,6 +176,7 @@ impl PgGuardRewriter {
                     func_name_wrapper,
                     generics,
                     func_call,
+                    srf_null_check.clone(),
                     true,
                 ),
                 true,
@@ -156,6 +191,7 @@ impl PgGuardRewriter {
                     func_name_wrapper,
                     generics,
                     func_call,
+                    srf_null_check.clone(),
                     false,
                 ),
                 true,
@@ -170,6 +206,7 @@ impl PgGuardRewriter {
                     func_name_wrapper,
                     generics,
                     func_call,
+                    srf_null_check.clone(),
                     true,
                 ),
                 true,
@@ -219,6 +256,54 @@ impl PgGuardRewriter {
         }
     }
 
+    /// A function returning a `RecordType<(...)>` returns a single record, built using the tuple
+    /// descriptor Postgres derives from the function's OUT parameters
+    fn impl_record_udf(
+        types: Vec<String>,
+        func_span: Span,
+        prolog: proc_macro2::TokenStream,
+        vis: Visibility,
+        func_name_wrapper: Ident,
+        generics: &Generics,
+        func_call: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let numtypes = types.len();
+        let i = (0..numtypes).map(syn::Index::from);
+
+        quote_spanned! {func_span=>
+            #prolog
+
+            #[allow(clippy::missing_safety_doc)]
+            #[allow(clippy::redundant_closure)]
+            #[pg_guard]
+            #vis unsafe fn #func_name_wrapper #generics(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+
+                #func_call
+
+                let mut tupdesc: *mut pgx::pg_sys::TupleDescData = std::ptr::null_mut();
+
+                /* Build a tuple descriptor for our result type */
+                if pgx::pg_sys::get_call_result_type(fcinfo, std::ptr::null_mut(), &mut tupdesc) != pgx::pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE {
+                    pgx::error!("return type must be a row type");
+                }
+                let tupdesc = pgx::pg_sys::BlessTupleDesc(tupdesc);
+
+                let mut datums: [usize; #numtypes] = [0; #numtypes];
+                let mut nulls: [bool; #numtypes] = [false; #numtypes];
+                #(
+                    let datum = result.0.#i.into_datum();
+                    match datum {
+                        Some(datum) => { datums[#i] = datum as usize; },
+                        None => { nulls[#i] = true; }
+                    }
+                )*
+
+                let heap_tuple = pgx::pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+                pgx::heap_tuple_get_datum(heap_tuple)
+            }
+        }
+    }
+
     fn impl_setof_srf(
         types: Vec<String>,
         func_span: Span,
@@ -227,25 +312,12 @@ impl PgGuardRewriter {
         func_name_wrapper: Ident,
         generics: &Generics,
         func_call: proc_macro2::TokenStream,
+        null_check: proc_macro2::TokenStream,
         optional: bool,
     ) -> proc_macro2::TokenStream {
         let generic_type = proc_macro2::TokenStream::from_str(types.first().unwrap()).unwrap();
 
-        let result_handler = if optional {
-            quote! {
-                let result = match pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call result }) {
-                    Some(result) => result,
-                    None => {
-                        pgx::srf_return_done(fcinfo, &mut funcctx);
-                        return pgx::pg_return_null(fcinfo)
-                    }
-                };
-            }
-        } else {
-            quote! {
-                let result = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call result });
-            }
-        };
+        let result_handler = PgGuardRewriter::srf_result_handler(func_call, optional);
 
         quote_spanned! {func_span=>
             #prolog
@@ -265,6 +337,7 @@ impl PgGuardRewriter {
 
                     iterator_holder = pgx::PgBox::from_pg(funcctx.user_fctx as *mut IteratorHolder<#generic_type>);
 
+                    #null_check
                     #result_handler
 
                     iterator_holder.iter = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).leak_and_drop_on_delete(result);
@@ -299,6 +372,24 @@ impl PgGuardRewriter {
         }
     }
 
+    /// Calls the function, in the SRF's multi-call memory context, binding its iterator to
+    /// `result`.  A returned `None` becomes an empty iterator, so that it returns zero rows
+    /// through the same per-call path as an empty set does.
+    fn srf_result_handler(
+        func_call: proc_macro2::TokenStream,
+        optional: bool,
+    ) -> proc_macro2::TokenStream {
+        let result = if optional {
+            quote! { result.into_iter().flatten() }
+        } else {
+            quote! { result }
+        };
+
+        quote! {
+            let result = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call #result });
+        }
+    }
+
     fn impl_table_srf(
         types: Vec<String>,
         func_span: Span,
@@ -307,6 +398,7 @@ impl PgGuardRewriter {
         func_name_wrapper: Ident,
         generics: &Generics,
         func_call: proc_macro2::TokenStream,
+        null_check: proc_macro2::TokenStream,
         optional: bool,
     ) -> proc_macro2::TokenStream {
         let numtypes = types.len();
@@ -331,21 +423,7 @@ impl PgGuardRewriter {
         let composite_type = format!("({})", types.join(","));
         let generic_type = proc_macro2::TokenStream::from_str(&composite_type).unwrap();
 
-        let result_handler = if optional {
-            quote! {
-                let result = match pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call result }) {
-                    Some(result) => result,
-                    None => {
-                        pgx::srf_return_done(fcinfo, &mut funcctx);
-                        return pgx::pg_return_null(fcinfo)
-                    }
-                };
-            }
-        } else {
-            quote! {
-                let result = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call result });
-            }
-        };
+        let result_handler = PgGuardRewriter::srf_result_handler(func_call, optional);
 
         quote_spanned! {func_span=>
             #prolog
@@ -376,6 +454,7 @@ impl PgGuardRewriter {
                     });
                     iterator_holder = pgx::PgBox::from_pg(funcctx.user_fctx as *mut IteratorHolder<#generic_type>);
 
+                    #null_check
                     #result_handler
 
                     iterator_holder.iter = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).leak_and_drop_on_delete(result);
@@ -576,6 +655,15 @@ impl PgGuardRewriter {
         }
     }
 
+    pub fn rewrite_null_args(
+        &self,
+        func: ItemFn,
+        is_raw: bool,
+    ) -> Option<proc_macro2::TokenStream> {
+        let fsr = FunctionSignatureRewriter::new(func);
+        fsr.null_args(is_raw)
+    }
+
     pub fn rewrite_return_type(&self, func: ItemFn) -> proc_macro2::TokenStream {
         let fsr = FunctionSignatureRewriter::new(func);
         let result = fsr.return_type();
@@ -617,6 +705,20 @@ impl FunctionSignatureRewriter {
                     stream.extend(quote! {
                         result
                     });
+                } else if type_matches(type_, "Result") {
+                    // an Err is raised as an ERROR, and an Ok(None) is NULL
+                    stream.extend(quote! {
+                        match result.into_datum() {
+                            Some(datum) => datum,
+                            None => pgx::pg_return_null(fcinfo)
+                        }
+                    });
+                } else if type_matches(type_, "Materialize")
+                    || type_matches(type_, "pgx :: Materialize")
+                {
+                    stream.extend(quote! {
+                        result.materialize(fcinfo)
+                    });
                 } else if type_matches(type_, "()") {
                     stream.extend(quote! {
                        pgx::pg_return_void()
@@ -654,7 +756,7 @@ impl FunctionSignatureRewriter {
                         let is_option = type_matches(type_, "Option");
 
                         if have_fcinfo {
-                            panic!("When using `pg_sys::FunctionCallInfo` as an argument it must be the last argument")
+                            panic!("When using `pg_sys::FunctionCallInfo` or `FcInfo` as an argument it must be the last argument")
                         }
 
                         let ts = if is_option {
@@ -667,6 +769,11 @@ impl FunctionSignatureRewriter {
                             quote_spanned! {ident.span()=>
                                 let #name = fcinfo;
                             }
+                        } else if is_fcinfo_handle(type_) {
+                            have_fcinfo = true;
+                            quote_spanned! {ident.span()=>
+                                let #name = pgx::FcInfo::from_ptr(fcinfo);
+                            }
                         } else if is_raw {
                             quote_spanned! {ident.span()=>
                                 let #name = pgx::pg_getarg_datum_raw(fcinfo, #i) as #type_;
@@ -689,6 +796,32 @@ impl FunctionSignatureRewriter {
         stream
     }
 
+    /// An expression that's true when any argument that isn't an `Option<T>` is NULL, in which
+    /// case the function isn't called and returns NULL, just as if it were `STRICT`
+    fn null_args(&self, is_raw: bool) -> Option<proc_macro2::TokenStream> {
+        if is_raw {
+            return None;
+        }
+
+        let mut checks = Vec::new();
+        for (i, arg) in self.func.sig.inputs.iter().enumerate() {
+            if let FnArg::Typed(ty) = arg {
+                if !type_matches(&ty.ty, "Option")
+                    && !type_matches(&ty.ty, "pg_sys :: FunctionCallInfo")
+                    && !is_fcinfo_handle(&ty.ty)
+                {
+                    checks.push(quote! { pgx::pg_arg_is_null(fcinfo, #i) });
+                }
+            }
+        }
+
+        if checks.is_empty() {
+            None
+        } else {
+            Some(quote! { #(#checks)||* })
+        }
+    }
+
     fn return_type_is_datum(&self) -> bool {
         match &self.func.sig.output {
             ReturnType::Default => false,
@@ -697,12 +830,17 @@ impl FunctionSignatureRewriter {
     }
 }
 
-fn type_matches(ty: &Type, pattern: &str) -> bool {
+pub(crate) fn type_matches(ty: &Type, pattern: &str) -> bool {
     let type_string = format!("{}", quote! {#ty});
     type_string.starts_with(pattern)
 }
 
-fn extract_option_type(ty: &Type) -> proc_macro2::TokenStream {
+pub(crate) fn is_fcinfo_handle(ty: &Type) -> bool {
+    let type_string = format!("{}", quote! {#ty});
+    type_string == "FcInfo" || type_string == "pgx :: FcInfo"
+}
+
+pub(crate) fn extract_option_type(ty: &Type) -> proc_macro2::TokenStream {
     match ty {
         Type::Path(path) => {
             let mut stream = proc_macro2::TokenStream::new();
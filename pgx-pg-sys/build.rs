@@ -4,7 +4,9 @@
 extern crate build_deps;
 
 use bindgen::callbacks::MacroParsingBehavior;
-use pgx_utils::{get_pg_config, get_pgx_config_path, prefix_path, run_pg_config};
+use pgx_utils::{
+    get_pg_config, get_pgx_config_path, pg_config_override_var, prefix_path, run_pg_config,
+};
 use quote::quote;
 use rayon::prelude::*;
 use std::collections::HashSet;
@@ -74,6 +76,12 @@ fn main() -> Result<(), std::io::Error> {
     build_deps::rerun_if_changed_paths("include/*").unwrap();
     build_deps::rerun_if_changed_paths("cshim/pgx-cshim.c").unwrap();
     build_deps::rerun_if_changed_paths("cshim/Makefile").unwrap();
+    for major_version in &major_versions {
+        println!(
+            "cargo:rerun-if-env-changed={}",
+            pg_config_override_var(*major_version)
+        );
+    }
 
     let shim_mutex = Mutex::new(());
 
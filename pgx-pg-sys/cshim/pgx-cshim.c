@@ -18,6 +18,7 @@
 #include "nodes/pathnodes.h"
 #endif
 #include "parser/parsetree.h"
+#include "portability/instr_time.h"
 #include "utils/memutils.h"
 #include "utils/builtins.h"
 
@@ -44,6 +45,23 @@ void pgx_ereport(int level, int code, char *message, char *file, int lineno, int
                     errmsg("%s", message), errcontext_msg("%s:%d:%d", file, lineno, colno)));
 }
 
+PGDLLEXPORT void pgx_ereport_detail(int level, int code, char *message, char *detail);
+void pgx_ereport_detail(int level, int code, char *message, char *detail) {
+    ereport(level,
+            (errcode(code),
+                    errmsg("%s", message), detail != NULL ? errdetail("%s", detail) : 0));
+}
+
+PGDLLEXPORT void pgx_ereport_full(int level, int code, char *message, char *detail, char *hint, char *context);
+void pgx_ereport_full(int level, int code, char *message, char *detail, char *hint, char *context) {
+    ereport(level,
+            (errcode(code),
+                    errmsg("%s", message),
+                    detail != NULL ? errdetail("%s", detail) : 0,
+                    hint != NULL ? errhint("%s", hint) : 0,
+                    context != NULL ? errcontext_msg("%s", context) : 0));
+}
+
 PGDLLEXPORT void pgx_SET_VARSIZE(struct varlena *ptr, int size);
 void pgx_SET_VARSIZE(struct varlena *ptr, int size) {
     SET_VARSIZE(ptr, size);
@@ -81,6 +99,20 @@ Oid pgx_HeapTupleHeaderGetOid(HeapTupleHeader htup_header) {
 }
 #endif
 
+PGDLLEXPORT void pgx_INSTR_TIME_SET_CURRENT(instr_time *time);
+void pgx_INSTR_TIME_SET_CURRENT(instr_time *time) {
+    INSTR_TIME_SET_CURRENT(*time);
+}
+
+PGDLLEXPORT double pgx_instr_time_elapsed(instr_time *start);
+double pgx_instr_time_elapsed(instr_time *start) {
+    instr_time now;
+
+    INSTR_TIME_SET_CURRENT(now);
+    INSTR_TIME_SUBTRACT(now, *start);
+    return INSTR_TIME_GET_DOUBLE(now);
+}
+
 PGDLLEXPORT char *pgx_GETSTRUCT(HeapTuple tuple);
 char *pgx_GETSTRUCT(HeapTuple tuple) {
     return GETSTRUCT(tuple);
@@ -604,6 +604,18 @@ extern "C" {
     pub fn BeginCommand(commandTag: *const ::std::os::raw::c_char, dest: CommandDest);
 }
 #[pg_guard]
+extern "C" {
+    pub fn BeginCopyFrom(
+        pstate: *mut ParseState,
+        rel: Relation,
+        filename: *const ::std::os::raw::c_char,
+        is_program: bool,
+        data_source_cb: copy_data_source_cb,
+        attnamelist: *mut List,
+        options: *mut List,
+    ) -> CopyState;
+}
+#[pg_guard]
 extern "C" {
     pub fn BeginReportingGUCOptions();
 }
@@ -620,6 +632,14 @@ extern "C" {
     pub fn BootStrapXLOG();
 }
 #[pg_guard]
+extern "C" {
+    pub fn BufferGetBlockNumber(buffer: Buffer) -> BlockNumber;
+}
+#[pg_guard]
+extern "C" {
+    pub fn BufferGetLSNAtomic(buffer: Buffer) -> XLogRecPtr;
+}
+#[pg_guard]
 extern "C" {
     pub fn BuildDescForRelation(schema: *mut List) -> TupleDesc;
 }
@@ -660,6 +680,18 @@ extern "C" {
     ) -> HeapTuple;
 }
 #[pg_guard]
+extern "C" {
+    pub fn CacheRegisterRelcacheCallback(func: RelcacheCallbackFunction, arg: Datum);
+}
+#[pg_guard]
+extern "C" {
+    pub fn CacheRegisterSyscacheCallback(
+        cacheid: ::std::os::raw::c_int,
+        func: SyscacheCallbackFunction,
+        arg: Datum,
+    );
+}
+#[pg_guard]
 extern "C" {
     pub fn CachedPlanGetTargetList(
         plansource: *mut CachedPlanSource,
@@ -845,6 +877,10 @@ extern "C" {
     pub fn CopyErrorData() -> *mut ErrorData;
 }
 #[pg_guard]
+extern "C" {
+    pub fn CopyFromErrorCallback(arg: *mut ::std::os::raw::c_void);
+}
+#[pg_guard]
 extern "C" {
     pub fn CopyIndexTuple(source: IndexTuple) -> IndexTuple;
 }
@@ -1460,6 +1496,10 @@ extern "C" {
     pub fn EndCommand(commandTag: *const ::std::os::raw::c_char, dest: CommandDest);
 }
 #[pg_guard]
+extern "C" {
+    pub fn EndCopyFrom(cstate: CopyState);
+}
+#[pg_guard]
 extern "C" {
     pub fn EndParallelWorkerTransaction();
 }
@@ -2437,6 +2477,26 @@ extern "C" {
     pub fn GUC_check_errcode(sqlerrcode: ::std::os::raw::c_int);
 }
 #[pg_guard]
+extern "C" {
+    pub fn GenericXLogAbort(state: *mut GenericXLogState);
+}
+#[pg_guard]
+extern "C" {
+    pub fn GenericXLogFinish(state: *mut GenericXLogState) -> XLogRecPtr;
+}
+#[pg_guard]
+extern "C" {
+    pub fn GenericXLogRegisterBuffer(
+        state: *mut GenericXLogState,
+        buffer: Buffer,
+        flags: ::std::os::raw::c_int,
+    ) -> Page;
+}
+#[pg_guard]
+extern "C" {
+    pub fn GenericXLogStart(relation: Relation) -> *mut GenericXLogState;
+}
+#[pg_guard]
 extern "C" {
     pub fn GetActiveSnapshot() -> Snapshot;
 }
@@ -3297,6 +3357,10 @@ extern "C" {
     pub fn IsBackendPid(pid: ::std::os::raw::c_int) -> bool;
 }
 #[pg_guard]
+extern "C" {
+    pub fn IsBinaryCoercible(srctype: Oid, targettype: Oid) -> bool;
+}
+#[pg_guard]
 extern "C" {
     pub fn IsImportableForeignTable(
         tablename: *const ::std::os::raw::c_char,
@@ -3464,6 +3528,10 @@ extern "C" {
     ) -> LockAcquireResult;
 }
 #[pg_guard]
+extern "C" {
+    pub fn LockBuffer(buffer: Buffer, mode: ::std::os::raw::c_int);
+}
+#[pg_guard]
 extern "C" {
     pub fn LockCheckConflicts(
         lockMethodTable: LockMethod,
@@ -3609,6 +3677,10 @@ extern "C" {
     ) -> *mut TransitionCaptureState;
 }
 #[pg_guard]
+extern "C" {
+    pub fn MarkBufferDirty(buffer: Buffer);
+}
+#[pg_guard]
 extern "C" {
     pub fn MarkCurrentTransactionIdLoggedIfAny();
 }
@@ -4545,6 +4617,10 @@ extern "C" {
     pub fn ReThrowError(edata: *mut ErrorData);
 }
 #[pg_guard]
+extern "C" {
+    pub fn ReadBuffer(reln: Relation, blockNum: BlockNumber) -> Buffer;
+}
+#[pg_guard]
 extern "C" {
     pub fn ReadDir(dir: *mut DIR, dirname: *const ::std::os::raw::c_char) -> *mut dirent;
 }
@@ -4828,6 +4904,10 @@ extern "C" {
     pub fn RelationSupportsSysCache(relid: Oid) -> bool;
 }
 #[pg_guard]
+extern "C" {
+    pub fn ReleaseBuffer(buffer: Buffer);
+}
+#[pg_guard]
 extern "C" {
     pub fn ReleaseCachedPlan(plan: *mut CachedPlan, useResOwner: bool);
 }
@@ -5983,6 +6063,10 @@ extern "C" {
     pub fn TypenameGetTypidExtended(typname: *const ::std::os::raw::c_char, temp_ok: bool) -> Oid;
 }
 #[pg_guard]
+extern "C" {
+    pub fn UnlockReleaseBuffer(buffer: Buffer);
+}
+#[pg_guard]
 extern "C" {
     pub fn UnpinPortal(portal: Portal);
 }
@@ -24416,6 +24500,10 @@ extern "C" {
     ) -> usize;
 }
 #[pg_guard]
+extern "C" {
+    pub fn stringToQualifiedNameList(string: *const ::std::os::raw::c_char) -> *mut List;
+}
+#[pg_guard]
 extern "C" {
     pub fn stringTypeDatum(
         tp: Type,
@@ -27199,6 +27287,10 @@ extern "C" {
     pub static in6addr_nodelocal_allnodes: in6_addr;
 }
 #[pg_guard]
+extern "C" {
+    pub static mut ActivePortal: Portal;
+}
+#[pg_guard]
 extern "C" {
     pub static mut Array_nulls: bool;
 }
@@ -30036,6 +30128,11 @@ pub struct ConvertRowtypeExpr {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct CopyStateData {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct CreateAmStmt {
     pub type_: NodeTag,
     pub amname: *mut ::std::os::raw::c_char,
@@ -31063,6 +31160,11 @@ pub struct HASHELEMENT {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub struct GenericXLogState {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct HASHHDR {
     _unused: [u8; 0],
 }
@@ -39353,6 +39455,9 @@ pub const BTGreaterStrategyNumber: u32 = 5;
 pub const BTLessEqualStrategyNumber: u32 = 2;
 pub const BTLessStrategyNumber: u32 = 1;
 pub const BTMaxStrategyNumber: u32 = 5;
+pub const BUFFER_LOCK_EXCLUSIVE: u32 = 2;
+pub const BUFFER_LOCK_SHARE: u32 = 1;
+pub const BUFFER_LOCK_UNLOCK: u32 = 0;
 pub const BUFSIZ: u32 = 1024;
 pub const BUS_ADRALN: u32 = 1;
 pub const BUS_ADRERR: u32 = 2;
@@ -39923,6 +40028,7 @@ pub const FunctionParameterMode_FUNC_PARAM_INOUT: FunctionParameterMode = 98;
 pub const FunctionParameterMode_FUNC_PARAM_OUT: FunctionParameterMode = 111;
 pub const FunctionParameterMode_FUNC_PARAM_TABLE: FunctionParameterMode = 116;
 pub const FunctionParameterMode_FUNC_PARAM_VARIADIC: FunctionParameterMode = 118;
+pub const GENERIC_XLOG_FULL_IMAGE: u32 = 1;
 pub const GID_MAX: u32 = 2147483647;
 pub const GTSVECTOROID: u32 = 3642;
 pub const GUC_CUSTOM_PLACEHOLDER: u32 = 128;
@@ -40803,6 +40909,7 @@ pub const MAXNAMLEN: u32 = 255;
 pub const MAXPGPATH: u32 = 1024;
 pub const MAXTZLEN: u32 = 10;
 pub const MAX_CANON: u32 = 1024;
+pub const MAX_GENERIC_XLOG_PAGES: u32 = 4;
 pub const MAX_INPUT: u32 = 1024;
 pub const MAX_INTERVAL_PRECISION: u32 = 6;
 pub const MAX_KILOBYTES: u32 = 2147483647;
@@ -42194,6 +42301,7 @@ pub const XLOG_BLCKSZ: u32 = 8192;
 pub const XLOG_INCLUDE_ORIGIN: u32 = 1;
 pub const XLOG_INVALIDATIONS: u32 = 32;
 pub const XLOG_MARK_UNIMPORTANT: u32 = 2;
+pub const XLOG_NOOP: u32 = 32;
 pub const XLOG_RUNNING_XACTS: u32 = 16;
 pub const XLOG_STANDBY_LOCK: u32 = 0;
 pub const XLOG_XACT_ABORT: u32 = 32;
@@ -42682,6 +42790,7 @@ pub type CommandDest = u32;
 pub type CommandId = uint32;
 pub type ConstrType = u32;
 pub type ConstraintExclusionType = u32;
+pub type CopyState = *mut CopyStateData;
 pub type Cost = f64;
 pub type CostSelector = u32;
 pub type DateADT = int32;
@@ -43117,6 +43226,8 @@ pub type ReindexObjectType = u32;
 pub type RelOptKind = u32;
 pub type Relation = *mut RelationData;
 pub type RelationPtr = *mut Relation;
+pub type RelcacheCallbackFunction =
+    ::std::option::Option<unsafe extern "C" fn(arg: Datum, relid: Oid)>;
 pub type Relids = *mut Bitmapset;
 pub type RepOriginId = uint16;
 pub type ReplicationKind = u32;
@@ -43175,6 +43286,9 @@ pub type SubXactEvent = u32;
 pub type SyncCommitLevel = u32;
 pub type SysCacheIdentifier = u32;
 pub type SysScanDesc = *mut SysScanDescData;
+pub type SyscacheCallbackFunction = ::std::option::Option<
+    unsafe extern "C" fn(arg: Datum, cacheid: ::std::os::raw::c_int, hashvalue: uint32),
+>;
 pub type TableLikeOption = u32;
 pub type TimeADT = int64;
 pub type TimeLineID = uint32;
@@ -43385,6 +43499,13 @@ pub type check_function_callback = ::std::option::Option<
 >;
 pub type clock_t = __darwin_clock_t;
 pub type clockid_t = u32;
+pub type copy_data_source_cb = ::std::option::Option<
+    unsafe extern "C" fn(
+        outbuf: *mut ::std::os::raw::c_void,
+        minread: ::std::os::raw::c_int,
+        maxread: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int,
+>;
 pub type ct_rune_t = __darwin_ct_rune_t;
 pub type daddr_t = i32;
 pub type dev_t = __darwin_dev_t;
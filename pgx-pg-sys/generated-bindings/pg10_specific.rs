@@ -624,6 +624,16 @@ extern "C" {
     ) -> MemoryContext;
 }
 #[pg_guard]
+extern "C" {
+    pub fn NextCopyFrom(
+        cstate: CopyState,
+        econtext: *mut ExprContext,
+        values: *mut Datum,
+        nulls: *mut bool,
+        tupleOid: *mut Oid,
+    ) -> bool;
+}
+#[pg_guard]
 extern "C" {
     pub fn OpenTransientFile(
         fileName: FileName,
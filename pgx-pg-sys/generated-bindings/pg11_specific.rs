@@ -828,6 +828,16 @@ extern "C" {
     pub fn MemoryContextSetIdentifier(context: MemoryContext, id: *const ::std::os::raw::c_char);
 }
 #[pg_guard]
+extern "C" {
+    pub fn NextCopyFrom(
+        cstate: CopyState,
+        econtext: *mut ExprContext,
+        values: *mut Datum,
+        nulls: *mut bool,
+        tupleOid: *mut Oid,
+    ) -> bool;
+}
+#[pg_guard]
 extern "C" {
     pub fn OpenTransientFile(
         fileName: *const ::std::os::raw::c_char,
@@ -7,6 +7,7 @@
 #include "pgstat.h"
 #include "access/amapi.h"
 #include "access/genam.h"
+#include "access/generic_xlog.h"
 #include "access/htup.h"
 #include "access/htup_details.h"
 #include "access/reloptions.h"
@@ -20,8 +21,10 @@
 
 #include "catalog/index.h"
 #include "catalog/namespace.h"
+#include "catalog/pg_control.h"
 #include "catalog/pg_enum.h"
 #include "catalog/pg_type.h"
+#include "commands/copy.h"
 #include "commands/dbcommands.h"
 #include "commands/event_trigger.h"
 #include "catalog/dependency.h"
@@ -49,14 +52,17 @@
 #include "optimizer/pathnode.h"
 #include "optimizer/planner.h"
 #include "optimizer/restrictinfo.h"
+#include "parser/parse_coerce.h"
 #include "parser/parse_func.h"
 #include "parser/parse_type.h"
 #include "postmaster/bgworker.h"
 #include "storage/block.h"
+#include "storage/bufmgr.h"
 #include "storage/lwlock.h"
 #include "storage/procarray.h"
 #include "storage/itemptr.h"
 #include "storage/ipc.h"
+#include "tcop/pquery.h"
 #include "tcop/tcopprot.h"
 #include "tcop/utility.h"
 #include "utils/builtins.h"
@@ -65,12 +71,14 @@
 #include "utils/geo_decls.h"
 #undef double
 #include "utils/guc.h"
+#include "utils/inval.h"
 #include "utils/json.h"
 #include "utils/jsonb.h"
 #include "utils/syscache.h"
 #include "utils/lsyscache.h"
 #include "utils/memutils.h"
 #include "utils/palloc.h"
+#include "utils/regproc.h"
 #include "utils/rel.h"
 #include "utils/relcache.h"
 #include "utils/selfuncs.h"
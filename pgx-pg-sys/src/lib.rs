@@ -56,6 +56,9 @@ mod all_versions {
     pub const FirstNormalTransactionId: super::TransactionId = 3 as super::TransactionId;
     pub const MaxTransactionId: super::TransactionId = 0xFFFF_FFFF as super::TransactionId;
 
+    /// this comes from `catalog/pg_collation.dat`
+    pub const DEFAULT_COLLATION_OID: super::Oid = 100;
+
     #[inline]
     pub fn VARHDRSZ_EXTERNAL() -> usize {
         offset_of!(super::varattrib_1b_e, va_data)
@@ -222,6 +225,25 @@ mod internal {
         pub use crate::pg10_specific::tupleDesc as TupleDescData;
         pub use crate::pg10_specific::AllocSetContextCreate as AllocSetContextCreateExtended;
 
+        /// Create a `TupleDesc` of `natts` attributes, none of them filled in yet, with the
+        /// signature of Postgres 12, which dropped the `hasoid` argument
+        pub unsafe fn CreateTemplateTupleDesc(natts: ::std::os::raw::c_int) -> TupleDesc {
+            crate::pg10_specific::CreateTemplateTupleDesc(natts, false)
+        }
+
+        /// Connect a background worker to `dbname` as `username`, with the signature of Postgres
+        /// 11 and later.  Postgres 10 doesn't support any `flags`, so they're ignored.
+        pub unsafe fn BackgroundWorkerInitializeConnection(
+            dbname: *const ::std::os::raw::c_char,
+            username: *const ::std::os::raw::c_char,
+            _flags: uint32,
+        ) {
+            crate::pg10_specific::BackgroundWorkerInitializeConnection(
+                dbname as *mut std::os::raw::c_char,
+                username as *mut std::os::raw::c_char,
+            );
+        }
+
         pub unsafe fn add_string_reloption(
             kinds: bits32,
             name: *const ::std::os::raw::c_char,
@@ -319,6 +341,12 @@ mod internal {
 
         pub use crate::pg11_specific::tupleDesc as TupleDescData;
 
+        /// Create a `TupleDesc` of `natts` attributes, none of them filled in yet, with the
+        /// signature of Postgres 12, which dropped the `hasoid` argument
+        pub unsafe fn CreateTemplateTupleDesc(natts: ::std::os::raw::c_int) -> TupleDesc {
+            crate::pg11_specific::CreateTemplateTupleDesc(natts, false)
+        }
+
         /// # Safety
         ///
         /// This function wraps Postgres' internal `IndexBuildHeapScan` method, and therfore, is
@@ -6,7 +6,8 @@
 use crate::FlushErrorState;
 use std::any::Any;
 use std::cell::Cell;
-use std::panic::catch_unwind;
+use std::collections::HashMap;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 
 extern "C" {
     fn pg_re_throw();
@@ -18,7 +19,14 @@ extern "C" {
         lineno: i32,
         colno: i32,
     );
-
+    fn pgx_ereport_full(
+        level: i32,
+        code: i32,
+        message: *const std::os::raw::c_char,
+        detail: *const std::os::raw::c_char,
+        hint: *const std::os::raw::c_char,
+        context: *const std::os::raw::c_char,
+    );
 }
 
 #[derive(Clone, Debug)]
@@ -47,6 +55,7 @@ struct PanicLocation {
     file: String,
     line: u32,
     col: u32,
+    context: Vec<String>,
 }
 
 thread_local! { static PANIC_LOCATION: Cell<Option<PanicLocation>> = Cell::new(None) }
@@ -60,12 +69,33 @@ fn take_panic_location() -> PanicLocation {
             file: "<unknown>".to_string(),
             line: 0,
             col: 0,
+            context: Vec::new(),
         },
     })
 }
 
+/// Add a `CONTEXT` line to the Rust panic that's currently unwinding, to be reported once a
+/// `#[pg_guard]` boundary converts the panic into a Postgres ERROR.  Lines are reported in the
+/// order they're added.
+///
+/// This does nothing if no Rust panic is unwinding.  A Postgres ERROR already collected its
+/// `CONTEXT` lines when it was raised.
+pub fn add_panic_context(context: String) {
+    PANIC_LOCATION.with(|p| {
+        if let Some(mut location) = p.take() {
+            location.context.push(context);
+            p.set(Some(location));
+        }
+    })
+}
+
 pub fn register_pg_guard_panic_handler() {
     std::panic::set_hook(Box::new(|info| {
+        if info.payload().is::<JumpContext>() {
+            // a Postgres ERROR that's already been reported, so it has no Rust location
+            return;
+        }
+
         PANIC_LOCATION.with(|p| {
             let existing = p.take();
 
@@ -75,6 +105,7 @@ pub fn register_pg_guard_panic_handler() {
                         file: location.file().to_string(),
                         line: location.line(),
                         col: location.column(),
+                        context: Vec::new(),
                     }),
                     None => None,
                 }
@@ -85,6 +116,230 @@ pub fn register_pg_guard_panic_handler() {
     }))
 }
 
+/// What to do when a second error (a Rust panic or Postgres ERROR) is raised by the cleanup
+/// closure passed to `PgTryResult::unwrap_or_rethrow()` or `::finally_or_rethrow()`, while the
+/// error `pg_try()` caught is waiting to be rethrown
+///
+/// Rethrowing either error at that point would leave the other one half-handled, so by default
+/// the backend is terminated with a `FATAL` "error during error handling" instead.
+///
+/// This is the only place double faults are detected.  `guard()` and `#[pg_guard]` rethrow the
+/// error they catch without running any cleanup of their own, and a panic (or a Postgres ERROR
+/// converted into one) raised by a destructor while a panic is unwinding still aborts the
+/// process, as Rust always does for a panic while panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleFaultPolicy {
+    /// Raise a `FATAL`, which terminates the backend.  This is the default.
+    Fatal,
+
+    /// Flush Postgres' error state and raise a new `ERROR` describing both errors, which aborts
+    /// the current transaction
+    Error,
+
+    /// Abort the process, as `std::process::abort()` does, which causes the postmaster to restart
+    /// the cluster
+    Abort,
+}
+
+/// The two errors involved in a double fault, as passed to the `set_double_fault_hook()` hook
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoubleFault {
+    /// The error that was being handled
+    pub first: String,
+
+    /// The error raised while handling it
+    pub second: String,
+}
+
+impl std::fmt::Display for DoubleFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "error during error handling: {} (while handling: {})",
+            self.second, self.first
+        )
+    }
+}
+
+thread_local! {
+    static ERROR_DEPTH: Cell<usize> = Cell::new(0);
+    static DOUBLE_FAULT_POLICY: Cell<DoubleFaultPolicy> = Cell::new(DoubleFaultPolicy::Fatal);
+    static DOUBLE_FAULT_HOOK: Cell<Option<fn(&DoubleFault)>> = Cell::new(None);
+}
+
+/// Set what happens when an error is raised while another is being handled, returning the
+/// previous policy
+pub fn set_double_fault_policy(policy: DoubleFaultPolicy) -> DoubleFaultPolicy {
+    DOUBLE_FAULT_POLICY.with(|p| p.replace(policy))
+}
+
+/// Set a function to be called with each double fault before the `DoubleFaultPolicy` is applied,
+/// returning the previous hook.  This is mostly useful for observing double faults in tests.
+///
+/// The hook must not raise an error itself.
+pub fn set_double_fault_hook(hook: Option<fn(&DoubleFault)>) -> Option<fn(&DoubleFault)> {
+    DOUBLE_FAULT_HOOK.with(|h| h.replace(hook))
+}
+
+/// Is a `pg_try()` cleanup closure currently running, such that another error would be a double
+/// fault?
+pub fn is_handling_error() -> bool {
+    ERROR_DEPTH.with(|d| d.get() > 0)
+}
+
+fn describe_err(e: &(dyn Any + Send)) -> String {
+    match downcast_err(e) {
+        Ok(message) => message,
+        Err(_) => "a Postgres ERROR".to_string(),
+    }
+}
+
+fn double_fault(first: &(dyn Any + Send), second: &(dyn Any + Send)) -> ! {
+    let fault = DoubleFault {
+        first: describe_err(first),
+        second: describe_err(second),
+    };
+
+    if let Some(hook) = DOUBLE_FAULT_HOOK.with(|h| h.get()) {
+        hook(&fault);
+    }
+
+    // neither error will be rethrown, so forget them both
+    take_panic_location();
+    unsafe {
+        FlushErrorState();
+    }
+
+    let level = match DOUBLE_FAULT_POLICY.with(|p| p.get()) {
+        DoubleFaultPolicy::Fatal => crate::FATAL,
+        DoubleFaultPolicy::Error => crate::ERROR,
+        DoubleFaultPolicy::Abort => {
+            eprintln!("{}", fault);
+            std::process::abort()
+        }
+    };
+
+    let c_message = std::ffi::CString::new(fault.to_string()).unwrap();
+    let c_file = std::ffi::CString::new(file!()).unwrap();
+    unsafe {
+        pgx_ereport(
+            level as i32,
+            2600, // ERRCODE_INTERNAL_ERROR
+            c_message.as_ptr(),
+            c_file.as_ptr(),
+            line!() as i32,
+            0,
+        );
+    }
+    unreachable!("ereport() failed to report a double fault: {}", fault);
+}
+
+/// A copy of a Postgres ERROR (or Rust panic) caught by `guard_result()` or
+/// `PgTryResult::into_result()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgError {
+    pub sqlerrcode: i32,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+    pub context: Option<String>,
+}
+
+impl PgError {
+    /// The five-character SQLSTATE of this error, such as `42883`
+    pub fn sqlstate(&self) -> String {
+        // unpack what the PGSIXBIT/MAKE_SQLSTATE macros packed
+        (0..5)
+            .map(|i| (((self.sqlerrcode >> (6 * i)) & 0x3F) as u8 + b'0') as char)
+            .collect()
+    }
+
+    /// Raise this error again as a Postgres ERROR, with its original SQLSTATE, message, detail,
+    /// hint, and context
+    ///
+    /// Like any other ERROR raised from Rust, it unwinds the Rust stack first, so destructors
+    /// between here and the enclosing `#[pg_guard]` function run.
+    pub fn rethrow(&self) -> ! {
+        fn to_cstring(s: &str) -> std::ffi::CString {
+            std::ffi::CString::new(s.replace('\0', "")).unwrap()
+        }
+
+        let message = to_cstring(&self.message);
+        let detail = self.detail.as_deref().map(to_cstring);
+        let hint = self.hint.as_deref().map(to_cstring);
+        let context = self.context.as_deref().map(to_cstring);
+        let as_ptr =
+            |s: &Option<std::ffi::CString>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+
+        guard(|| unsafe {
+            pgx_ereport_full(
+                crate::ERROR as i32,
+                self.sqlerrcode,
+                message.as_ptr(),
+                as_ptr(&detail),
+                as_ptr(&hint),
+                as_ptr(&context),
+            );
+        });
+        unreachable!("ereport() failed to rethrow: {}", self);
+    }
+
+    /// Copy the error at the top of Postgres' error stack and then flush it
+    ///
+    /// `memcxt` is the `MemoryContext` that was current before the error was raised
+    unsafe fn copy_and_flush(memcxt: crate::MemoryContext) -> Self {
+        let error = PgError::copy(memcxt);
+        FlushErrorState();
+        error
+    }
+
+    /// Copy the error at the top of Postgres' error stack, leaving it there so it can still be
+    /// rethrown
+    unsafe fn copy(memcxt: crate::MemoryContext) -> Self {
+        // CopyErrorData() can't be called while in the ErrorContext
+        crate::CurrentMemoryContext = memcxt;
+
+        let edata = crate::CopyErrorData();
+        let error = PgError {
+            sqlerrcode: (*edata).sqlerrcode,
+            message: cstr_to_string((*edata).message).unwrap_or_default(),
+            detail: cstr_to_string((*edata).detail),
+            hint: cstr_to_string((*edata).hint),
+            context: cstr_to_string((*edata).context),
+        };
+        crate::FreeErrorData(edata);
+
+        error
+    }
+
+    /// A `PgError` for a Rust panic!(), which has no Postgres ErrorData
+    fn from_panic(message: String) -> Self {
+        PgError {
+            sqlerrcode: 2600, // ERRCODE_INTERNAL_ERROR
+            message,
+            detail: None,
+            hint: None,
+            context: None,
+        }
+    }
+}
+
+impl std::fmt::Display for PgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.sqlstate(), self.message)
+    }
+}
+
+impl std::error::Error for PgError {}
+
+unsafe fn cstr_to_string(s: *const std::os::raw::c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        Some(std::ffi::CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
+
 /// A `std::result::Result`-type value returned from `pg_try()` that allows for performing cleanup
 /// work after a closure raised an error and before it is possibly rethrown
 #[must_use = "this `PgTryResult` may be be holding a Postgres ERROR.  It must be consumed or rethrown"]
@@ -113,6 +368,35 @@ impl<T> PgTryResult<T> {
         }
     }
 
+    /// Convert into a `Result`, copying a caught Postgres ERROR (or Rust panic) into a `PgError`
+    /// and flushing it from Postgres' error state
+    ///
+    /// `memcxt` is the `MemoryContext` that was current when the try block started.  Generally
+    /// you'll want `guard_result()` instead, which takes care of that for you.
+    ///
+    /// ## Safety
+    ///
+    /// Like `::unwrap_or()`, this does not rethrow a caught ERROR.  It's only safe to continue
+    /// if the caught ERROR didn't leave behind resources (locks, buffer pins, etc) that only
+    /// transaction abort would release -- a failed catalog lookup is fine, a failed write is not.
+    pub unsafe fn into_result(self, memcxt: crate::MemoryContext) -> Result<T, PgError> {
+        match self.0 {
+            Ok(result) => Ok(result),
+            Err(e) => match downcast_err(e.as_ref()) {
+                Ok(message) => {
+                    // a Rust panic!(), so there's no Postgres ErrorData to copy
+                    let location = take_panic_location();
+                    let mut error = PgError::from_panic(message);
+                    if !location.context.is_empty() {
+                        error.context = Some(location.context.join("\n"));
+                    }
+                    Err(error)
+                }
+                Err(_) => Err(PgError::copy_and_flush(memcxt)),
+            },
+        }
+    }
+
     /// Perform some operation cleanup operation after the try block if an error was thrown.
     ///
     /// ## Safety
@@ -196,6 +480,195 @@ where
     try_guard(try_func)
 }
 
+/// Call a function that might raise a Postgres ERROR, returning the ERROR as a `PgError` instead
+/// of propagating it
+///
+/// ## Safety
+///
+/// The caught ERROR is not rethrown, so it's only safe to continue if the function didn't leave
+/// behind resources (locks, buffer pins, etc) that only transaction abort would release.  This is
+/// fine for recoverable lookups such as `LookupFuncName()` with `missing_ok = false`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx_pg_sys::{guard_result, LookupFuncName, List, Oid};
+///
+/// # let funcname: *mut List = std::ptr::null_mut();
+/// let result: Result<Oid, _> =
+///     unsafe { guard_result(|| LookupFuncName(funcname, 0, std::ptr::null(), false)) };
+/// if let Err(e) = result {
+///     eprintln!("lookup failed with {}", e.sqlstate());
+/// }
+/// ```
+pub unsafe fn guard_result<Func, R>(f: Func) -> Result<R, PgError>
+where
+    Func: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
+{
+    let memcxt = crate::CurrentMemoryContext;
+    pg_try(f).into_result(memcxt)
+}
+
+/// An error caught by one of a `PgTryBuilder`'s catch handlers
+///
+/// It dereferences to a `PgError` copy of the error.  Returning from the handler marks the error
+/// as handled, while `::rethrow()` propagates the original error.
+pub struct CaughtError {
+    error: PgError,
+    payload: Box<dyn Any + Send>,
+}
+
+impl CaughtError {
+    /// Propagate the original error, as `PG_RE_THROW()` would.  The `PgTryBuilder`'s finally
+    /// block still runs first.
+    pub fn rethrow(self) -> ! {
+        resume_unwind(self.payload)
+    }
+}
+
+impl std::ops::Deref for CaughtError {
+    type Target = PgError;
+
+    fn deref(&self) -> &Self::Target {
+        &self.error
+    }
+}
+
+impl std::fmt::Debug for CaughtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+/// Run a block of Rust code (which may call into Postgres) with Postgres-style `PG_TRY()`,
+/// `PG_CATCH()`, and `PG_FINALLY()` handling
+///
+/// A caught Postgres ERROR (or Rust panic) is passed to the handler registered for its SQLSTATE
+/// with `::catch_when()`, or otherwise to the `::catch_others()` handler, and is considered
+/// handled once that handler returns.  Rust panics are caught as `ERRCODE_INTERNAL_ERROR`.  An
+/// error without a handler propagates unchanged.  The `::finally()` block always runs, after any
+/// handler.
+///
+/// Handling an ERROR (rather than rethrowing it) leaves behind whatever resources (locks, buffer
+/// pins, SPI connections, etc) only transaction abort would release.  So if the try block might
+/// raise one after acquiring any, such as through SPI, run the builder in a subtransaction and
+/// roll it back once an error was handled, as PL/pgSQL's `EXCEPTION` blocks do.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx_pg_sys::PgTryBuilder;
+///
+/// # fn might_fail() -> i32 { 42 }
+/// let value = PgTryBuilder::new(|| might_fail())
+///     .catch_when(33816706 /* ERRCODE_DIVISION_BY_ZERO */, |_| 0)
+///     .catch_others(|e| {
+///         eprintln!("rethrowing {}", *e);
+///         e.rethrow()
+///     })
+///     .finally(|| eprintln!("done"))
+///     .execute();
+/// ```
+pub struct PgTryBuilder<'a, R, F: FnOnce() -> R + std::panic::UnwindSafe> {
+    func: F,
+    when: HashMap<i32, Box<dyn FnOnce(CaughtError) -> R + 'a>>,
+    others: Option<Box<dyn FnOnce(CaughtError) -> R + 'a>>,
+    finally: Option<Box<dyn FnOnce() + 'a>>,
+}
+
+impl<'a, R, F: FnOnce() -> R + std::panic::UnwindSafe> PgTryBuilder<'a, R, F> {
+    pub fn new(func: F) -> Self {
+        PgTryBuilder {
+            func,
+            when: HashMap::new(),
+            others: None,
+            finally: None,
+        }
+    }
+
+    /// Handle errors with the specified SQLSTATE code, such as
+    /// `PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO`
+    ///
+    /// ## Panics
+    ///
+    /// If a handler for `sqlerrcode` was already registered
+    pub fn catch_when<C, H>(mut self, sqlerrcode: C, handler: H) -> Self
+    where
+        C: Into<i32>,
+        H: FnOnce(CaughtError) -> R + 'a,
+    {
+        let sqlerrcode = sqlerrcode.into();
+        if self.when.insert(sqlerrcode, Box::new(handler)).is_some() {
+            panic!("PgTryBuilder already catches SQLSTATE {}", sqlerrcode);
+        }
+        self
+    }
+
+    /// Handle errors that no `::catch_when()` handler does
+    pub fn catch_others<H>(mut self, handler: H) -> Self
+    where
+        H: FnOnce(CaughtError) -> R + 'a,
+    {
+        self.others = Some(Box::new(handler));
+        self
+    }
+
+    /// Run `finally` after the try block and any catch handler, whether or not an error was raised
+    pub fn finally<H>(mut self, finally: H) -> Self
+    where
+        H: FnOnce() + 'a,
+    {
+        self.finally = Some(Box::new(finally));
+        self
+    }
+
+    /// Run the try block, and the catch handlers and finally block as necessary
+    pub fn execute(mut self) -> R {
+        let memcxt = unsafe { crate::CurrentMemoryContext };
+        let prev_error_context_stack = unsafe { crate::error_context_stack };
+
+        let result = match catch_unwind(self.func) {
+            Ok(result) => Ok(result),
+            Err(payload) => {
+                unsafe {
+                    crate::error_context_stack = prev_error_context_stack;
+                }
+
+                let (error, is_postgres_error) = match downcast_err(payload.as_ref()) {
+                    Ok(message) => (PgError::from_panic(message), false),
+                    Err(_) => (unsafe { PgError::copy(memcxt) }, true),
+                };
+
+                match self.when.remove(&error.sqlerrcode).or(self.others) {
+                    Some(handler) => {
+                        let caught = CaughtError { error, payload };
+                        let result = catch_unwind(AssertUnwindSafe(|| handler(caught)));
+                        if result.is_ok() {
+                            // the error was handled, so it's gone
+                            if is_postgres_error {
+                                unsafe { FlushErrorState() };
+                            } else {
+                                take_panic_location();
+                            }
+                        }
+                        result
+                    }
+                    None => Err(payload),
+                }
+            }
+        };
+
+        if let Some(finally) = self.finally {
+            finally();
+        }
+
+        match result {
+            Ok(result) => result,
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+}
+
 fn try_guard<Try, R>(try_func: Try) -> PgTryResult<R>
 where
     Try: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
@@ -214,28 +687,54 @@ where
     Catch: FnOnce(),
 {
     // call our catch function to do any cleanup work that might be necessary
-    // before we end up rethrowing the error
-    catch_func();
+    // before we end up rethrowing the error.  An error raised by it is a double fault
+    ERROR_DEPTH.with(|d| d.set(d.get() + 1));
+    let result = catch_unwind(AssertUnwindSafe(catch_func));
+    ERROR_DEPTH.with(|d| d.set(d.get() - 1));
+    if let Err(second) = result {
+        double_fault(error.as_ref(), second.as_ref());
+    }
 
     // determine how to rethrow the error
-    match downcast_err(error) {
+    match downcast_err(error.as_ref()) {
         // the error is a String, which means it was originally a Rust panic!(), so
         // translate it into an elog(ERROR), including the code location that caused
         // the panic!()
         Ok(message) => {
             let location = take_panic_location();
             let c_message = std::ffi::CString::new(message.clone()).unwrap();
-            let c_file = std::ffi::CString::new(location.file).unwrap();
 
             unsafe {
-                pgx_ereport(
-                    crate::ERROR as i32,
-                    2600, // ERRCODE_INTERNAL_ERROR
-                    c_message.as_ptr(),
-                    c_file.as_ptr(),
-                    location.line as i32,
-                    location.col as i32,
-                );
+                if location.context.is_empty() {
+                    let c_file = std::ffi::CString::new(location.file).unwrap();
+                    pgx_ereport(
+                        crate::ERROR as i32,
+                        2600, // ERRCODE_INTERNAL_ERROR
+                        c_message.as_ptr(),
+                        c_file.as_ptr(),
+                        location.line as i32,
+                        location.col as i32,
+                    );
+                } else {
+                    // the same location line pgx_ereport() adds, followed by those added with
+                    // add_panic_context() while unwinding
+                    let context = std::iter::once(format!(
+                        "{}:{}:{}",
+                        location.file, location.line, location.col
+                    ))
+                    .chain(location.context.into_iter())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                    let c_context = std::ffi::CString::new(context.replace('\0', "")).unwrap();
+                    pgx_ereport_full(
+                        crate::ERROR as i32,
+                        2600, // ERRCODE_INTERNAL_ERROR
+                        c_message.as_ptr(),
+                        std::ptr::null(),
+                        std::ptr::null(),
+                        c_context.as_ptr(),
+                    );
+                }
             }
             unreachable!("ereport() failed at depth==0 with message: {}", message);
         }
@@ -250,7 +749,7 @@ where
 
 /// convert types of `e` that we understand/expect into either a
 /// `Ok(String)` or a `Err<JumpContext>`
-fn downcast_err(e: Box<dyn Any + Send>) -> Result<String, JumpContext> {
+fn downcast_err(e: &(dyn Any + Send)) -> Result<String, JumpContext> {
     if let Some(cxt) = e.downcast_ref::<JumpContext>() {
         Err(cxt.clone())
     } else if let Some(s) = e.downcast_ref::<&str>() {
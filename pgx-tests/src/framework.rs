@@ -220,6 +220,14 @@ pub fn client() -> (postgres::Client, String) {
     (client, session_id)
 }
 
+/// Connect to the test database, starting Postgres first if no test has yet.  This is for
+/// `#[test]` functions that need to run statements outside of the transaction a `#[pg_test]`
+/// runs in, such as `CALL`ing a procedure that commits
+pub fn test_client(postgresql_conf: Vec<&'static str>) -> (postgres::Client, String) {
+    initialize_test_framework(postgresql_conf);
+    client()
+}
+
 fn install_extension() {
     eprintln!("installing extension");
     let mut command = Command::new("cargo-pgx")
@@ -0,0 +1,120 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::acl::{self, AclError, AclMode, SecurityContext, SecurityFlags};
+    use pgx::*;
+
+    fn role_oid(name: &str) -> pg_sys::Oid {
+        Spi::get_one_with_args("SELECT oid FROM pg_roles WHERE rolname = $1", args![name])
+            .expect("no such role")
+    }
+
+    #[pg_extern]
+    fn acl_checked_select(relid: pg_sys::Oid) -> Result<bool, AclError> {
+        acl::check_relation_privilege(relid, AclMode::SELECT | AclMode::UPDATE)?;
+        Ok(true)
+    }
+
+    #[pg_test]
+    fn test_check_relation_privilege() {
+        Spi::run("CREATE TABLE tests.acl_test (id int)");
+        Spi::run("CREATE ROLE acl_test_reader");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.acl_test'::regclass::oid")
+            .expect("relid was NULL");
+        let reader = role_oid("acl_test_reader");
+
+        // the test runs as a superuser
+        assert_eq!(
+            acl::check_relation_privilege(relid, AclMode::SELECT),
+            Ok(())
+        );
+        assert_eq!(
+            acl::check_relation_privilege_as(relid, reader, AclMode::SELECT),
+            Err(AclError::NoPrivilege)
+        );
+
+        Spi::run("GRANT SELECT ON tests.acl_test TO acl_test_reader");
+        assert_eq!(
+            acl::check_relation_privilege_as(relid, reader, AclMode::SELECT),
+            Ok(())
+        );
+        assert_eq!(
+            acl::check_relation_privilege_as(relid, reader, AclMode::DELETE),
+            Err(AclError::NoPrivilege)
+        );
+    }
+
+    #[pg_test(error = "permission denied")]
+    fn test_acl_error_is_raised() {
+        Spi::run("CREATE TABLE tests.acl_raise_test (id int)");
+        Spi::run("CREATE ROLE acl_test_nobody");
+        Spi::run("GRANT USAGE ON SCHEMA tests TO acl_test_nobody");
+        Spi::run("SET LOCAL ROLE acl_test_nobody");
+        Spi::run("SELECT tests.acl_checked_select('tests.acl_raise_test'::regclass::oid)");
+    }
+
+    #[pg_test]
+    fn test_role_membership() {
+        Spi::run("CREATE ROLE acl_test_group");
+        Spi::run("CREATE ROLE acl_test_member NOINHERIT IN ROLE acl_test_group");
+        let group = role_oid("acl_test_group");
+        let member = role_oid("acl_test_member");
+
+        assert!(acl::has_role_membership(group, member));
+        assert!(!acl::has_role_membership(member, group));
+
+        // NOINHERIT members don't automatically have the group's privileges
+        assert!(!acl::has_privileges_of_role(group, member));
+        assert!(acl::has_privileges_of_role(member, member));
+    }
+
+    #[pg_test]
+    fn test_security_context() {
+        Spi::run("CREATE ROLE acl_test_switched");
+        let role = role_oid("acl_test_switched");
+        let (userid, flags) = acl::current_security_context();
+
+        {
+            let _context =
+                unsafe { SecurityContext::switch_to(role, SecurityFlags::LOCAL_USERID_CHANGE) };
+            assert_eq!(
+                acl::current_security_context(),
+                (role, flags | SecurityFlags::LOCAL_USERID_CHANGE)
+            );
+            assert_eq!(
+                Spi::get_one::<String>("SELECT current_user::text"),
+                Some("acl_test_switched".to_string())
+            );
+        }
+
+        assert_eq!(acl::current_security_context(), (userid, flags));
+    }
+
+    #[pg_test]
+    fn test_security_context_restored_on_panic() {
+        Spi::run("CREATE ROLE acl_test_panicked");
+        let role = role_oid("acl_test_panicked");
+        let before = acl::current_security_context();
+
+        let result = std::panic::catch_unwind(|| {
+            let _context = unsafe { SecurityContext::switch_to(role, SecurityFlags::empty()) };
+            panic!("panicked while switched");
+        });
+
+        assert!(result.is_err());
+        assert_eq!(acl::current_security_context(), before);
+    }
+
+    #[pg_test(error = "cannot create temporary table within security-restricted operation")]
+    fn test_security_context_restricted_operation() {
+        let _context = unsafe {
+            SecurityContext::switch_to(pg_sys::GetUserId(), SecurityFlags::RESTRICTED_OPERATION)
+        };
+        Spi::run("CREATE TEMP TABLE acl_test_temp (id int)");
+    }
+}
@@ -10,6 +10,21 @@ fn anyarray_arg(array: AnyArray) -> Json {
         .expect("conversion to json returned null")
 }
 
+#[pg_extern]
+fn anyarray_sum_i32(array: AnyArray) -> Option<i32> {
+    array
+        .try_into_array::<i32>()
+        .unwrap_or_else(|e| panic!("{}", e))
+        .map(|array| array.iter().map(|v| v.unwrap_or(0)).sum())
+}
+
+#[pg_extern]
+fn anyelement_as_text(element: AnyElement) -> Option<String> {
+    element
+        .try_into::<String>()
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
@@ -24,4 +39,49 @@ mod tests {
             .expect("anyarray_arg() returned null");
         assert_eq!(json.0, json! {[1,2,3]})
     }
+
+    #[pg_test]
+    fn test_anyarray_try_into_array() {
+        let sum = Spi::get_one::<i32>("SELECT anyarray_sum_i32(ARRAY[1, 2, 3]::integer[]);")
+            .expect("anyarray_sum_i32() returned null");
+        assert_eq!(6, sum);
+    }
+
+    #[pg_test(error = "expected a value of type integer, but got bigint")]
+    fn test_anyarray_try_into_array_wrong_type() {
+        Spi::get_one::<i32>("SELECT anyarray_sum_i32(ARRAY[1, 2, 3]::bigint[]);");
+    }
+
+    #[pg_test]
+    fn test_anyarray_try_into() {
+        let datum = vec![1i64, 2, 3].into_datum().expect("array was NULL");
+        let array = unsafe { AnyArray::from_datum(datum, false, pg_sys::INT8ARRAYOID) }
+            .expect("array was NULL");
+        assert_eq!(
+            array.try_into::<Vec<Option<i64>>>(),
+            Ok(Some(vec![Some(1), Some(2), Some(3)]))
+        );
+        assert_eq!(
+            array.try_into::<Vec<Option<i32>>>(),
+            Err(TypeMismatchError {
+                expected: pg_sys::INT4ARRAYOID,
+                actual: pg_sys::INT8ARRAYOID,
+            })
+        );
+    }
+
+    #[pg_test]
+    fn test_anyelement_try_into() {
+        let text = Spi::get_one::<String>("SELECT anyelement_as_text('hello'::text);")
+            .expect("anyelement_as_text() returned null");
+        assert_eq!("hello", text);
+
+        let text = Spi::get_one::<String>("SELECT anyelement_as_text(NULL::text);");
+        assert!(text.is_none());
+    }
+
+    #[pg_test(error = "expected a value of type text, but got integer")]
+    fn test_anyelement_try_into_wrong_type() {
+        Spi::get_one::<String>("SELECT anyelement_as_text(42);");
+    }
 }
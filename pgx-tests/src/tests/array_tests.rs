@@ -94,6 +94,96 @@ fn return_zero_length_vec() -> Vec<i32> {
     Vec::new()
 }
 
+#[pg_extern]
+fn return_array_with_nulls() -> Vec<Option<i32>> {
+    vec![Some(1), None, Some(3), None]
+}
+
+#[pg_extern]
+fn build_int_array(n: i32) -> ArrayBuilder<i32> {
+    let mut builder = ArrayBuilder::with_capacity(n as usize);
+    for i in 0..n {
+        builder.push(if i % 3 == 0 { None } else { Some(i) });
+    }
+    builder
+}
+
+#[pg_extern]
+fn build_text_array(n: i32) -> ArrayBuilder<String> {
+    // deliberately too small, so the array body has to grow
+    let mut builder = ArrayBuilder::with_capacity(1);
+    for i in 0..n {
+        builder.push(Some(format!("value-{}", i)));
+    }
+    builder
+}
+
+#[pg_extern]
+fn build_empty_array() -> ArrayBuilder<i64> {
+    ArrayBuilder::with_capacity(0)
+}
+
+#[pg_extern]
+fn sum_array_f64_sliced(values: Array<f64>) -> f64 {
+    values.as_slice().iter().sum()
+}
+
+#[pg_extern]
+fn count_nan_sliced(values: Array<f64>) -> i32 {
+    values.as_slice().iter().filter(|v| v.is_nan()).count() as i32
+}
+
+#[pg_extern]
+fn float4_array_len_sliced(values: Array<f32>) -> i32 {
+    values.as_slice().len() as i32
+}
+
+#[pg_extern]
+fn float8_array_roundtrip(values: Vec<Option<f64>>) -> Vec<Option<f64>> {
+    values
+}
+
+#[pg_extern]
+fn text_array_join(values: Vec<&str>) -> String {
+    values.join("|")
+}
+
+#[pg_extern]
+fn text_array_lengths(values: Array<String>) -> Vec<Option<i32>> {
+    values
+        .iter()
+        .map(|v| v.map(|v| v.chars().count() as i32))
+        .collect()
+}
+
+#[pg_extern]
+fn text_array_upper(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|v| v.to_uppercase()).collect()
+}
+
+#[pg_extern]
+fn array_shape(values: Array<i32>) -> Vec<i32> {
+    let mut shape = values.dims().to_vec();
+    shape.extend_from_slice(values.lower_bounds());
+    shape
+}
+
+#[pg_extern]
+fn array_identity(values: Array<i32>) -> Array<i32> {
+    values
+}
+
+#[pg_extern]
+fn array_reshape(values: Array<i32>, ncols: i32) -> Array<i32> {
+    let elements = values.iter().collect::<Vec<_>>();
+    Array::from_rows(
+        elements
+            .chunks(ncols as usize)
+            .map(|row| row.to_vec())
+            .collect(),
+    )
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
@@ -212,4 +302,232 @@ mod tests {
             .expect("failed to get SPI result");
         assert!(rc)
     }
+
+    #[pg_test]
+    fn test_return_array_with_nulls() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT ARRAY[1, NULL, 3, NULL]::integer[] IS NOT DISTINCT FROM return_array_with_nulls();",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_with_nulls_round_trip() {
+        let values = Spi::get_one::<Vec<Option<i32>>>("SELECT return_array_with_nulls();")
+            .expect("failed to get SPI result");
+        assert_eq!(values, vec![Some(1), None, Some(3), None]);
+    }
+
+    #[pg_test]
+    fn test_array_builder_with_nulls() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT array_agg(CASE WHEN i % 3 = 0 THEN NULL ELSE i END) IS NOT DISTINCT FROM build_int_array(1000) FROM generate_series(0, 999) i;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_builder_text() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT array_agg('value-' || i) = build_text_array(1000) FROM generate_series(0, 999) i;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_builder_empty() {
+        let rc = Spi::get_one::<bool>("SELECT ARRAY[]::bigint[] = build_empty_array();")
+            .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_sum_array_f64_sliced() {
+        let sum = Spi::get_one::<f64>("SELECT sum_array_f64_sliced(ARRAY[1.5, 2.5, 3]::float8[])");
+        assert_eq!(sum, Some(7.0));
+    }
+
+    #[pg_test]
+    fn test_float4_array_sliced_len() {
+        let len = Spi::get_one::<i32>("SELECT float4_array_len_sliced(ARRAY[1, 2, 3]::real[])");
+        assert_eq!(len, Some(3));
+    }
+
+    #[pg_test]
+    fn test_count_nan_sliced() {
+        let count = Spi::get_one::<i32>(
+            "SELECT count_nan_sliced(ARRAY['NaN', 1, 'Infinity', 'NaN']::float8[])",
+        );
+        assert_eq!(count, Some(2));
+    }
+
+    #[pg_test(error = "array contains NULL")]
+    fn test_as_slice_with_nulls() {
+        Spi::get_one::<f64>("SELECT sum_array_f64_sliced(ARRAY[1, NULL]::float8[])");
+    }
+
+    #[pg_test(error = "array elements of length -1 cannot be viewed as a slice of f64")]
+    fn test_as_slice_wrong_type() {
+        let datum = vec!["a", "b"].into_datum();
+        let array =
+            unsafe { Array::<f64>::from_datum(datum.unwrap(), false, pg_sys::TEXTARRAYOID) }
+                .expect("array was NULL");
+        array.as_slice();
+    }
+
+    #[pg_test]
+    fn test_float8_array_special_values() {
+        let values = Spi::get_one::<Vec<Option<f64>>>(
+            "SELECT float8_array_roundtrip(ARRAY['NaN', 'Infinity', '-Infinity', '-0', NULL]::float8[])",
+        )
+        .expect("array was NULL");
+
+        assert_eq!(values.len(), 5);
+        assert!(values[0].unwrap().is_nan());
+        assert_eq!(values[1], Some(f64::INFINITY));
+        assert_eq!(values[2], Some(f64::NEG_INFINITY));
+        assert_eq!(values[3].map(f64::to_bits), Some((-0.0f64).to_bits()));
+        assert_eq!(values[4], None);
+    }
+
+    #[pg_test]
+    fn test_float8_array_preserves_bits() {
+        let input = vec![
+            Some(f64::NAN),
+            Some(f64::INFINITY),
+            Some(f64::NEG_INFINITY),
+            Some(-0.0),
+            Some(f64::MIN_POSITIVE),
+            None,
+        ];
+        let datum = input.clone().into_datum();
+        let output = unsafe {
+            Vec::<Option<f64>>::from_datum(datum.unwrap(), false, 1022) // FLOAT8ARRAYOID
+        }
+        .expect("array was NULL");
+
+        let bits = |values: &Vec<Option<f64>>| {
+            values
+                .iter()
+                .map(|v| v.map(f64::to_bits))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bits(&output), bits(&input));
+    }
+
+    #[pg_test]
+    fn test_float_nan_survives_spi() {
+        let value = Spi::get_one_with_args::<f64>(
+            "SELECT $1",
+            vec![(PgBuiltInOids::FLOAT8OID.oid(), f64::NAN.into_datum())],
+        )
+        .expect("value was NULL");
+        assert_eq!(value.to_bits(), f64::NAN.to_bits());
+
+        let value = Spi::get_one_with_args::<f32>(
+            "SELECT $1",
+            vec![(PgBuiltInOids::FLOAT4OID.oid(), f32::NAN.into_datum())],
+        )
+        .expect("value was NULL");
+        assert_eq!(value.to_bits(), f32::NAN.to_bits());
+    }
+
+    #[pg_test]
+    fn test_text_array_to_vec_string() {
+        let values = Spi::get_one::<Vec<String>>("SELECT ARRAY['a', 'bc', 'ñandú', '日本語']")
+            .expect("SPI result was NULL");
+        assert_eq!(values, vec!["a", "bc", "ñandú", "日本語"]);
+    }
+
+    #[pg_test]
+    fn test_text_array_with_nulls_to_vec_option_string() {
+        let values = Spi::get_one::<Vec<Option<String>>>("SELECT ARRAY['Grüße', NULL, '', NULL]")
+            .expect("SPI result was NULL");
+        assert_eq!(
+            values,
+            vec![Some("Grüße".to_string()), None, Some(String::new()), None]
+        );
+    }
+
+    #[pg_test]
+    fn test_text_array_vec_str_argument() {
+        let joined = Spi::get_one::<String>("SELECT text_array_join(ARRAY['α', 'β', 'γ'])");
+        assert_eq!(joined, Some("α|β|γ".to_string()));
+    }
+
+    #[pg_test]
+    fn test_text_array_vec_str_toasted() {
+        Spi::run("CREATE TABLE text_array_toast (a text[])");
+        Spi::run("INSERT INTO text_array_toast SELECT array_agg(repeat('é', 1000)) FROM generate_series(1, 100)");
+        let length = Spi::get_one::<i32>("SELECT length(text_array_join(a)) FROM text_array_toast");
+        assert_eq!(length, Some(100 * 1000 + 99));
+    }
+
+    #[pg_test]
+    fn test_text_array_borrowed_array() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT text_array_lengths(ARRAY['abc', NULL, 'ü']) IS NOT DISTINCT FROM ARRAY[3, NULL, 1]",
+        )
+        .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_text_array_vec_string_round_trip() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT text_array_upper(ARRAY['straße', 'ñ']) = ARRAY['STRASSE', 'Ñ']",
+        )
+        .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test(error = "array contains a NULL element, which requires a Vec<Option<T>>")]
+    fn test_text_array_null_element_to_vec_string() {
+        Spi::get_one::<Vec<String>>("SELECT ARRAY['a', NULL]");
+    }
+
+    #[pg_test]
+    fn test_array_dims() {
+        let shape =
+            Spi::get_one::<Vec<Option<i32>>>("SELECT array_shape('[0:1][3:5]={{1,2,3},{4,5,6}}')");
+        assert_eq!(shape, Some(vec![Some(2), Some(3), Some(0), Some(3)]));
+
+        let shape = Spi::get_one::<Vec<Option<i32>>>("SELECT array_shape(ARRAY[1, 2, 3])");
+        assert_eq!(shape, Some(vec![Some(3), Some(1)]));
+
+        let shape = Spi::get_one::<Vec<Option<i32>>>("SELECT array_shape('{}')");
+        assert_eq!(shape, Some(vec![]));
+    }
+
+    #[pg_test]
+    fn test_array_round_trip_preserves_dims() {
+        let rc = Spi::get_one::<String>(
+            "SELECT array_identity('[0:1][3:5]={{1,2,3},{4,5,NULL}}')::text",
+        );
+        assert_eq!(rc, Some("[0:1][3:5]={{1,2,3},{4,5,NULL}}".to_string()));
+    }
+
+    #[pg_test]
+    fn test_array_from_rows() {
+        let rc =
+            Spi::get_one::<String>("SELECT array_reshape(ARRAY[1, 2, NULL, 4, 5, 6], 3)::text");
+        assert_eq!(rc, Some("{{1,2,NULL},{4,5,6}}".to_string()));
+
+        let rc = Spi::get_one::<String>("SELECT array_reshape('{}', 3)::text");
+        assert_eq!(rc, Some("{}".to_string()));
+
+        let matrix = Array::<i32>::from_rows(vec![vec![Some(1)], vec![Some(2)]]);
+        assert_eq!(matrix.ndims(), 2);
+        assert_eq!(matrix.dims(), &[2, 1]);
+        assert_eq!(matrix.lower_bounds(), &[1, 1]);
+        assert_eq!(matrix.iter().collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+    }
+
+    #[pg_test(error = "multidimensional arrays must have sub-arrays with matching dimensions")]
+    fn test_array_from_ragged_rows() {
+        Array::<i32>::from_rows(vec![vec![Some(1), Some(2)], vec![Some(3)]]);
+    }
 }
@@ -0,0 +1,51 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::bgworkers::*;
+    use pgx::*;
+    use std::time::Duration;
+
+    const WAIT_FOR_SIGTERM: i32 = 42;
+
+    #[pg_guard]
+    pub extern "C" fn bgworker_tests_main(arg: pg_sys::Datum) {
+        BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGTERM);
+
+        if unsafe { i32::from_datum(arg, false, pg_sys::INT4OID) } == Some(WAIT_FOR_SIGTERM) {
+            while BackgroundWorker::wait_latch(Some(Duration::from_millis(100))) {}
+        }
+    }
+
+    fn spawn(arg: i32) -> DynamicBackgroundWorker {
+        BackgroundWorkerBuilder::new("bgworker_tests")
+            .set_function("bgworker_tests_main")
+            .set_library("pgx_tests")
+            .enable_shmem_access(None)
+            .set_argument(arg.into_datum())
+            .spawn_dynamic()
+            .expect("no background worker slots available")
+    }
+
+    #[pg_test]
+    fn test_dynamic_bgworker_exits() {
+        let worker = spawn(0);
+        assert_eq!(worker.wait_for_shutdown(), BackgroundWorkerStatus::Stopped);
+    }
+
+    #[pg_test]
+    fn test_dynamic_bgworker_terminate() {
+        let worker = spawn(WAIT_FOR_SIGTERM);
+        let pid = worker.wait_for_startup().expect("worker did not start");
+
+        assert!(pid > 0);
+        assert_eq!(worker.pid(), Ok(pid));
+
+        worker.terminate();
+        assert_eq!(worker.wait_for_shutdown(), BackgroundWorkerStatus::Stopped);
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_catalog_tables() {
+        Spi::run("CREATE SCHEMA catalog_test");
+        Spi::run("CREATE TABLE catalog_test.a (id int)");
+        Spi::run("CREATE TABLE catalog_test.b (id int)");
+        Spi::run("CREATE VIEW catalog_test.v AS SELECT 1");
+        let namespace_oid = Spi::get_one::<pg_sys::Oid>(
+            "SELECT oid FROM pg_namespace WHERE nspname = 'catalog_test'",
+        )
+        .expect("namespace oid was NULL");
+
+        let mut tables = pgx::catalog::tables()
+            .in_namespace(namespace_oid)
+            .collect::<Vec<_>>();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].name, "a");
+        assert_eq!(tables[0].namespace.as_deref(), Some("catalog_test"));
+        assert!(tables[0].is_table());
+        assert_eq!(
+            Some(tables[1].oid),
+            Spi::get_one::<pg_sys::Oid>("SELECT 'catalog_test.b'::regclass::oid")
+        );
+    }
+
+    #[pg_test]
+    fn test_catalog_relations() {
+        let pg_class = pgx::catalog::relations()
+            .find(|relation| relation.name == "pg_class")
+            .expect("didn't find pg_class");
+
+        assert_eq!(pg_class.oid, pg_sys::RelationRelationId);
+        assert_eq!(pg_class.namespace.as_deref(), Some("pg_catalog"));
+        assert_eq!(pg_class.relkind, pg_sys::RELKIND_RELATION);
+    }
+
+    #[pg_test]
+    fn test_catalog_temp_tables() {
+        Spi::run("CREATE TEMPORARY TABLE catalog_temp (id int)");
+        let table = pgx::catalog::tables()
+            .find(|relation| relation.name == "catalog_temp")
+            .expect("didn't find our temp table");
+        assert!(table.is_temp());
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[derive(PostgresType)]
+#[composite]
+pub struct LineItem {
+    sku: String,
+    #[pgx(sql_type = "numeric(10, 2)", not_null)]
+    price: Numeric,
+    #[pgx(sql_type = "varchar(20)")]
+    note: Option<String>,
+}
+
+#[pg_extern]
+fn line_item(sku: &str, price: Numeric) -> LineItem {
+    LineItem {
+        sku: sku.to_string(),
+        price,
+        note: None,
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_composite_sql_type_override() {
+        let types = Spi::get_one::<&str>(
+            "SELECT string_agg(attname || ' ' || format_type(atttypid, atttypmod), ', ' ORDER BY attnum)
+               FROM pg_attribute
+              WHERE attrelid = (SELECT typrelid FROM pg_type WHERE typname = 'lineitem')
+                AND attnum > 0",
+        );
+        assert_eq!(
+            types,
+            Some("sku text, price numeric(10,2), note character varying(20)")
+        );
+    }
+
+    #[pg_test]
+    fn test_composite_not_null_roundtrip() {
+        let price = Spi::get_one::<&str>("SELECT (line_item('abc', 9.99)).price::text");
+        assert_eq!(price, Some("9.99"));
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_copy_table() {
+        Spi::run("CREATE TABLE copy_test (id int4, name text, extra text DEFAULT 'default')");
+    }
+
+    #[pg_test]
+    fn test_copy_from_text() {
+        create_copy_table();
+        let data = "1\tone\tx\n2\t\\N\ty\n3\ttab\\there\tz\n";
+        let count = Spi::copy_from("COPY copy_test FROM STDIN", data.as_bytes());
+        assert_eq!(count, 3);
+
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM copy_test WHERE name IS NULL"),
+            Some(1)
+        );
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM copy_test WHERE id = 3"),
+            Some("tab\there".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_copy_from_csv_with_columns() {
+        create_copy_table();
+        let data = "id,name\n1,\"one, two\"\n2,three\n";
+        let count = Spi::copy_from(
+            "COPY copy_test (id, name) FROM STDIN WITH (FORMAT csv, HEADER true)",
+            data.as_bytes(),
+        );
+        assert_eq!(count, 2);
+
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM copy_test WHERE id = 1"),
+            Some("one, two".to_string())
+        );
+        assert_eq!(
+            Spi::get_one::<String>("SELECT extra FROM copy_test WHERE id = 2"),
+            Some("default".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_copy_from_binary() {
+        Spi::run("CREATE TABLE copy_binary_test (id int4)");
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"PGCOPY\n\xff\r\n\0");
+        data.extend_from_slice(&0i32.to_be_bytes()); // flags
+        data.extend_from_slice(&0i32.to_be_bytes()); // header extension length
+        for id in &[42i32, -7] {
+            data.extend_from_slice(&1i16.to_be_bytes());
+            data.extend_from_slice(&4i32.to_be_bytes());
+            data.extend_from_slice(&id.to_be_bytes());
+        }
+        data.extend_from_slice(&(-1i16).to_be_bytes());
+
+        let count = Spi::copy_from(
+            "COPY copy_binary_test FROM STDIN WITH (FORMAT binary)",
+            data.as_slice(),
+        );
+        assert_eq!(count, 2);
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT sum(id) FROM copy_binary_test"),
+            Some(35)
+        );
+    }
+
+    #[pg_test]
+    fn test_copy_from_rows() {
+        create_copy_table();
+        let rows = (1..=1000).map(|i| {
+            vec![
+                Some(i.to_string()),
+                if i % 2 == 0 {
+                    None
+                } else {
+                    Some(format!("line\n{}", i))
+                },
+            ]
+        });
+
+        let count = Spi::copy_from_rows("COPY copy_test (id, name) FROM STDIN", rows);
+        assert_eq!(count, 1000);
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM copy_test WHERE name IS NULL"),
+            Some(500)
+        );
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM copy_test WHERE id = 999"),
+            Some("line\n999".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_copy_to() {
+        let mut output = Vec::new();
+        let count = Spi::copy_to(
+            "SELECT i, CASE WHEN i = 2 THEN NULL ELSE 'a\tb' END FROM generate_series(1, 3) i",
+            &mut output,
+        )
+        .expect("copy_to failed");
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1\ta\\tb\n2\t\\N\n3\ta\\tb\n"
+        );
+    }
+
+    #[pg_test]
+    fn test_copy_round_trip() {
+        create_copy_table();
+        Spi::run(
+            "INSERT INTO copy_test SELECT i, 'name ' || i, NULL FROM generate_series(1, 2500) i",
+        );
+
+        let mut output = Vec::new();
+        Spi::copy_to("SELECT * FROM copy_test", &mut output).expect("copy_to failed");
+        Spi::run("TRUNCATE copy_test");
+
+        let count = Spi::copy_from("COPY copy_test FROM STDIN", output.as_slice());
+        assert_eq!(count, 2500);
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM copy_test WHERE extra IS NULL"),
+            Some(2500)
+        );
+    }
+
+    #[pg_test(error = "expected a COPY ... FROM STDIN statement")]
+    fn test_copy_from_requires_copy_from() {
+        create_copy_table();
+        Spi::copy_from("COPY copy_test TO STDOUT", "".as_bytes());
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use std::ffi::{CStr, CString};
+
+#[derive(Debug)]
+#[repr(C)]
+struct HexInt {
+    value: i64,
+}
+
+extension_sql! { r#"CREATE TYPE hexint;"# }
+
+#[pg_extern(immutable)]
+fn hexint_in(input: &CStr) -> PgBox<HexInt> {
+    let digits = input
+        .to_bytes()
+        .strip_prefix(b"0x")
+        .unwrap_or_else(|| panic!("{:?} isn't a hexint", input));
+    let value = digits.iter().fold(0i64, |value, digit| {
+        let digit = (*digit as char)
+            .to_digit(16)
+            .unwrap_or_else(|| panic!("{:?} isn't a hexint", input));
+        value * 16 + digit as i64
+    });
+
+    let mut hexint = PgBox::<HexInt>::alloc();
+    hexint.value = value;
+    hexint
+}
+
+#[pg_extern(immutable)]
+fn hexint_out(hexint: PgBox<HexInt>) -> CString {
+    CString::new(format!("0x{:x}", hexint.value)).unwrap()
+}
+
+extension_sql! { r#"
+CREATE TYPE hexint (
+   internallength = 8,
+   input = hexint_in,
+   output = hexint_out,
+   alignment = double
+);
+"#}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use crate::tests::cstring_tests::HexInt;
+    use pgx::*;
+    use std::ffi::CString;
+
+    #[pg_extern]
+    fn cstring_roundtrip(input: CString) -> CString {
+        input
+    }
+
+    #[pg_test]
+    fn test_hexint_in() {
+        let hexint =
+            Spi::get_one::<PgBox<HexInt>>("SELECT '0xff'::hexint").expect("hexint was NULL");
+        assert_eq!(hexint.value, 255);
+    }
+
+    #[pg_test]
+    fn test_hexint_out() {
+        let string = Spi::get_one::<&str>("SELECT '0x2A'::hexint::text");
+        assert_eq!(string, Some("0x2a"));
+    }
+
+    #[pg_test(error = "\"42\" isn't a hexint")]
+    fn test_hexint_in_invalid() {
+        Spi::run("SELECT '42'::hexint");
+    }
+
+    #[pg_test]
+    fn test_cstring_roundtrip() {
+        let cstring = Spi::get_one::<CString>("SELECT tests.cstring_roundtrip('pgx'::cstring)");
+        assert_eq!(cstring, Some(CString::new("pgx").unwrap()));
+    }
+}
@@ -17,6 +17,11 @@ fn option_default_argument(a: Option<default!(&str, "NULL")>) -> &str {
     }
 }
 
+#[pg_extern]
+fn trailing_default_arguments(a: i32, b: default!(i32, 10), c: default!(i32, 100)) -> i32 {
+    a + b + c
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
@@ -54,4 +59,15 @@ mod tests {
             .expect("didn't get SPI result");
         assert_eq!(result, "test");
     }
+
+    #[pg_test]
+    fn test_trailing_default_arguments() {
+        let result = Spi::get_one::<i32>("SELECT trailing_default_arguments(1);")
+            .expect("didn't get SPI result");
+        assert_eq!(result, 111);
+
+        let result = Spi::get_one::<i32>("SELECT trailing_default_arguments(1, c => 0);")
+            .expect("didn't get SPI result");
+        assert_eq!(result, 11);
+    }
 }
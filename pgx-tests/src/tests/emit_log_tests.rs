@@ -0,0 +1,59 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    static mut CAPTURED: Vec<(PgLogLevel, String, String)> = Vec::new();
+
+    fn capture(message: &mut LogMessage) {
+        if let Some(text) = message.message() {
+            if text.starts_with("emit_log_hook test") {
+                let captured = (message.level(), message.sqlstate(), text.into_owned());
+                unsafe { CAPTURED.push(captured) }
+
+                // keep our test messages out of the server log
+                message.set_output_to_server(false);
+            }
+        }
+    }
+
+    fn warn_from_callback(message: &mut LogMessage) {
+        if message.message().as_deref() == Some("emit_log_hook test: recursion") {
+            // this message doesn't get passed back to the callbacks
+            warning!("emit_log_hook test: raised by a callback");
+        }
+    }
+
+    #[pg_test]
+    fn test_emit_log_hook_captures_warning() {
+        register_emit_log_hook(capture);
+        warning!("emit_log_hook test: captured");
+
+        let captured = unsafe { &CAPTURED };
+        assert!(captured.contains(&(
+            PgLogLevel::WARNING,
+            "01000".to_string(),
+            "emit_log_hook test: captured".to_string()
+        )));
+    }
+
+    #[pg_test]
+    fn test_emit_log_hook_does_not_recurse() {
+        register_emit_log_hook(warn_from_callback);
+        register_emit_log_hook(capture);
+        warning!("emit_log_hook test: recursion");
+
+        let captured = unsafe { &CAPTURED };
+        assert!(captured
+            .iter()
+            .any(|(_, _, text)| text == "emit_log_hook test: recursion"));
+        assert!(!captured
+            .iter()
+            .any(|(_, _, text)| text == "emit_log_hook test: raised by a callback"));
+    }
+}
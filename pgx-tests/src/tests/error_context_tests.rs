@@ -0,0 +1,101 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn raise(message: &str) {
+        ereport(
+            PgLogLevel::ERROR,
+            PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+            message,
+            file!(),
+            line!(),
+            column!(),
+        );
+    }
+
+    #[pg_test]
+    fn test_error_context_added_to_error() {
+        let result = unsafe {
+            guard_result(|| {
+                let row = std::cell::Cell::new(0);
+                let _context =
+                    PgErrorContext::new(|| format!("while processing row {}", row.get()));
+                for i in 1..=10 {
+                    row.set(i);
+                    if i == 7 {
+                        raise("row is unlucky");
+                    }
+                }
+            })
+        };
+        let error = result.expect_err("guard_result did not catch the ERROR");
+        assert_eq!(error.message, "row is unlucky");
+        assert!(error
+            .context
+            .expect("ERROR has no context")
+            .contains("while processing row 7"));
+    }
+
+    #[pg_test]
+    fn test_error_context_nested() {
+        let result = unsafe {
+            guard_result(|| {
+                let _outer = PgErrorContext::new(|| "outer".to_string());
+                let _inner = PgErrorContext::new(|| "inner".to_string());
+                raise("nested");
+            })
+        };
+        let context = result
+            .expect_err("guard_result did not catch the ERROR")
+            .context
+            .expect("ERROR has no context");
+        assert!(context.find("inner").unwrap() < context.find("outer").unwrap());
+    }
+
+    #[pg_test]
+    fn test_error_context_added_to_panic() {
+        let result = unsafe {
+            guard_result(|| {
+                let _outer = PgErrorContext::new(|| "outer".to_string());
+                let _inner = PgErrorContext::new(|| "inner".to_string());
+                error!("panicked");
+            })
+        };
+        let error = result.expect_err("guard_result did not catch the panic");
+        assert_eq!(error.message, "panicked");
+        assert_eq!(error.context, Some("inner\nouter".to_string()));
+    }
+
+    #[pg_test(error = "panicked with context")]
+    fn test_error_context_reported_with_panic() {
+        let _context = PgErrorContext::new(|| "while panicking".to_string());
+        panic!("panicked with context");
+    }
+
+    #[pg_test]
+    fn test_error_context_popped_on_drop() {
+        let before = unsafe { pg_sys::error_context_stack };
+        {
+            let _context = PgErrorContext::new(|| "popped".to_string());
+            assert_ne!(before, unsafe { pg_sys::error_context_stack });
+        }
+        assert_eq!(before, unsafe { pg_sys::error_context_stack });
+
+        // dropping guards out of order still leaves the stack as it was
+        let first = PgErrorContext::new(|| "first".to_string());
+        let second = PgErrorContext::new(|| "second".to_string());
+        drop(first);
+        drop(second);
+        assert_eq!(before, unsafe { pg_sys::error_context_stack });
+
+        let result = unsafe { guard_result(|| raise("no context")) };
+        let error = result.expect_err("guard_result did not catch the ERROR");
+        assert!(!error.context.unwrap_or_default().contains("popped"));
+    }
+}
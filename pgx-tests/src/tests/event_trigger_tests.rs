@@ -0,0 +1,71 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_eventtrigger]
+fn audit_ddl(trigger: &PgEventTrigger) {
+    let is_create_stmt = trigger
+        .parse_tree()
+        .map(|node| is_a(node.as_ptr(), pg_sys::NodeTag_T_CreateStmt))
+        .unwrap_or(false);
+
+    Spi::run(&format!(
+        "INSERT INTO ddl_audit (event, tag, is_create_stmt) VALUES ('{}', '{}', {})",
+        trigger.event(),
+        trigger.tag(),
+        is_create_stmt
+    ));
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_event_trigger() {
+        Spi::run("CREATE TABLE ddl_audit (event text, tag text, is_create_stmt bool)");
+        Spi::run("CREATE EVENT TRIGGER audit_ddl ON ddl_command_end EXECUTE PROCEDURE audit_ddl()");
+
+        Spi::run("CREATE TABLE audited_by_event_trigger (id int)");
+        Spi::run("CREATE INDEX audited_by_event_trigger_idx ON audited_by_event_trigger (id)");
+
+        // the event trigger inserted these rows in this transaction, so read them read/write
+        Spi::connect(|mut client| {
+            let rows = client
+                .update(
+                    "SELECT event, tag, is_create_stmt FROM ddl_audit ORDER BY tag DESC",
+                    None,
+                    None,
+                )
+                .map(|row| {
+                    (
+                        row.get_datum::<String>(1).unwrap(),
+                        row.get_datum::<String>(2).unwrap(),
+                        row.get_datum::<bool>(3).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                rows,
+                vec![
+                    (
+                        "ddl_command_end".to_string(),
+                        "CREATE TABLE".to_string(),
+                        true
+                    ),
+                    (
+                        "ddl_command_end".to_string(),
+                        "CREATE INDEX".to_string(),
+                        false
+                    ),
+                ]
+            );
+            Ok(Some(()))
+        });
+    }
+}
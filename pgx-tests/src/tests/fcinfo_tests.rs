@@ -91,6 +91,11 @@ fn returns_tuple() -> (name!(id, i32), name!(title, String)) {
     (42, "pgx".into())
 }
 
+#[pg_extern]
+fn returns_record() -> RecordType<(name!(id, i32), name!(title, String))> {
+    RecordType((42, "pgx".into()))
+}
+
 #[pg_extern]
 fn same_name(same_name: &str) -> &str {
     same_name
@@ -245,10 +250,140 @@ mod tests {
         assert_eq!((Some(42), Some("pgx".into())), result)
     }
 
+    #[pg_test]
+    fn test_returns_tuple_is_set() {
+        let retset =
+            Spi::get_one::<bool>("SELECT proretset FROM pg_proc WHERE proname = 'returns_tuple';");
+        assert_eq!(retset, Some(true))
+    }
+
+    #[pg_test]
+    fn test_returns_record() {
+        let title = Spi::get_one::<String>("SELECT (returns_record()).title;");
+        assert_eq!(title, Some("pgx".into()));
+
+        let result = Spi::get_two::<bool, String>(
+            "SELECT proretset, array_to_string(proargnames, ',') FROM pg_proc WHERE proname = 'returns_record';",
+        );
+        assert_eq!((Some(false), Some("id,title".into())), result)
+    }
+
+    #[pg_test]
+    fn test_oid_function_call() {
+        let int4pl = Spi::get_one::<pg_sys::Oid>("SELECT 'int4pl'::regproc::oid;")
+            .expect("int4pl not found");
+        let result =
+            unsafe { oid_function_call::<i32>(int4pl, vec![2.into_datum(), 3.into_datum()]) };
+        assert_eq!(result, Some(5));
+    }
+
+    #[pg_test]
+    fn test_oid_function_call_strict_null_arg() {
+        let int4pl = Spi::get_one::<pg_sys::Oid>("SELECT 'int4pl'::regproc::oid;")
+            .expect("int4pl not found");
+        let result = unsafe { oid_function_call::<i32>(int4pl, vec![2.into_datum(), None]) };
+        assert_eq!(result, None);
+    }
+
+    #[pg_test(error = "function 177 accepts 2 arguments, but 1 were provided")]
+    fn test_oid_function_call_too_few_args() {
+        let int4pl = Spi::get_one::<pg_sys::Oid>("SELECT 'int4pl'::regproc::oid;")
+            .expect("int4pl not found");
+        unsafe { oid_function_call::<i32>(int4pl, vec![2.into_datum()]) };
+    }
+
+    #[pg_test]
+    fn test_oid_function_call_coll() {
+        let lower = Spi::get_one::<pg_sys::Oid>("SELECT 'lower(text)'::regprocedure::oid;")
+            .expect("lower(text) not found");
+        let result = unsafe {
+            oid_function_call_coll::<String>(
+                lower,
+                100, // DEFAULT_COLLATION_OID
+                vec!["PGX".into_datum()],
+            )
+        };
+        assert_eq!(result, Some("pgx".into()));
+    }
+
     /// ensures that we can have a `#[pg_extern]` function with an argument that
     /// shares its name
     #[pg_test]
     fn test_same_name() {
         assert_eq!("test", same_name("test"));
     }
+
+    #[pg_extern]
+    fn fcinfo_collation(_input: &str, fcinfo: FcInfo) -> pg_sys::Oid {
+        fcinfo.collation()
+    }
+
+    #[pg_extern]
+    fn fcinfo_args(a: Option<i32>, _b: Option<&str>, fcinfo: FcInfo) -> String {
+        format!(
+            "{} {:?} {} {} {}",
+            fcinfo.nargs(),
+            fcinfo.get_arg::<i32>(0),
+            fcinfo.arg_is_null(0),
+            fcinfo.arg_is_null(1),
+            a.is_none()
+        )
+    }
+
+    #[pg_extern]
+    fn fcinfo_arg_out_of_range(fcinfo: FcInfo) -> bool {
+        fcinfo.arg_is_null(0)
+    }
+
+    #[pg_test]
+    fn test_fcinfo_collation() {
+        let is_c = Spi::get_one::<bool>(
+            "SELECT tests.fcinfo_collation('a' COLLATE \"C\") = (SELECT oid FROM pg_collation WHERE collname = 'C')",
+        );
+        assert_eq!(is_c, Some(true));
+
+        let collation = Spi::get_one::<pg_sys::Oid>("SELECT tests.fcinfo_collation('a')");
+        assert_eq!(collation, Some(100)); // DEFAULT_COLLATION_OID
+    }
+
+    #[pg_test]
+    fn test_fcinfo_args() {
+        let result = Spi::get_one::<&str>("SELECT tests.fcinfo_args(NULL, 'b')");
+        assert_eq!(result, Some("2 None true false true"));
+
+        let result = Spi::get_one::<&str>("SELECT tests.fcinfo_args(42, NULL)");
+        assert_eq!(result, Some("2 Some(42) false true false"));
+    }
+
+    #[pg_test(error = "argument 0 is out of range for a function called with 0 arguments")]
+    fn test_fcinfo_arg_out_of_range() {
+        Spi::get_one::<bool>("SELECT tests.fcinfo_arg_out_of_range()");
+    }
+
+    #[pg_extern]
+    fn fcinfo_call_count(_row: i32, fcinfo: FcInfo) -> i64 {
+        let mut count = fcinfo.fn_extra(|| 0i64);
+        *count += 1;
+        *count
+    }
+
+    #[pg_extern]
+    fn fcinfo_extra_wrong_type(fcinfo: FcInfo) -> i64 {
+        fcinfo.fn_extra(|| 0i32);
+        *fcinfo.fn_extra(|| 0i64)
+    }
+
+    #[pg_test]
+    fn test_fcinfo_fn_extra() {
+        let query = "SELECT max(tests.fcinfo_call_count(i)) FROM generate_series(1, 3) i";
+        assert_eq!(Spi::get_one::<i64>(query), Some(3));
+
+        // each query has its own FmgrInfo, so the count starts over
+        assert_eq!(Spi::get_one::<i64>(query), Some(3));
+    }
+
+    #[pg_test(error = "fn_extra holds a value of a different type than i64")]
+    fn test_fcinfo_fn_extra_wrong_type() {
+        Spi::get_one::<i64>("SELECT tests.fcinfo_extra_wrong_type()");
+    }
 }
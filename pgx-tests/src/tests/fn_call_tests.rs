@@ -0,0 +1,92 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn fn_call_concat(a: &str, b: &str) -> String {
+        format!("{}{}", a, b)
+    }
+
+    #[pg_extern]
+    fn fn_call_by_name(name: &str, a: i32, b: i32) -> Result<Option<i32>, FnCallError> {
+        fn_call::<i32>(name, args![a, b])
+    }
+
+    #[pg_test]
+    fn test_fn_call() {
+        assert_eq!(fn_call::<i32>("int4pl", args![2, 3]), Ok(Some(5)));
+
+        // upper() raises an ERROR if it isn't called with a collation
+        assert_eq!(
+            fn_call::<String>("pg_catalog.upper", args!["abc"]),
+            Ok(Some("ABC".to_string()))
+        );
+        assert_eq!(
+            fn_call::<String>("tests.fn_call_concat", args!["a", "b"]),
+            Ok(Some("ab".to_string()))
+        );
+    }
+
+    #[pg_test]
+    fn test_fn_call_strict_null() {
+        assert_eq!(fn_call::<String>("upper", args![None::<&str>]), Ok(None));
+    }
+
+    #[pg_test]
+    fn test_fn_call_not_found() {
+        assert_eq!(
+            fn_call::<i32>("tests.no_such_function", args![1]),
+            Err(FnCallError::NotFound(
+                "tests.no_such_function(integer)".to_string()
+            ))
+        );
+    }
+
+    #[pg_test]
+    fn test_fn_call_ambiguous() {
+        Spi::run(
+            "CREATE FUNCTION tests.fn_call_ambiguous(a int8, b int4) RETURNS int8 LANGUAGE sql AS 'SELECT a + b';
+             CREATE FUNCTION tests.fn_call_ambiguous(a int4, b int8) RETURNS int8 LANGUAGE sql AS 'SELECT a + b';",
+        );
+
+        assert_eq!(
+            fn_call::<i64>("tests.fn_call_ambiguous", args![1, 2]),
+            Err(FnCallError::Ambiguous(
+                "tests.fn_call_ambiguous(integer, integer)".to_string()
+            ))
+        );
+    }
+
+    #[pg_test]
+    fn test_fn_call_type_mismatches() {
+        assert_eq!(
+            fn_call::<i64>("int8pl", args![1, 2]),
+            Err(FnCallError::ArgumentTypes(
+                "int8pl(bigint, bigint)".to_string()
+            ))
+        );
+        assert_eq!(
+            fn_call::<i64>("int4pl", args![1, 2]),
+            Err(FnCallError::ReturnType(
+                "int4pl(integer, integer)".to_string()
+            ))
+        );
+        assert_eq!(
+            fn_call::<i32>("generate_series", args![1, 2]),
+            Err(FnCallError::NotCallable(
+                "generate_series(integer, integer)".to_string()
+            ))
+        );
+    }
+
+    #[pg_test(error = "function tests.fn_call_missing(integer, integer) does not exist")]
+    fn test_fn_call_error() {
+        Spi::get_one::<i32>("SELECT tests.fn_call_by_name('tests.fn_call_missing', 1, 2)");
+    }
+}
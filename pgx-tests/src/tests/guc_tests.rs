@@ -136,4 +136,61 @@ mod tests {
         Spi::run("SET test.enum = 'three'");
         assert_eq!(GUC.get(), TestEnum::Three);
     }
+
+    struct EvenLengthHooks;
+    impl GucStringHooks for EvenLengthHooks {
+        fn check(value: Option<&str>) -> Result<(), String> {
+            match value {
+                Some(value) if value.len() % 2 != 0 => {
+                    Err(format!("\"{}\" doesn't have an even length", value))
+                }
+                _ => Ok(()),
+            }
+        }
+
+        fn assign(value: Option<&str>) {
+            ASSIGNED.with(|assigned| *assigned.borrow_mut() = value.map(|value| value.to_string()));
+        }
+    }
+
+    thread_local! {
+        static ASSIGNED: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+    }
+
+    #[pg_test]
+    fn test_string_guc_with_hooks() {
+        static GUC: GucSetting<Option<&'static str>> = GucSetting::new(Some("ab"));
+        GucRegistry::define_string_guc_with_hooks::<EvenLengthHooks>(
+            "test.hooked_string",
+            "test hooked string guc",
+            "test hooked string guc",
+            &GUC,
+            GucContext::Userset,
+        );
+        assert_eq!(
+            ASSIGNED.with(|assigned| assigned.borrow().clone()),
+            Some("ab".to_string())
+        );
+
+        Spi::run("SET test.hooked_string = 'abcd'");
+        assert_eq!(GUC.get().unwrap(), "abcd");
+        assert_eq!(
+            ASSIGNED.with(|assigned| assigned.borrow().clone()),
+            Some("abcd".to_string())
+        );
+    }
+
+    #[pg_test(error = "\"abc\" doesn't have an even length")]
+    fn test_string_guc_check_hook_rejects() {
+        static GUC: GucSetting<Option<&'static str>> = GucSetting::new(None);
+        GucRegistry::define_string_guc_with_hooks::<EvenLengthHooks>(
+            "test.hooked_string",
+            "test hooked string guc",
+            "test hooked string guc",
+            &GUC,
+            GucContext::Userset,
+        );
+
+        Spi::run("SET test.hooked_string = 'abc'");
+    }
 }
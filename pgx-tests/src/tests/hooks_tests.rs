@@ -15,6 +15,7 @@ mod tests {
 
         struct TestHook {
             events: u32,
+            rel_paths: Vec<(Vec<Index>, Oid, usize, usize)>,
         }
         impl PgHooks for TestHook {
             fn executor_start(
@@ -81,12 +82,49 @@ mod tests {
                 self.events += 1;
                 prev_hook(parse, cursor_options, bound_params)
             }
+
+            fn set_rel_pathlist(
+                &mut self,
+                root: PgBox<PlannerInfo>,
+                rel: PgBox<RelOptInfo>,
+                rti: Index,
+                rte: PgBox<RangeTblEntry>,
+                prev_hook: fn(
+                    PgBox<PlannerInfo>,
+                    PgBox<RelOptInfo>,
+                    Index,
+                    PgBox<RangeTblEntry>,
+                ) -> HookResult<()>,
+            ) -> HookResult<()> {
+                assert_eq!(root.rel(rti).map(|r| r.as_ptr()), Some(rel.as_ptr()));
+                assert_eq!(root.all_baserels(), vec![rti]);
+                self.rel_paths.push((
+                    rel.relids(),
+                    rte.relid,
+                    rel.baserestrictinfo().len(),
+                    rel.pathlist().len(),
+                ));
+                prev_hook(root, rel, rti, rte)
+            }
         }
 
-        static mut HOOK: TestHook = TestHook { events: 0 };
+        static mut HOOK: TestHook = TestHook {
+            events: 0,
+            rel_paths: Vec::new(),
+        };
         pgx::hooks::register_hook(&mut HOOK);
         Spi::run("SELECT 1");
         assert_eq!(6, HOOK.events);
+        assert!(HOOK.rel_paths.is_empty());
+
+        Spi::run("CREATE TABLE tests.hooks_table (id int)");
+        Spi::run("SELECT * FROM tests.hooks_table WHERE id = 1 AND id > 0");
+        let relid = Spi::get_one::<Oid>("SELECT 'tests.hooks_table'::regclass::oid")
+            .expect("relid was NULL");
+        assert_eq!(HOOK.rel_paths[0].0, vec![1]);
+        assert_eq!(HOOK.rel_paths[0].1, relid);
+        assert_eq!(HOOK.rel_paths[0].2, 2);
+        assert!(HOOK.rel_paths[0].3 > 0);
 
         // TODO:  it'd be nice to also test that .commit() and .abort() also get called
         //    but I don't see how to do that since we're running *inside* a transaction here
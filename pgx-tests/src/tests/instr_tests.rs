@@ -0,0 +1,23 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::time::Duration;
+
+    #[pg_test]
+    fn test_instr_timer() {
+        let timer = InstrTimer::start();
+        Spi::run("SELECT pg_sleep(0.05)");
+        let first = timer.elapsed();
+        let second = timer.elapsed();
+
+        assert!(first >= Duration::from_millis(50), "{:?}", first);
+        assert!(first < Duration::from_secs(10), "{:?}", first);
+        assert!(second >= first);
+    }
+}
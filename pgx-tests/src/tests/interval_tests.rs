@@ -0,0 +1,69 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::convert::TryFrom;
+
+    #[pg_extern]
+    fn interval_add_month(interval: Interval) -> Interval {
+        Interval::new(interval.months() + 1, interval.days(), interval.micros())
+    }
+
+    #[pg_test]
+    fn test_interval_from_sql() {
+        let interval =
+            Spi::get_one::<Interval>("SELECT '1 year 2 mons 3 days 00:00:04.5'::interval")
+                .expect("interval was NULL");
+        assert_eq!(interval.months(), 14);
+        assert_eq!(interval.days(), 3);
+        assert_eq!(interval.micros(), 4_500_000);
+    }
+
+    #[pg_test]
+    fn test_interval_roundtrip() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT tests.interval_add_month('11 mons 1 day 1 hour'::interval) = '1 year 1 day 1 hour'::interval",
+        )
+        .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_interval_display() {
+        let expected = Spi::get_one::<String>("SELECT '-2 days 00:00:01'::interval::text")
+            .expect("text was NULL");
+        assert_eq!(Interval::new(0, -2, 1_000_000).to_string(), expected);
+    }
+
+    #[pg_test]
+    fn test_interval_constructors() {
+        assert_eq!(Interval::from_months(3), Interval::new(3, 0, 0));
+        assert_eq!(Interval::from_days(3), Interval::new(0, 3, 0));
+        assert_eq!(Interval::from_micros(3), Interval::new(0, 0, 3));
+        assert_eq!(Interval::default(), Interval::new(0, 0, 0));
+    }
+
+    #[pg_test]
+    fn test_interval_duration() {
+        let duration = time::Duration::try_from(Interval::new(0, 1, 1_500_000)).unwrap();
+        assert_eq!(
+            duration,
+            time::Duration::hours(24) + time::Duration::milliseconds(1500)
+        );
+        assert_eq!(
+            Interval::from(time::Duration::seconds(90)),
+            Interval::from_micros(90_000_000)
+        );
+
+        let with_months = Interval::new(1, 1, 0);
+        assert_eq!(
+            time::Duration::try_from(with_months),
+            Err(IntervalHasMonthsError(with_months))
+        );
+    }
+}
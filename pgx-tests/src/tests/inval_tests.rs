@@ -0,0 +1,55 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[pg_test]
+    fn test_relcache_callback() {
+        Spi::run("CREATE TABLE tests.inval_test (id int)");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.inval_test'::regclass::oid")
+            .expect("relid was NULL");
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let for_callback = Rc::clone(&seen);
+        let receipt =
+            register_relcache_callback(move |relid| for_callback.borrow_mut().push(relid));
+
+        Spi::run("ALTER TABLE tests.inval_test ADD COLUMN name text");
+        assert!(seen.borrow().contains(&relid));
+
+        receipt.unregister_callback();
+        seen.borrow_mut().clear();
+        Spi::run("ALTER TABLE tests.inval_test ADD COLUMN other text");
+        assert!(seen.borrow().is_empty());
+    }
+
+    #[pg_test]
+    fn test_syscache_callback() {
+        Spi::run("CREATE FUNCTION tests.inval_fn() RETURNS int LANGUAGE sql AS 'SELECT 1'");
+
+        let seen = Rc::new(RefCell::new(0));
+        let for_callback = Rc::clone(&seen);
+        let receipt = register_syscache_callback(pg_sys::SysCacheIdentifier_PROCOID, move |_| {
+            *for_callback.borrow_mut() += 1
+        });
+
+        Spi::run(
+            "CREATE OR REPLACE FUNCTION tests.inval_fn() RETURNS int LANGUAGE sql AS 'SELECT 2'",
+        );
+        assert!(*seen.borrow() > 0);
+
+        receipt.unregister_callback();
+        *seen.borrow_mut() = 0;
+        Spi::run(
+            "CREATE OR REPLACE FUNCTION tests.inval_fn() RETURNS int LANGUAGE sql AS 'SELECT 3'",
+        );
+        assert_eq!(*seen.borrow(), 0);
+    }
+}
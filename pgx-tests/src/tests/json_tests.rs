@@ -8,6 +8,19 @@ mod tests {
     use crate as pgx_tests;
 
     use pgx::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Document {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    #[pg_extern]
+    fn document_add_tag(mut doc: PgJsonB<Document>, tag: String) -> PgJsonB<Document> {
+        doc.tags.push(tag);
+        doc
+    }
 
     #[pg_test]
     fn test_json() {
@@ -54,4 +67,160 @@ mod tests {
         assert_eq!(user.first_name, "Blah");
         assert_eq!(user.last_name, "McBlahFace");
     }
+
+    #[cfg(feature = "pg12")]
+    #[pg_test]
+    fn test_jsonb_path_query() {
+        use serde_json::json;
+
+        let jsonb = JsonB(json!({"a": [1, 2, 3], "b": {"c": "d"}}));
+        assert_eq!(
+            jsonb
+                .path_query("$.a[*] ? (@ > 1)")
+                .expect("invalid jsonpath"),
+            vec![json!(2), json!(3)]
+        );
+        assert_eq!(
+            jsonb.path_query("$.b").expect("invalid jsonpath"),
+            vec![json!({"c": "d"})]
+        );
+        assert!(jsonb
+            .path_query("$.missing")
+            .expect("invalid jsonpath")
+            .is_empty());
+    }
+
+    #[cfg(feature = "pg12")]
+    #[pg_test]
+    fn test_jsonb_path_exists() {
+        use serde_json::json;
+
+        let jsonb = JsonB(json!({"a": [1, 2, 3]}));
+        assert_eq!(jsonb.path_exists("$.a[*] ? (@ == 2)"), Ok(true));
+        assert_eq!(jsonb.path_exists("$.a[*] ? (@ == 4)"), Ok(false));
+    }
+
+    #[cfg(feature = "pg12")]
+    #[pg_test]
+    fn test_jsonb_path_errors() {
+        use serde_json::json;
+
+        let jsonb = JsonB(json!({"a": 1}));
+        let error = jsonb
+            .path_query("$.a[")
+            .expect_err("malformed jsonpath was accepted");
+        assert_eq!(error.sqlstate(), "42601");
+
+        assert!(jsonb.path_exists("$$$").is_err());
+        assert!(jsonb.path_query("strict $.missing").is_err());
+    }
+
+    #[pg_test]
+    fn test_pgjsonb_roundtrip() {
+        let doc = Spi::get_one::<PgJsonB<Document>>(
+            r#"SELECT tests.document_add_tag('{"title": "pgx", "tags": ["rust"]}', 'postgres')"#,
+        )
+        .expect("document was NULL");
+        assert_eq!(
+            doc.0,
+            Document {
+                title: "pgx".to_string(),
+                tags: vec!["rust".to_string(), "postgres".to_string()],
+            }
+        );
+
+        let tags = Spi::get_one::<JsonB>(
+            r#"SELECT tests.document_add_tag('{"title": "pgx", "tags": []}', 'sql') -> 'tags'"#,
+        )
+        .expect("tags were NULL");
+        assert_eq!(tags.0, serde_json::json!(["sql"]));
+    }
+
+    #[pg_test]
+    fn test_pgjsonb_deserialize_error() {
+        use std::convert::TryFrom;
+
+        let datum = JsonB(serde_json::json!({"title": 42})).into_datum();
+        let error = unsafe { PgJsonB::<Document>::try_from_datum(datum.unwrap(), false) }
+            .expect_err("invalid document was deserialized");
+        assert!(error.serde_error().is_data());
+
+        let error = PgJsonB::<Document>::try_from(JsonB(serde_json::json!([])))
+            .expect_err("invalid document was deserialized");
+        let message = error.to_string();
+        assert!(message.starts_with("cannot deserialize jsonb as "));
+        assert!(message.contains("Document"));
+    }
+
+    #[pg_extern]
+    fn jsonb_value_echo(value: JsonBValue) -> JsonBValue {
+        value
+    }
+
+    #[pg_test]
+    fn test_jsonb_value_from_sql() {
+        let value = Spi::get_one::<JsonBValue>(
+            r#"SELECT '{"price": 1.10, "id": "x", "tags": [true, null]}'::jsonb"#,
+        )
+        .expect("jsonb was NULL");
+
+        // jsonb orders keys by length and then bytewise
+        assert_eq!(
+            value,
+            JsonBValue::Object(vec![
+                ("id".to_string(), "x".into()),
+                (
+                    "tags".to_string(),
+                    vec![JsonBValue::Bool(true), JsonBValue::Null].into()
+                ),
+                (
+                    "price".to_string(),
+                    JsonBValue::Numeric(Numeric("1.10".to_string()))
+                ),
+            ])
+        );
+    }
+
+    #[pg_test]
+    fn test_jsonb_value_numeric_fidelity() {
+        let json = r#"{"amount": 12345678901234567890.123456789012345678900, "rate": 0.1}"#;
+        let rc = Spi::get_one::<bool>(&format!(
+            "SELECT tests.jsonb_value_echo('{0}')::text = '{0}'::jsonb::text",
+            json
+        ))
+        .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_jsonb_value_scalars() {
+        for (sql, expected) in vec![
+            (
+                "SELECT '\"hi\"'::jsonb",
+                JsonBValue::String("hi".to_string()),
+            ),
+            (
+                "SELECT '0.000'::jsonb",
+                JsonBValue::Numeric(Numeric("0.000".to_string())),
+            ),
+            ("SELECT 'false'::jsonb", JsonBValue::Bool(false)),
+            ("SELECT 'null'::jsonb", JsonBValue::Null),
+            ("SELECT '[]'::jsonb", JsonBValue::Array(vec![])),
+        ] {
+            let value = Spi::get_one::<JsonBValue>(sql).expect("jsonb was NULL");
+            assert_eq!(value, expected);
+
+            let rc = Spi::get_one_with_args::<bool>(
+                &format!("SELECT $1 = ({})", sql),
+                vec![(PgBuiltInOids::JSONBOID.oid(), expected.into_datum())],
+            )
+            .expect("SPI result was NULL");
+            assert!(rc, "{} didn't round trip", sql);
+        }
+    }
+
+    #[pg_test(error = "a jsonb numeric can't be NaN")]
+    fn test_jsonb_value_nan() {
+        JsonBValue::Numeric(Numeric("NaN".to_string())).into_datum();
+    }
 }
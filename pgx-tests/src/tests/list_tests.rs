@@ -0,0 +1,60 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_list_push_and_iter_ptr() {
+        let mut list = PgList::<pg_sys::Node>::new();
+        let a = PgNodeFactory::makeIndexAmRoutine();
+        let b = PgNodeFactory::makeIndexAmRoutine();
+
+        list.push(a.as_ptr() as *mut pg_sys::Node);
+        list.push(b.as_ptr() as *mut pg_sys::Node);
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.get_ptr(1), Some(b.as_ptr() as *mut pg_sys::Node));
+        assert_eq!(
+            list.iter_ptr().collect::<Vec<_>>(),
+            vec![
+                a.as_ptr() as *mut pg_sys::Node,
+                b.as_ptr() as *mut pg_sys::Node
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_list_push_int() {
+        let mut list = PgList::<i32>::new();
+        list.push_int(1);
+        list.push_int(2);
+        list.push_int(3);
+
+        assert_eq!(list.get_int(2), Some(3));
+        assert_eq!(list.iter_int().sum::<i32>(), 6);
+    }
+
+    #[pg_test]
+    fn test_list_push_oid() {
+        let mut list = PgList::<pg_sys::Oid>::new();
+        list.push_oid(pg_sys::INT4OID);
+        list.push_oid(pg_sys::TEXTOID);
+
+        assert_eq!(
+            list.iter_oid().collect::<Vec<_>>(),
+            vec![pg_sys::INT4OID, pg_sys::TEXTOID]
+        );
+    }
+
+    #[pg_test(error = "PgList does not contain ints")]
+    fn test_list_wrong_cell_type() {
+        let mut list = PgList::<pg_sys::Oid>::new();
+        list.push_oid(pg_sys::INT4OID);
+        list.get_int(0);
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn lsn_advance(lsn: Lsn, bytes: i64) -> Lsn {
+        Lsn(lsn.0 + bytes as u64)
+    }
+
+    #[pg_test]
+    fn test_lsn_from_sql() {
+        let lsn = Spi::get_one::<Lsn>("SELECT '16/B374D848'::pg_lsn").expect("lsn was NULL");
+        assert_eq!(lsn, Lsn(0x16_B374_D848));
+        assert_eq!(lsn.to_string(), "16/B374D848");
+    }
+
+    #[pg_test]
+    fn test_lsn_roundtrip() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT tests.lsn_advance('0/FFFFFFFF'::pg_lsn, 1) = '1/0'::pg_lsn",
+        )
+        .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_lsn_parse() {
+        assert_eq!("0/0".parse::<Lsn>(), Ok(Lsn(0)));
+        assert_eq!("a/1b".parse::<Lsn>(), Ok(Lsn(0xA_0000_001B)));
+        assert!("".parse::<Lsn>().is_err());
+        assert!("0/".parse::<Lsn>().is_err());
+        assert!("123456789/0".parse::<Lsn>().is_err());
+        assert!("0/x".parse::<Lsn>().is_err());
+        assert!(!Lsn::default().is_valid());
+        assert!(Lsn(1) < Lsn(0x1_0000_0000));
+    }
+
+    #[pg_test]
+    fn test_lsn_current() {
+        let lsn = Spi::get_one::<Lsn>("SELECT pg_current_wal_lsn()").expect("lsn was NULL");
+        assert!(lsn.is_valid());
+        let text = Spi::get_one::<String>("SELECT pg_current_wal_lsn()::text").unwrap();
+        assert!(text.parse::<Lsn>().unwrap() >= lsn);
+    }
+}
@@ -41,4 +41,210 @@ mod tests {
 
         assert!(did_drop.load(Ordering::SeqCst))
     }
+
+    #[pg_test]
+    fn test_leak_and_drop_on_reset() {
+        let did_drop = Arc::new(AtomicBool::new(false));
+
+        unsafe {
+            let context = pg_sys::AllocSetContextCreateExtended(
+                PgMemoryContexts::CurrentMemoryContext.value(),
+                "test\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+
+            let cache = PgMemoryContexts::For(context)
+                .leak_and_drop_on_delete_ref(std::collections::HashMap::<i32, TestObject>::new());
+            cache.insert(
+                1,
+                TestObject {
+                    did_drop: did_drop.clone(),
+                },
+            );
+            assert!(!did_drop.load(Ordering::SeqCst));
+
+            pg_sys::MemoryContextReset(context);
+            assert!(did_drop.load(Ordering::SeqCst));
+
+            pg_sys::MemoryContextDelete(context);
+        }
+    }
+
+    #[pg_test]
+    fn test_mem_allocated() {
+        PgMemoryContexts::Transient {
+            parent: PgMemoryContexts::CurrentMemoryContext.value(),
+            name: "test",
+            min_context_size: 4096,
+            initial_block_size: 4096,
+            max_block_size: 1024 * 1024,
+        }
+        .switch_to(|context| {
+            let before = PgMemoryContexts::CurrentMemoryContext.mem_allocated();
+            context.palloc(64 * 1024);
+            let after = PgMemoryContexts::CurrentMemoryContext.mem_allocated();
+
+            assert!(after.used_bytes >= before.used_bytes + 64 * 1024);
+            assert_eq!(after.used_bytes, after.total_bytes - after.free_bytes);
+        });
+    }
+
+    #[pg_test]
+    fn test_mem_allocated_recursive() {
+        unsafe {
+            let parent = pg_sys::AllocSetContextCreateExtended(
+                PgMemoryContexts::CurrentMemoryContext.value(),
+                "parent\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+            let child = pg_sys::AllocSetContextCreateExtended(
+                parent,
+                "child\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+            PgMemoryContexts::For(child).palloc(64 * 1024);
+
+            let parent_only = PgMemoryContexts::For(parent).mem_allocated();
+            let child_only = PgMemoryContexts::For(child).mem_allocated();
+            let recursive = PgMemoryContexts::For(parent).mem_allocated_recursive();
+
+            assert!(child_only.used_bytes >= 64 * 1024);
+            assert_eq!(
+                recursive.total_bytes,
+                parent_only.total_bytes + child_only.total_bytes
+            );
+            assert_eq!(
+                recursive.used_bytes,
+                parent_only.used_bytes + child_only.used_bytes
+            );
+
+            pg_sys::MemoryContextDelete(parent);
+        }
+    }
+
+    #[pg_test]
+    fn test_reset_each() {
+        let drops = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        struct CountDrop(Arc<std::sync::atomic::AtomicUsize>);
+        impl Drop for CountDrop {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        unsafe {
+            let context = pg_sys::AllocSetContextCreateExtended(
+                PgMemoryContexts::CurrentMemoryContext.value(),
+                "test\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+
+            let mut seen = Vec::new();
+            PgMemoryContexts::For(context).reset_each(0..1000, |alloc_ctx, i| {
+                assert_eq!(pg_sys::CurrentMemoryContext, context);
+                assert_eq!(drops.load(Ordering::SeqCst), i);
+
+                alloc_ctx.leak_and_drop_on_delete(CountDrop(drops.clone()));
+                pg_sys::palloc(1024 * 1024);
+                seen.push(i);
+            });
+
+            assert_ne!(pg_sys::CurrentMemoryContext, context);
+            assert_eq!(drops.load(Ordering::SeqCst), 1000);
+            assert_eq!(seen.len(), 1000);
+            assert!(PgMemoryContexts::For(context).mem_allocated().used_bytes < 1024 * 1024);
+
+            pg_sys::MemoryContextDelete(context);
+        }
+    }
+
+    #[pg_test(error = "cannot reset_each() the CurrentMemoryContext or one of its parents")]
+    fn test_reset_each_current_context() {
+        unsafe { PgMemoryContexts::CurrentMemoryContext.reset_each(0..1, |_, _| {}) };
+    }
+
+    #[pg_test]
+    fn test_reset_each_error() {
+        unsafe {
+            let outer = pg_sys::CurrentMemoryContext;
+            let context = pg_sys::AllocSetContextCreateExtended(
+                outer,
+                "test\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+
+            // the ERROR is caught in a subtransaction, which is rolled back to clean up after it
+            in_rolled_back_subtransaction(|| {
+                pg_try(|| {
+                    PgMemoryContexts::For(context).reset_each(0..10, |_, i| {
+                        pg_sys::palloc(1024 * 1024);
+                        if i == 3 {
+                            error!("failed on item {}", i);
+                        }
+                    })
+                })
+                .unwrap_or(());
+
+                assert_eq!(pg_sys::CurrentMemoryContext, outer);
+            });
+            assert!(PgMemoryContexts::For(context).mem_allocated().used_bytes < 1024 * 1024);
+
+            // nothing reset_each() registered on the context is left to fire
+            pg_sys::MemoryContextDelete(context);
+        }
+    }
+
+    #[pg_test]
+    fn test_palloc0_slice() {
+        let slice = PgMemoryContexts::CurrentMemoryContext.palloc0_slice::<i64>(16);
+        assert_eq!(slice.len(), 16);
+        assert!(slice.iter().all(|v| *v == 0));
+        assert_eq!(
+            slice.as_ptr() as usize % pg_sys::MAXIMUM_ALIGNOF as usize,
+            0
+        );
+    }
+
+    #[pg_test]
+    fn test_repalloc_slice() {
+        let slice = PgMemoryContexts::CurrentMemoryContext.palloc0_slice::<i32>(2);
+        slice[0] = 1;
+        slice[1] = 2;
+
+        let grown = unsafe { PgMemoryContexts::repalloc_slice(slice, 1000) };
+        assert_eq!(grown.len(), 1000);
+        assert_eq!(&grown[..3], &[1, 2, 0]);
+        assert!(grown[2..].iter().all(|v| *v == 0));
+
+        let shrunk = unsafe { PgMemoryContexts::repalloc_slice(grown, 1) };
+        assert_eq!(shrunk, &[1]);
+    }
+
+    #[pg_test(
+        error = "200000000 elements of i64 exceed the maximum allocation size of 1073741823 bytes"
+    )]
+    fn test_palloc_slice_too_large() {
+        PgMemoryContexts::CurrentMemoryContext.palloc_slice::<i64>(200_000_000);
+    }
+
+    #[repr(align(64))]
+    struct Overaligned;
+
+    #[pg_test(
+        error = "pgx_tests::tests::memcxt_tests::tests::Overaligned requires an alignment of 64 bytes, but Postgres only aligns to 8"
+    )]
+    fn test_palloc_slice_overaligned() {
+        PgMemoryContexts::CurrentMemoryContext.palloc_slice::<Overaligned>(1);
+    }
 }
@@ -1,30 +1,74 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+mod acl_tests;
 mod anyarray_tests;
 mod array_tests;
+mod bgworker_tests;
 mod bytea_tests;
+mod catalog_tests;
+mod composite_type_tests;
+mod copy_tests;
+mod cstring_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
+mod emit_log_tests;
 mod enum_type_tests;
+mod error_context_tests;
+mod event_trigger_tests;
 mod fcinfo_tests;
+mod fn_call_tests;
 mod guc_tests;
 mod hooks_tests;
 mod inet_tests;
+mod instr_tests;
+mod interval_tests;
+mod inval_tests;
 mod json_tests;
+mod list_tests;
 mod log_tests;
+mod lsn_tests;
 mod memcxt_tests;
+mod money_tests;
+mod name_tests;
 mod node_tests;
+mod notify_tests;
 mod numeric_tests;
+mod oid_tests;
+mod oidvector_tests;
+mod opclass_tests;
 mod pg_extern_args_tests;
 mod pg_try_tests;
+mod pgstat_tests;
+mod procedure_tests;
+mod query_tests;
+mod record_builder_tests;
+mod record_tests;
+mod rel_tests;
+mod reloptions_tests;
+mod result_tests;
+mod scankey_tests;
 mod schema_tests;
+mod shmem_tests;
+mod spi_nonatomic_tests;
 mod spi_tests;
 mod srf_tests;
 mod struct_type_tests;
+mod syscache_tests;
+mod tableam_tests;
+mod tid_tests;
+mod trigger_tests;
+mod tupleslot_tests;
+mod type_io_tests;
+mod typecache_tests;
+mod varbit_tests;
 mod variadic_tests;
+mod varlena_tests;
+mod version_tests;
 mod xact_callback_tests;
+mod xact_tests;
 mod xid64_tests;
+mod xlog_tests;
 
 pgx::pg_module_magic!();
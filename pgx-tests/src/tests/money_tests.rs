@@ -0,0 +1,49 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn double_money(money: Money) -> Money {
+        money * 2
+    }
+
+    #[pg_test]
+    fn test_money_from_sql() {
+        let money = Spi::get_one::<Money>("SELECT 12.34::money").expect("money was NULL");
+        assert_eq!(money, Money(1234));
+    }
+
+    #[pg_test]
+    fn test_money_roundtrip() {
+        let rc =
+            Spi::get_one::<bool>("SELECT tests.double_money('1234.56'::money) = '2469.12'::money")
+                .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_money_display() {
+        let expected =
+            Spi::get_one::<String>("SELECT '-1234.56'::money::text").expect("text was NULL");
+        assert_eq!(Money(-123456).to_string(), expected);
+    }
+
+    #[pg_test]
+    fn test_money_arithmetic() {
+        assert_eq!(Money(150) + Money(250), Money(400));
+        assert_eq!(Money(150) - Money(250), Money(-100));
+        assert_eq!(Money(150) * 3, Money(450));
+        assert_eq!(-Money(150), Money(-150));
+    }
+
+    #[pg_test(error = "money out of range")]
+    fn test_money_overflow() {
+        let _ = Money(i64::MAX) + Money(1);
+    }
+}
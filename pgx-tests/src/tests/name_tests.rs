@@ -0,0 +1,57 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn name_roundtrip(name: Name) -> Name {
+        name
+    }
+
+    #[pg_extern]
+    fn char_roundtrip(c: i8) -> i8 {
+        c
+    }
+
+    #[pg_test]
+    fn test_name_from_catalog() {
+        let relname =
+            Spi::get_one::<Name>("SELECT relname FROM pg_class WHERE oid = 'pg_class'::regclass");
+        assert_eq!(relname, Some(Name::new("pg_class")));
+    }
+
+    #[pg_test]
+    fn test_name_roundtrip() {
+        let result = Spi::get_one::<bool>("SELECT tests.name_roundtrip('pgx') = 'pgx'::name")
+            .expect("SPI result was NULL");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_name_truncates() {
+        let long = "x".repeat(100);
+        let name = Name::new(&long);
+        assert_eq!(name.len(), 63);
+
+        // multibyte characters aren't split
+        let long = "é".repeat(40);
+        let name = Name::new(&long);
+        assert_eq!(name.len(), 62);
+    }
+
+    #[pg_test]
+    fn test_char_roundtrip() {
+        let result = Spi::get_one::<bool>("SELECT tests.char_roundtrip('r') = 'r'::\"char\"")
+            .expect("SPI result was NULL");
+        assert!(result);
+
+        let relkind =
+            Spi::get_one::<i8>("SELECT relkind FROM pg_class WHERE oid = 'pg_class'::regclass");
+        assert_eq!(relkind, Some(b'r' as i8));
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::time::Duration;
+
+    #[pg_test]
+    fn test_warn_once_per() {
+        let window = Duration::from_secs(3600);
+        assert!(warn_once_per(window, "test_warn_once_per", "first"));
+        assert!(!warn_once_per(window, "test_warn_once_per", "second"));
+        assert!(!warn_once_per(window, "test_warn_once_per", "third"));
+
+        // keys are limited independently
+        assert!(notice_once_per(window, "test_warn_once_per other", "first"));
+    }
+
+    #[pg_test]
+    fn test_notice_once_per_elapsed_window() {
+        let window = Duration::from_millis(10);
+        assert!(notice_once_per(window, "test_notice_once_per", "first"));
+        assert!(!notice_once_per(window, "test_notice_once_per", "second"));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(notice_once_per(window, "test_notice_once_per", "third"));
+    }
+
+    #[pg_test(error = "log_once_per() can't rate-limit ERROR messages")]
+    fn test_log_once_per_error() {
+        log_once_per(PgLogLevel::ERROR, Duration::from_secs(1), "error", "boom");
+    }
+}
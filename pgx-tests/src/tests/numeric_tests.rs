@@ -25,6 +25,11 @@ mod tests {
         std::u64::MAX.into()
     }
 
+    #[pg_extern]
+    fn i128_roundtrip(value: i128) -> i128 {
+        value
+    }
+
     #[pg_test]
     fn test_return_an_i32_numeric() {
         let result = Spi::get_one::<bool>("SELECT 32::numeric = tests.return_an_i32_numeric();")
@@ -63,4 +68,43 @@ mod tests {
             .to_string();
         assert_eq!("invalid Numeric value: foo", &error);
     }
+
+    #[pg_test]
+    fn test_i128_roundtrip() {
+        let result = Spi::get_one::<bool>(
+            "SELECT tests.i128_roundtrip(170141183460469231731687303715884105727) = 170141183460469231731687303715884105727::numeric;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+
+        let min = Spi::get_one::<i128>(
+            "SELECT tests.i128_roundtrip(-170141183460469231731687303715884105728);",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(min, std::i128::MIN);
+    }
+
+    #[pg_test]
+    fn test_i128_from_numeric_with_zero_scale() {
+        let result =
+            Spi::get_one::<i128>("SELECT 42.000::numeric;").expect("failed to get SPI result");
+        assert_eq!(result, 42);
+    }
+
+    #[pg_test(error = "numeric value 42.5 has a fractional part")]
+    fn test_i128_from_fractional_numeric() {
+        Spi::get_one::<i128>("SELECT 42.5::numeric;");
+    }
+
+    #[pg_test(
+        error = "numeric value 170141183460469231731687303715884105728 is out of range for an i128"
+    )]
+    fn test_i128_from_overflowing_numeric() {
+        Spi::get_one::<i128>("SELECT 170141183460469231731687303715884105728::numeric;");
+    }
+
+    #[pg_test(error = "numeric value NaN can't be converted into an i128")]
+    fn test_i128_from_nan_numeric() {
+        Spi::get_one::<i128>("SELECT 'NaN'::numeric;");
+    }
 }
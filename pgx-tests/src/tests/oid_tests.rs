@@ -0,0 +1,58 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn oid_is_builtin(oid: PgOid) -> bool {
+        matches!(oid, PgOid::BuiltIn(_))
+    }
+
+    #[pg_test]
+    fn test_regtype() {
+        assert_eq!(regtype("int4"), PgOid::BuiltIn(PgBuiltInOids::INT4OID));
+        assert_eq!(
+            regtype("pg_catalog.varchar(255)"),
+            PgOid::BuiltIn(PgBuiltInOids::VARCHAROID)
+        );
+        assert_eq!(regtype("no_such_type"), PgOid::InvalidOid);
+    }
+
+    #[pg_test]
+    fn test_regclass() {
+        Spi::run("CREATE TABLE oid_test_table (id int)");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'oid_test_table'::regclass::oid")
+            .expect("relid was NULL");
+
+        assert_eq!(regclass("oid_test_table"), PgOid::Custom(relid));
+        assert_eq!(regclass("public.oid_test_table"), PgOid::Custom(relid));
+        assert_eq!(regclass("no_such_table"), PgOid::InvalidOid);
+    }
+
+    #[pg_test]
+    fn test_oid_from_datum() {
+        let oid = Spi::get_one::<PgOid>("SELECT 'text'::regtype::oid").expect("oid was NULL");
+        assert_eq!(oid, PgOid::BuiltIn(PgBuiltInOids::TEXTOID));
+
+        let relid =
+            Spi::get_one::<PgOid>("SELECT 'pg_class'::regclass::oid").expect("oid was NULL");
+        assert_eq!(relid, PgOid::Custom(pg_sys::RelationRelationId));
+    }
+
+    #[pg_test]
+    fn test_oid_argument() {
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT tests.oid_is_builtin('int8'::regtype::oid)"),
+            Some(true)
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT tests.oid_is_builtin('pg_class'::regclass::oid)"),
+            Some(false)
+        );
+    }
+}
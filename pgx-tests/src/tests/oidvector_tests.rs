@@ -0,0 +1,77 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn int2vector_roundtrip(v: Int2Vector) -> Int2Vector {
+        v
+    }
+
+    #[pg_extern]
+    fn oidvector_roundtrip(v: OidVector) -> OidVector {
+        v
+    }
+
+    #[pg_test]
+    fn test_int2vector_from_catalog() {
+        let indkey = Spi::get_one::<Vec<i16>>(
+            "SELECT indkey FROM pg_index WHERE indexrelid = 'pg_class_relname_nsp_index'::regclass",
+        )
+        .expect("indkey was NULL");
+        let expected = Spi::get_one::<Vec<Option<i16>>>(
+            "SELECT array_agg(attnum ORDER BY attnum) FROM pg_attribute \
+             WHERE attrelid = 'pg_class'::regclass AND attname IN ('relname', 'relnamespace')",
+        )
+        .expect("array_agg was NULL");
+        assert_eq!(
+            indkey,
+            expected.into_iter().map(Option::unwrap).collect::<Vec<_>>()
+        );
+    }
+
+    #[pg_test]
+    fn test_oidvector_from_catalog() {
+        let argtypes = Spi::get_one::<OidVector>(
+            "SELECT proargtypes FROM pg_proc WHERE oid = 'int4pl'::regproc",
+        )
+        .expect("proargtypes was NULL");
+        assert_eq!(argtypes, OidVector(vec![pg_sys::INT4OID, pg_sys::INT4OID]));
+    }
+
+    #[pg_test]
+    fn test_int2vector_roundtrip() {
+        let result = Spi::get_one::<bool>(
+            "SELECT tests.int2vector_roundtrip('1 2 3') = '1 2 3'::int2vector",
+        )
+        .expect("SPI result was NULL");
+        assert!(result);
+
+        let empty = Spi::get_one::<Int2Vector>("SELECT tests.int2vector_roundtrip('')")
+            .expect("SPI result was NULL");
+        assert!(empty.is_empty());
+    }
+
+    #[pg_test]
+    fn test_oidvector_roundtrip() {
+        let result = Spi::get_one::<String>("SELECT tests.oidvector_roundtrip('23 25')::text")
+            .expect("SPI result was NULL");
+        assert_eq!(result, "23 25");
+    }
+
+    #[pg_test]
+    fn test_vec_from_int2_array() {
+        let values = Spi::get_one::<Vec<i16>>("SELECT ARRAY[4, 5, 6]::int2[]");
+        assert_eq!(values, Some(vec![4, 5, 6]));
+    }
+
+    #[pg_test(error = "a vector datum must be one-dimensional and can't contain NULLs")]
+    fn test_vec_from_int2_array_with_nulls() {
+        Spi::get_one::<Vec<i16>>("SELECT ARRAY[4, NULL]::int2[]");
+    }
+}
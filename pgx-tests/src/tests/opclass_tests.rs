@@ -0,0 +1,114 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(PostgresType, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SemVer {
+    major: i32,
+    minor: i32,
+}
+
+#[pg_operator(immutable)]
+#[opname(<)]
+#[opclass_operator(btree, 1)]
+fn semver_lt(left: SemVer, right: SemVer) -> bool {
+    left < right
+}
+
+#[pg_operator(immutable)]
+#[opname(<=)]
+#[opclass_operator(btree, 2)]
+fn semver_le(left: SemVer, right: SemVer) -> bool {
+    left <= right
+}
+
+#[pg_operator(immutable)]
+#[opname(=)]
+#[opclass_operator(btree, 3)]
+#[opclass_operator(hash, 1)]
+fn semver_eq(left: SemVer, right: SemVer) -> bool {
+    left == right
+}
+
+#[pg_operator(immutable)]
+#[opname(>=)]
+#[opclass_operator(btree, 4)]
+fn semver_ge(left: SemVer, right: SemVer) -> bool {
+    left >= right
+}
+
+#[pg_operator(immutable)]
+#[opname(>)]
+#[opclass_operator(btree, 5)]
+fn semver_gt(left: SemVer, right: SemVer) -> bool {
+    left > right
+}
+
+#[pg_extern(immutable)]
+#[opclass_function(btree, 1)]
+fn semver_cmp(left: SemVer, right: SemVer) -> i32 {
+    match left.cmp(&right) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+        std::cmp::Ordering::Greater => 1,
+    }
+}
+
+#[pg_extern(immutable)]
+#[opclass_function(hash, 1)]
+fn semver_hash(semver: SemVer) -> i32 {
+    // a hash index stores these, so they must not change, as `DefaultHasher`'s may
+    let key = ((semver.major as i64) << 32) | (semver.minor as u32 as i64);
+    unsafe { direct_function_call::<i32>(pg_sys::hashint8, vec![key.into_datum()]) }
+        .expect("hashint8() returned NULL")
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_versions_table() {
+        Spi::run("CREATE TABLE tests.versions (v semver)");
+        Spi::run(
+            r#"INSERT INTO tests.versions VALUES
+                ('{"major": 1, "minor": 10}'), ('{"major": 0, "minor": 9}'), ('{"major": 1, "minor": 2}')"#,
+        );
+    }
+
+    #[pg_test]
+    fn test_opclasses_are_default_for_type() {
+        let count = Spi::get_one::<i64>(
+            "SELECT count(*) FROM pg_opclass WHERE opcintype = 'semver'::regtype AND opcdefault",
+        );
+        assert_eq!(count, Some(2));
+    }
+
+    #[pg_test]
+    fn test_btree_opclass() {
+        create_versions_table();
+        Spi::run("CREATE INDEX versions_btree ON tests.versions (v)");
+
+        let sorted =
+            Spi::get_one::<&str>("SELECT string_agg(v::text, ' ' ORDER BY v) FROM tests.versions");
+        assert_eq!(
+            sorted,
+            Some(r#"{"major":0,"minor":9} {"major":1,"minor":2} {"major":1,"minor":10}"#)
+        );
+    }
+
+    #[pg_test]
+    fn test_hash_opclass() {
+        create_versions_table();
+        Spi::run("CREATE INDEX versions_hash ON tests.versions USING hash (v)");
+
+        let count = Spi::get_one::<i64>(
+            r#"SELECT count(*) FROM tests.versions WHERE v = '{"major": 1, "minor": 2}'"#,
+        );
+        assert_eq!(count, Some(1));
+    }
+}
@@ -20,4 +20,89 @@ mod tests {
         .expect("failed to get SPI result");
         assert!(result)
     }
+
+    #[pg_extern(stable, parallel_safe)]
+    fn is_stable_parallel_safe() {}
+
+    #[pg_extern(immutable, parallel_restricted)]
+    fn is_immutable_parallel_restricted() {}
+
+    #[pg_test]
+    fn test_stable_parallel_safe() {
+        let result = Spi::get_one::<bool>(
+            "SELECT provolatile = 's' AND proparallel = 's' FROM pg_proc WHERE proname = 'is_stable_parallel_safe'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_immutable_parallel_restricted() {
+        let result = Spi::get_one::<bool>(
+            "SELECT provolatile = 'i' AND proparallel = 'r' FROM pg_proc WHERE proname = 'is_immutable_parallel_restricted'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_extern]
+    fn strict_add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    #[pg_extern]
+    fn mixed_nullability(a: i32, b: Option<i32>) -> i32 {
+        a + b.unwrap_or(100)
+    }
+
+    #[pg_extern]
+    fn mixed_nullability_srf(
+        count: i32,
+        start: Option<i32>,
+    ) -> impl std::iter::Iterator<Item = i32> {
+        let start = start.unwrap_or(0);
+        start..start + count
+    }
+
+    #[pg_test]
+    fn test_strict() {
+        let is_strict = |name: &str| {
+            Spi::get_one::<bool>(&format!(
+                "SELECT proisstrict FROM pg_proc WHERE proname = '{}'",
+                name
+            ))
+            .expect("failed to get SPI result")
+        };
+        assert!(is_strict("strict_add"));
+        assert!(!is_strict("mixed_nullability"));
+
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT tests.strict_add(1, NULL)"),
+            None
+        );
+    }
+
+    #[pg_test]
+    fn test_mixed_nullability() {
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT tests.mixed_nullability(1, NULL)"),
+            Some(101)
+        );
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT tests.mixed_nullability(NULL, 2)"),
+            None
+        );
+    }
+
+    #[pg_test]
+    fn test_mixed_nullability_srf() {
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.mixed_nullability_srf(3, NULL)"),
+            Some(3)
+        );
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.mixed_nullability_srf(NULL, 1)"),
+            Some(0)
+        );
+    }
 }
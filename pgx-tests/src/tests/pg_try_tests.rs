@@ -62,8 +62,166 @@ mod tests {
         pg_try(|| panic!("rethrow a panic")).unwrap_or_rethrow(|| ());
     }
 
-    #[pg_test(error = "panic in rethrow")]
-    fn test_pg_try_unwrap_or_rethrow_with_error_in_rethrow() {
+    thread_local! {
+        static DOUBLE_FAULTS: std::cell::RefCell<Vec<DoubleFault>> = std::cell::RefCell::new(Vec::new());
+    }
+
+    fn record_double_fault(fault: &DoubleFault) {
+        DOUBLE_FAULTS.with(|faults| faults.borrow_mut().push(fault.clone()));
+    }
+
+    #[pg_extern]
+    fn raise_double_fault() {
         pg_try(|| panic!("rethrow a panic")).unwrap_or_rethrow(|| panic!("panic in rethrow"));
     }
+
+    #[pg_test]
+    fn test_pg_try_unwrap_or_rethrow_with_error_in_rethrow() {
+        let prev_policy = set_double_fault_policy(DoubleFaultPolicy::Error);
+        let prev_hook = set_double_fault_hook(Some(record_double_fault));
+
+        // the ERROR is raised through SPI, so it's caught in a subtransaction that's rolled back
+        let result = in_rolled_back_subtransaction(|| {
+            PgTryBuilder::new(|| Ok(Spi::run("SELECT tests.raise_double_fault()")))
+                .catch_others(|e| Err(e.message.clone()))
+                .finally(|| {
+                    set_double_fault_policy(prev_policy);
+                    set_double_fault_hook(prev_hook);
+                })
+                .execute()
+        });
+
+        assert_eq!(
+            result,
+            Err(
+                "error during error handling: panic in rethrow (while handling: rethrow a panic)"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            DOUBLE_FAULTS.with(|faults| faults.borrow().clone()),
+            vec![DoubleFault {
+                first: "rethrow a panic".to_string(),
+                second: "panic in rethrow".to_string(),
+            }]
+        );
+        assert!(!is_handling_error());
+    }
+
+    #[pg_test]
+    fn test_guard_result_no_error() {
+        let result = unsafe { guard_result(|| 42) };
+        assert_eq!(42, result.expect("guard_result returned an error"));
+    }
+
+    #[pg_test]
+    fn test_guard_result_with_postgres_error() {
+        let result = unsafe {
+            guard_result(|| {
+                let name =
+                    std::ffi::CStr::from_bytes_with_nul(b"this_function_does_not_exist\0").unwrap();
+                direct_function_call_as_datum(pg_sys::regprocin, vec![name.into_datum()])
+            })
+        };
+        let error = result.expect_err("guard_result did not catch the ERROR");
+        assert_eq!("42883", error.sqlstate());
+        assert!(error.message.contains("this_function_does_not_exist"));
+
+        // and Postgres is still usable afterwards
+        let value = Spi::get_one::<i32>("SELECT 42").expect("SPI result was NULL");
+        assert_eq!(42, value);
+    }
+
+    #[pg_test]
+    fn test_guard_result_with_panic() {
+        let result = unsafe { guard_result(|| panic!("caught a panic")) };
+        let error: PgError = result.expect_err("guard_result did not catch the panic");
+        assert_eq!("XX000", error.sqlstate());
+        assert_eq!("caught a panic", error.message);
+    }
+
+    #[pg_test]
+    fn test_pg_try_builder_no_error() {
+        let finally_ran = std::cell::Cell::new(false);
+        let result = PgTryBuilder::new(|| 42)
+            .catch_others(|_| 99)
+            .finally(|| finally_ran.set(true))
+            .execute();
+        assert_eq!(42, result);
+        assert!(finally_ran.get());
+    }
+
+    #[pg_test]
+    fn test_pg_try_builder_catch_when() {
+        let finally_ran = std::cell::Cell::new(false);
+
+        // the ERROR is raised through SPI, so it's caught in a subtransaction that's rolled back
+        let result = in_rolled_back_subtransaction(|| {
+            PgTryBuilder::new(|| Spi::get_one::<i32>("SELECT 1 / 0"))
+                .catch_when(PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO, |e| {
+                    assert_eq!("22012", e.sqlstate());
+                    Some(-1)
+                })
+                .catch_others(|e| e.rethrow())
+                .finally(|| finally_ran.set(true))
+                .execute()
+        });
+        assert_eq!(Some(-1), result);
+        assert!(finally_ran.get());
+
+        // and Postgres is still usable afterwards
+        assert_eq!(Some(42), Spi::get_one::<i32>("SELECT 42"));
+    }
+
+    #[pg_test]
+    fn test_pg_try_builder_catch_others_with_panic() {
+        let result = PgTryBuilder::new(|| -> i32 { panic!("caught by catch_others") })
+            .catch_when(PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO, |_| 1)
+            .catch_others(|e| {
+                assert_eq!("caught by catch_others", e.message);
+                2
+            })
+            .execute();
+        assert_eq!(2, result);
+    }
+
+    #[pg_test(error = "division by zero")]
+    fn test_pg_try_builder_rethrow() {
+        PgTryBuilder::new(|| Spi::get_one::<i32>("SELECT 1 / 0"))
+            .catch_others(|e| e.rethrow())
+            .execute();
+    }
+
+    #[pg_test(error = "division by zero")]
+    fn test_pg_try_builder_uncaught() {
+        PgTryBuilder::new(|| Spi::get_one::<i32>("SELECT 1 / 0"))
+            .catch_when(PgSqlErrorCode::ERRCODE_UNDEFINED_TABLE, |_| None)
+            .execute();
+    }
+
+    #[pg_test]
+    fn test_pg_error_rethrow() {
+        let mut error = unsafe {
+            guard_result(|| {
+                ereport(
+                    PgLogLevel::ERROR,
+                    PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+                    "original",
+                    file!(),
+                    line!(),
+                    column!(),
+                )
+            })
+        }
+        .expect_err("guard_result did not catch the ERROR");
+        error.detail = Some("some detail".to_string());
+        error.hint = Some("some hint".to_string());
+
+        let rethrown = unsafe { guard_result(|| error.rethrow()) }
+            .expect_err("guard_result did not catch the rethrown ERROR");
+        assert_eq!(rethrown.sqlstate(), "22000");
+        assert_eq!(rethrown.message, "original");
+        assert_eq!(rethrown.detail, Some("some detail".to_string()));
+        assert_eq!(rethrown.hint, Some("some hint".to_string()));
+    }
 }
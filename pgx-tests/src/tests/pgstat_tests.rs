@@ -0,0 +1,42 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_report_activity() {
+        report_activity("pgx is doing something");
+
+        let query = Spi::get_one::<String>(
+            "SELECT query FROM pg_stat_activity WHERE pid = pg_backend_pid();",
+        );
+        assert_eq!(query, Some("pgx is doing something".into()));
+    }
+
+    #[pg_test]
+    fn test_report_progress() {
+        start_progress(
+            pg_sys::ProgressCommandType_PROGRESS_COMMAND_VACUUM,
+            pg_sys::RelationRelationId,
+        );
+        report_progress(0, 42);
+        report_progress_multi(&[(1, 100), (2, 200)]);
+
+        let params = Spi::get_three::<i64, i64, i64>(
+            "SELECT param1, param2, param3 FROM pg_stat_get_progress_info('VACUUM') WHERE pid = pg_backend_pid();",
+        );
+        end_progress();
+
+        assert_eq!(params, (Some(42), Some(100), Some(200)));
+    }
+
+    #[pg_test(error = "progress parameter index 100 is out of bounds")]
+    fn test_report_progress_out_of_bounds() {
+        report_progress(100, 0);
+    }
+}
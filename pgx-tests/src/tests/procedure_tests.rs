@@ -0,0 +1,150 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#![cfg(any(feature = "pg11", feature = "pg12"))]
+
+use pgx::*;
+
+// procedures only exist on Postgres 11+, and the schema is shared by all versions, so these
+// provide their own CREATE PROCEDURE statements rather than use the generated ones.  The
+// generated statements are tested by cargo-pgx's `schema::tests`
+
+#[pg_proc(sql = r#"
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 110000 THEN
+        EXECUTE $sql$CREATE PROCEDURE proc_insert(id integer) LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@'$sql$;
+    END IF;
+END;
+$$;
+"#)]
+fn proc_insert(id: i32) {
+    Spi::run(&format!("INSERT INTO tests.proc_test VALUES ({})", id));
+}
+
+#[pg_proc(sql = r#"
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 110000 THEN
+        EXECUTE $sql$CREATE PROCEDURE proc_add_to(value integer, INOUT total integer) LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@'$sql$;
+    END IF;
+END;
+$$;
+"#)]
+fn proc_add_to(value: i32, total: &mut i32) {
+    *total += value;
+}
+
+#[pg_proc(sql = r#"
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 110000 THEN
+        EXECUTE $sql$CREATE PROCEDURE proc_split(INOUT name text, INOUT suffix text) LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@'$sql$;
+    END IF;
+END;
+$$;
+"#)]
+fn proc_split(name: &mut String, suffix: &mut Option<String>) {
+    if let Some(dot) = name.rfind('.') {
+        *suffix = Some(name[dot + 1..].to_string());
+        name.truncate(dot);
+    } else {
+        *suffix = None;
+    }
+}
+
+#[pg_proc(sql = r#"
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 110000 THEN
+        EXECUTE $sql$CREATE PROCEDURE proc_insert_committed(count integer) LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@'$sql$;
+    END IF;
+END;
+$$;
+"#)]
+fn proc_insert_committed(count: i32, fcinfo: pg_sys::FunctionCallInfo) {
+    Spi::connect_nonatomic(fcinfo, |mut client| {
+        for id in 1..=count {
+            client.update(
+                &format!("INSERT INTO tests.proc_commit_test VALUES ({})", id),
+                None,
+                None,
+            );
+            client.commit();
+        }
+
+        client.update("INSERT INTO tests.proc_commit_test VALUES (-1)", None, None);
+        client.rollback();
+    });
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_proc_call() {
+        Spi::run("CREATE TABLE tests.proc_test (id int)");
+        Spi::run("CALL proc_insert(42)");
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT id FROM tests.proc_test"),
+            Some(42)
+        );
+    }
+
+    #[pg_test]
+    fn test_proc_inout() {
+        assert_eq!(Spi::get_one::<i32>("CALL proc_add_to(1, 41)"), Some(42));
+    }
+
+    #[pg_test]
+    fn test_proc_multiple_inout() {
+        assert_eq!(
+            Spi::get_two::<String, String>("CALL proc_split('archive.tar', NULL)"),
+            (Some("archive".to_string()), Some("tar".to_string()))
+        );
+        assert_eq!(
+            Spi::get_two::<String, String>("CALL proc_split('readme', 'txt')"),
+            (Some("readme".to_string()), None)
+        );
+    }
+
+    #[pg_test]
+    fn test_proc_null_argument() {
+        assert_eq!(Spi::get_one::<i32>("CALL proc_add_to(NULL, 41)"), None);
+    }
+
+    #[pg_test(error = "invalid transaction termination")]
+    fn test_proc_commit_in_transaction_block() {
+        Spi::run("CREATE TABLE tests.proc_commit_test (id int)");
+        Spi::run("CALL proc_insert_committed(1)");
+    }
+
+    // a procedure can only commit when it's CALLed outside of a transaction block, which a
+    // #[pg_test] never is, so this needs its own connection
+    #[test]
+    fn test_proc_commit() {
+        let (mut client, _) = pgx_tests::test_client(crate::pg_test::postgresql_conf_options());
+        client
+            .simple_query("CREATE TABLE tests.proc_commit_test (id int)")
+            .expect("failed to create tests.proc_commit_test");
+        client
+            .simple_query("CALL proc_insert_committed(3)")
+            .expect("failed to CALL proc_insert_committed()");
+
+        let ids = client
+            .query("SELECT id FROM tests.proc_commit_test ORDER BY id", &[])
+            .expect("failed to query tests.proc_commit_test")
+            .iter()
+            .map(|row| row.get::<_, i32>(0))
+            .collect::<Vec<_>>();
+        client
+            .simple_query("DROP TABLE tests.proc_commit_test")
+            .expect("failed to drop tests.proc_commit_test");
+
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+}
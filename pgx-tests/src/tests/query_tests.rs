@@ -0,0 +1,43 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn current_query_text() -> Option<String> {
+        current_query()
+    }
+
+    #[pg_test]
+    fn test_current_query() {
+        let query = current_query().expect("no current query");
+        assert!(query.contains("test_current_query"));
+    }
+
+    #[pg_test]
+    fn test_current_query_through_spi() {
+        // SPI doesn't change the query string, so it's still the statement that called this test
+        let query =
+            Spi::get_one::<String>("SELECT tests.current_query_text()").expect("no current query");
+        assert!(query.contains("test_current_query_through_spi"));
+    }
+
+    #[pg_test]
+    fn test_current_command_tag() {
+        assert_eq!(current_command_tag(), Some("SELECT".to_string()));
+    }
+
+    #[pg_test]
+    fn test_set_current_query() {
+        set_current_query("SELECT 'custom query'");
+        assert_eq!(current_query(), Some("SELECT 'custom query'".to_string()));
+
+        set_current_query("SELECT 'another query'");
+        assert_eq!(current_query(), Some("SELECT 'another query'".to_string()));
+    }
+}
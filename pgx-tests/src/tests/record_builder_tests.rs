@@ -0,0 +1,88 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn record_powers(n: i32, count: i32) -> Record {
+        let builder = RecordBuilder::new((1..=count).map(|i| (format!("p{}", i), pg_sys::INT8OID)));
+        builder.build(
+            (1..=count)
+                .map(|i| (n as i64).pow(i as u32).into_datum())
+                .collect(),
+        )
+    }
+
+    #[pg_extern]
+    fn record_pairs(count: i32) -> impl std::iter::Iterator<Item = Record> {
+        let builder = RecordBuilder::new(vec![("n", pg_sys::INT4OID), ("name", pg_sys::TEXTOID)]);
+        let records = (1..=count)
+            .map(|i| {
+                let name = if i % 2 == 0 {
+                    None
+                } else {
+                    Some(format!("odd {}", i))
+                };
+                builder.build(vec![i.into_datum(), name.into_datum()])
+            })
+            .collect::<Vec<_>>();
+        records.into_iter()
+    }
+
+    #[pg_test]
+    fn test_record_builder_columns_from_input() {
+        let sum = Spi::get_one::<i64>(
+            "SELECT p1 + p2 + p3 FROM tests.record_powers(2, 3) AS t(p1 bigint, p2 bigint, p3 bigint)",
+        );
+        assert_eq!(sum, Some(14));
+
+        let p5 = Spi::get_one::<i64>(
+            "SELECT p5 FROM tests.record_powers(3, 5) AS t(p1 bigint, p2 bigint, p3 bigint, p4 bigint, p5 bigint)",
+        );
+        assert_eq!(p5, Some(243));
+    }
+
+    #[pg_test]
+    fn test_record_builder_setof() {
+        let names = Spi::get_one::<String>(
+            "SELECT string_agg(coalesce(name, 'null'), ',' ORDER BY n) FROM tests.record_pairs(4) AS t(n int, name text)",
+        );
+        assert_eq!(names, Some("odd 1,null,odd 3,null".to_string()));
+    }
+
+    #[pg_test]
+    fn test_record_builder_tupdesc() {
+        let builder = RecordBuilder::new(vec![("a", pg_sys::INT4OID), ("b", pg_sys::BOOLOID)]);
+        let tupdesc = builder.tupdesc();
+        assert_eq!(tupdesc.len(), 2);
+        assert_eq!(tupdesc.get(1).unwrap().atttypid, pg_sys::BOOLOID);
+
+        let record = builder.build(vec![Some(42i32.into_datum().unwrap()), None]);
+        let (a, b) = unsafe {
+            <(Option<i32>, Option<bool>)>::from_datum(
+                record.into_datum().unwrap(),
+                false,
+                pg_sys::RECORDOID,
+            )
+        }
+        .expect("record was NULL");
+        assert_eq!(a, Some(42));
+        assert_eq!(b, None);
+    }
+
+    #[pg_test(error = "expected 2 values for a record, but got 1")]
+    fn test_record_builder_wrong_value_count() {
+        RecordBuilder::new(vec![("a", pg_sys::INT4OID), ("b", pg_sys::INT4OID)])
+            .build(vec![1.into_datum()]);
+    }
+
+    #[pg_test(error = "function return row and query-specified return row do not match")]
+    fn test_record_builder_column_definition_mismatch() {
+        Spi::run("SELECT * FROM tests.record_powers(2, 2) AS t(p1 int, p2 int)");
+    }
+}
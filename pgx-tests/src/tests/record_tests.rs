@@ -0,0 +1,66 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_record_from_row() {
+        let row = Spi::get_one::<(Option<i32>, Option<String>, Option<bool>)>(
+            "SELECT ROW(42, 'pgx'::text, NULL::bool)",
+        );
+        assert_eq!(row, Some((Some(42), Some("pgx".to_string()), None)));
+    }
+
+    #[pg_test]
+    fn test_record_roundtrip() {
+        let record = (
+            Some(1i16),
+            Some(2i32),
+            Some(3i64),
+            Some("four"),
+            None::<bool>,
+            Some(6.0f32),
+            Some(7.0f64),
+            Some("eight".to_string()),
+        );
+        let datum = record.clone().into_datum().expect("record was NULL");
+        let roundtrip = unsafe {
+            <(
+                Option<i16>,
+                Option<i32>,
+                Option<i64>,
+                Option<&str>,
+                Option<bool>,
+                Option<f32>,
+                Option<f64>,
+                Option<String>,
+            )>::from_datum(datum, false, pg_sys::RECORDOID)
+        };
+        assert_eq!(roundtrip, Some(record));
+    }
+
+    #[pg_test]
+    fn test_record_as_query_argument() {
+        let record = (Some(42), Some("pgx"));
+        let text = Spi::get_one_with_args::<String>(
+            "SELECT $1::text",
+            vec![(PgOid::from(pg_sys::RECORDOID), record.into_datum())],
+        );
+        assert_eq!(text, Some("(42,pgx)".to_string()));
+    }
+
+    #[pg_test(error = "expected a record with 2 fields, but it has 3")]
+    fn test_record_field_count_mismatch() {
+        Spi::get_one::<(Option<i32>, Option<i32>)>("SELECT ROW(1, 2, 3)");
+    }
+
+    #[pg_test(error = "record field 2 is of type integer, but expected text")]
+    fn test_record_field_type_mismatch() {
+        Spi::get_one::<(Option<i32>, Option<String>)>("SELECT ROW(1, 2)");
+    }
+}
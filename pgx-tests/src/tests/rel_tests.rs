@@ -0,0 +1,189 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn open_test_table() -> PgRelation {
+        Spi::run("CREATE TABLE tests.rel_attributes (id int NOT NULL, dropped text, name text)");
+        Spi::run("ALTER TABLE tests.rel_attributes DROP COLUMN dropped");
+        unsafe { PgRelation::open_with_name("tests.rel_attributes") }.expect("table not found")
+    }
+
+    #[pg_test]
+    fn test_relation_attributes() {
+        let relation = open_test_table();
+        let tupdesc = relation.tuple_desc();
+
+        // the dropped column is still in the tuple descriptor, but isn't iterated
+        assert_eq!(tupdesc.len(), 3);
+        let attributes = tupdesc
+            .attributes()
+            .map(|att| {
+                (
+                    att.name().to_string(),
+                    att.type_oid().value(),
+                    att.is_not_null(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attributes,
+            vec![
+                ("id".to_string(), pg_sys::INT4OID, true),
+                ("name".to_string(), pg_sys::TEXTOID, false),
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_relation_attno() {
+        let relation = open_test_table();
+        let tupdesc = relation.tuple_desc();
+
+        assert_eq!(tupdesc.attno("id"), Some(1));
+        assert_eq!(tupdesc.attno("name"), Some(3));
+        assert_eq!(tupdesc.attno("dropped"), None);
+        assert_eq!(tupdesc.attno("missing"), None);
+    }
+
+    #[pg_test]
+    fn test_tupdesc_form_tuple() {
+        let relation = open_test_table();
+        let tupdesc = relation.tuple_desc();
+
+        let tuple = tupdesc.form_tuple(&[
+            (42.into_datum().unwrap(), false),
+            (0, true),
+            ("pgx".into_datum().unwrap(), false),
+        ]);
+        assert_eq!(tuple.get_by_name::<i32>("id"), Some(42));
+        assert_eq!(tuple.get_by_name::<&str>("name"), Some("pgx"));
+    }
+
+    #[pg_test]
+    fn test_tupdesc_modify_tuple() {
+        let relation = open_test_table();
+        let tupdesc = relation.tuple_desc();
+
+        let tuple = tupdesc.form_tuple(&[
+            (42.into_datum().unwrap(), false),
+            (0, true),
+            ("pgx".into_datum().unwrap(), false),
+        ]);
+        let modified = tupdesc.modify_tuple(&tuple, &[(3, 0, true)]);
+        assert_eq!(modified.get_by_name::<i32>("id"), Some(42));
+        assert_eq!(modified.get_by_name::<&str>("name"), None);
+
+        // the original tuple is unchanged
+        assert_eq!(tuple.get_by_name::<&str>("name"), Some("pgx"));
+    }
+
+    #[pg_test(error = "expected 3 values for a tuple, but got 1")]
+    fn test_tupdesc_form_tuple_wrong_count() {
+        let relation = open_test_table();
+        relation
+            .tuple_desc()
+            .form_tuple(&[(42.into_datum().unwrap(), false)]);
+    }
+
+    fn open_scan_table() -> PgRelation {
+        Spi::run("CREATE TABLE tests.rel_scan (id int, name text)");
+        Spi::run("INSERT INTO tests.rel_scan VALUES (1, 'one'), (2, 'two'), (3, NULL)");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.rel_scan'::regclass::oid")
+            .expect("relid was NULL");
+        PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE)
+    }
+
+    #[pg_test]
+    fn test_relation_scan() {
+        let relation = open_scan_table();
+        let mut rows = relation
+            .scan()
+            .map(|tuple| {
+                (
+                    tuple.get_by_name::<i32>("id"),
+                    tuple.get_by_name::<String>("name"),
+                )
+            })
+            .collect::<Vec<_>>();
+        rows.sort();
+
+        assert_eq!(
+            rows,
+            vec![
+                (Some(1), Some("one".to_string())),
+                (Some(2), Some("two".to_string())),
+                (Some(3), None),
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_relation_scan_with_keys() {
+        let relation = open_scan_table();
+        let mut key = unsafe { std::mem::zeroed::<pg_sys::ScanKeyData>() };
+        unsafe {
+            pg_sys::ScanKeyInit(
+                &mut key,
+                1,
+                pg_sys::BTEqualStrategyNumber as pg_sys::StrategyNumber,
+                65, // F_INT4EQ
+                2i32.into_datum().unwrap(),
+            );
+        }
+
+        let names = relation
+            .scan_with_keys(vec![key])
+            .map(|tuple| tuple.get_by_name::<String>("name"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("two".to_string())]);
+    }
+
+    #[pg_test]
+    fn test_relation_scan_empty() {
+        Spi::run("CREATE TABLE tests.rel_scan_empty (id int)");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.rel_scan_empty'::regclass::oid")
+            .expect("relid was NULL");
+        let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+        assert_eq!(relation.scan().count(), 0);
+    }
+
+    #[pg_test]
+    fn test_relation_indices() {
+        Spi::run("CREATE TABLE tests.rel_indices (id int PRIMARY KEY, a text, b int)");
+        Spi::run("CREATE INDEX rel_indices_b_a ON tests.rel_indices (b, a text_pattern_ops)");
+        Spi::run("CREATE INDEX rel_indices_expr ON tests.rel_indices (lower(a))");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.rel_indices'::regclass::oid")
+            .expect("relid was NULL");
+        let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+
+        let mut indices = relation
+            .indices(pg_sys::AccessShareLock as pg_sys::LOCKMODE)
+            .filter(|index| index.is_valid() && index.is_ready())
+            .collect::<Vec<_>>();
+        indices.sort_by(|a, b| a.name().cmp(b.name()));
+        assert_eq!(indices.len(), 3);
+
+        assert_eq!(indices[0].name(), "rel_indices_b_a");
+        assert_eq!(indices[0].key_attnums(), vec![3, 2]);
+        assert!(!indices[0].is_unique());
+        let text_pattern_ops = Spi::get_one::<pg_sys::Oid>(
+            "SELECT oid FROM pg_opclass WHERE opcname = 'text_pattern_ops' AND opcmethod = 403",
+        )
+        .expect("opclass oid was NULL");
+        assert_eq!(indices[0].opclasses()[1], text_pattern_ops);
+
+        assert_eq!(indices[1].name(), "rel_indices_expr");
+        assert_eq!(indices[1].key_attnums(), vec![0]);
+
+        assert_eq!(indices[2].name(), "rel_indices_pkey");
+        assert!(indices[2].is_primary() && indices[2].is_unique());
+        assert_eq!(indices[2].key_attnums(), vec![1]);
+        assert_eq!(indices[2].heap_relation().unwrap().oid(), relid);
+    }
+}
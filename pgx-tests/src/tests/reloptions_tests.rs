@@ -0,0 +1,65 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn test_options() -> RelOptionsBuilder {
+        RelOptionsBuilder::new()
+            .add_bool("enabled", "a bool", true)
+            .add_int("pages", "an int", 128, 1, 1024)
+            .add_real("ratio", "a real", 0.5, 0.0, 1.0)
+            .add_string("label", "a string", Some("none"))
+            .add_string("comment", "a string without a default", None)
+    }
+
+    #[pg_test]
+    fn test_reloptions_parse() {
+        let builder = test_options();
+        let reloptions = vec!["pages=16", "label=custom", "enabled=false"]
+            .into_datum()
+            .unwrap();
+        let parsed = builder.parse(reloptions, true);
+        let options = builder.options(parsed);
+
+        assert!(!options.get_bool("enabled"));
+        assert_eq!(options.get_int("pages"), 16);
+        assert_eq!(options.get_real("ratio"), 0.5);
+        assert_eq!(options.get_string("label"), Some("custom".to_string()));
+        assert_eq!(options.get_string("comment"), None);
+    }
+
+    #[pg_test]
+    fn test_reloptions_defaults() {
+        let builder = test_options();
+        let options = builder.options(std::ptr::null_mut());
+
+        assert!(options.get_bool("enabled"));
+        assert_eq!(options.get_int("pages"), 128);
+        assert_eq!(options.get_real("ratio"), 0.5);
+        assert_eq!(options.get_string("label"), Some("none".to_string()));
+        assert_eq!(options.get_string("comment"), None);
+    }
+
+    #[pg_test(error = "unrecognized parameter \"bogus\"")]
+    fn test_reloptions_unrecognized() {
+        let builder = test_options();
+        builder.parse(vec!["bogus=1"].into_datum().unwrap(), true);
+    }
+
+    #[pg_test(error = "value 0 out of bounds for option \"pages\"")]
+    fn test_reloptions_out_of_bounds() {
+        let builder = test_options();
+        builder.parse(vec!["pages=0"].into_datum().unwrap(), true);
+    }
+
+    #[pg_test(error = "reloption \"pages\" is of a different type")]
+    fn test_reloptions_wrong_type() {
+        let builder = test_options();
+        builder.options(std::ptr::null_mut()).get_bool("pages");
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[derive(Debug)]
+enum FlagError {
+    Empty,
+    Invalid(String),
+}
+
+impl std::fmt::Display for FlagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlagError::Empty => write!(f, "flag is empty"),
+            FlagError::Invalid(input) => write!(f, "invalid flag: {}", input),
+        }
+    }
+}
+
+impl SqlStateError for FlagError {
+    fn sqlstate(&self) -> PgSqlErrorCode {
+        match self {
+            FlagError::Empty => PgSqlErrorCode::ERRCODE_ZERO_LENGTH_CHARACTER_STRING,
+            FlagError::Invalid(_) => PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+        }
+    }
+
+    fn detail(&self) -> Option<String> {
+        match self {
+            FlagError::Empty => None,
+            FlagError::Invalid(_) => Some("flags are either 'yes' or 'no'".to_string()),
+        }
+    }
+}
+
+#[pg_extern]
+fn parse_flag(input: &str) -> Result<bool, FlagError> {
+    match input {
+        "" => Err(FlagError::Empty),
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        other => Err(FlagError::Invalid(other.to_string())),
+    }
+}
+
+#[pg_extern]
+fn parse_optional_int(input: &str) -> Result<Option<i32>, String> {
+    if input.is_empty() {
+        Ok(None)
+    } else {
+        input
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("not an integer: {}", e))
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_result_ok() {
+        assert_eq!(Spi::get_one::<bool>("SELECT parse_flag('yes')"), Some(true));
+        assert_eq!(Spi::get_one::<bool>("SELECT parse_flag('no')"), Some(false));
+    }
+
+    #[pg_test]
+    fn test_result_ok_none_is_null() {
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT parse_optional_int('42')"),
+            Some(42)
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT parse_optional_int('') IS NULL"),
+            Some(true)
+        );
+    }
+
+    #[pg_test(error = "flag is empty")]
+    fn test_result_err() {
+        Spi::get_one::<bool>("SELECT parse_flag('')");
+    }
+
+    #[pg_test(error = "not an integer: invalid digit found in string")]
+    fn test_result_err_string() {
+        Spi::get_one::<i32>("SELECT parse_optional_int('abc')");
+    }
+
+    #[pg_test]
+    fn test_result_err_sqlstate() {
+        // the ERRORs are raised through SPI, so each is caught in a subtransaction that's rolled
+        // back
+        let invalid = in_rolled_back_subtransaction(|| {
+            PgTryBuilder::new(|| Spi::get_one::<bool>("SELECT parse_flag('maybe')"))
+                .catch_when(PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION, |e| {
+                    assert_eq!("22P02", e.sqlstate());
+                    assert_eq!("invalid flag: maybe", e.message);
+                    assert_eq!(Some("flags are either 'yes' or 'no'".to_string()), e.detail);
+                    None
+                })
+                .execute()
+        });
+        assert_eq!(invalid, None);
+
+        let empty = in_rolled_back_subtransaction(|| {
+            PgTryBuilder::new(|| Spi::get_one::<bool>("SELECT parse_flag('')"))
+                .catch_when(PgSqlErrorCode::ERRCODE_ZERO_LENGTH_CHARACTER_STRING, |e| {
+                    assert_eq!(None, e.detail);
+                    None
+                })
+                .execute()
+        });
+        assert_eq!(empty, None);
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn open_scankey_table() -> PgRelation {
+        Spi::run("CREATE TABLE tests.scankeys (id int, name text)");
+        Spi::run("CREATE INDEX scankeys_name ON tests.scankeys (name text_pattern_ops)");
+        Spi::run("INSERT INTO tests.scankeys SELECT i, 'row ' || i FROM generate_series(1, 10) i");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.scankeys'::regclass::oid")
+            .expect("relid was NULL");
+        PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE)
+    }
+
+    fn scan_ids(relation: &PgRelation, keys: ScanKeys) -> Vec<i32> {
+        let mut ids = relation
+            .scan_with_keys(keys.into_keys())
+            .map(|tuple| tuple.get_by_name::<i32>("id").expect("id was NULL"))
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[pg_test]
+    fn test_scankeys_equals() {
+        let relation = open_scankey_table();
+        let keys = ScanKeys::new(&relation).equals(2, "row 7");
+        assert_eq!(scan_ids(&relation, keys), vec![7]);
+    }
+
+    #[pg_test]
+    fn test_scankeys_range() {
+        let relation = open_scankey_table();
+        let keys = ScanKeys::new(&relation)
+            .greater_than(1, 3i32)
+            .less_or_equal(1, 5i32);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(scan_ids(&relation, keys), vec![4, 5]);
+    }
+
+    #[pg_test]
+    fn test_scankeys_cross_type() {
+        let relation = open_scankey_table();
+        let keys = ScanKeys::new(&relation)
+            .greater_or_equal(1, 9i64)
+            .less_than(1, 10i16);
+        assert_eq!(scan_ids(&relation, keys), vec![9]);
+    }
+
+    #[pg_test]
+    fn test_scankeys_index_opclass() {
+        let relation = open_scankey_table();
+        let index = relation
+            .indices(pg_sys::AccessShareLock as pg_sys::LOCKMODE)
+            .next()
+            .expect("index not found");
+        let keys = ScanKeys::new(&index).less_than(1, "row 5").into_keys();
+
+        let text_pattern_lt = Spi::get_one::<pg_sys::Oid>("SELECT 'text_pattern_lt'::regproc::oid")
+            .expect("proc oid was NULL");
+        assert_eq!(keys[0].sk_attno, 1);
+        assert_eq!(keys[0].sk_func.fn_oid, text_pattern_lt);
+    }
+
+    #[pg_test(
+        error = "column \"id\" is of type integer, which cannot be compared to a value of type text"
+    )]
+    fn test_scankeys_type_mismatch() {
+        let relation = open_scankey_table();
+        ScanKeys::new(&relation).equals(1, "seven");
+    }
+
+    #[pg_test(error = "attribute number 3 is not a column of \"scankeys\"")]
+    fn test_scankeys_bad_attno() {
+        let relation = open_scankey_table();
+        ScanKeys::new(&relation).equals(3, 1i32);
+    }
+}
@@ -1,12 +1,67 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use pgx::*;
+
+// declared ahead of the type it returns, to ensure it's ordered after it
+#[pg_extern]
+fn func_returning_later_type() -> LaterType {
+    LaterType::Second
+}
+
+#[derive(PostgresEnum)]
+pub enum LaterType {
+    First,
+    Second,
+}
 
 mod test_schema {
     use pgx::*;
 
     #[pg_extern]
     fn func_in_diff_schema() {}
+
+    // declared ahead of the function it requires, to ensure it's ordered after it
+    extension_sql!(
+        r#"CREATE TABLE test_schema.custom_sql_results AS SELECT test_schema.func_with_custom_sql() AS value;"#,
+        name = "custom_sql_results",
+        requires = [func_with_custom_sql]
+    );
+
+    #[pg_extern(
+        sql = r#"CREATE OR REPLACE FUNCTION test_schema.func_with_custom_sql() RETURNS integer IMMUTABLE STRICT COST 1000 LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@';"#
+    )]
+    fn func_with_custom_sql() -> i32 {
+        42
+    }
+
+    // can't be inferred from the signature, so it's declared
+    #[pg_extern(requires = [custom_sql_results])]
+    fn func_reading_custom_sql_results() -> i32 {
+        Spi::get_one("SELECT value FROM test_schema.custom_sql_results;").unwrap()
+    }
+}
+
+#[pg_schema]
+mod pg_schema_test {
+    use pgx::*;
+
+    #[derive(PostgresEnum, PartialEq, Debug)]
+    pub enum Shade {
+        Light,
+        Dark,
+    }
+
+    #[pg_extern]
+    fn darken(_shade: Shade) -> Shade {
+        Shade::Dark
+    }
+}
+
+// returns a type from another schema
+#[pg_extern]
+fn default_shade() -> pg_schema_test::Shade {
+    pg_schema_test::Shade::Light
 }
 
 #[cfg(any(test, feature = "pg_test"))]
@@ -20,4 +75,57 @@ mod tests {
     fn test_in_different_schema() {
         Spi::run("SELECT test_schema.func_in_diff_schema();");
     }
+
+    #[pg_test]
+    fn test_pg_extern_custom_sql() {
+        let cost = Spi::get_one::<f32>(
+            "SELECT procost FROM pg_proc WHERE oid = 'test_schema.func_with_custom_sql'::regproc;",
+        );
+        assert_eq!(cost, Some(1000.0));
+    }
+
+    #[pg_test]
+    fn test_extension_sql_requires() {
+        let value = Spi::get_one::<i32>("SELECT value FROM test_schema.custom_sql_results;");
+        assert_eq!(value, Some(42));
+    }
+
+    #[pg_test]
+    fn test_function_ordered_after_its_types() {
+        let value = Spi::get_one::<&str>("SELECT func_returning_later_type()::text;");
+        assert_eq!(value, Some("Second"));
+    }
+
+    #[pg_test]
+    fn test_pg_extern_requires() {
+        let value = Spi::get_one::<i32>("SELECT test_schema.func_reading_custom_sql_results();");
+        assert_eq!(value, Some(42));
+    }
+
+    #[pg_test]
+    fn test_pg_schema() {
+        let namespace = Spi::get_one::<&str>(
+            "SELECT typnamespace::regnamespace::text FROM pg_type WHERE typname = 'shade';",
+        );
+        assert_eq!(namespace, Some("pg_schema_test"));
+
+        let value = Spi::get_one::<&str>("SELECT pg_schema_test.darken('Light')::text;");
+        assert_eq!(value, Some("Dark"));
+
+        let value = Spi::get_one::<&str>("SELECT default_shade()::text;");
+        assert_eq!(value, Some("Light"));
+    }
+
+    #[pg_test]
+    fn test_pg_schema_type_lookup() {
+        use crate::tests::schema_tests::pg_schema_test::Shade;
+
+        // found even though pg_schema_test isn't on the search_path
+        let typoid = Spi::get_one::<pg_sys::Oid>("SELECT 'pg_schema_test.shade'::regtype::oid;");
+        assert_eq!(Some(Shade::type_oid()), typoid);
+        assert_eq!(
+            unsafe { Shade::from_datum(Shade::Dark.into_datum().unwrap(), false, typoid.unwrap()) },
+            Some(Shade::Dark)
+        );
+    }
 }
@@ -0,0 +1,22 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test(
+        error = "shared memory can only be requested from _PG_init() when the extension is loaded via shared_preload_libraries"
+    )]
+    fn test_shmem_request_without_preload() {
+        pg_shmem_request!(1024);
+    }
+
+    #[pg_test]
+    fn test_no_shmem_requested() {
+        assert_eq!(requested_shmem_space(), 0);
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#![cfg(any(feature = "pg11", feature = "pg12"))]
+
+use pgx::*;
+
+#[pg_extern]
+fn commit_in_procedure(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    Spi::connect_nonatomic(fcinfo, |client| {
+        client.update("INSERT INTO nonatomic_test VALUES (1)", None, None);
+        client.commit();
+    });
+    0
+}
+
+// procedures only exist on Postgres 11+, and this script is shared by all versions
+extension_sql! { r#"
+CREATE TABLE nonatomic_test (id int);
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 110000 THEN
+        EXECUTE $sql$CREATE PROCEDURE commit_in_procedure() LANGUAGE c AS 'MODULE_PATHNAME', 'commit_in_procedure_wrapper'$sql$;
+    END IF;
+END;
+$$;
+"#}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    // tests run inside a transaction block, where CALL is atomic
+    #[pg_test(error = "invalid transaction termination")]
+    fn test_commit_in_atomic_call() {
+        Spi::run("CALL commit_in_procedure()");
+    }
+
+    #[pg_test(error = "invalid transaction termination")]
+    fn test_rollback_in_atomic_connection() {
+        Spi::execute(|client| client.rollback());
+    }
+}
@@ -2,11 +2,21 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 
+use pgx::*;
+
+#[derive(PostgresType)]
+#[composite]
+pub struct SpiPerson {
+    id: i32,
+    name: Option<String>,
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
     use crate as pgx_tests;
 
+    use super::SpiPerson;
     use pgx::*;
 
     #[pg_test(error = "syntax error at or near \"THIS\"")]
@@ -141,6 +151,49 @@ mod tests {
         assert!(Spi::get_one::<i32>("SELECT 1 LIMIT 0").is_none());
     }
 
+    #[pg_test]
+    fn test_spi_get_by_name() {
+        Spi::execute(|client| {
+            let table = client
+                .select("SELECT 'test' AS s, 42 AS i, NULL::bool AS b", None, None)
+                .first();
+            assert_eq!(Ok(Some(42)), table.get_by_name::<i32>("i"));
+            assert_eq!(Ok(Some("test")), table.get_by_name::<&str>("s"));
+            assert_eq!(Ok(None), table.get_by_name::<bool>("b"));
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_heap_tuple_get_by_name() {
+        Spi::execute(|client| {
+            let values = client
+                .select(
+                    "SELECT x AS value, x * 2 AS doubled FROM generate_series(1, 3) x",
+                    None,
+                    None,
+                )
+                .map(|row| {
+                    (
+                        row.get_by_name::<i32>("doubled").unwrap().unwrap(),
+                        row.get_by_name::<i32>("value").unwrap().unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(vec![(2, 1), (4, 2), (6, 3)], values);
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_get_by_name_missing_column() {
+        Spi::execute(|client| {
+            let table = client.select("SELECT 42 AS i", None, None).first();
+            match table.get_by_name::<i32>("does_not_exist") {
+                Err(SpiError::Noattribute) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        });
+    }
+
     #[pg_extern]
     fn do_panic() {
         panic!("did a panic");
@@ -150,4 +203,472 @@ mod tests {
     fn test_panic_via_spi() {
         Spi::run("SELECT tests.do_panic();");
     }
+
+    #[pg_test]
+    fn test_spi_explain() {
+        let plan = Spi::explain("SELECT 1").0;
+        assert_eq!(plan[0]["Plan"]["Node Type"], "Result");
+        assert!(plan[0]["Plan"].get("Actual Rows").is_none());
+    }
+
+    #[pg_test]
+    fn test_spi_explain_analyze() {
+        Spi::run("CREATE TABLE tests.explain_analyze (id int)");
+        let plan = Spi::explain_analyze("INSERT INTO tests.explain_analyze VALUES (1)").0;
+        assert_eq!(plan[0]["Plan"]["Node Type"], "ModifyTable");
+        assert!(plan[0]["Plan"].get("Actual Rows").is_some());
+
+        // EXPLAIN ANALYZE really executes the query
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM tests.explain_analyze");
+        assert_eq!(count, Some(1));
+    }
+
+    #[pg_test(error = "CREATE TABLE is not allowed in a non-volatile function")]
+    fn test_spi_select_read_only() {
+        Spi::execute(|client| {
+            client.select_read_only(
+                "CREATE TABLE tests.select_is_read_only (id int)",
+                None,
+                None,
+            );
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_select_sees_prior_updates() {
+        Spi::run("CREATE TABLE tests.insert_then_select (id int)");
+        Spi::run("INSERT INTO tests.insert_then_select VALUES (1)");
+        Spi::execute(|client| {
+            let count = client
+                .select("SELECT count(*) FROM tests.insert_then_select", None, None)
+                .first()
+                .get_one::<i64>();
+            assert_eq!(count, Some(1));
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_update_sees_prior_updates() {
+        Spi::execute(|mut client| {
+            client.update("CREATE TABLE tests.update_then_select (id int)", None, None);
+            client.update(
+                "INSERT INTO tests.update_then_select VALUES (1)",
+                None,
+                None,
+            );
+            let count = client
+                .update("SELECT count(*) FROM tests.update_then_select", None, None)
+                .first()
+                .get_one::<i64>();
+            assert_eq!(count, Some(1));
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_update_returning() {
+        let mut rows = Vec::new();
+        Spi::connect(|mut client| {
+            client.update(
+                "CREATE TABLE tests.update_returning (id serial, name text)",
+                None,
+                None,
+            );
+            rows = client.update_returning(
+                "INSERT INTO tests.update_returning (name) VALUES ($1), ($2) RETURNING id, name",
+                Some(args!["alice", "bob"]),
+            );
+            Ok(Some(()))
+        });
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get_by_name::<i32>("id"), Some(1));
+        assert_eq!(
+            rows[0].get_by_name::<String>("name"),
+            Some("alice".to_string())
+        );
+        assert_eq!(rows[1].get_by_name::<i32>("id"), Some(2));
+
+        let deleted = Spi::connect_and_return(|mut client| {
+            client
+                .update_returning(
+                    "DELETE FROM tests.update_returning WHERE name = 'bob' RETURNING id",
+                    None,
+                )
+                .len() as i64
+        });
+        assert_eq!(deleted, 1);
+    }
+
+    #[pg_test(
+        error = "update_returning() requires an INSERT, UPDATE, or DELETE with a RETURNING clause, but the query's result was Insert"
+    )]
+    fn test_spi_update_returning_without_returning() {
+        Spi::execute(|mut client| {
+            client.update("CREATE TABLE tests.no_returning (id int)", None, None);
+            client.update_returning("INSERT INTO tests.no_returning VALUES (1)", None);
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_into_heap_tuples() {
+        let mut rows = Vec::new();
+        Spi::connect(|client| {
+            rows = client
+                .select(
+                    "SELECT 1 AS id, 'one' AS name UNION ALL SELECT 2, NULL",
+                    None,
+                    None,
+                )
+                .into_heap_tuples();
+            Ok(Some(()))
+        });
+
+        // the tuples are still valid now that SPI is disconnected
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].len(), 2);
+        assert_eq!(rows[0].get_by_name::<i32>("id"), Some(1));
+        assert_eq!(
+            rows[0].get_by_name::<String>("name"),
+            Some("one".to_string())
+        );
+        assert_eq!(rows[1].get_by_name::<i32>("id"), Some(2));
+        assert_eq!(rows[1].get_by_name::<String>("name"), None);
+    }
+
+    #[pg_test]
+    fn test_spi_into_heap_tuples_empty() {
+        let mut rows = Vec::new();
+        Spi::connect(|client| {
+            rows = client
+                .select("SELECT 1 WHERE false", None, None)
+                .into_heap_tuples();
+            Ok(Some(()))
+        });
+
+        assert!(rows.is_empty());
+    }
+
+    #[pg_test]
+    fn test_spi_array_outlives_connection() {
+        let mut array = None;
+        Spi::connect(|client| {
+            array = client
+                .select(
+                    "SELECT array_agg(x) FROM generate_series(1, 1000) x",
+                    None,
+                    None,
+                )
+                .first()
+                .copy_datum::<Array<i32>>(1);
+            Ok(Some(()))
+        });
+
+        // the array was copied out of SPI's memory before it was disconnected
+        let array = array.expect("array was NULL");
+        assert_eq!(array.len(), 1000);
+        assert_eq!(array.iter().map(|x| x.unwrap()).sum::<i32>(), 500500);
+    }
+
+    #[pg_test]
+    fn test_spi_text_array_outlives_connection() {
+        let mut arrays = Vec::new();
+        Spi::connect(|client| {
+            arrays = client
+                .select(
+                    "SELECT array_agg(x::text ORDER BY x) FROM generate_series(1, 3) x \
+                     UNION ALL SELECT ARRAY['a', NULL]",
+                    None,
+                    None,
+                )
+                .map(|row| row.copy_datum::<Array<&str>>(1))
+                .collect::<Vec<_>>();
+            Ok(Some(()))
+        });
+
+        assert_eq!(arrays.len(), 2);
+        let first = arrays[0].as_ref().expect("array was NULL");
+        assert_eq!(
+            first.iter().collect::<Vec<_>>(),
+            vec![Some("1"), Some("2"), Some("3")]
+        );
+        let second = arrays[1].as_ref().expect("array was NULL");
+        assert_eq!(second.iter().collect::<Vec<_>>(), vec![Some("a"), None]);
+    }
+
+    #[pg_test]
+    fn test_spi_get_one_with_args_composite() {
+        Spi::run("CREATE TABLE spi_people (id int, name text)");
+        Spi::run("INSERT INTO spi_people VALUES (1, 'alice'), (2, NULL)");
+
+        let query = "SELECT spi_people FROM spi_people WHERE id = $1";
+        let alice = Spi::get_one_with_args::<PgHeapTuple>(
+            query,
+            vec![(PgBuiltInOids::INT4OID.oid(), 1.into_datum())],
+        )
+        .expect("row was NULL");
+        assert_eq!(alice.len(), 2);
+        assert_eq!(alice.get_by_name::<i32>("id"), Some(1));
+        assert_eq!(
+            alice.get_by_name::<String>("name"),
+            Some("alice".to_string())
+        );
+
+        let unnamed = Spi::get_one_with_args::<PgHeapTuple>(
+            query,
+            vec![(PgBuiltInOids::INT4OID.oid(), 2.into_datum())],
+        )
+        .expect("row was NULL");
+        assert_eq!(unnamed.get_by_name::<String>("name"), None);
+
+        let missing = Spi::get_one_with_args::<PgHeapTuple>(
+            query,
+            vec![(PgBuiltInOids::INT4OID.oid(), 3.into_datum())],
+        );
+        assert!(missing.is_none());
+    }
+
+    #[pg_test]
+    fn test_spi_get_one_with_args_null_composite() {
+        Spi::run("CREATE TABLE spi_people (id int, name text)");
+
+        let row = Spi::get_one_with_args::<PgHeapTuple>(
+            "SELECT NULL::spi_people WHERE $1",
+            vec![(PgBuiltInOids::BOOLOID.oid(), true.into_datum())],
+        );
+        assert!(row.is_none());
+    }
+
+    #[pg_test]
+    fn test_spi_get_datum_as_string() {
+        Spi::connect(|client| {
+            let row = client
+                .select(
+                    "SELECT 42::int4, NULL::text, '{1,2,3}'::int[], '(1,2)'::point",
+                    None,
+                    None,
+                )
+                .first()
+                .get_heap_tuple()
+                .expect("no rows");
+
+            assert_eq!(row.get_datum_as_string(1), Some("42".to_string()));
+            assert_eq!(row.get_datum_as_string(2), None);
+            assert_eq!(row.get_datum_as_string(3), Some("{1,2,3}".to_string()));
+            assert_eq!(row.get_datum_as_string(4), Some("(1,2)".to_string()));
+            assert_eq!(row.get_datum_as_string(5), None);
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_try_select_error() {
+        Spi::connect(|client| {
+            match client.try_select("SELECT * FROM tests.no_such_table", None, None) {
+                Err(SpiError::Postgres(e)) => {
+                    assert_eq!(e.sqlstate(), "42P01");
+                    assert_eq!(e.message, "relation \"tests.no_such_table\" does not exist");
+                }
+                _ => panic!("try_select() didn't return a Postgres error"),
+            }
+
+            // the connection is still usable
+            let one = client
+                .select("SELECT 1", None, None)
+                .first()
+                .get_one::<i32>();
+            assert_eq!(one, Some(1));
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_try_update_rolls_back() {
+        Spi::connect(|mut client| {
+            client.update("CREATE TABLE tests.try_update (id int)", None, None);
+            client
+                .try_update("INSERT INTO tests.try_update VALUES (1)", None, None)
+                .expect("insert failed");
+
+            let result = client.try_update(
+                "INSERT INTO tests.try_update SELECT 1 / (x - 2) FROM generate_series(1, 3) x",
+                None,
+                None,
+            );
+            match result {
+                Err(SpiError::Postgres(e)) => assert_eq!(e.sqlstate(), "22012"),
+                _ => panic!("try_update() didn't return a Postgres error"),
+            }
+
+            let count = client
+                .update("SELECT count(*) FROM tests.try_update", None, None)
+                .first()
+                .get_one::<i64>();
+            assert_eq!(count, Some(1));
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test(error = "relation \"tests.no_such_table\" does not exist")]
+    fn test_spi_try_select_rethrow() {
+        Spi::connect::<(), _>(|client| {
+            client.try_select("SELECT * FROM tests.no_such_table", None, None)?;
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_args_with_nulls() {
+        let (a, b) = Spi::get_two_with_args::<String, bool>(
+            "SELECT pg_typeof($2)::text || ' ' || $1 || ' ' || $3, $2 IS NULL",
+            vec![
+                42.into_spi_arg(),
+                None::<i32>.into_spi_arg(),
+                "x".into_spi_arg(),
+            ],
+        );
+        assert_eq!(a, Some("integer 42 x".to_string()));
+        assert_eq!(b, Some(true));
+    }
+
+    #[pg_test]
+    fn test_spi_args_macro() {
+        let (a, b) = Spi::get_two_with_args::<String, String>(
+            "SELECT pg_typeof($1)::text || ' ' || pg_typeof($2)::text || ' ' || pg_typeof($3)::text, $2 || coalesce($3::text, '-')",
+            args![1i32, "abc", None::<f64>],
+        );
+        assert_eq!(a, Some("integer text double precision".to_string()));
+        assert_eq!(b, Some("abc-".to_string()));
+    }
+
+    #[pg_test]
+    fn test_spi_iterator_adapters() {
+        Spi::connect(|client| {
+            let evens = client
+                .select("SELECT generate_series(1, 10)", None, None)
+                .filter_map(|row| row.get_datum::<i32>(1))
+                .filter(|i| i % 2 == 0)
+                .collect::<Vec<_>>();
+            assert_eq!(evens, vec![2, 4, 6, 8, 10]);
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_iterator_is_fused() {
+        Spi::connect(|mut client| {
+            let mut table = client.update("SELECT generate_series(1, 3)", None, None);
+            assert_eq!(table.size_hint(), (3, Some(3)));
+            assert!(table.next().is_some());
+            assert_eq!(table.size_hint(), (2, Some(2)));
+            assert_eq!(table.by_ref().count(), 2);
+            assert!(table.next().is_none());
+            assert!(table.next().is_none());
+
+            // a later query doesn't change how many rows an earlier result has
+            let first = client.select("SELECT generate_series(1, 5)", None, None);
+            let second = client.select("SELECT 1", None, None);
+            assert_eq!(second.count(), 1);
+            assert_eq!(first.count(), 5);
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_spi_connect_and_return() {
+        let (count, names) = Spi::connect_and_return(|client| {
+            let names = client
+                .select(
+                    "SELECT 'name ' || i FROM generate_series(1, 3) i",
+                    None,
+                    None,
+                )
+                .filter_map(|row| row.get_datum::<String>(1))
+                .collect::<Vec<_>>();
+            (names.len() as i64, names)
+        });
+        assert_eq!(count, 3);
+        assert_eq!(names, vec!["name 1", "name 2", "name 3"]);
+    }
+
+    #[pg_test]
+    fn test_spi_connect_and_return_str() {
+        let s = Spi::connect_and_return(|_client| {
+            // allocated in SPI's memory context, which is freed when the connection closes
+            let copy = PgMemoryContexts::CurrentMemoryContext.palloc_slice::<u8>(5);
+            copy.copy_from_slice(b"hello");
+            Some(unsafe { std::str::from_utf8_unchecked(copy) })
+        });
+        Spi::run("SELECT repeat('x', 100000)");
+        assert_eq!(s, Some("hello"));
+    }
+
+    #[pg_test]
+    fn test_spi_connect_and_return_heap_tuple() {
+        let tuple = Spi::connect_and_return(|client| {
+            client
+                .select("SELECT 42 AS a, 'forty-two'::text AS b", None, None)
+                .into_heap_tuples()
+                .pop()
+        })
+        .expect("no tuple returned");
+        assert_eq!(tuple.get_by_name::<i32>("a"), Some(42));
+        assert_eq!(
+            tuple.get_by_name::<String>("b"),
+            Some("forty-two".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_spi_insert_all() {
+        Spi::run("CREATE TABLE spi_people (id int, name text, created bool DEFAULT true)");
+
+        let people = vec![
+            SpiPerson {
+                id: 1,
+                name: Some("alice".to_string()),
+            },
+            SpiPerson { id: 2, name: None },
+            SpiPerson {
+                id: 3,
+                name: Some("carol".to_string()),
+            },
+        ];
+        let mut count = 0;
+        Spi::connect(|mut client| {
+            count = client.insert_all("spi_people", people);
+            Ok(Some(()))
+        });
+        assert_eq!(count, 3);
+
+        let names = Spi::get_one::<String>(
+            "SELECT string_agg(id || '=' || coalesce(name, 'NULL'), ',' ORDER BY id) FROM spi_people WHERE created",
+        );
+        assert_eq!(names, Some("1=alice,2=NULL,3=carol".to_string()));
+    }
+
+    #[pg_test]
+    fn test_spi_insert_all_empty() {
+        Spi::run("CREATE TABLE spi_people (id int, name text)");
+
+        let mut count = 1;
+        Spi::connect(|mut client| {
+            count = client.insert_all("spi_people", Vec::<SpiPerson>::new());
+            Ok(Some(()))
+        });
+        assert_eq!(count, 0);
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM spi_people"),
+            Some(0)
+        );
+    }
+
+    #[pg_test(error = "column \"name\" of relation \"spi_ids\" does not exist")]
+    fn test_spi_insert_all_missing_column() {
+        Spi::run("CREATE TABLE spi_ids (id int)");
+
+        Spi::connect(|mut client| {
+            client.insert_all("spi_ids", vec![SpiPerson { id: 1, name: None }]);
+            Ok(Some(()))
+        });
+    }
 }
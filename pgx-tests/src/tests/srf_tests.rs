@@ -62,6 +62,100 @@ fn return_none_setof_iterator() -> Option<impl std::iter::Iterator<Item = i32>>
     }
 }
 
+#[pg_extern]
+fn setof_count_to(n: i32) -> Option<impl std::iter::Iterator<Item = i32>> {
+    if n < 0 {
+        None
+    } else {
+        Some(1..=n)
+    }
+}
+
+#[pg_extern]
+fn table_count_to(
+    n: i32,
+) -> Option<impl std::iter::Iterator<Item = (name!(i, i32), name!(square, i32))>> {
+    if n < 0 {
+        None
+    } else {
+        Some((1..=n).map(|i| (i, i * i)))
+    }
+}
+
+#[pg_extern]
+fn materialize_squares(n: i32) -> Materialize {
+    Materialize::new(move |store| {
+        for i in 1..=n {
+            store.put_values(vec![i.into_datum(), (i * i).into_datum()]);
+        }
+    })
+}
+
+#[pg_extern]
+fn materialize_nulls() -> Materialize {
+    Materialize::new(|store| {
+        store.put_values(vec![1i32.into_datum(), None]);
+        store.put_values(vec![None, "b".into_datum()]);
+    })
+}
+
+#[derive(PostgresType)]
+#[composite]
+pub struct Dog {
+    name: String,
+    age: i32,
+    nickname: Option<String>,
+}
+
+#[pg_extern]
+fn example_dogs() -> impl std::iter::Iterator<Item = Dog> {
+    vec![
+        Dog {
+            name: "Brandy".to_string(),
+            age: 7,
+            nickname: None,
+        },
+        Dog {
+            name: "Nami".to_string(),
+            age: 2,
+            nickname: Some("Nams".to_string()),
+        },
+    ]
+    .into_iter()
+}
+
+#[pg_extern]
+fn dog_description(dog: Dog) -> String {
+    match dog.nickname {
+        Some(nickname) => format!("{} ({}), age {}", dog.name, nickname, dog.age),
+        None => format!("{}, age {}", dog.name, dog.age),
+    }
+}
+
+#[pg_extern(
+    sql = "CREATE FUNCTION value_per_call_count_to(integer) RETURNS SETOF integer STRICT LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@';"
+)]
+fn value_per_call_count_to(n: i32, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    if srf_is_first_call(fcinfo) {
+        srf_first_call_init(fcinfo).user_state(|| 1..=n);
+    }
+
+    let mut funcctx = srf_per_call_setup(fcinfo);
+    let next = funcctx
+        .user_state::<std::ops::RangeInclusive<i32>, _>(|| unreachable!())
+        .next();
+    match next {
+        Some(i) => {
+            srf_return_next(fcinfo, &mut funcctx);
+            i.into_datum().unwrap()
+        }
+        None => {
+            srf_return_done(fcinfo, &mut funcctx);
+            pg_return_null(fcinfo)
+        }
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
@@ -69,6 +163,16 @@ mod tests {
 
     use pgx::*;
 
+    #[pg_test]
+    fn test_value_per_call_user_state() {
+        let sum = Spi::get_one::<i64>("SELECT sum(i) FROM value_per_call_count_to(4) i");
+        assert_eq!(sum, Some(10));
+
+        // stopping early leaves the state to be dropped with the query
+        let first = Spi::get_one::<i32>("SELECT * FROM value_per_call_count_to(4) LIMIT 1");
+        assert_eq!(first, Some(1));
+    }
+
     #[pg_test]
     fn test_generate_series() {
         let cnt = Spi::connect(|client| {
@@ -159,4 +263,98 @@ mod tests {
 
         assert_eq!(cnt, Some(0))
     }
+
+    #[pg_test]
+    fn test_setof_none_empty_single_many() {
+        for (n, expected) in vec![(-1, 0), (0, 0), (1, 1), (1000, 1000)] {
+            let setof = Spi::get_one::<i64>(&format!("SELECT count(*) FROM setof_count_to({})", n))
+                .expect("count was NULL");
+            assert_eq!(setof, expected, "setof_count_to({})", n);
+
+            let table = Spi::get_one::<i64>(&format!("SELECT count(*) FROM table_count_to({})", n))
+                .expect("count was NULL");
+            assert_eq!(table, expected, "table_count_to({})", n);
+        }
+    }
+
+    #[pg_test]
+    fn test_setof_none_and_empty_in_target_list() {
+        // each call has to end its set properly for the next row's call to start a new one
+        let sum = Spi::get_one::<i64>(
+            "SELECT sum(v) FROM (SELECT setof_count_to(n) AS v FROM generate_series(-2, 4) n) t",
+        )
+        .expect("sum was NULL");
+        assert_eq!(sum, 1 + 3 + 6 + 10);
+
+        let cnt = Spi::get_one::<i64>(
+            "SELECT count(*) FROM (SELECT table_count_to(n) FROM generate_series(-2, 4) n) t",
+        )
+        .expect("count was NULL");
+        assert_eq!(cnt, 1 + 2 + 3 + 4);
+    }
+
+    #[pg_test]
+    fn test_setof_none_and_empty_lateral() {
+        let sum = Spi::get_one::<i64>(
+            "SELECT sum(square) FROM generate_series(-2, 3) n, LATERAL table_count_to(n)",
+        )
+        .expect("sum was NULL");
+        assert_eq!(sum, 1 + (1 + 4) + (1 + 4 + 9));
+    }
+
+    #[pg_test]
+    fn test_materialize() {
+        let sum = Spi::get_one::<i64>(
+            "SELECT sum(square) FROM materialize_squares(1000) AS t(i int, square int);",
+        );
+        assert_eq!(sum, Some((1..=1000i64).map(|i| i * i).sum()));
+    }
+
+    #[pg_test]
+    fn test_materialize_with_nulls() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT array_agg(a) IS NOT DISTINCT FROM ARRAY[1, NULL]::int[] AND array_agg(b) IS NOT DISTINCT FROM ARRAY[NULL, 'b'] FROM materialize_nulls() AS t(a int, b text);",
+        );
+        assert_eq!(rc, Some(true));
+    }
+
+    #[pg_test(error = "a column definition list is required for functions returning \"record\"")]
+    fn test_materialize_requires_column_definitions() {
+        Spi::run("SELECT * FROM materialize_squares(3);");
+    }
+
+    #[pg_test]
+    fn test_setof_composite_type() {
+        let ages = Spi::get_one::<i64>("SELECT sum(age) FROM example_dogs();");
+        assert_eq!(ages, Some(9));
+
+        let nickname =
+            Spi::get_one::<&str>("SELECT nickname FROM example_dogs() WHERE name = 'Nami';");
+        assert_eq!(nickname, Some("Nams"));
+
+        let rettype = Spi::get_one::<&str>(
+            "SELECT prorettype::regtype::text FROM pg_proc WHERE proname = 'example_dogs';",
+        );
+        assert_eq!(rettype, Some("dog"));
+    }
+
+    #[pg_test]
+    fn test_composite_type_roundtrip() {
+        let descriptions = Spi::get_one::<&str>(
+            "SELECT string_agg(dog_description(d), '; ') FROM example_dogs() d;",
+        );
+        assert_eq!(descriptions, Some("Brandy, age 7; Nami (Nams), age 2"));
+    }
+
+    #[pg_test]
+    fn test_composite_type_off_search_path() {
+        let function = Spi::get_one::<String>(
+            "SELECT quote_ident(n.nspname) || '.example_dogs' FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace WHERE p.proname = 'example_dogs';",
+        )
+        .expect("example_dogs() not found");
+
+        Spi::run("SET LOCAL search_path TO pg_catalog;");
+        let ages = Spi::get_one::<i64>(&format!("SELECT sum(age) FROM {}();", function));
+        assert_eq!(ages, Some(9));
+    }
 }
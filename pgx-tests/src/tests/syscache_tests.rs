@@ -0,0 +1,62 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_syscache_pg_class() {
+        let tuple =
+            PgSysCache::search_form::<pg_sys::FormData_pg_class>(pg_sys::RelationRelationId)
+                .expect("pg_class not found");
+        let form = tuple.get_struct();
+
+        assert_eq!(name_data_to_str(&form.relname), "pg_class");
+        assert_eq!(form.relkind as u8, pg_sys::RELKIND_RELATION);
+        assert_eq!(form.relnamespace, 11); // PG_CATALOG_NAMESPACE
+    }
+
+    #[pg_test]
+    fn test_syscache_pg_type() {
+        let tuple = PgSysCache::search_form::<pg_sys::FormData_pg_type>(pg_sys::INT4OID)
+            .expect("int4 not found");
+
+        assert_eq!(name_data_to_str(&tuple.get_struct().typname), "int4");
+        assert_eq!(tuple.get_struct().typlen, 4);
+    }
+
+    #[pg_test]
+    fn test_syscache_get_attr() {
+        let tuple =
+            PgSysCache::search_form::<pg_sys::FormData_pg_class>(pg_sys::RelationRelationId)
+                .expect("pg_class not found");
+        let relname =
+            unsafe { tuple.get_attr::<Name>(pg_sys::Anum_pg_class_relname as pg_sys::AttrNumber) };
+
+        assert_eq!(relname, Some(Name::new("pg_class")));
+    }
+
+    #[pg_test]
+    fn test_syscache_not_found() {
+        let tuple = PgSysCache::search_form::<pg_sys::FormData_pg_class>(pg_sys::InvalidOid);
+        assert!(tuple.is_none());
+    }
+
+    #[pg_test]
+    fn test_syscache_multi_key_search() {
+        let tuple = unsafe {
+            PgSysCache::search::<pg_sys::FormData_pg_attribute, _>(
+                pg_sys::SysCacheIdentifier_ATTNUM,
+                (pg_sys::RelationRelationId, 1i16),
+            )
+        }
+        .expect("attribute not found");
+
+        assert_eq!(tuple.get_struct().attrelid, pg_sys::RelationRelationId);
+        assert_eq!(tuple.get_struct().attnum, 1);
+    }
+}
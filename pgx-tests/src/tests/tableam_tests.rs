@@ -0,0 +1,136 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#![cfg(feature = "pg12")]
+
+use pgx::*;
+
+/// A table access method whose tables are always empty
+struct EmptyAm;
+
+impl TableAccessMethod for EmptyAm {
+    fn scan_begin(
+        rel: &PgRelation,
+        snapshot: pg_sys::Snapshot,
+        nkeys: i32,
+        key: *mut pg_sys::ScanKeyData,
+        pscan: pg_sys::ParallelTableScanDesc,
+        flags: u32,
+    ) -> pg_sys::TableScanDesc {
+        let mut scan = PgBox::<pg_sys::TableScanDescData>::alloc0();
+        scan.rs_rd = rel.as_ptr();
+        scan.rs_snapshot = snapshot;
+        scan.rs_nkeys = nkeys;
+        scan.rs_key = key;
+        scan.rs_flags = flags;
+        scan.rs_parallel = pscan;
+        scan.into_pg()
+    }
+
+    fn scan_getnextslot(
+        _scan: pg_sys::TableScanDesc,
+        _direction: pg_sys::ScanDirection,
+        _slot: &mut PgTupleTableSlot,
+    ) -> bool {
+        false
+    }
+
+    fn scan_end(scan: pg_sys::TableScanDesc) {
+        unsafe { pg_sys::pfree(scan as void_mut_ptr) }
+    }
+
+    fn tuple_insert(
+        _rel: &PgRelation,
+        _slot: &mut PgTupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _options: i32,
+        _bistate: *mut pg_sys::BulkInsertStateData,
+    ) {
+        panic!("empty_am tables are read-only")
+    }
+
+    fn tuple_delete(
+        _rel: &PgRelation,
+        _tid: pg_sys::ItemPointer,
+        _cid: pg_sys::CommandId,
+        _snapshot: pg_sys::Snapshot,
+        _crosscheck: pg_sys::Snapshot,
+        _wait: bool,
+        _tmfd: *mut pg_sys::TM_FailureData,
+        _changing_part: bool,
+    ) -> pg_sys::TM_Result {
+        panic!("empty_am tables are read-only")
+    }
+
+    fn tuple_update(
+        _rel: &PgRelation,
+        _otid: pg_sys::ItemPointer,
+        _slot: &mut PgTupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _snapshot: pg_sys::Snapshot,
+        _crosscheck: pg_sys::Snapshot,
+        _wait: bool,
+        _tmfd: *mut pg_sys::TM_FailureData,
+        _lockmode: *mut pg_sys::LockTupleMode,
+        _update_indexes: *mut bool,
+    ) -> pg_sys::TM_Result {
+        panic!("empty_am tables are read-only")
+    }
+
+    fn relation_set_new_filenode(
+        _rel: &PgRelation,
+        _newrnode: &pg_sys::RelFileNode,
+        _persistence: std::os::raw::c_char,
+        freeze_xid: &mut pg_sys::TransactionId,
+        minmulti: &mut pg_sys::MultiXactId,
+    ) {
+        *freeze_xid = pg_sys::InvalidTransactionId;
+        *minmulti = 0; // InvalidMultiXactId
+    }
+
+    fn relation_size(_rel: &PgRelation, _fork_number: pg_sys::ForkNumber) -> u64 {
+        0
+    }
+}
+
+pg_table_am!(empty_am_handler, EmptyAm);
+
+// table access methods only exist on Postgres 12+, and this script is shared by all versions
+extension_sql! { r#"
+DO $$
+BEGIN
+    IF current_setting('server_version_num')::int >= 120000 THEN
+        EXECUTE $sql$CREATE FUNCTION empty_am_handler(internal) RETURNS table_am_handler LANGUAGE c AS 'MODULE_PATHNAME', 'empty_am_handler_wrapper'$sql$;
+        EXECUTE $sql$CREATE ACCESS METHOD empty_am TYPE TABLE HANDLER empty_am_handler$sql$;
+    END IF;
+END;
+$$;
+"#}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_empty_table_am_scan() {
+        Spi::run("CREATE TABLE tests.empty_am_table (id int) USING empty_am");
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM tests.empty_am_table")
+            .expect("SPI result was NULL");
+        assert_eq!(count, 0);
+    }
+
+    #[pg_test(error = "empty_am tables are read-only")]
+    fn test_empty_table_am_insert() {
+        Spi::run("CREATE TABLE tests.empty_am_insert (id int) USING empty_am");
+        Spi::run("INSERT INTO tests.empty_am_insert VALUES (1)");
+    }
+
+    #[pg_test(error = "this table access method does not support indexes")]
+    fn test_empty_table_am_create_index() {
+        Spi::run("CREATE TABLE tests.empty_am_index (id int) USING empty_am");
+        Spi::run("CREATE INDEX ON tests.empty_am_index (id)");
+    }
+}
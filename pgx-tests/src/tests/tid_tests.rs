@@ -0,0 +1,45 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn tid_next_block(tid: Tid) -> Tid {
+        Tid::new(tid.block_number() + 1, tid.offset_number())
+    }
+
+    #[pg_test]
+    fn test_tid_from_sql() {
+        let tid = Spi::get_one::<Tid>("SELECT '(70000,3)'::tid").expect("tid was NULL");
+        assert_eq!(tid.block_number(), 70000);
+        assert_eq!(tid.offset_number(), 3);
+        assert_eq!(tid, Tid::new(70000, 3));
+    }
+
+    #[pg_test]
+    fn test_tid_roundtrip() {
+        let rc = Spi::get_one::<bool>("SELECT tests.tid_next_block('(1,2)'::tid) = '(2,2)'::tid")
+            .expect("SPI result was NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_tid_ctid() {
+        Spi::run("CREATE TABLE tid_test AS SELECT 1 AS id");
+        let tid = Spi::get_one::<Tid>("SELECT ctid FROM tid_test").expect("ctid was NULL");
+        assert!(tid.is_valid());
+        assert_eq!(tid.to_string(), "(0,1)");
+    }
+
+    #[pg_test]
+    fn test_tid_ordering() {
+        assert!(Tid::new(0, 2) < Tid::new(1, 1));
+        assert!(Tid::new(1, 1) < Tid::new(1, 2));
+        assert!(!Tid::default().is_valid());
+    }
+}
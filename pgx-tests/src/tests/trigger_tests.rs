@@ -0,0 +1,83 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn audit_transition_tables(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    let trigger = PgTrigger::from_fcinfo(fcinfo);
+
+    let old_ids = trigger
+        .old_table()
+        .map(|rows| {
+            rows.map(|row| row.get_by_name::<i32>("id").unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let new_ids = trigger
+        .new_table()
+        .map(|rows| {
+            rows.map(|row| row.get_by_name::<i32>("id").unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    Spi::run(&format!(
+        "INSERT INTO transition_audit (old_ids, new_ids) VALUES (ARRAY{:?}::int[], ARRAY{:?}::int[])",
+        old_ids, new_ids
+    ));
+
+    0
+}
+
+extension_sql! { r#"
+CREATE FUNCTION audit_transition_tables() RETURNS trigger LANGUAGE c AS 'MODULE_PATHNAME', 'audit_transition_tables_wrapper';
+"#}
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_audited_table() {
+        Spi::run("CREATE TABLE transition_audit (old_ids int[], new_ids int[])");
+        Spi::run("CREATE TABLE audited (id int, value text)");
+        Spi::run("INSERT INTO audited VALUES (1, 'a'), (2, 'b')");
+        Spi::run(
+            "CREATE TRIGGER audited_update AFTER UPDATE ON audited \
+             REFERENCING OLD TABLE AS old_rows NEW TABLE AS new_rows \
+             FOR EACH STATEMENT EXECUTE PROCEDURE audit_transition_tables()",
+        );
+        Spi::run(
+            "CREATE TRIGGER audited_insert AFTER INSERT ON audited \
+             REFERENCING NEW TABLE AS new_rows \
+             FOR EACH STATEMENT EXECUTE PROCEDURE audit_transition_tables()",
+        );
+    }
+
+    #[pg_test]
+    fn test_transition_tables_on_update() {
+        create_audited_table();
+        Spi::run("UPDATE audited SET id = id * 10");
+
+        let (old_ids, new_ids) = Spi::get_two::<Vec<Option<i32>>, Vec<Option<i32>>>(
+            "SELECT old_ids, new_ids FROM transition_audit",
+        );
+        assert_eq!(old_ids, Some(vec![Some(1), Some(2)]));
+        assert_eq!(new_ids, Some(vec![Some(10), Some(20)]));
+    }
+
+    #[pg_test]
+    fn test_transition_tables_on_insert() {
+        create_audited_table();
+        Spi::run("INSERT INTO audited VALUES (3, 'c')");
+
+        let (old_ids, new_ids) = Spi::get_two::<Vec<Option<i32>>, Vec<Option<i32>>>(
+            "SELECT old_ids, new_ids FROM transition_audit",
+        );
+        assert_eq!(old_ids, Some(vec![]));
+        assert_eq!(new_ids, Some(vec![Some(3)]));
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn slot_tupdesc<'a>() -> PgTupleDesc<'a> {
+        Spi::run("CREATE TYPE tests.slot_type AS (id int, name text)");
+        let typid = Spi::get_one::<i32>("SELECT 'tests.slot_type'::regtype::oid::int")
+            .expect("SPI result was NULL") as pg_sys::Oid;
+        unsafe { PgTupleDesc::from_pg_is_copy(pg_sys::lookup_rowtype_tupdesc_copy(typid, -1)) }
+    }
+
+    #[pg_test]
+    fn test_tupleslot_set_and_get() {
+        let tupdesc = slot_tupdesc();
+        let mut slot = PgTupleTableSlot::new(&tupdesc);
+        assert_eq!(slot.natts(), 2);
+        assert!(slot.is_empty());
+
+        slot.clear();
+        slot.set(1, 42i32);
+        slot.set(2, Option::<&str>::None);
+        slot.store_virtual();
+        assert!(!slot.is_empty());
+
+        assert_eq!(slot.get::<i32>(1), Some(42));
+        assert_eq!(slot.get::<String>(2), None);
+    }
+
+    #[pg_test]
+    fn test_tupleslot_materialize() {
+        let tupdesc = slot_tupdesc();
+        let mut slot = PgTupleTableSlot::new(&tupdesc);
+
+        slot.clear();
+        slot.set(1, 7i32);
+        slot.set(2, "seven");
+        slot.store_virtual();
+        slot.materialize();
+
+        assert_eq!(slot.get::<i32>(1), Some(7));
+        assert_eq!(slot.get::<String>(2), Some("seven".to_string()));
+    }
+
+    #[pg_test(error = "attribute number 3 is out of bounds for a TupleTableSlot with 2 attributes")]
+    fn test_tupleslot_attno_out_of_bounds() {
+        let tupdesc = slot_tupdesc();
+        let mut slot = PgTupleTableSlot::new(&tupdesc);
+        slot.set(3, 1i32);
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_datum_to_cstring() {
+        let text = unsafe { datum_to_cstring(42i32.into_datum(), pg_sys::INT4OID) };
+        assert_eq!(text, Some("42".to_string()));
+
+        let text = unsafe { datum_to_cstring(vec![1i32, 2, 3].into_datum(), pg_sys::INT4ARRAYOID) };
+        assert_eq!(text, Some("{1,2,3}".to_string()));
+
+        let text = unsafe { datum_to_cstring(None, pg_sys::INT4OID) };
+        assert_eq!(text, None);
+    }
+
+    #[pg_test]
+    fn test_cstring_to_datum() {
+        let datum = cstring_to_datum(Some("t"), pg_sys::BOOLOID);
+        assert_eq!(
+            unsafe { bool::from_datum(datum.unwrap(), false, pg_sys::BOOLOID) },
+            Some(true)
+        );
+
+        let datum = cstring_to_datum(Some("hello"), pg_sys::TEXTOID);
+        assert_eq!(
+            unsafe { String::from_datum(datum.unwrap(), false, pg_sys::TEXTOID) },
+            Some("hello".to_string())
+        );
+
+        assert_eq!(cstring_to_datum(None, pg_sys::INT4OID), None);
+    }
+
+    #[pg_test]
+    fn test_type_io_round_trip() {
+        let datum = cstring_to_datum(Some("1.50"), pg_sys::NUMERICOID);
+        let text = unsafe { datum_to_cstring(datum, pg_sys::NUMERICOID) };
+        assert_eq!(text, Some("1.50".to_string()));
+    }
+
+    #[pg_test(error = "invalid input syntax for type boolean: \"maybe\"")]
+    fn test_cstring_to_datum_invalid() {
+        cstring_to_datum(Some("maybe"), pg_sys::BOOLOID);
+    }
+
+    #[pg_test(error = "domain positive_int does not allow null values")]
+    fn test_cstring_to_datum_domain_null() {
+        Spi::run("CREATE DOMAIN positive_int AS int NOT NULL CHECK (VALUE > 0)");
+        let domain = Spi::get_one::<pg_sys::Oid>("SELECT 'positive_int'::regtype::oid")
+            .expect("type oid was NULL");
+        cstring_to_datum(None, domain);
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::typecache::TypeCacheFlags;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_typecache_storage() {
+        let int8 = pgx::typecache::lookup(pg_sys::INT8OID, TypeCacheFlags::empty());
+        assert_eq!(int8.type_oid(), pg_sys::INT8OID);
+        assert_eq!(int8.typlen(), 8);
+        assert_eq!(int8.typalign(), b'd');
+        assert_eq!(int8.typtype(), b'b');
+
+        let text = pgx::typecache::lookup(pg_sys::TEXTOID, TypeCacheFlags::empty());
+        assert_eq!(text.typlen(), -1);
+        assert!(!text.typbyval());
+        assert_eq!(text.typstorage(), b'x');
+    }
+
+    #[pg_test]
+    fn test_typecache_operators() {
+        let int4 = pgx::typecache::lookup(
+            pg_sys::INT4OID,
+            TypeCacheFlags::EQ_OPR | TypeCacheFlags::LT_OPR | TypeCacheFlags::CMP_PROC,
+        );
+        let eq = Spi::get_one::<pg_sys::Oid>("SELECT '=(int4,int4)'::regoperator::oid");
+        let lt = Spi::get_one::<pg_sys::Oid>("SELECT '<(int4,int4)'::regoperator::oid");
+        assert_eq!(int4.eq_opr(), eq);
+        assert_eq!(int4.lt_opr(), lt);
+        assert!(int4.cmp_proc().is_some());
+
+        // a type without a default btree opclass has no comparison operators
+        let point = pgx::typecache::lookup(pg_sys::POINTOID, TypeCacheFlags::LT_OPR);
+        assert_eq!(point.lt_opr(), None);
+    }
+
+    #[pg_test]
+    fn test_typecache_hash_proc_finfo() {
+        let int4 = pgx::typecache::lookup(pg_sys::INT4OID, TypeCacheFlags::HASH_PROC_FINFO);
+        let finfo = int4.hash_proc_finfo().expect("int4 has no hash function");
+        let hash = unsafe {
+            pg_sys::FunctionCall1Coll(
+                finfo as *const _ as *mut _,
+                pg_sys::InvalidOid,
+                42i32.into_datum().unwrap(),
+            )
+        };
+        let expected =
+            Spi::get_one::<i32>("SELECT hashint4(42)").expect("SPI result was NULL") as u32;
+        assert_eq!(hash as u32, expected);
+    }
+
+    #[pg_test]
+    fn test_typecache_tuple_desc() {
+        Spi::run("CREATE TYPE typecache_test AS (a int, b text)");
+        let oid = Spi::get_one::<pg_sys::Oid>("SELECT 'typecache_test'::regtype::oid")
+            .expect("SPI result was NULL");
+
+        let entry = pgx::typecache::lookup(oid, TypeCacheFlags::TUPDESC);
+        assert_eq!(entry.typtype(), b'c');
+        let tupdesc = entry.tuple_desc().expect("composite type has no TupleDesc");
+        assert_eq!(tupdesc.len(), 2);
+
+        let int4 = pgx::typecache::lookup(pg_sys::INT4OID, TypeCacheFlags::TUPDESC);
+        assert!(int4.tuple_desc().is_none());
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn varbit_roundtrip(bits: VarBit) -> VarBit {
+        bits
+    }
+
+    #[pg_extern]
+    fn varbit_from_bools(bits: Array<bool>) -> VarBit {
+        bits.iter().map(|bit| bit.unwrap_or(false)).collect()
+    }
+
+    #[pg_test]
+    fn test_varbit_from_sql() {
+        let bits = Spi::get_one::<VarBit>("SELECT B'1010000011'::varbit").expect("bits were NULL");
+        assert_eq!(bits.len(), 10);
+        assert_eq!(
+            Vec::<bool>::from(bits.clone()),
+            vec![true, false, true, false, false, false, false, false, true, true]
+        );
+        assert_eq!(bits.as_bytes(), &[0b1010_0000, 0b1100_0000]);
+        assert_eq!(bits.to_string(), "1010000011");
+    }
+
+    #[pg_test]
+    fn test_fixed_length_bit_from_sql() {
+        let bits = Spi::get_one::<VarBit>("SELECT B'101'::bit(3)").expect("bits were NULL");
+        assert_eq!(bits.iter().collect::<Vec<_>>(), vec![true, false, true]);
+    }
+
+    #[pg_test]
+    fn test_varbit_roundtrip() {
+        let result = Spi::get_one::<bool>(
+            "SELECT tests.varbit_roundtrip(B'110010011') = B'110010011'::varbit",
+        )
+        .expect("SPI result was NULL");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_varbit_from_bools() {
+        let result = Spi::get_one::<String>(
+            "SELECT tests.varbit_from_bools(ARRAY[true, true, false, true, false, false, false, false, true])::text",
+        );
+        assert_eq!(result, Some("110100001".to_string()));
+    }
+
+    #[pg_test]
+    fn test_varbit_empty() {
+        let result = Spi::get_one::<bool>("SELECT tests.varbit_from_bools(ARRAY[]::bool[]) = B''")
+            .expect("SPI result was NULL");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_varbit_set_and_get() {
+        let mut bits = VarBit::with_len(12);
+        bits.set(0, true);
+        bits.set(11, true);
+        bits.set(0, false);
+        bits.push(true);
+
+        assert_eq!(bits.get(0), Some(false));
+        assert_eq!(bits.get(11), Some(true));
+        assert_eq!(bits.get(12), Some(true));
+        assert_eq!(bits.get(13), None);
+        assert_eq!(bits.to_string(), "0000000000011");
+        assert_eq!(VarBit::from(vec![false, true]).to_string(), "01");
+    }
+
+    #[pg_test(error = "bit index 3 is out of bounds for a bit string of length 3")]
+    fn test_varbit_set_out_of_bounds() {
+        let mut bits = VarBit::with_len(3);
+        bits.set(3, true);
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_extern]
+    fn borrowed_str_len(s: &str) -> i32 {
+        s.len() as i32
+    }
+
+    #[pg_test]
+    fn test_pg_varlena_detoasts_compressed_value() {
+        Spi::run("CREATE TABLE tests.varlena_test (value text)");
+        Spi::run("INSERT INTO tests.varlena_test VALUES (repeat('a', 100000))");
+
+        Spi::connect(|client| {
+            let varlena = client
+                .select("SELECT value FROM tests.varlena_test", None, None)
+                .first()
+                .get_one::<PgVarlena<pg_sys::varlena>>()
+                .expect("value was NULL");
+
+            // a value this large and repetitive is stored compressed, so detoasting copies it
+            assert!(varlena.is_copy());
+
+            let text = unsafe { text_to_rust_str_unchecked(varlena.as_ptr()) };
+            assert_eq!(text.len(), 100000);
+            assert!(text.chars().all(|c| c == 'a'));
+
+            Ok(Some(()))
+        });
+    }
+
+    #[pg_test]
+    fn test_pg_varlena_plain_value_is_not_copied() {
+        let datum = rust_str_to_text_p("pgx").into_pg() as pg_sys::Datum;
+        let varlena = unsafe { PgVarlena::<pg_sys::varlena>::detoast(datum) };
+
+        assert!(!varlena.is_copy());
+        assert_eq!(
+            unsafe { text_to_rust_str_unchecked(varlena.as_ptr()) },
+            "pgx"
+        );
+    }
+
+    #[pg_test]
+    fn test_borrowed_str_from_toasted_value() {
+        Spi::run("CREATE TABLE tests.borrowed_str (value text)");
+        Spi::run("INSERT INTO tests.borrowed_str VALUES (repeat('a', 100000))");
+
+        let len =
+            Spi::get_one::<i32>("SELECT tests.borrowed_str_len(value) FROM tests.borrowed_str");
+        assert_eq!(len, Some(100000));
+    }
+
+    #[pg_test]
+    fn test_text_to_rust_str_invalid_utf8() {
+        let varlena = rust_byte_slice_to_bytea(&[b'a', 0xff]);
+        assert!(unsafe { text_to_rust_str(varlena.as_ptr()) }.is_err());
+
+        let varlena = rust_str_to_text_p("pgx");
+        assert_eq!(unsafe { text_to_rust_str(varlena.as_ptr()) }, Ok("pgx"));
+    }
+
+    #[pg_test(
+        error = "text datum is not valid UTF-8: invalid utf-8 sequence of 1 bytes from index 1"
+    )]
+    fn test_str_from_datum_invalid_utf8() {
+        let datum = rust_byte_slice_to_bytea(&[b'a', 0xff]).into_pg() as pg_sys::Datum;
+        unsafe { <&str>::from_datum(datum, false, pg_sys::TEXTOID) };
+    }
+}
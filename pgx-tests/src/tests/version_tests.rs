@@ -0,0 +1,31 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_pg_version() {
+        let server_version_num =
+            Spi::get_one::<&str>("SELECT current_setting('server_version_num')")
+                .expect("server_version_num was NULL");
+        assert_eq!(pg_version().to_string(), server_version_num);
+        assert_eq!(pg_version() / 10000, PG_MAJOR_VERSION);
+    }
+
+    #[pg_test]
+    fn test_pg_major_version() {
+        assert_eq!(PG_MAJOR_VERSION, pg_sys::get_pg_major_version_num() as u32);
+        assert_eq!(PG_VERSION_NUM, pg_sys::PG_VERSION_NUM);
+    }
+
+    #[pg_test]
+    fn test_create_template_tupdesc() {
+        let tupdesc = unsafe { PgTupleDesc::from_pg_is_copy(pg_sys::CreateTemplateTupleDesc(3)) };
+        assert_eq!(tupdesc.len(), 3);
+    }
+}
@@ -2,6 +2,39 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 
+use pgx::*;
+use std::cell::RefCell;
+
+thread_local! {
+    static BACKEND_XACT_EVENTS: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+    static BACKEND_XACT_RECEIPTS: RefCell<Vec<BackendCallbackReceipt>> = RefCell::new(Vec::new());
+}
+
+#[pg_extern]
+fn register_backend_xact_test_callbacks() {
+    let commit = register_backend_xact_callback(PgXactCallbackEvent::Commit, || {
+        BACKEND_XACT_EVENTS.with(|events| events.borrow_mut().push("commit"))
+    });
+    let abort = register_backend_xact_callback(PgXactCallbackEvent::Abort, || {
+        BACKEND_XACT_EVENTS.with(|events| events.borrow_mut().push("abort"))
+    });
+    BACKEND_XACT_RECEIPTS.with(|receipts| receipts.borrow_mut().extend(vec![commit, abort]));
+}
+
+#[pg_extern]
+fn unregister_backend_xact_test_callbacks() {
+    BACKEND_XACT_RECEIPTS.with(|receipts| {
+        for receipt in receipts.borrow_mut().drain(..) {
+            receipt.unregister_callback();
+        }
+    });
+}
+
+#[pg_extern]
+fn backend_xact_test_events() -> String {
+    BACKEND_XACT_EVENTS.with(|events| events.borrow().join(","))
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests {
     #[allow(unused_imports)]
@@ -18,4 +51,72 @@ mod tests {
             info!("TESTMSG: Called on abort")
         });
     }
+
+    #[pg_test]
+    fn test_backend_subxact_callback() {
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let started = std::rc::Rc::clone(&events);
+        let start_receipt =
+            register_backend_subxact_callback(PgSubXactCallbackEvent::StartSub, move |_, _| {
+                started.borrow_mut().push("start")
+            });
+        let aborted = std::rc::Rc::clone(&events);
+        let abort_receipt =
+            register_backend_subxact_callback(PgSubXactCallbackEvent::AbortSub, move |_, _| {
+                aborted.borrow_mut().push("abort")
+            });
+
+        // the callbacks are called every time, not just once
+        in_rolled_back_subtransaction(|| ());
+        in_rolled_back_subtransaction(|| ());
+        assert_eq!(*events.borrow(), vec!["start", "abort", "start", "abort"]);
+
+        start_receipt.unregister_callback();
+        abort_receipt.unregister_callback();
+        in_rolled_back_subtransaction(|| ());
+        assert_eq!(events.borrow().len(), 4);
+    }
+
+    #[pg_test]
+    fn test_backend_subxact_callback_ids() {
+        let ids = std::rc::Rc::new(std::cell::Cell::new((0, 0)));
+        let captured = std::rc::Rc::clone(&ids);
+        let receipt = register_backend_subxact_callback(
+            PgSubXactCallbackEvent::StartSub,
+            move |my_subid, parent_subid| captured.set((my_subid, parent_subid)),
+        );
+
+        in_rolled_back_subtransaction(|| ());
+        receipt.unregister_callback();
+
+        let (my_subid, parent_subid) = ids.get();
+        assert!(my_subid > parent_subid);
+        assert_ne!(parent_subid, 0);
+    }
+
+    // the callbacks fire as each transaction commits or aborts, which a #[pg_test] never does
+    // until it's finished, so this needs its own connection
+    #[test]
+    fn test_backend_xact_callback() {
+        let (mut client, _) = pgx_tests::test_client(crate::pg_test::postgresql_conf_options());
+        let mut run = |query: &str| client.simple_query(query).map(|_| ());
+
+        // registering the callbacks is committed, too
+        run("SELECT register_backend_xact_test_callbacks()").expect("failed to register");
+        run("SELECT 1").expect("failed to SELECT 1");
+        run("BEGIN; SELECT 1; ROLLBACK").expect("failed to roll back");
+        assert!(run("SELECT 1 / 0").is_err());
+
+        // callbacks that are unregistered aren't called, even for the current transaction
+        run("SELECT unregister_backend_xact_test_callbacks()").expect("failed to unregister");
+        run("SELECT 1").expect("failed to SELECT 1");
+        run("BEGIN; SELECT 1; ROLLBACK").expect("failed to roll back");
+
+        let events = client
+            .query_one("SELECT backend_xact_test_events()", &[])
+            .expect("failed to get the events")
+            .get::<_, String>(0);
+        assert_eq!(events, "commit,commit,abort,abort");
+    }
 }
@@ -0,0 +1,70 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_in_rolled_back_subtransaction() {
+        let count = in_rolled_back_subtransaction(|| {
+            Spi::run("CREATE TABLE tests.rolled_back AS SELECT generate_series(1, 10) x");
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.rolled_back")
+        });
+        assert_eq!(count, Some(10));
+
+        let exists = Spi::get_one::<bool>("SELECT to_regclass('tests.rolled_back') IS NOT NULL")
+            .expect("SPI result was NULL");
+        assert!(!exists);
+    }
+
+    #[pg_test]
+    fn test_in_rolled_back_subtransaction_keeps_value() {
+        let value = in_rolled_back_subtransaction(|| {
+            Spi::get_one::<String>("SELECT repeat('x', 1000)").expect("SPI result was NULL")
+        });
+        assert_eq!(value, "x".repeat(1000));
+    }
+
+    #[pg_test(transactional)]
+    fn test_transactional_pg_test() {
+        Spi::run("CREATE TABLE tests.transactional_test (id int)");
+        Spi::run("INSERT INTO tests.transactional_test VALUES (1)");
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM tests.transactional_test")
+            .expect("SPI result was NULL");
+        assert_eq!(count, 1);
+    }
+
+    #[pg_test]
+    fn test_transactional_pg_test_rolled_back() {
+        Spi::run("SELECT tests.test_transactional_pg_test()");
+
+        let exists =
+            Spi::get_one::<bool>("SELECT to_regclass('tests.transactional_test') IS NOT NULL")
+                .expect("SPI result was NULL");
+        assert!(!exists);
+    }
+
+    #[pg_test(transactional)]
+    fn test_transactional_pg_test_early_return() {
+        Spi::run("CREATE TABLE tests.transactional_return_test (id int)");
+        if Spi::get_one::<bool>("SELECT true").expect("SPI result was NULL") {
+            return;
+        }
+        unreachable!("didn't return early");
+    }
+
+    #[pg_test]
+    fn test_transactional_pg_test_early_return_rolled_back() {
+        Spi::run("SELECT tests.test_transactional_pg_test_early_return()");
+
+        let exists = Spi::get_one::<bool>(
+            "SELECT to_regclass('tests.transactional_return_test') IS NOT NULL",
+        )
+        .expect("SPI result was NULL");
+        assert!(!exists);
+    }
+}
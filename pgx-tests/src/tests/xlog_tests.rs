@@ -0,0 +1,176 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// the current value of the page's prune hint, read through a working copy of the page
+    unsafe fn prune_xid(relation: &PgRelation, buffer: pg_sys::Buffer) -> pg_sys::TransactionId {
+        let mut xlog = GenericXLog::start(relation);
+        let page = xlog.register_buffer(buffer, false) as *mut pg_sys::PageHeaderData;
+        (*page).pd_prune_xid
+    }
+
+    fn open_xlog_table() -> PgRelation {
+        Spi::run("CREATE TABLE tests.xlog_test (id int)");
+        Spi::run("INSERT INTO tests.xlog_test VALUES (1)");
+        let relid = Spi::get_one::<pg_sys::Oid>("SELECT 'tests.xlog_test'::regclass::oid")
+            .expect("relid was NULL");
+        PgRelation::with_lock(relid, pg_sys::RowExclusiveLock as pg_sys::LOCKMODE)
+    }
+
+    #[pg_test]
+    fn test_generic_xlog_finish() {
+        let relation = open_xlog_table();
+        unsafe {
+            let buffer = pg_sys::ReadBuffer(relation.as_ptr(), 0);
+            pg_sys::LockBuffer(buffer, pg_sys::BUFFER_LOCK_EXCLUSIVE as i32);
+            let before = Lsn(pg_sys::BufferGetLSNAtomic(buffer));
+            let xid = pg_sys::GetCurrentTransactionId();
+
+            let mut xlog = GenericXLog::start(&relation);
+            let page = xlog.register_buffer(buffer, false) as *mut pg_sys::PageHeaderData;
+            (*page).pd_prune_xid = xid;
+            let lsn = xlog.finish();
+
+            assert_eq!(prune_xid(&relation, buffer), xid);
+            assert!(lsn.is_valid() && lsn > before);
+            assert_eq!(Lsn(pg_sys::BufferGetLSNAtomic(buffer)), lsn);
+            pg_sys::UnlockReleaseBuffer(buffer);
+        }
+    }
+
+    #[pg_test]
+    fn test_generic_xlog_abort() {
+        let relation = open_xlog_table();
+        unsafe {
+            let buffer = pg_sys::ReadBuffer(relation.as_ptr(), 0);
+            pg_sys::LockBuffer(buffer, pg_sys::BUFFER_LOCK_EXCLUSIVE as i32);
+            let before = pg_sys::BufferGetLSNAtomic(buffer);
+            let original = prune_xid(&relation, buffer);
+
+            let mut xlog = GenericXLog::start(&relation);
+            let page = xlog.register_buffer(buffer, false) as *mut pg_sys::PageHeaderData;
+            (*page).pd_prune_xid = original + 1;
+            drop(xlog);
+
+            assert_eq!(prune_xid(&relation, buffer), original);
+            assert_eq!(pg_sys::BufferGetLSNAtomic(buffer), before);
+            pg_sys::UnlockReleaseBuffer(buffer);
+        }
+    }
+
+    #[pg_test]
+    fn test_xlog_record_builder() {
+        let before = Lsn(unsafe { pg_sys::GetXLogInsertRecPtr() });
+        let lsn = unsafe {
+            XLogRecordBuilder::begin().register_data(b"pgx").insert(
+                pg_sys::RmgrIds_RM_XLOG_ID as pg_sys::RmgrId,
+                pg_sys::XLOG_NOOP as u8,
+            )
+        };
+        assert!(lsn > before);
+    }
+
+    #[pg_test]
+    fn test_xlog_record_builder_discarded() {
+        drop(XLogRecordBuilder::begin().register_data(b"discarded"));
+
+        // would raise "XLogBeginInsert was already called" if the first wasn't reset
+        let lsn = unsafe {
+            XLogRecordBuilder::begin().insert(
+                pg_sys::RmgrIds_RM_XLOG_ID as pg_sys::RmgrId,
+                pg_sys::XLOG_NOOP as u8,
+            )
+        };
+        assert!(lsn.is_valid());
+    }
+
+    struct TestRmgr;
+
+    static mut REDONE: Vec<u8> = Vec::new();
+
+    impl CustomRmgr for TestRmgr {
+        const NAME: &'static str = "test_rmgr";
+
+        fn redo(record: &XLogRecordRef) {
+            if record.data().is_empty() {
+                panic!("empty test_rmgr record");
+            }
+            unsafe { REDONE.extend_from_slice(record.data()) }
+        }
+
+        fn desc(buf: &mut StringInfo, record: &XLogRecordRef) {
+            buf.push_str(&format!(
+                "{} bytes from xid {}",
+                record.data().len(),
+                record.xid()
+            ));
+        }
+
+        fn identify(info: u8) -> Option<&'static std::ffi::CStr> {
+            match info & !(pg_sys::XLR_INFO_MASK as u8) {
+                0x10 => Some(std::ffi::CStr::from_bytes_with_nul(b"APPEND\0").unwrap()),
+                _ => None,
+            }
+        }
+    }
+
+    /// call `f` with a reader that has decoded a test_rmgr record of `data`
+    unsafe fn with_test_record<F: FnOnce(*mut pg_sys::XLogReaderState)>(data: &mut [u8], f: F) {
+        let mut record = pg_sys::XLogRecord {
+            xl_info: 0x10,
+            xl_xid: 42,
+            ..Default::default()
+        };
+        let mut reader = std::mem::zeroed::<pg_sys::XLogReaderState>();
+        reader.decoded_record = &mut record;
+        reader.main_data = data.as_mut_ptr() as *mut std::os::raw::c_char;
+        reader.main_data_len = data.len() as u32;
+        f(&mut reader)
+    }
+
+    #[pg_test]
+    fn test_rmgr_callbacks() {
+        let callbacks = RmgrCallbacks::of::<TestRmgr>();
+        unsafe {
+            with_test_record(&mut b"hello".to_vec(), |reader| {
+                (callbacks.redo)(reader);
+                assert_eq!(REDONE, b"hello");
+
+                let buf = StringInfo::new();
+                let ptr: pg_sys::StringInfo = buf.into();
+                (callbacks.desc)(ptr, reader);
+                let desc = StringInfo::from_pg(ptr).expect("StringInfo is NULL");
+                assert_eq!(desc.to_string(), "5 bytes from xid 42");
+
+                let name = std::ffi::CStr::from_ptr((callbacks.identify)(0x10));
+                assert_eq!(name.to_str(), Ok("APPEND"));
+                assert!((callbacks.identify)(0x20).is_null());
+            });
+        }
+    }
+
+    #[pg_test(error = "empty test_rmgr record")]
+    fn test_rmgr_redo_panics() {
+        let callbacks = RmgrCallbacks::of::<TestRmgr>();
+        unsafe { with_test_record(&mut [], |reader| (callbacks.redo)(reader)) }
+    }
+
+    #[pg_test]
+    fn test_register_custom_rmgr() {
+        // a no-op until pgx supports Postgres 15
+        assert!(!register_custom_rmgr::<TestRmgr>(RM_MIN_CUSTOM_ID));
+    }
+
+    #[pg_test(
+        error = "cannot register WAL resource manager \"test_rmgr\" with id 10: custom resource manager ids start at 128"
+    )]
+    fn test_register_custom_rmgr_builtin_id() {
+        register_custom_rmgr::<TestRmgr>(10);
+    }
+}
@@ -163,6 +163,10 @@ pub fn get_target_dir() -> PathBuf {
 }
 
 pub fn get_pg_config(major_version: u16) -> Option<String> {
+    if let Ok(pg_config) = std::env::var(pg_config_override_var(major_version)) {
+        return Some(pg_config);
+    }
+
     let paths = load_pgx_config();
     match major_version {
         10 => Some(paths.pg10),
@@ -172,6 +176,39 @@ pub fn get_pg_config(major_version: u16) -> Option<String> {
     }
 }
 
+/// Use `pg_config` instead of the `pg_config` that `cargo pgx init` configured for
+/// `major_version`, both in this process and in any it spawns, such as `cargo build`.
+///
+/// It's an error for `pg_config` to be for a different major version, or to not report the
+/// directories we need to build and install extensions.
+pub fn override_pg_config(major_version: u16, pg_config: &str) {
+    let pg_config = Some(pg_config.to_string());
+    let found_version = get_pg_config_major_version(&pg_config);
+    if found_version != major_version {
+        exit_with_error!(
+            "`{}` is for Postgres {}, which conflicts with the `pg{}` feature.  Either use the `pg_config` for Postgres {}, or choose `pg{}` instead",
+            pg_config.unwrap(),
+            found_version,
+            major_version,
+            major_version,
+            found_version
+        )
+    }
+
+    for arg in &["--includedir-server", "--libdir", "--pkglibdir"] {
+        if run_pg_config(&pg_config, arg).is_empty() {
+            exit_with_error!("`{}` did not report its {}", pg_config.unwrap(), arg)
+        }
+    }
+
+    std::env::set_var(pg_config_override_var(major_version), pg_config.unwrap());
+}
+
+/// The environment variable that overrides the configured `pg_config` for `major_version`
+pub fn pg_config_override_var(major_version: u16) -> String {
+    format!("PGX_PG{}_CONFIG", major_version)
+}
+
 pub fn get_pg_config_major_version(pg_config: &Option<String>) -> u16 {
     let version_string = run_pg_config(&pg_config, "--version");
     let version_parts = version_string.split_whitespace().collect::<Vec<&str>>();
@@ -313,7 +350,10 @@ pub enum ExternArgs {
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
+    Transactional,
     Error(String),
+    Sql(String),
+    Requires(Vec<String>),
 }
 
 #[derive(Debug)]
@@ -321,6 +361,7 @@ pub enum CategorizedType {
     Iterator(Vec<String>),
     OptionalIterator(Vec<String>),
     Tuple(Vec<String>),
+    Record(Vec<String>),
     Default,
 }
 
@@ -346,6 +387,7 @@ pub fn parse_extern_attributes(attr: TokenStream2) -> HashSet<ExternArgs> {
                     "parallel_safe" => args.insert(ExternArgs::ParallelSafe),
                     "parallel_unsafe" => args.insert(ExternArgs::ParallelUnsafe),
                     "parallel_restricted" => args.insert(ExternArgs::ParallelRestricted),
+                    "transactional" => args.insert(ExternArgs::Transactional),
                     "error" => {
                         let _punc = itr.next().unwrap();
                         let literal = itr.next().unwrap();
@@ -356,6 +398,21 @@ pub fn parse_extern_attributes(attr: TokenStream2) -> HashSet<ExternArgs> {
                         let message = message[1..message.len() - 1].to_string();
                         args.insert(ExternArgs::Error(message.to_string()))
                     }
+                    "sql" => {
+                        let _punc = itr.next().unwrap();
+                        let literal = itr.next().unwrap();
+                        args.insert(ExternArgs::Sql(unquote_string_literal(
+                            &literal.to_string(),
+                        )))
+                    }
+                    "requires" => {
+                        let _punc = itr.next().unwrap();
+                        match itr.next() {
+                            Some(TokenTree::Group(group)) => args
+                                .insert(ExternArgs::Requires(parse_requires_list(group.stream()))),
+                            _ => panic!("`requires` must be a list"),
+                        }
+                    }
                     _ => false,
                 };
             }
@@ -366,6 +423,156 @@ pub fn parse_extern_attributes(attr: TokenStream2) -> HashSet<ExternArgs> {
     args
 }
 
+/// Parse the contents of a `requires = [...]` list into the names of the required items
+///
+/// Entries can be identifiers, paths (of which only the final identifier is kept), or string
+/// literals.
+pub fn parse_requires_list(tokens: TokenStream2) -> Vec<String> {
+    let mut requires = Vec::new();
+    let mut current = None;
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => current = Some(ident.to_string()),
+            TokenTree::Literal(literal) => {
+                current = Some(unquote_string_literal(&literal.to_string()))
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ',' => requires.extend(current.take()),
+            _ => {}
+        }
+    }
+    requires.extend(current.take());
+    requires
+}
+
+/// Convert the source text of a (possibly raw) string literal into the string it represents
+pub fn unquote_string_literal(literal: &str) -> String {
+    if literal.starts_with('r') {
+        // raw strings don't have escapes, so just remove the `r`, `#`s, and quotes
+        let hashes = literal[1..].chars().take_while(|c| *c == '#').count();
+        literal[1 + hashes + 1..literal.len() - hashes - 1].to_string()
+    } else {
+        let literal = unescape::unescape(literal).expect("failed to unescape");
+
+        // trim leading/trailing quotes around the literal
+        literal[1..literal.len() - 1].to_string()
+    }
+}
+
+/// Ensure that at most one volatility marker and at most one parallel-safety marker were given
+pub fn validate_extern_attributes(args: &HashSet<ExternArgs>) -> Result<(), String> {
+    let volatility = [
+        ExternArgs::Immutable,
+        ExternArgs::Stable,
+        ExternArgs::Volatile,
+    ];
+    let parallel = [
+        ExternArgs::ParallelSafe,
+        ExternArgs::ParallelRestricted,
+        ExternArgs::ParallelUnsafe,
+    ];
+
+    if volatility.iter().filter(|v| args.contains(v)).count() > 1 {
+        return Err("accepts only one of `immutable`, `stable`, or `volatile`".to_string());
+    } else if parallel.iter().filter(|v| args.contains(v)).count() > 1 {
+        return Err(
+            "accepts only one of `parallel_safe`, `parallel_restricted`, or `parallel_unsafe`"
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Postgres requires that every argument after one with a `default!()` value also has one, so
+/// check that here rather than let `CREATE FUNCTION` fail when the extension is installed
+pub fn validate_default_args(func: &ItemFn) -> Result<(), String> {
+    let mut defaulted_arg = None;
+
+    for input in &func.sig.inputs {
+        let (pat, ty) = match input {
+            syn::FnArg::Typed(pat_type) => (&pat_type.pat, &pat_type.ty),
+            syn::FnArg::Receiver(_) => continue,
+        };
+        let name = quote! {#pat}.to_string();
+        let type_string = quote! {#ty}.to_string();
+
+        // these aren't SQL arguments
+        if type_string == "pg_sys :: FunctionCallInfo"
+            || type_string == "FcInfo"
+            || type_string == "pgx :: FcInfo"
+        {
+            continue;
+        }
+
+        if type_string.starts_with("default !") || type_string.starts_with("Option < default !") {
+            defaulted_arg = Some(name);
+        } else if let Some(defaulted_arg) = &defaulted_arg {
+            return Err(format!(
+                "argument `{}` must have a `default!()` value because it follows `{}`, which has one",
+                name, defaulted_arg
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The `#[pgx(...)]` attributes of a `#[composite]` type's field
+#[derive(Debug, Default, PartialEq)]
+pub struct CompositeFieldArgs {
+    /// `sql_type = "..."`: the SQL type of the attribute, used instead of the one that the
+    /// field's Rust type translates to
+    pub sql_type: Option<String>,
+
+    /// `not_null`: the field can never be NULL, which is checked when it's converted to and
+    /// from a Datum, as Postgres doesn't allow constraints on a composite type's attributes
+    pub not_null: bool,
+}
+
+pub fn parse_composite_field_args(field: &syn::Field) -> Result<CompositeFieldArgs, String> {
+    let mut args = CompositeFieldArgs::default();
+
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("pgx")) {
+        let list = match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list,
+            _ => return Err("expected `#[pgx(...)]`".to_string()),
+        };
+
+        for nested in list.nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("not_null") => {
+                    args.not_null = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(sql_type),
+                    ..
+                })) if path.is_ident("sql_type") => args.sql_type = Some(sql_type.value()),
+                other => {
+                    return Err(format!(
+                        "unrecognized #[pgx] field attribute: {}",
+                        quote! {#other}
+                    ))
+                }
+            }
+        }
+    }
+
+    let is_option = match &field.ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Option"),
+        _ => false,
+    };
+    if args.not_null && is_option {
+        return Err("an `Option<T>` field can't be `not_null`".to_string());
+    }
+
+    Ok(args)
+}
+
 pub fn categorize_return_type(func: &ItemFn) -> CategorizedType {
     let rt = &func.sig.output;
 
@@ -390,6 +597,9 @@ pub fn categorize_type(ty: &Type) -> CategorizedType {
                                     CategorizedType::Iterator(i) => {
                                         CategorizedType::OptionalIterator(i)
                                     }
+                                    CategorizedType::Record(_) => {
+                                        panic!("Option<RecordType<...>> is not supported")
+                                    }
 
                                     _ => result,
                                 };
@@ -402,6 +612,20 @@ pub fn categorize_type(ty: &Type) -> CategorizedType {
                             break;
                         }
                     }
+                } else if segment.ident.to_string() == "RecordType" {
+                    match &segment.arguments {
+                        PathArguments::AngleBracketed(a) => match a.args.first().unwrap() {
+                            GenericArgument::Type(Type::Tuple(tuple)) => {
+                                let mut types = Vec::new();
+                                for ty in &tuple.elems {
+                                    types.push(quote! {#ty}.to_string())
+                                }
+                                return CategorizedType::Record(types);
+                            }
+                            _ => panic!("RecordType<T> must wrap a tuple"),
+                        },
+                        _ => panic!("RecordType must have a generic type"),
+                    }
                 }
             }
             CategorizedType::Default
@@ -487,7 +711,11 @@ pub fn categorize_type(ty: &Type) -> CategorizedType {
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse_extern_attributes, ExternArgs};
+    use crate::{
+        get_pg_config, parse_composite_field_args, parse_extern_attributes, pg_config_override_var,
+        unquote_string_literal, validate_default_args, validate_extern_attributes,
+        CompositeFieldArgs, ExternArgs,
+    };
     use std::str::FromStr;
     use syn::export::TokenStream2;
 
@@ -501,4 +729,110 @@ mod tests {
             "syntax error at or near \"THIS\"".to_string()
         )));
     }
+
+    #[test]
+    fn parse_volatility_and_parallel_args() {
+        let ts = TokenStream2::from_str("immutable, parallel_safe").unwrap();
+
+        let args = parse_extern_attributes(ts);
+        assert!(args.contains(&ExternArgs::Immutable));
+        assert!(args.contains(&ExternArgs::ParallelSafe));
+        assert!(validate_extern_attributes(&args).is_ok());
+    }
+
+    #[test]
+    fn conflicting_volatility_args() {
+        let ts = TokenStream2::from_str("immutable, stable").unwrap();
+        assert!(validate_extern_attributes(&parse_extern_attributes(ts)).is_err());
+
+        let ts = TokenStream2::from_str("parallel_safe, parallel_unsafe").unwrap();
+        assert!(validate_extern_attributes(&parse_extern_attributes(ts)).is_err());
+    }
+
+    #[test]
+    fn parse_sql_arg() {
+        let ts = TokenStream2::from_str("immutable, sql = \"SELECT 1;\"").unwrap();
+
+        let args = parse_extern_attributes(ts);
+        assert!(args.contains(&ExternArgs::Immutable));
+        assert!(args.contains(&ExternArgs::Sql("SELECT 1;".to_string())));
+    }
+
+    #[test]
+    fn parse_requires_arg() {
+        let ts = TokenStream2::from_str("immutable, requires = [a, my_mod::b, \"c\"]").unwrap();
+
+        let args = parse_extern_attributes(ts);
+        assert!(args.contains(&ExternArgs::Immutable));
+        assert!(args.contains(&ExternArgs::Requires(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string()
+        ])));
+        assert_eq!(args.len(), 2);
+    }
+
+    #[test]
+    fn default_args_must_trail() {
+        let func: syn::ItemFn = syn::parse_str(
+            "fn f(fcinfo: pg_sys::FunctionCallInfo, a: i32, b: default!(i32, 10), c: Option<default!(&str, \"NULL\")>) {}",
+        )
+        .unwrap();
+        assert!(validate_default_args(&func).is_ok());
+
+        let func: syn::ItemFn = syn::parse_str("fn f(a: default!(i32, 10), b: i32) {}").unwrap();
+        assert_eq!(
+            validate_default_args(&func),
+            Err(
+                "argument `b` must have a `default!()` value because it follows `a`, which has one"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn composite_field_args() {
+        let fields: syn::FieldsNamed = syn::parse_str(
+            "{ a: i32, #[pgx(sql_type = \"numeric(10,2)\", not_null)] b: Numeric, #[pgx(not_null)] c: Option<i32>, #[pgx(nullable)] d: i32 }",
+        )
+        .unwrap();
+        let fields = fields.named.iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_composite_field_args(fields[0]),
+            Ok(CompositeFieldArgs::default())
+        );
+        assert_eq!(
+            parse_composite_field_args(fields[1]),
+            Ok(CompositeFieldArgs {
+                sql_type: Some("numeric(10,2)".to_string()),
+                not_null: true,
+            })
+        );
+        assert_eq!(
+            parse_composite_field_args(fields[2]),
+            Err("an `Option<T>` field can't be `not_null`".to_string())
+        );
+        assert_eq!(
+            parse_composite_field_args(fields[3]),
+            Err("unrecognized #[pgx] field attribute: nullable".to_string())
+        );
+    }
+
+    #[test]
+    fn unquote_literals() {
+        assert_eq!(unquote_string_literal("\"a \\\"b\\\"\""), "a \"b\"");
+        assert_eq!(unquote_string_literal("r\"a \\b\""), "a \\b");
+        assert_eq!(unquote_string_literal("r#\"a \"b\"\"#"), "a \"b\"");
+    }
+
+    #[test]
+    fn pg_config_override() {
+        std::env::set_var(pg_config_override_var(11), "/opt/pg11/bin/pg_config");
+        assert_eq!(
+            get_pg_config(11),
+            Some("/opt/pg11/bin/pg_config".to_string())
+        );
+        std::env::remove_var(pg_config_override_var(11));
+    }
 }
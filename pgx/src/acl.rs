@@ -0,0 +1,220 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Privilege and role membership checks, for functions (such as `SECURITY DEFINER` ones) that
+//! need to do their own authorization, and a guard for running code as another user
+use crate::{pg_sys, PgSqlErrorCode, SqlStateError};
+use std::fmt;
+
+bitflags! {
+    /// The privileges that can be granted on an object, mirroring the `ACL_xxx` flags from
+    /// parsenodes.h
+    pub struct AclMode: u32 {
+        const INSERT      = pg_sys::ACL_INSERT;
+        const SELECT      = pg_sys::ACL_SELECT;
+        const UPDATE      = pg_sys::ACL_UPDATE;
+        const DELETE      = pg_sys::ACL_DELETE;
+        const TRUNCATE    = pg_sys::ACL_TRUNCATE;
+        const REFERENCES  = pg_sys::ACL_REFERENCES;
+        const TRIGGER     = pg_sys::ACL_TRIGGER;
+        const EXECUTE     = pg_sys::ACL_EXECUTE;
+        const USAGE       = pg_sys::ACL_USAGE;
+        const CREATE      = pg_sys::ACL_CREATE;
+        const CREATE_TEMP = pg_sys::ACL_CREATE_TEMP;
+        const CONNECT     = pg_sys::ACL_CONNECT;
+    }
+}
+
+/// Why a privilege check failed, as reported by Postgres' `pg_xxx_aclcheck()` functions.
+///
+/// Returning it as the `Err` of a `#[pg_extern]` function's `Result` raises an `ERROR` with
+/// `ERRCODE_INSUFFICIENT_PRIVILEGE`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AclError {
+    /// The role holds none of the requested privileges
+    NoPrivilege,
+
+    /// The role doesn't own the object
+    NotOwner,
+}
+
+impl fmt::Display for AclError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AclError::NoPrivilege => f.write_str("permission denied"),
+            AclError::NotOwner => f.write_str("must be owner"),
+        }
+    }
+}
+
+impl SqlStateError for AclError {
+    fn sqlstate(&self) -> PgSqlErrorCode {
+        PgSqlErrorCode::ERRCODE_INSUFFICIENT_PRIVILEGE
+    }
+}
+
+fn acl_result(result: pg_sys::AclResult) -> Result<(), AclError> {
+    match result {
+        pg_sys::AclResult_ACLCHECK_OK => Ok(()),
+        pg_sys::AclResult_ACLCHECK_NO_PRIV => Err(AclError::NoPrivilege),
+        pg_sys::AclResult_ACLCHECK_NOT_OWNER => Err(AclError::NotOwner),
+        unknown => panic!("Unrecognized AclResult: {}", unknown),
+    }
+}
+
+/// Check that the current user holds at least one of the privileges in `mode` on the relation
+/// `relid`, like `has_table_privilege()` does, but without raising an `ERROR` when they don't.
+///
+/// Within a `SECURITY DEFINER` function the current user is the function's owner.  Use
+/// `check_relation_privilege_as()` with `pg_sys::GetSessionUserId()` to check the role that
+/// called it instead.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use pgx::acl::{AclError, AclMode};
+///
+/// #[pg_extern]
+/// fn row_count(relation: PgRelation) -> Result<i64, AclError> {
+///     acl::check_relation_privilege(relation.oid(), AclMode::SELECT)?;
+///     let query = format!("SELECT count(*) FROM {}", relation.name());
+///     Ok(Spi::get_one(&query).unwrap_or_default())
+/// }
+/// ```
+///
+/// ## Panics
+///
+/// Postgres raises an `ERROR` if the relation doesn't exist
+pub fn check_relation_privilege(relid: pg_sys::Oid, mode: AclMode) -> Result<(), AclError> {
+    check_relation_privilege_as(relid, unsafe { pg_sys::GetUserId() }, mode)
+}
+
+/// Like `check_relation_privilege()`, but checks the privileges of the role `roleid`
+pub fn check_relation_privilege_as(
+    relid: pg_sys::Oid,
+    roleid: pg_sys::Oid,
+    mode: AclMode,
+) -> Result<(), AclError> {
+    acl_result(unsafe { pg_sys::pg_class_aclcheck(relid, roleid, mode.bits()) })
+}
+
+/// Check that the current user holds at least one of the privileges in `mode` on the schema
+/// `nspid`.  See `check_relation_privilege()` for details.
+pub fn check_namespace_privilege(nspid: pg_sys::Oid, mode: AclMode) -> Result<(), AclError> {
+    acl_result(unsafe { pg_sys::pg_namespace_aclcheck(nspid, pg_sys::GetUserId(), mode.bits()) })
+}
+
+/// Check that the current user holds at least one of the privileges in `mode` on the function
+/// `funcid`.  See `check_relation_privilege()` for details.
+pub fn check_function_privilege(funcid: pg_sys::Oid, mode: AclMode) -> Result<(), AclError> {
+    acl_result(unsafe { pg_sys::pg_proc_aclcheck(funcid, pg_sys::GetUserId(), mode.bits()) })
+}
+
+/// Is `member` a member of `role`, directly or indirectly, like `pg_has_role(member, role,
+/// 'MEMBER')`?  Superusers are members of every role.
+pub fn has_role_membership(role: pg_sys::Oid, member: pg_sys::Oid) -> bool {
+    unsafe { pg_sys::is_member_of_role(member, role) }
+}
+
+/// Does `member` have the privileges of `role`, like `pg_has_role(member, role, 'USAGE')`?
+///
+/// Unlike `has_role_membership()`, this is false when the membership is only through roles
+/// that don't `INHERIT` their privileges.
+pub fn has_privileges_of_role(role: pg_sys::Oid, member: pg_sys::Oid) -> bool {
+    unsafe { pg_sys::has_privs_of_role(member, role) }
+}
+
+bitflags! {
+    /// The flags of a security context, mirroring the `SECURITY_xxx` flags from miscadmin.h
+    pub struct SecurityFlags: i32 {
+        /// The user id was changed locally, as by a `SECURITY DEFINER` function, so `SET ROLE`
+        /// and `SET SESSION AUTHORIZATION` are refused
+        const LOCAL_USERID_CHANGE  = pg_sys::SECURITY_LOCAL_USERID_CHANGE as i32;
+
+        /// Operations that untrusted code could abuse, such as creating temporary tables, are
+        /// refused
+        const RESTRICTED_OPERATION = pg_sys::SECURITY_RESTRICTED_OPERATION as i32;
+
+        /// Row-level security isn't applied to a table's owner, even when the table is set to
+        /// `FORCE ROW LEVEL SECURITY`
+        const NOFORCE_RLS          = pg_sys::SECURITY_NOFORCE_RLS as i32;
+    }
+}
+
+/// The current user id and security context flags, from `GetUserIdAndSecContext()`
+pub fn current_security_context() -> (pg_sys::Oid, SecurityFlags) {
+    let mut userid = pg_sys::InvalidOid;
+    let mut sec_context = 0;
+    unsafe { pg_sys::GetUserIdAndSecContext(&mut userid, &mut sec_context) };
+    (userid, SecurityFlags::from_bits_truncate(sec_context))
+}
+
+/// Switches the current user id and security context flags, as Postgres does to run
+/// maintenance such as `VACUUM` as a table's owner, and restores the previous ones when it's
+/// dropped, including while unwinding from a panic.  If the transaction aborts instead,
+/// Postgres restores them itself.
+///
+/// **This is security-sensitive.**  Code run while the guard is in scope has every privilege of
+/// the new user, and if that's a superuser, it bypasses all permission checks and row-level
+/// security.  Keep the scope as small as possible, don't run SQL that users control within it,
+/// and add `SecurityFlags::RESTRICTED_OPERATION` whenever anything it does could run
+/// user-defined code, such as index expressions or triggers.
+///
+/// Guards must be dropped in the reverse order they were created, which scoping ensures.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use pgx::acl::{SecurityContext, SecurityFlags};
+///
+/// #[pg_extern]
+/// fn purge_audit_log() {
+///     let owner = Spi::get_one::<pg_sys::Oid>(
+///         "SELECT relowner FROM pg_class WHERE oid = 'audit_log'::regclass",
+///     )
+///     .expect("relowner was NULL");
+///
+///     let _context = unsafe {
+///         SecurityContext::switch_to(
+///             owner,
+///             SecurityFlags::LOCAL_USERID_CHANGE | SecurityFlags::RESTRICTED_OPERATION,
+///         )
+///     };
+///     Spi::run("DELETE FROM audit_log WHERE logged_at < now() - interval '1 year'");
+/// }
+/// ```
+#[must_use = "the previous security context is restored as soon as the guard is dropped"]
+pub struct SecurityContext {
+    saved_userid: pg_sys::Oid,
+    saved_sec_context: i32,
+}
+
+impl SecurityContext {
+    /// Switch to `userid`, adding `flags` to the current security context's flags
+    ///
+    /// ## Safety
+    ///
+    /// This bypasses Postgres' privilege checks for everything done until the guard is
+    /// dropped, so the caller must ensure that nothing done within its scope can be influenced
+    /// by a user who doesn't already hold `userid`'s privileges.  `userid` must be the Oid of
+    /// an existing role.
+    pub unsafe fn switch_to(userid: pg_sys::Oid, flags: SecurityFlags) -> Self {
+        let mut saved_userid = pg_sys::InvalidOid;
+        let mut saved_sec_context = 0;
+        pg_sys::GetUserIdAndSecContext(&mut saved_userid, &mut saved_sec_context);
+        pg_sys::SetUserIdAndSecContext(userid, saved_sec_context | flags.bits());
+
+        SecurityContext {
+            saved_userid,
+            saved_sec_context,
+        }
+    }
+}
+
+impl Drop for SecurityContext {
+    fn drop(&mut self) {
+        unsafe { pg_sys::SetUserIdAndSecContext(self.saved_userid, self.saved_sec_context) }
+    }
+}
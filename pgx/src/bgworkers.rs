@@ -46,6 +46,7 @@ bitflags! {
 }
 
 /// The various points in which a BackgroundWorker can be started by Postgres
+#[derive(Copy, Clone)]
 pub enum BgWorkerStartTime {
     PostmasterStart = pg_sys::BgWorkerStartTime_BgWorkerStart_PostmasterStart as isize,
     ConsistentState = pg_sys::BgWorkerStartTime_BgWorkerStart_ConsistentState as isize,
@@ -128,10 +129,6 @@ impl BackgroundWorker {
         let user: *const i8 = user.as_ref().map_or(std::ptr::null(), |i| i.as_ptr());
 
         unsafe {
-            #[cfg(feature = "pg10")]
-            pg_sys::BackgroundWorkerInitializeConnection(db as *mut i8, user as *mut i8);
-
-            #[cfg(any(feature = "pg11", feature = "pg12"))]
             pg_sys::BackgroundWorkerInitializeConnection(db, user, 0);
         };
     }
@@ -377,8 +374,64 @@ impl BackgroundWorkerBuilder {
     /// Once properly configured, call `load()` to get the BackgroundWorker registered and
     /// started at the proper time by Postgres.
     pub fn load(self: Self) {
+        let mut bgw = self.to_bgw();
+
+        unsafe {
+            pg_sys::RegisterBackgroundWorker(&mut bgw);
+            if self.bgw_flags.contains(BGWflags::BGWORKER_SHMEM_ACCESS)
+                && self.shared_memory_startup_fn.is_some()
+            {
+                PREV_SHMEM_STARTUP_HOOK = pg_sys::shmem_startup_hook;
+                pg_sys::shmem_startup_hook = self.shared_memory_startup_fn;
+            }
+        };
+    }
+
+    /// Register and start this BackgroundWorker from within a running backend, rather than
+    /// from `_PG_init()`.
+    ///
+    /// If no notify pid has been set, the current backend's pid (`pg_sys::MyProcPid`) is used
+    /// so that the returned `DynamicBackgroundWorker` can wait on the worker's startup and
+    /// shutdown.
+    ///
+    /// Returns `None` if Postgres has no free background worker slots.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use pgx::bgworkers::BackgroundWorkerBuilder;
+    /// use pgx::IntoDatum;
+    ///
+    /// let worker = BackgroundWorkerBuilder::new("My Dynamic BGWorker")
+    ///     .set_function("background_worker_main")
+    ///     .set_library("example")
+    ///     .set_argument(42i32.into_datum())
+    ///     .spawn_dynamic()
+    ///     .expect("no background worker slots available");
+    ///
+    /// let pid = worker.wait_for_startup().expect("worker failed to start");
+    /// ```
+    pub fn spawn_dynamic(mut self: Self) -> Option<DynamicBackgroundWorker> {
+        if self.bgw_notify_pid == 0 {
+            self.bgw_notify_pid = unsafe { pg_sys::MyProcPid };
+        }
+
+        let mut bgw = self.to_bgw();
+        let mut handle = std::ptr::null_mut();
+
+        if unsafe { pg_sys::RegisterDynamicBackgroundWorker(&mut bgw, &mut handle) } {
+            Some(DynamicBackgroundWorker {
+                handle,
+                notify_pid: self.bgw_notify_pid,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn to_bgw(&self) -> pg_sys::BackgroundWorker {
         #[cfg(feature = "pg10")]
-        let mut bgw = pg_sys::BackgroundWorker {
+        let bgw = pg_sys::BackgroundWorker {
             bgw_name: RpgffiChar::from(&self.bgw_name[..]).0,
             bgw_flags: self.bgw_flags.bits(),
             bgw_start_time: self.bgw_start_time as u32,
@@ -394,7 +447,7 @@ impl BackgroundWorkerBuilder {
         };
 
         #[cfg(any(feature = "pg11", feature = "pg12"))]
-        let mut bgw = pg_sys::BackgroundWorker {
+        let bgw = pg_sys::BackgroundWorker {
             bgw_name: RpgffiChar::from(&self.bgw_name[..]).0,
             bgw_type: RpgffiChar::from(&self.bgw_type[..]).0,
             bgw_flags: self.bgw_flags.bits(),
@@ -410,15 +463,84 @@ impl BackgroundWorkerBuilder {
             bgw_notify_pid: self.bgw_notify_pid,
         };
 
-        unsafe {
-            pg_sys::RegisterBackgroundWorker(&mut bgw);
-            if self.bgw_flags.contains(BGWflags::BGWORKER_SHMEM_ACCESS)
-                && self.shared_memory_startup_fn.is_some()
-            {
-                PREV_SHMEM_STARTUP_HOOK = pg_sys::shmem_startup_hook;
-                pg_sys::shmem_startup_hook = self.shared_memory_startup_fn;
-            }
-        };
+        bgw
+    }
+}
+
+/// The state of a dynamically started BackgroundWorker, as reported by Postgres
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BackgroundWorkerStatus {
+    Started,
+    NotYetStarted,
+    Stopped,
+    PostmasterDied,
+}
+
+impl From<pg_sys::BgwHandleStatus> for BackgroundWorkerStatus {
+    fn from(status: pg_sys::BgwHandleStatus) -> Self {
+        match status {
+            pg_sys::BgwHandleStatus_BGWH_STARTED => BackgroundWorkerStatus::Started,
+            pg_sys::BgwHandleStatus_BGWH_NOT_YET_STARTED => BackgroundWorkerStatus::NotYetStarted,
+            pg_sys::BgwHandleStatus_BGWH_STOPPED => BackgroundWorkerStatus::Stopped,
+            pg_sys::BgwHandleStatus_BGWH_POSTMASTER_DIED => BackgroundWorkerStatus::PostmasterDied,
+            _ => panic!("unrecognized BgwHandleStatus: {}", status),
+        }
+    }
+}
+
+/// A handle to a BackgroundWorker started with `BackgroundWorkerBuilder::spawn_dynamic()`
+pub struct DynamicBackgroundWorker {
+    handle: *mut pg_sys::BackgroundWorkerHandle,
+    notify_pid: pg_sys::pid_t,
+}
+
+impl DynamicBackgroundWorker {
+    /// The worker's current pid, or its status if it isn't currently running
+    pub fn pid(&self) -> Result<pg_sys::pid_t, BackgroundWorkerStatus> {
+        let mut pid = 0;
+        match unsafe { pg_sys::GetBackgroundWorkerPid(self.handle, &mut pid) }.into() {
+            BackgroundWorkerStatus::Started => Ok(pid),
+            status => Err(status),
+        }
+    }
+
+    /// Block until Postgres has started the worker, returning its pid
+    ///
+    /// ## Panics
+    ///
+    /// This function will panic if the current backend isn't the worker's notify pid, as it
+    /// would never be signaled.
+    pub fn wait_for_startup(&self) -> Result<pg_sys::pid_t, BackgroundWorkerStatus> {
+        self.assert_notify_pid();
+
+        let mut pid = 0;
+        match unsafe { pg_sys::WaitForBackgroundWorkerStartup(self.handle, &mut pid) }.into() {
+            BackgroundWorkerStatus::Started => Ok(pid),
+            status => Err(status),
+        }
+    }
+
+    /// Block until the worker has exited
+    ///
+    /// ## Panics
+    ///
+    /// This function will panic if the current backend isn't the worker's notify pid, as it
+    /// would never be signaled.
+    pub fn wait_for_shutdown(self) -> BackgroundWorkerStatus {
+        self.assert_notify_pid();
+
+        unsafe { pg_sys::WaitForBackgroundWorkerShutdown(self.handle) }.into()
+    }
+
+    /// Ask Postgres to terminate the worker
+    pub fn terminate(&self) {
+        unsafe { pg_sys::TerminateBackgroundWorker(self.handle) }
+    }
+
+    fn assert_notify_pid(&self) {
+        if self.notify_pid != unsafe { pg_sys::MyProcPid } {
+            panic!("only the background worker's notify pid can wait on it");
+        }
     }
 }
 
@@ -3,8 +3,8 @@
 
 //! Provides safe wrappers around Postgres' "Transaction" and "Sub Transaction" hook system
 
-use crate::pg_sys;
-use std::cell::RefCell;
+use crate::{pg_guard, pg_sys, void_mut_ptr};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -370,3 +370,185 @@ where
 
     SubXactCallbackReceipt(wrapped_func)
 }
+
+/// Registering a callback for the life of the backend returns a `BackendCallbackReceipt` that can
+/// be used to unregister it, in this or any later transaction
+pub struct BackendCallbackReceipt(Rc<Cell<bool>>);
+
+impl BackendCallbackReceipt {
+    /// Consumes this `BackendCallbackReceipt` and unregisters the callback it represents, which
+    /// won't be called again
+    pub fn unregister_callback(self) {
+        self.0.set(false);
+    }
+}
+
+/// A callback registered for the life of the backend
+pub(crate) struct BackendHook<F: ?Sized> {
+    registered: Rc<Cell<bool>>,
+    func: RefCell<Box<F>>,
+}
+
+impl<F: ?Sized> BackendHook<F> {
+    pub(crate) fn new(func: Box<F>) -> (Rc<Self>, BackendCallbackReceipt) {
+        let registered = Rc::new(Cell::new(true));
+        let hook = Rc::new(BackendHook {
+            registered: Rc::clone(&registered),
+            func: RefCell::new(func),
+        });
+        (hook, BackendCallbackReceipt(registered))
+    }
+
+    pub(crate) fn call(&self, f: impl FnOnce(&mut F)) {
+        if self.registered.get() {
+            // a callback whose work fires the same event again isn't called recursively
+            if let Ok(mut func) = self.func.try_borrow_mut() {
+                f(&mut func)
+            }
+        }
+    }
+}
+
+/// Drop unregistered hooks from `hooks` and return the rest, as a copy, so that the callbacks
+/// are free to register (or unregister) others
+pub(crate) fn registered_hooks<F: ?Sized>(
+    hooks: Option<&mut Vec<Rc<BackendHook<F>>>>,
+) -> Vec<Rc<BackendHook<F>>> {
+    match hooks {
+        Some(hooks) => {
+            hooks.retain(|hook| hook.registered.get());
+            hooks.clone()
+        }
+        None => Vec::new(),
+    }
+}
+
+type BackendXactHooks = HashMap<PgXactCallbackEvent, Vec<Rc<BackendHook<dyn FnMut()>>>>;
+type BackendSubXactHooks = HashMap<
+    PgSubXactCallbackEvent,
+    Vec<Rc<BackendHook<dyn FnMut(pg_sys::SubTransactionId, pg_sys::SubTransactionId)>>>,
+>;
+
+static mut BACKEND_XACT_HOOKS: Option<BackendXactHooks> = None;
+static mut BACKEND_SUBXACT_HOOKS: Option<BackendSubXactHooks> = None;
+
+/// Register a closure to be called every time one of the `PgXactCallbackEvent` events fires, in
+/// every transaction, for the life of the backend, just like `pg_sys::RegisterXactCallback()`.
+/// This is usually done in `_PG_init()`.
+///
+/// Unlike `register_xact_callback()`, which runs its closure once, in the current transaction,
+/// the closure can be called any number of times.  Closures are called in the order in which they
+/// were registered, under `#[pg_guard]`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+/// use std::rc::Rc;
+///
+/// let cache = Rc::new(RefCell::new(HashMap::<i32, String>::new()));
+/// let for_abort = Rc::clone(&cache);
+///
+/// // entries cached during an aborted transaction might not be valid
+/// register_backend_xact_callback(PgXactCallbackEvent::Abort, move || {
+///     for_abort.borrow_mut().clear()
+/// });
+/// ```
+///
+/// ## Safety
+///
+/// As with `register_xact_callback()`, a Rust `panic!()` or Postgres `ereport(ERROR)` while
+/// handling a `PgXactCallbackEvent::Commit` or `PgXactCallbackEvent::Abort` event will cause the
+/// Postgres backend to abort.
+pub fn register_backend_xact_callback<F>(
+    which_event: PgXactCallbackEvent,
+    f: F,
+) -> BackendCallbackReceipt
+where
+    F: FnMut() + 'static,
+{
+    #[pg_guard]
+    unsafe extern "C" fn pgx_backend_xact_callback(event: pg_sys::XactEvent, _arg: void_mut_ptr) {
+        let which_event = PgXactCallbackEvent::translate_pg_event(event);
+        let hooks = registered_hooks(
+            BACKEND_XACT_HOOKS
+                .as_mut()
+                .and_then(|hooks| hooks.get_mut(&which_event)),
+        );
+
+        for hook in hooks {
+            hook.call(|func| func());
+        }
+    }
+
+    let hooks = unsafe {
+        BACKEND_XACT_HOOKS.get_or_insert_with(|| {
+            pg_sys::RegisterXactCallback(Some(pgx_backend_xact_callback), std::ptr::null_mut());
+            HashMap::new()
+        })
+    };
+
+    let (hook, receipt) = BackendHook::new(Box::new(f) as Box<dyn FnMut()>);
+    hooks.entry(which_event).or_default().push(hook);
+    receipt
+}
+
+/// Register a closure to be called every time one of the `PgSubXactCallbackEvent` events fires,
+/// for the life of the backend, just like `pg_sys::RegisterSubXactCallback()`.
+///
+/// The closure is passed the subtransaction's id and its parent's.  See
+/// `register_backend_xact_callback()` for details.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// register_backend_subxact_callback(PgSubXactCallbackEvent::AbortSub, |my_subid, _parent_subid| {
+///     // release whatever subtransaction `my_subid` acquired
+/// });
+/// ```
+pub fn register_backend_subxact_callback<F>(
+    which_event: PgSubXactCallbackEvent,
+    f: F,
+) -> BackendCallbackReceipt
+where
+    F: FnMut(pg_sys::SubTransactionId, pg_sys::SubTransactionId) + 'static,
+{
+    #[pg_guard]
+    unsafe extern "C" fn pgx_backend_subxact_callback(
+        event: pg_sys::SubXactEvent,
+        my_subid: pg_sys::SubTransactionId,
+        parent_subid: pg_sys::SubTransactionId,
+        _arg: void_mut_ptr,
+    ) {
+        let which_event = PgSubXactCallbackEvent::translate_pg_event(event);
+        let hooks = registered_hooks(
+            BACKEND_SUBXACT_HOOKS
+                .as_mut()
+                .and_then(|hooks| hooks.get_mut(&which_event)),
+        );
+
+        for hook in hooks {
+            hook.call(|func| func(my_subid, parent_subid));
+        }
+    }
+
+    let hooks = unsafe {
+        BACKEND_SUBXACT_HOOKS.get_or_insert_with(|| {
+            pg_sys::RegisterSubXactCallback(
+                Some(pgx_backend_subxact_callback),
+                std::ptr::null_mut(),
+            );
+            HashMap::new()
+        })
+    };
+
+    let (hook, receipt) = BackendHook::new(
+        Box::new(f) as Box<dyn FnMut(pg_sys::SubTransactionId, pg_sys::SubTransactionId)>
+    );
+    hooks.entry(which_event).or_default().push(hook);
+    receipt
+}
@@ -0,0 +1,210 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Iterate over the relations in the `pg_class` system catalog without opening each one
+
+use crate::{name_data_to_str, pg_sys, void_mut_ptr};
+
+/// A `pg_class` entry, as found by `relations()` or `tables()`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CatalogRelation {
+    pub oid: pg_sys::Oid,
+    pub name: String,
+    pub namespace_oid: pg_sys::Oid,
+
+    /// The namespace's name, or `None` if it was dropped since the catalog snapshot was taken
+    pub namespace: Option<String>,
+
+    /// One of the `pg_sys::RELKIND_xxx` constants
+    pub relkind: u8,
+
+    /// One of the `pg_sys::RELPERSISTENCE_xxx` constants
+    pub relpersistence: u8,
+}
+
+impl CatalogRelation {
+    /// Is this an ordinary or partitioned table?
+    pub fn is_table(&self) -> bool {
+        self.relkind == pg_sys::RELKIND_RELATION
+            || self.relkind == pg_sys::RELKIND_PARTITIONED_TABLE
+    }
+
+    pub fn is_temp(&self) -> bool {
+        self.relpersistence == pg_sys::RELPERSISTENCE_TEMP
+    }
+}
+
+/// Scan `pg_class` for every relation in the database, with the current catalog snapshot.
+///
+/// Other sessions' temporary relations are skipped, as they can't be accessed anyways.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// for relation in pgx::catalog::relations().filter(|r| r.relkind == pg_sys::RELKIND_VIEW) {
+///     if let Some(namespace) = relation.namespace {
+///         info!("view {}.{}", namespace, relation.name);
+///     }
+/// }
+/// ```
+pub fn relations() -> CatalogRelations {
+    scan_pg_class(false)
+}
+
+/// Like `relations()`, but only returns ordinary and partitioned tables
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let public = unsafe { pg_sys::get_namespace_oid(std::ffi::CString::new("public").unwrap().as_ptr(), false) };
+/// let names = pgx::catalog::tables()
+///     .in_namespace(public)
+///     .map(|table| table.name)
+///     .collect::<Vec<_>>();
+/// ```
+pub fn tables() -> CatalogRelations {
+    scan_pg_class(true)
+}
+
+fn scan_pg_class(tables_only: bool) -> CatalogRelations {
+    unsafe {
+        let rel = pg_sys::relation_open(
+            pg_sys::RelationRelationId,
+            pg_sys::AccessShareLock as pg_sys::LOCKMODE,
+        );
+        let scan = pg_sys::systable_beginscan(
+            rel,
+            pg_sys::InvalidOid,
+            false,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+        );
+
+        CatalogRelations {
+            rel,
+            scan,
+            namespace_oid: None,
+            tables_only,
+        }
+    }
+}
+
+/// An open scan of `pg_class`, closed when this is dropped
+pub struct CatalogRelations {
+    rel: pg_sys::Relation,
+    scan: pg_sys::SysScanDesc,
+    namespace_oid: Option<pg_sys::Oid>,
+    tables_only: bool,
+}
+
+impl CatalogRelations {
+    /// Only return relations in the namespace (schema) with the specified Oid
+    pub fn in_namespace(mut self, namespace_oid: pg_sys::Oid) -> Self {
+        self.namespace_oid = Some(namespace_oid);
+        self
+    }
+
+    fn wants(&self, form: &pg_sys::FormData_pg_class) -> bool {
+        let relkind = form.relkind as u8;
+
+        if self.tables_only
+            && relkind != pg_sys::RELKIND_RELATION
+            && relkind != pg_sys::RELKIND_PARTITIONED_TABLE
+        {
+            return false;
+        }
+
+        if let Some(namespace_oid) = self.namespace_oid {
+            if form.relnamespace != namespace_oid {
+                return false;
+            }
+        }
+
+        // we can't see the contents of another backend's temp tables
+        form.relpersistence as u8 != pg_sys::RELPERSISTENCE_TEMP
+            || unsafe { pg_sys::isTempNamespace(form.relnamespace) }
+    }
+}
+
+impl Iterator for CatalogRelations {
+    type Item = CatalogRelation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let tuple = unsafe { pg_sys::systable_getnext(self.scan) };
+            if tuple.is_null() {
+                return None;
+            }
+
+            let form = unsafe { get_struct::<pg_sys::FormData_pg_class>(tuple) };
+            if !self.wants(form) {
+                continue;
+            }
+
+            return Some(CatalogRelation {
+                oid: unsafe { relation_oid(tuple, form) },
+                name: name_data_to_str(&form.relname).to_string(),
+                namespace_oid: form.relnamespace,
+                namespace: namespace_name(form.relnamespace),
+                relkind: form.relkind as u8,
+                relpersistence: form.relpersistence as u8,
+            });
+        }
+    }
+}
+
+impl Drop for CatalogRelations {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::systable_endscan(self.scan);
+            pg_sys::relation_close(self.rel, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+        }
+    }
+}
+
+/// mimics the `GETSTRUCT()` macro from htup_details.h
+unsafe fn get_struct<'a, T>(tuple: pg_sys::HeapTuple) -> &'a T {
+    let data = (*tuple).t_data;
+    &*((data as *mut std::os::raw::c_char).add((*data).t_hoff as usize) as *const T)
+}
+
+/// Before Postgres 12, a catalog row's Oid is a system column stored just before its user data.
+/// mimics the `HeapTupleGetOid()` macro from htup_details.h
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn relation_oid(tuple: pg_sys::HeapTuple, _form: &pg_sys::FormData_pg_class) -> pg_sys::Oid {
+    let data = (*tuple).t_data;
+    if (*data).t_infomask as u32 & pg_sys::HEAP_HASOID == 0 {
+        pg_sys::InvalidOid
+    } else {
+        let oid =
+            (data as *mut std::os::raw::c_char).add((*data).t_hoff as usize) as *const pg_sys::Oid;
+        oid.sub(1).read_unaligned()
+    }
+}
+
+#[cfg(feature = "pg12")]
+unsafe fn relation_oid(_tuple: pg_sys::HeapTuple, form: &pg_sys::FormData_pg_class) -> pg_sys::Oid {
+    form.oid
+}
+
+/// `get_namespace_name()` returns NULL if the namespace no longer exists
+fn namespace_name(namespace_oid: pg_sys::Oid) -> Option<String> {
+    unsafe {
+        let name = pg_sys::get_namespace_name(namespace_oid);
+        if name.is_null() {
+            return None;
+        }
+
+        let string = std::ffi::CStr::from_ptr(name)
+            .to_str()
+            .expect("namespace name is not valid UTF8")
+            .to_string();
+        pg_sys::pfree(name as void_mut_ptr);
+        Some(string)
+    }
+}
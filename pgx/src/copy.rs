@@ -0,0 +1,482 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Bulk loading and unloading of data in the formats understood by `COPY`, through SPI.
+//!
+//! `COPY ... FROM STDIN` and `COPY ... TO STDOUT` can't be run through SPI because the backend
+//! would try to talk to the client.  Instead, `Spi::copy_from()` feeds `COPY`'s own input parser
+//! (`BeginCopyFrom()`/`NextCopyFrom()`) from a Rust `Read`, and `Spi::copy_to()` streams a query's
+//! results into a Rust `Write` using `COPY`'s text format.
+
+use crate::{is_a, pg_sys, void_mut_ptr, PgList, PgRelation, Spi};
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::os::raw::c_int;
+
+thread_local! {
+    /// The `Read` that the active `Spi::copy_from()` is consuming, which Postgres' data source
+    /// callback has no other way to reach
+    static COPY_SOURCE: Cell<void_mut_ptr> = Cell::new(std::ptr::null_mut());
+}
+
+/// How many rows `Spi::copy_to()` fetches from its cursor at a time
+const COPY_TO_BATCH_SIZE: i64 = 1000;
+
+impl Spi {
+    /// Run a `COPY table [(columns)] FROM STDIN [WITH (options)]` statement, reading the data
+    /// from `reader` instead of the client connection
+    ///
+    /// Every format and option that `COPY FROM` accepts is supported, including `FORMAT binary`.
+    /// Rows are inserted with an SPI `INSERT`, so the table's permissions, row level security
+    /// policies, triggers and constraints are enforced.  Returns the number of rows loaded.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let data = "1\tone\n2\ttwo\n";
+    /// let count = Spi::copy_from("COPY numbers (id, name) FROM STDIN", data.as_bytes());
+    /// assert_eq!(count, 2);
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If `copy_statement` isn't a single `COPY ... FROM STDIN` statement, or if reading from
+    /// `reader` fails.  Malformed data raises the same ERRORs that `COPY` does.
+    pub fn copy_from<R: Read>(copy_statement: &str, mut reader: R) -> u64 {
+        let copy_stmt = parse_copy_from(copy_statement);
+
+        Spi::connect(|_| unsafe {
+            let rel = pg_sys::relation_openrv((*copy_stmt).relation, pg_sys::RowExclusiveLock as _);
+            let relation = PgRelation::from_pg(rel);
+            let natts = relation.tuple_desc().len();
+
+            // the same columns CopyGetAttnums() picks when COPY is given no column list
+            let attnums = if (*copy_stmt).attlist.is_null() {
+                relation
+                    .tuple_desc()
+                    .attributes()
+                    .filter(|att| !is_generated(att))
+                    .map(|att| att.num() as usize)
+                    .collect()
+            } else {
+                PgList::<pg_sys::Node>::from_pg((*copy_stmt).attlist)
+                    .iter_ptr()
+                    .map(|name| {
+                        // each column name is a String Value node
+                        let name = CStr::from_ptr((*(name as *mut pg_sys::Value)).val.str)
+                            .to_str()
+                            .unwrap();
+                        relation.tuple_desc().attno(name).unwrap_or_else(|| {
+                            panic!(
+                                "column \"{}\" of relation \"{}\" does not exist",
+                                name,
+                                relation.name()
+                            )
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let plan = prepare_insert(&relation, &attnums);
+
+            let pstate = pg_sys::make_parsestate(std::ptr::null_mut());
+            let previous_source =
+                COPY_SOURCE.with(|source| source.replace(&mut reader as *mut R as void_mut_ptr));
+            let cstate = pg_sys::BeginCopyFrom(
+                pstate,
+                rel,
+                std::ptr::null(),
+                false,
+                Some(copy_read_callback::<R>),
+                (*copy_stmt).attlist,
+                (*copy_stmt).options,
+            );
+
+            // have ERRORs report the line of input they happened on, just like COPY does.  The
+            // callback is called by Postgres itself, so it must be the raw C function rather
+            // than `pg_sys`'s `#[pg_guard]` wrapper
+            extern "C" {
+                fn CopyFromErrorCallback(arg: void_mut_ptr);
+            }
+            let mut errcallback = pg_sys::ErrorContextCallback {
+                previous: pg_sys::error_context_stack,
+                callback: Some(CopyFromErrorCallback),
+                arg: cstate as void_mut_ptr,
+            };
+            pg_sys::error_context_stack = &mut errcallback;
+            let _restore = RestoreCopyState {
+                error_context: errcallback.previous,
+                source: previous_source,
+            };
+
+            let econtext = pg_sys::CreateStandaloneExprContext();
+            let mut values = vec![0 as pg_sys::Datum; natts];
+            let mut nulls = vec![false; natts];
+            let mut args = vec![0 as pg_sys::Datum; attnums.len()];
+            let mut argnulls = vec![' ' as std::os::raw::c_char; attnums.len()];
+            let mut count = 0u64;
+
+            loop {
+                let oldcontext = pg_sys::CurrentMemoryContext;
+                pg_sys::CurrentMemoryContext = (*econtext).ecxt_per_tuple_memory;
+                let found = next_copy_from(cstate, econtext, &mut values, &mut nulls);
+                pg_sys::CurrentMemoryContext = oldcontext;
+
+                if !found {
+                    break;
+                }
+
+                for (i, attnum) in attnums.iter().enumerate() {
+                    args[i] = values[attnum - 1];
+                    argnulls[i] = if nulls[attnum - 1] { 'n' } else { ' ' } as std::os::raw::c_char;
+                }
+
+                Spi::check_status(pg_sys::SPI_execute_plan(
+                    plan,
+                    args.as_mut_ptr(),
+                    argnulls.as_ptr(),
+                    false,
+                    0,
+                ));
+                pg_sys::MemoryContextReset((*econtext).ecxt_per_tuple_memory);
+                count += 1;
+            }
+
+            pg_sys::EndCopyFrom(cstate);
+            pg_sys::FreeExprContext(econtext, true);
+            pg_sys::free_parsestate(pstate);
+            pg_sys::SPI_freeplan(plan);
+
+            // like COPY, keep the lock until the end of the transaction
+            pg_sys::relation_close(rel, pg_sys::NoLock as _);
+
+            Ok(Some(count as i64))
+        })
+        .unwrap() as u64
+    }
+
+    /// Run a `COPY ... FROM STDIN` statement whose data comes from an iterator of rows, where
+    /// each column's value is in its type's text representation and `None` is NULL
+    ///
+    /// The rows are escaped and streamed to `Spi::copy_from()` in `COPY`'s text format, so
+    /// `copy_statement` must not specify another format.  Returns the number of rows loaded.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let rows = vec![
+    ///     vec![Some("1".to_string()), Some("one".to_string())],
+    ///     vec![Some("2".to_string()), None],
+    /// ];
+    /// Spi::copy_from_rows("COPY numbers (id, name) FROM STDIN", rows);
+    /// ```
+    pub fn copy_from_rows<I: IntoIterator<Item = Vec<Option<String>>>>(
+        copy_statement: &str,
+        rows: I,
+    ) -> u64 {
+        Spi::copy_from(
+            copy_statement,
+            CopyTextRows {
+                rows: rows.into_iter(),
+                buffer: Vec::new(),
+                position: 0,
+            },
+        )
+    }
+
+    /// Run `query` and write its results to `writer` in `COPY`'s text format, as
+    /// `COPY (query) TO STDOUT` would
+    ///
+    /// Columns are separated by tabs, rows end with a newline, and NULLs are written as `\N`.
+    /// Results are fetched through a cursor in batches, so large result sets aren't held in
+    /// memory.  Returns the number of rows written, or the first error returned by `writer`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let mut output = Vec::new();
+    /// Spi::copy_to("SELECT id, name FROM numbers ORDER BY id", &mut output).unwrap();
+    /// ```
+    pub fn copy_to<W: Write>(query: &str, mut writer: W) -> std::io::Result<u64> {
+        let query = CString::new(query).expect("query contained a null byte");
+        let mut result = Ok(0);
+
+        Spi::connect(|_| unsafe {
+            let plan = pg_sys::SPI_prepare(query.as_ptr(), 0, std::ptr::null_mut());
+            if plan.is_null() {
+                Spi::check_status(pg_sys::SPI_result);
+            }
+            let portal = pg_sys::SPI_cursor_open(
+                std::ptr::null(),
+                plan,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                // not read-only, so the query sees rows written earlier in the transaction
+                false,
+            );
+
+            let mut count = 0u64;
+            let mut line = Vec::new();
+            'fetch: loop {
+                pg_sys::SPI_cursor_fetch(portal, true, COPY_TO_BATCH_SIZE);
+                let tuptable = pg_sys::SPI_tuptable;
+                let processed = pg_sys::SPI_processed as usize;
+                if processed == 0 {
+                    pg_sys::SPI_freetuptable(tuptable);
+                    break;
+                }
+
+                let tupdesc = (*tuptable).tupdesc;
+                let tuples = std::slice::from_raw_parts((*tuptable).vals, processed);
+                for tuple in tuples {
+                    line.clear();
+                    for attno in 1..=(*tupdesc).natts {
+                        if attno > 1 {
+                            line.push(b'\t');
+                        }
+
+                        let value = pg_sys::SPI_getvalue(*tuple, tupdesc, attno);
+                        if value.is_null() {
+                            line.extend_from_slice(b"\\N");
+                        } else {
+                            escape_copy_text(CStr::from_ptr(value).to_bytes(), &mut line);
+                            pg_sys::pfree(value as void_mut_ptr);
+                        }
+                    }
+                    line.push(b'\n');
+
+                    if let Err(e) = writer.write_all(&line) {
+                        result = Err(e);
+                        pg_sys::SPI_freetuptable(tuptable);
+                        break 'fetch;
+                    }
+                    count += 1;
+                }
+
+                pg_sys::SPI_freetuptable(tuptable);
+            }
+
+            pg_sys::SPI_cursor_close(portal);
+            pg_sys::SPI_freeplan(plan);
+
+            if result.is_ok() {
+                result = writer.flush().map(|_| count);
+            }
+            Ok(Some(()))
+        });
+
+        result
+    }
+}
+
+/// Parse `copy_statement`, making sure it's a single `COPY ... FROM STDIN` we can run
+fn parse_copy_from(copy_statement: &str) -> *mut pg_sys::CopyStmt {
+    let query = CString::new(copy_statement).expect("COPY statement contained a null byte");
+    let statements =
+        PgList::<pg_sys::RawStmt>::from_pg(unsafe { pg_sys::pg_parse_query(query.as_ptr()) });
+    if statements.len() != 1 {
+        panic!("expected a single COPY statement");
+    }
+
+    let stmt = unsafe { (*statements.get_ptr(0).unwrap()).stmt };
+    if !is_a(stmt, pg_sys::NodeTag_T_CopyStmt) {
+        panic!("expected a COPY statement");
+    }
+
+    let copy_stmt = stmt as *mut pg_sys::CopyStmt;
+    unsafe {
+        if !(*copy_stmt).is_from || (*copy_stmt).relation.is_null() {
+            panic!("expected a COPY ... FROM STDIN statement");
+        } else if !(*copy_stmt).filename.is_null() || (*copy_stmt).is_program {
+            panic!("COPY FROM a file or program is not supported, use STDIN");
+        }
+
+        #[cfg(feature = "pg12")]
+        {
+            if !(*copy_stmt).whereClause.is_null() {
+                panic!("COPY FROM ... WHERE is not supported");
+            }
+        }
+    }
+
+    copy_stmt
+}
+
+/// Prepare the `INSERT` that loads each row `NextCopyFrom()` produces into `relation`'s
+/// `attnums` columns
+unsafe fn prepare_insert(relation: &PgRelation, attnums: &[usize]) -> pg_sys::SPIPlanPtr {
+    let tupdesc = relation.tuple_desc();
+    let mut columns = Vec::with_capacity(attnums.len());
+    let mut params = Vec::with_capacity(attnums.len());
+    let mut argtypes = Vec::with_capacity(attnums.len());
+
+    for (i, attnum) in attnums.iter().enumerate() {
+        let att = tupdesc.get(attnum - 1).unwrap();
+        columns.push(quote_identifier(att.name()));
+        params.push(format!("${}", i + 1));
+        argtypes.push(att.atttypid);
+    }
+
+    let insert = CString::new(format!(
+        "INSERT INTO {}.{} ({}) VALUES ({})",
+        quote_identifier(relation.namespace()),
+        quote_identifier(relation.name()),
+        columns.join(", "),
+        params.join(", ")
+    ))
+    .unwrap();
+
+    let plan = pg_sys::SPI_prepare(
+        insert.as_ptr(),
+        argtypes.len() as i32,
+        argtypes.as_mut_ptr(),
+    );
+    if plan.is_null() {
+        Spi::check_status(pg_sys::SPI_result);
+    }
+    plan
+}
+
+pub(crate) fn quote_identifier(ident: &str) -> String {
+    let ident = CString::new(ident).unwrap();
+    unsafe { CStr::from_ptr(pg_sys::quote_identifier(ident.as_ptr())) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+fn is_generated(_att: &pg_sys::FormData_pg_attribute) -> bool {
+    false
+}
+
+#[cfg(feature = "pg12")]
+fn is_generated(att: &pg_sys::FormData_pg_attribute) -> bool {
+    att.attgenerated != 0
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn next_copy_from(
+    cstate: pg_sys::CopyState,
+    econtext: *mut pg_sys::ExprContext,
+    values: &mut [pg_sys::Datum],
+    nulls: &mut [bool],
+) -> bool {
+    // only set when the input has OIDS, which we let Postgres reject when it inserts
+    let mut tuple_oid = pg_sys::InvalidOid;
+    pg_sys::NextCopyFrom(
+        cstate,
+        econtext,
+        values.as_mut_ptr(),
+        nulls.as_mut_ptr(),
+        &mut tuple_oid,
+    )
+}
+
+#[cfg(feature = "pg12")]
+unsafe fn next_copy_from(
+    cstate: pg_sys::CopyState,
+    econtext: *mut pg_sys::ExprContext,
+    values: &mut [pg_sys::Datum],
+    nulls: &mut [bool],
+) -> bool {
+    pg_sys::NextCopyFrom(cstate, econtext, values.as_mut_ptr(), nulls.as_mut_ptr())
+}
+
+/// Postgres' `copy_data_source_cb` for `Spi::copy_from()`, which reads from the `R` in
+/// `COPY_SOURCE` until at least `minread` bytes are available or it reaches the end of input
+unsafe extern "C" fn copy_read_callback<R: Read>(
+    outbuf: void_mut_ptr,
+    minread: c_int,
+    maxread: c_int,
+) -> c_int {
+    pg_sys::guard(std::panic::AssertUnwindSafe(|| {
+        let reader = &mut *(COPY_SOURCE.with(|source| source.get()) as *mut R);
+        let buffer = std::slice::from_raw_parts_mut(outbuf as *mut u8, maxread as usize);
+        let mut len = 0;
+
+        while len < minread as usize {
+            match reader.read(&mut buffer[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => panic!("could not read COPY data: {}", e),
+            }
+        }
+
+        len as c_int
+    }))
+}
+
+/// Restores the state `Spi::copy_from()` replaced, even when an ERROR unwinds through it, as
+/// an `error_context_stack` entry that outlives our stack frame would crash the next ERROR
+struct RestoreCopyState {
+    error_context: *mut pg_sys::ErrorContextCallback,
+    source: void_mut_ptr,
+}
+
+impl Drop for RestoreCopyState {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::error_context_stack = self.error_context;
+        }
+        COPY_SOURCE.with(|source| source.set(self.source));
+    }
+}
+
+/// Escape `value` for `COPY`'s text format, appending it to `into`
+fn escape_copy_text(value: &[u8], into: &mut Vec<u8>) {
+    for byte in value {
+        match byte {
+            b'\\' => into.extend_from_slice(b"\\\\"),
+            b'\t' => into.extend_from_slice(b"\\t"),
+            b'\n' => into.extend_from_slice(b"\\n"),
+            b'\r' => into.extend_from_slice(b"\\r"),
+            _ => into.push(*byte),
+        }
+    }
+}
+
+/// A `Read` over rows of text values, encoded as `COPY`'s text format one row at a time
+struct CopyTextRows<I: Iterator<Item = Vec<Option<String>>>> {
+    rows: I,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl<I: Iterator<Item = Vec<Option<String>>>> Read for CopyTextRows<I> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.buffer.len() {
+            let row = match self.rows.next() {
+                Some(row) => row,
+                None => return Ok(0),
+            };
+
+            self.buffer.clear();
+            self.position = 0;
+            for (i, value) in row.iter().enumerate() {
+                if i > 0 {
+                    self.buffer.push(b'\t');
+                }
+
+                match value {
+                    Some(value) => escape_copy_text(value.as_bytes(), &mut self.buffer),
+                    None => self.buffer.extend_from_slice(b"\\N"),
+                }
+            }
+            self.buffer.push(b'\n');
+        }
+
+        let len = buf.len().min(self.buffer.len() - self.position);
+        buf[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
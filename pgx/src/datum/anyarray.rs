@@ -2,7 +2,7 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 
-use crate::{pg_sys, FromDatum, IntoDatum};
+use crate::{pg_sys, Array, FromDatum, IntoDatum, TypeMismatchError};
 
 #[derive(Debug, Clone, Copy)]
 pub struct AnyArray {
@@ -23,6 +23,29 @@ impl AnyArray {
     pub fn into<T: FromDatum>(&self) -> Option<T> {
         unsafe { T::from_datum(self.datum(), false, self.oid()) }
     }
+
+    /// The type oid of this array's elements
+    pub fn element_oid(&self) -> pg_sys::Oid {
+        unsafe { pg_sys::get_element_type(self.typoid) }
+    }
+
+    /// Convert to `T` (such as `Vec<i32>`), but only if this array's resolved type is `T`'s
+    /// type.  Otherwise `Err(TypeMismatchError)`
+    #[inline]
+    pub fn try_into<T: FromDatum + IntoDatum>(&self) -> Result<Option<T>, TypeMismatchError> {
+        TypeMismatchError::check(T::type_oid(), self.oid())?;
+        Ok(self.into())
+    }
+
+    /// Convert to an `Array<T>`, but only if this array's elements are of `T`'s type.
+    /// Otherwise `Err(TypeMismatchError)`, whose oids are those of the element types
+    #[inline]
+    pub fn try_into_array<'a, T: FromDatum + IntoDatum>(
+        &self,
+    ) -> Result<Option<Array<'a, T>>, TypeMismatchError> {
+        TypeMismatchError::check(T::type_oid(), self.element_oid())?;
+        Ok(self.into())
+    }
 }
 
 impl FromDatum for AnyArray {
@@ -3,6 +3,8 @@
 
 
 use crate::{pg_sys, FromDatum, IntoDatum};
+use std::ffi::CStr;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy)]
 pub struct AnyElement {
@@ -23,6 +25,51 @@ impl AnyElement {
     pub fn into<T: FromDatum>(&self) -> Option<T> {
         unsafe { T::from_datum(self.datum(), false, self.oid()) }
     }
+
+    /// Convert to `T`, but only if this element's resolved type is `T`'s type.  Otherwise
+    /// `Err(TypeMismatchError)`
+    #[inline]
+    pub fn try_into<T: FromDatum + IntoDatum>(&self) -> Result<Option<T>, TypeMismatchError> {
+        TypeMismatchError::check(T::type_oid(), self.oid())?;
+        Ok(self.into())
+    }
+}
+
+/// The error returned when a polymorphic argument, such as an `AnyElement` or `AnyArray`, is
+/// converted into a Rust type other than the one Postgres resolved it to
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TypeMismatchError {
+    pub expected: pg_sys::Oid,
+    pub actual: pg_sys::Oid,
+}
+
+impl TypeMismatchError {
+    pub(crate) fn check(expected: pg_sys::Oid, actual: pg_sys::Oid) -> Result<(), Self> {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(TypeMismatchError { expected, actual })
+        }
+    }
+}
+
+impl fmt::Display for TypeMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a value of type {}, but got {}",
+            type_name(self.expected),
+            type_name(self.actual)
+        )
+    }
+}
+
+impl std::error::Error for TypeMismatchError {}
+
+fn type_name(typoid: pg_sys::Oid) -> String {
+    unsafe { CStr::from_ptr(pg_sys::format_type_be(typoid)) }
+        .to_string_lossy()
+        .into_owned()
 }
 
 impl FromDatum for AnyElement {
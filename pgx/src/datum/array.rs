@@ -1,6 +1,7 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use crate::datum::array_builder::maxalign;
 use crate::{pg_sys, void_mut_ptr, FromDatum, IntoDatum, PgMemoryContexts};
 use serde::Serializer;
 use std::marker::PhantomData;
@@ -13,6 +14,8 @@ pub struct Array<'a, T: FromDatum> {
     elements: *mut pg_sys::Datum,
     nulls: *mut bool,
     typoid: pg_sys::Oid,
+    elem_typlen: i16,
+    elem_byval: bool,
     nelems: usize,
     elem_slice: &'a [pg_sys::Datum],
     null_slice: &'a [bool],
@@ -58,6 +61,8 @@ impl<'a, T: FromDatum> Array<'a, T> {
             elements,
             nulls,
             typoid: pg_sys::InvalidOid,
+            elem_typlen: std::mem::size_of::<pg_sys::Datum>() as i16,
+            elem_byval: true,
             nelems,
             elem_slice: std::slice::from_raw_parts(elements, nelems),
             null_slice: std::slice::from_raw_parts(nulls, nelems),
@@ -71,6 +76,8 @@ impl<'a, T: FromDatum> Array<'a, T> {
         elements: *mut pg_sys::Datum,
         nulls: *mut bool,
         typoid: pg_sys::Oid,
+        elem_typlen: i16,
+        elem_byval: bool,
         nelems: usize,
     ) -> Self {
         Array::<T> {
@@ -79,6 +86,8 @@ impl<'a, T: FromDatum> Array<'a, T> {
             elements,
             nulls,
             typoid,
+            elem_typlen,
+            elem_byval,
             nelems,
             elem_slice: unsafe { std::slice::from_raw_parts(elements, nelems) },
             null_slice: unsafe { std::slice::from_raw_parts(nulls, nelems) },
@@ -86,6 +95,111 @@ impl<'a, T: FromDatum> Array<'a, T> {
         }
     }
 
+    /// Build a 2-dimensional array, in the `CurrentMemoryContext`, with a row per inner `Vec`.
+    /// Elements that are `None` become NULLs.
+    ///
+    /// ## Panics
+    ///
+    /// If the rows aren't all the same length
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let matrix = Array::<i32>::from_rows(vec![vec![Some(1), Some(2)], vec![Some(3), None]]);
+    /// assert_eq!(matrix.dims(), &[2, 2]);
+    /// ```
+    pub fn from_rows(rows: Vec<Vec<Option<T>>>) -> Self
+    where
+        T: IntoDatum,
+    {
+        let ncols = rows.first().map_or(0, |row| row.len());
+        let mut dims = [rows.len() as i32, ncols as i32];
+        let mut lbounds = [1, 1];
+        let mut datums = Vec::with_capacity(rows.len() * ncols);
+        let mut nulls = Vec::with_capacity(rows.len() * ncols);
+        for row in rows {
+            if row.len() != ncols {
+                panic!("multidimensional arrays must have sub-arrays with matching dimensions");
+            }
+
+            for element in row {
+                let datum = element.and_then(|element| element.into_datum());
+                nulls.push(datum.is_none());
+                datums.push(datum.unwrap_or(0));
+            }
+        }
+
+        unsafe {
+            let array = if datums.is_empty() {
+                pg_sys::construct_empty_array(T::type_oid())
+            } else {
+                let mut typlen = 0;
+                let mut typbyval = false;
+                let mut typalign = 0;
+                pg_sys::get_typlenbyvalalign(
+                    T::type_oid(),
+                    &mut typlen,
+                    &mut typbyval,
+                    &mut typalign,
+                );
+
+                pg_sys::construct_md_array(
+                    datums.as_mut_ptr(),
+                    nulls.as_mut_ptr(),
+                    2,
+                    dims.as_mut_ptr(),
+                    lbounds.as_mut_ptr(),
+                    T::type_oid(),
+                    typlen as i32,
+                    typbyval,
+                    typalign,
+                )
+            };
+
+            Array::from_datum(
+                array as pg_sys::Datum,
+                false,
+                pg_sys::get_array_type(T::type_oid()),
+            )
+            .expect("constructed array is NULL")
+        }
+    }
+
+    /// The number of dimensions, which is 0 for an empty array
+    pub fn ndims(&self) -> usize {
+        self.header().ndim as usize
+    }
+
+    /// The length of each dimension.  The elements are stored, and iterated, in row-major order.
+    pub fn dims(&self) -> &[i32] {
+        let ndims = self.ndims();
+
+        // mimics ARR_DIMS()
+        unsafe {
+            std::slice::from_raw_parts(
+                (self.array_type as *const u8).add(std::mem::size_of::<pg_sys::ArrayType>())
+                    as *const i32,
+                ndims,
+            )
+        }
+    }
+
+    /// The lower bound of each dimension, which is the subscript of its first element, and is
+    /// usually 1
+    pub fn lower_bounds(&self) -> &[i32] {
+        // mimics ARR_LBOUND()
+        unsafe {
+            let dims = self.dims();
+            std::slice::from_raw_parts(dims.as_ptr().add(dims.len()), dims.len())
+        }
+    }
+
+    fn header(&self) -> &pg_sys::ArrayType {
+        unsafe { self.array_type.as_ref() }.expect("attempt to dereference a NULL array")
+    }
+
     pub fn into_array_type(self) -> *const pg_sys::ArrayType {
         if self.array_type.is_null() {
             panic!("attempt to dereference a NULL array");
@@ -96,13 +210,47 @@ impl<'a, T: FromDatum> Array<'a, T> {
         ptr
     }
 
+    /// View the elements as a slice of `T`, without copying them.
+    ///
+    /// This is only possible for arrays without NULLs whose elements are passed by value and are
+    /// the same size as `T`, such as an `integer[]` as `i32`s or a `float8[]` as `f64`s.  The
+    /// slice points directly into the array's data, so values such as NaN are exactly as
+    /// Postgres stored them.
+    ///
+    /// ## Panics
+    ///
+    /// If the array contains NULLs, or its elements can't be viewed as a `T`
     pub fn as_slice(&self) -> &[T] {
-        let sizeof_type = std::mem::size_of::<T>();
-        let sizeof_datums = std::mem::size_of_val(self.elem_slice);
+        if !self.elem_byval || self.elem_typlen as usize != std::mem::size_of::<T>() {
+            panic!(
+                "array elements of length {} cannot be viewed as a slice of {}",
+                self.elem_typlen,
+                std::any::type_name::<T>()
+            );
+        }
+
+        if self.array_type.is_null() {
+            // an Array::over() a set of Datums
+            return unsafe {
+                std::slice::from_raw_parts(self.elem_slice.as_ptr() as *const T, self.nelems)
+            };
+        }
+
         unsafe {
+            let array = self.array_type.as_ref().unwrap();
+            if array.dataoffset != 0 {
+                // ARR_HASNULL()
+                panic!("array contains NULL");
+            }
+
+            // mimics ARR_DATA_PTR() for an array without a null bitmap
+            let offset = maxalign(
+                std::mem::size_of::<pg_sys::ArrayType>()
+                    + 2 * std::mem::size_of::<i32>() * array.ndim as usize,
+            );
             std::slice::from_raw_parts(
-                self.elem_slice.as_ptr() as *const T,
-                sizeof_datums / sizeof_type,
+                (self.array_type as *const u8).add(offset) as *const T,
+                self.nelems,
             )
         }
     }
@@ -316,10 +464,32 @@ impl<'a, T: FromDatum> FromDatum for Array<'a, T> {
                 elements,
                 nulls,
                 typoid,
+                typlen,
+                typbyval,
                 nelems as usize,
             ))
         }
     }
+
+    unsafe fn from_datum_in_memory_context(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("array was flagged not null but datum is zero");
+        } else {
+            memory_context.switch_to(|_| {
+                // copy the whole array into this memory context, as by-reference elements
+                // point directly into it
+                let copy = pg_sys::pg_detoast_datum_copy(datum as *mut pg_sys::varlena);
+                Self::from_datum(copy as pg_sys::Datum, false, typoid)
+            })
+        }
+    }
 }
 
 impl<T: FromDatum> FromDatum for Vec<Option<T>> {
@@ -343,8 +513,99 @@ impl<T: FromDatum> FromDatum for Vec<Option<T>> {
             Some(v)
         }
     }
+
+    unsafe fn from_datum_in_memory_context(
+        memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        let array =
+            Array::<T>::from_datum_in_memory_context(memory_context, datum, is_null, typoid)?;
+        Some(array.iter().collect())
+    }
+}
+
+/// Converts a `text[]` (or `varchar[]`, etc) that can't contain NULLs.  Use
+/// `Vec<Option<String>>` for arrays that might.
+///
+/// ## Panics
+///
+/// If the array contains a NULL element, or an element isn't valid UTF-8
+impl FromDatum for Vec<String> {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<Vec<String>> {
+        Vec::<Option<String>>::from_datum(datum, is_null, typoid).map(non_null_elements)
+    }
+}
+
+/// Like `Vec<String>`, but each `&str` borrows directly from the array, which is only copied if
+/// it's toasted.
+///
+/// ## Panics
+///
+/// If the array contains a NULL element, or an element isn't valid UTF-8
+impl<'a> FromDatum for Vec<&'a str> {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<Vec<&'a str>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("array was flagged not null but datum is zero");
+        } else {
+            // detoast it ourselves, as an `Array` frees the detoasted copy it makes when it's
+            // dropped, and our &strs point into it
+            let array = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let array = Array::<&str>::from_datum(array as pg_sys::Datum, false, typoid)?;
+            Some(non_null_elements(array.iter().collect()))
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: pg_sys::Oid,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("array was flagged not null but datum is zero");
+        } else {
+            memory_context.switch_to(|_| {
+                let copy = pg_sys::pg_detoast_datum_copy(datum as *mut pg_sys::varlena);
+                Self::from_datum(copy as pg_sys::Datum, false, typoid)
+            })
+        }
+    }
+}
+
+/// Returns the array itself, so its dimensions and lower bounds are preserved
+impl<'a, T: FromDatum + IntoDatum> IntoDatum for Array<'a, T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.into_array_type() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        unsafe { pg_sys::get_array_type(T::type_oid()) }
+    }
+}
+
+fn non_null_elements<T>(elements: Vec<Option<T>>) -> Vec<T> {
+    elements
+        .into_iter()
+        .map(|element| {
+            element.unwrap_or_else(|| {
+                panic!("array contains a NULL element, which requires a Vec<Option<T>>")
+            })
+        })
+        .collect()
 }
 
+/// Builds a Postgres array from the elements of this `Vec<T>`.
+///
+/// When `T` is an `Option<U>`, elements that are `None` become NULLs in the array's null bitmap,
+/// which makes `Vec<Option<U>>` the symmetric counterpart of `FromDatum for Vec<Option<T>>`
 impl<T> IntoDatum for Vec<T>
 where
     T: IntoDatum,
@@ -0,0 +1,225 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, set_varsize, varatt_is_4b_u, varsize_any, void_mut_ptr, IntoDatum};
+use std::marker::PhantomData;
+
+/// Builds a one-dimensional Postgres array by writing each element directly into a palloc'd
+/// array body, avoiding the intermediate `Vec<T>` (and `Vec<pg_sys::Datum>`) that converting a
+/// `Vec<T>` requires.
+///
+/// All memory is allocated in the `CurrentMemoryContext`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn generate_array(n: i32) -> ArrayBuilder<i32> {
+///     let mut builder = ArrayBuilder::with_capacity(n as usize);
+///     for i in 0..n {
+///         builder.push(if i % 2 == 0 { Some(i) } else { None });
+///     }
+///     builder
+/// }
+/// ```
+pub struct ArrayBuilder<T: IntoDatum> {
+    data: *mut u8,
+    capacity: usize,
+    len: usize,
+    nitems: usize,
+    nulls: Vec<u8>,
+    has_nulls: bool,
+    typlen: i16,
+    typbyval: bool,
+    typalign: u8,
+    _marker: PhantomData<T>,
+}
+
+impl<T: IntoDatum> ArrayBuilder<T> {
+    /// Create a new `ArrayBuilder` with room for at least `nitems` elements.  Fixed-length
+    /// element types never need to grow the array body beyond that.
+    pub fn with_capacity(nitems: usize) -> Self {
+        let mut typlen = 0;
+        let mut typbyval = false;
+        let mut typalign = 0;
+        unsafe {
+            pg_sys::get_typlenbyvalalign(T::type_oid(), &mut typlen, &mut typbyval, &mut typalign);
+        }
+
+        let typalign = typalign as u8;
+        let elem_size = if typlen > 0 {
+            align(typlen as usize, typalign)
+        } else {
+            // just a guess for variable-length types
+            16
+        };
+        let capacity = std::cmp::max(nitems * elem_size, 64);
+
+        ArrayBuilder {
+            data: unsafe { pg_sys::palloc(capacity) as *mut u8 },
+            capacity,
+            len: 0,
+            nitems: 0,
+            nulls: Vec::with_capacity((nitems + 7) / 8),
+            has_nulls: false,
+            typlen,
+            typbyval,
+            typalign,
+            _marker: PhantomData,
+        }
+    }
+
+    /// How many elements, including NULLs, have been pushed?
+    pub fn len(&self) -> usize {
+        self.nitems
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nitems == 0
+    }
+
+    /// Append an element, where `None` is a NULL element
+    pub fn push(&mut self, value: Option<T>) {
+        if self.nitems % 8 == 0 {
+            self.nulls.push(0);
+        }
+
+        match value.and_then(|value| value.into_datum()) {
+            Some(datum) => {
+                // in a Postgres null bitmap, a set bit means the element is *not* NULL
+                *self.nulls.last_mut().unwrap() |= 1 << (self.nitems % 8) as u8;
+                unsafe { self.append(datum) }
+            }
+            None => self.has_nulls = true,
+        }
+
+        self.nitems += 1;
+    }
+
+    /// Assemble the final `ArrayType` and return it as a `pg_sys::Datum`.
+    ///
+    /// The array is assembled in place:  the array body is `repalloc`'d to make room for the
+    /// array header and shifted up behind it, so the elements are never held twice.
+    pub fn build(self) -> pg_sys::Datum {
+        unsafe {
+            if self.nitems == 0 {
+                pg_sys::pfree(self.data as void_mut_ptr);
+                return pg_sys::construct_empty_array(T::type_oid()) as pg_sys::Datum;
+            }
+
+            // mimics the ARR_OVERHEAD_NONULLS() and ARR_OVERHEAD_WITHNULLS() macros from array.h
+            let header = std::mem::size_of::<pg_sys::ArrayType>() + 2 * std::mem::size_of::<i32>();
+            let bitmap_len = if self.has_nulls { self.nulls.len() } else { 0 };
+            let overhead = maxalign(header + bitmap_len);
+            let total = overhead + self.len;
+
+            let base = pg_sys::repalloc(self.data as void_mut_ptr, total) as *mut u8;
+            std::ptr::copy(base, base.add(overhead), self.len);
+            std::ptr::write_bytes(base, 0, overhead);
+
+            let array = base as *mut pg_sys::ArrayType;
+            set_varsize(array as *mut pg_sys::varlena, total as i32);
+            (*array).ndim = 1;
+            (*array).dataoffset = if self.has_nulls { overhead as i32 } else { 0 };
+            (*array).elemtype = T::type_oid();
+
+            // ARR_DIMS() and ARR_LBOUND()
+            let dims = (array as *mut u8).add(std::mem::size_of::<pg_sys::ArrayType>()) as *mut i32;
+            *dims = self.nitems as i32;
+            *dims.add(1) = 1;
+
+            if self.has_nulls {
+                std::ptr::copy_nonoverlapping(self.nulls.as_ptr(), base.add(header), bitmap_len);
+            }
+
+            array as pg_sys::Datum
+        }
+    }
+
+    /// mimics the `ArrayCastAndSet()` function from arrayfuncs.c
+    unsafe fn append(&mut self, datum: pg_sys::Datum) {
+        let offset = align(self.len, self.typalign);
+
+        if self.typbyval {
+            let size = self.typlen as usize;
+            self.reserve(offset + size);
+
+            // mimics the `store_att_byval()` macro from tupmacs.h
+            let dst = self.data.add(offset);
+            match size {
+                1 => (dst as *mut i8).write_unaligned(datum as i8),
+                2 => (dst as *mut i16).write_unaligned(datum as i16),
+                4 => (dst as *mut i32).write_unaligned(datum as i32),
+                8 => (dst as *mut pg_sys::Datum).write_unaligned(datum),
+                _ => panic!("unsupported by-value type length: {}", size),
+            }
+            self.len = offset + size;
+        } else if self.typlen > 0 {
+            self.copy_in(offset, datum as *const u8, self.typlen as usize);
+        } else if self.typlen == -1 {
+            let original = datum as *mut pg_sys::varlena;
+
+            // arrays can't contain toasted or short-header values
+            let varlena = if varatt_is_4b_u(original) {
+                original
+            } else {
+                pg_sys::pg_detoast_datum(original)
+            };
+
+            self.copy_in(offset, varlena as *const u8, varsize_any(varlena));
+            if varlena != original {
+                pg_sys::pfree(varlena as void_mut_ptr);
+            }
+        } else if self.typlen == -2 {
+            let cstr = datum as *const std::os::raw::c_char;
+            self.copy_in(offset, cstr as *const u8, pg_sys::strlen(cstr) as usize + 1);
+        } else {
+            panic!("unsupported type length: {}", self.typlen)
+        }
+    }
+
+    unsafe fn copy_in(&mut self, offset: usize, src: *const u8, size: usize) {
+        self.reserve(offset + size);
+        std::ptr::copy_nonoverlapping(src, self.data.add(offset), size);
+        self.len = offset + size;
+    }
+
+    unsafe fn reserve(&mut self, needed: usize) {
+        if needed > self.capacity {
+            let capacity = std::cmp::max(needed, self.capacity * 2);
+            self.data = pg_sys::repalloc(self.data as void_mut_ptr, capacity) as *mut u8;
+            self.capacity = capacity;
+        }
+    }
+}
+
+impl<T: IntoDatum> IntoDatum for ArrayBuilder<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.build())
+    }
+
+    fn type_oid() -> u32 {
+        unsafe { pg_sys::get_array_type(T::type_oid()) }
+    }
+}
+
+/// mimics the `att_align_nominal()` macro from tupmacs.h
+fn align(len: usize, typalign: u8) -> usize {
+    let alignment = match typalign {
+        b'c' => 1,
+        b's' => pg_sys::ALIGNOF_SHORT as usize,
+        b'i' => pg_sys::ALIGNOF_INT as usize,
+        b'd' => pg_sys::ALIGNOF_DOUBLE as usize,
+        _ => panic!("invalid typalign: {}", typalign),
+    };
+
+    (len + alignment - 1) & !(alignment - 1)
+}
+
+/// mimics the `MAXALIGN()` macro from c.h
+pub(crate) fn maxalign(len: usize) -> usize {
+    let alignment = pg_sys::MAXIMUM_ALIGNOF as usize;
+    (len + alignment - 1) & !(alignment - 1)
+}
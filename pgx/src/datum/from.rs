@@ -3,7 +3,7 @@
 
 //! for converting a pg_sys::Datum and a corresponding "is_null" bool into a typed Option
 
-use crate::{pg_sys, text_to_rust_str_unchecked, varlena_to_byte_slice, PgBox, PgMemoryContexts};
+use crate::{pg_sys, text_to_rust_str, varlena_to_byte_slice, PgBox, PgMemoryContexts, PgOid};
 use std::ffi::CStr;
 
 /// Convert a `(pg_sys::Datum, is_null:bool, type_oid:pg_sys::Oid)` tuple into a Rust type
@@ -130,6 +130,17 @@ impl FromDatum for u32 {
     }
 }
 
+impl FromDatum for PgOid {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<PgOid> {
+        if is_null {
+            None
+        } else {
+            Some(PgOid::from(datum as pg_sys::Oid))
+        }
+    }
+}
+
 /// for bigint
 impl FromDatum for i64 {
     #[inline]
@@ -175,8 +186,10 @@ impl<'a> FromDatum for &'a str {
         } else if datum == 0 {
             panic!("a varlena Datum was flagged as non-null but the datum is zero");
         } else {
+            // this only copies (into the CurrentMemoryContext) if the Datum is toasted, so the
+            // returned &str lives as long as the original Datum or that memory context
             let varlena = pg_sys::pg_detoast_datum_packed(datum as *mut pg_sys::varlena);
-            Some(text_to_rust_str_unchecked(varlena))
+            Some(text_to_checked_str(varlena))
         }
     }
 
@@ -202,12 +215,20 @@ impl<'a> FromDatum for &'a str {
                 let varlena = pg_sys::pg_detoast_datum_packed(detoasted);
 
                 // and now we return it as a &str
-                Some(text_to_rust_str_unchecked(varlena))
+                Some(text_to_checked_str(varlena))
             })
         }
     }
 }
 
+#[inline]
+unsafe fn text_to_checked_str<'a>(varlena: *const pg_sys::varlena) -> &'a str {
+    match text_to_rust_str(varlena) {
+        Ok(s) => s,
+        Err(e) => panic!("text datum is not valid UTF-8: {}", e),
+    }
+}
+
 /// for text, varchar, or any `pg_sys::varlena`-based type
 ///
 /// This returns a **copy**, allocated and managed by Rust, of the underlying `varlena` Datum
@@ -253,6 +274,16 @@ impl<'a> FromDatum for &'a std::ffi::CStr {
     }
 }
 
+/// for cstring
+///
+/// The bytes are copied as-is, without any UTF8 validation
+impl FromDatum for std::ffi::CString {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self> {
+        <&CStr>::from_datum(datum, is_null, typoid).map(|cstr| cstr.to_owned())
+    }
+}
+
 /// for bytea
 impl<'a> FromDatum for &'a [u8] {
     #[inline]
@@ -0,0 +1,159 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{direct_function_call, pg_sys, FromDatum, IntoDatum, PgBox};
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::fmt;
+
+/// A Postgres `interval`, which keeps its months, days, and microseconds separately, because
+/// neither a month nor a day (across a daylight saving time change) has a fixed length.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn quarter_after(interval: Interval) -> Interval {
+///     Interval::new(interval.months() + 3, interval.days(), interval.micros())
+/// }
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    micros: i64,
+}
+
+impl Interval {
+    /// An interval of `months` months, `days` days, and `micros` microseconds
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Interval {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    /// An interval of `months` months, such as `Interval::from_months(12)` for `1 year`
+    pub fn from_months(months: i32) -> Self {
+        Interval::new(months, 0, 0)
+    }
+
+    /// An interval of `days` days
+    pub fn from_days(days: i32) -> Self {
+        Interval::new(0, days, 0)
+    }
+
+    /// An interval of `micros` microseconds
+    pub fn from_micros(micros: i64) -> Self {
+        Interval::new(0, 0, micros)
+    }
+
+    /// The months portion of this interval, which also counts its years
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    /// The days portion of this interval
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// The microseconds portion of this interval, which counts its hours, minutes, and seconds
+    pub fn micros(&self) -> i64 {
+        self.micros
+    }
+}
+
+impl fmt::Display for Interval {
+    /// Formats the value the same way Postgres' `interval_out()` does, according to
+    /// `IntervalStyle`, such as `1 year 2 mons 3 days 04:05:06`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cstr = unsafe {
+            direct_function_call::<&CStr>(pg_sys::interval_out, vec![self.into_datum()])
+                .expect("interval_out() returned NULL")
+        };
+        f.write_str(&cstr.to_string_lossy())
+    }
+}
+
+/// The error returned when an `Interval` with a months portion is converted into a
+/// `time::Duration`, as a month doesn't have a fixed length
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IntervalHasMonthsError(pub Interval);
+
+impl fmt::Display for IntervalHasMonthsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "an interval of {} months can't be converted into a Duration",
+            self.0.months
+        )
+    }
+}
+
+impl std::error::Error for IntervalHasMonthsError {}
+
+impl TryFrom<Interval> for time::Duration {
+    type Error = IntervalHasMonthsError;
+
+    /// Converts the days and microseconds portions of `interval`, counting a day as 24 hours
+    /// like Postgres' `justify_hours()` does
+    fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+        if interval.months != 0 {
+            return Err(IntervalHasMonthsError(interval));
+        }
+
+        Ok(time::Duration::days(interval.days as i64)
+            + time::Duration::microseconds(interval.micros))
+    }
+}
+
+impl From<time::Duration> for Interval {
+    /// Converts `duration` into an `Interval` of only microseconds, truncating anything smaller
+    /// than a microsecond
+    ///
+    /// ## Panics
+    ///
+    /// If `duration` doesn't fit in 64 bits of microseconds
+    fn from(duration: time::Duration) -> Self {
+        let micros = i64::try_from(duration.whole_microseconds()).expect("interval out of range");
+        Interval::from_micros(micros)
+    }
+}
+
+impl FromDatum for Interval {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Interval> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("interval datum is declared non-null but Datum is zero");
+        } else {
+            let interval = datum as *const pg_sys::Interval;
+            Some(Interval::new(
+                (*interval).month,
+                (*interval).day,
+                (*interval).time,
+            ))
+        }
+    }
+}
+
+impl IntoDatum for Interval {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let mut interval = PgBox::<pg_sys::Interval>::alloc();
+        interval.month = self.months;
+        interval.day = self.days;
+        interval.time = self.micros;
+
+        Some(interval.into_pg() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::INTERVALOID
+    }
+}
@@ -7,7 +7,8 @@
 //! cast of the primitive type to pg_sys::Datum
 
 use crate::{
-    direct_function_call, pg_sys, rust_byte_slice_to_bytea, rust_str_to_text_p, PgBox, PgOid,
+    direct_function_call, ereport_with_detail, pg_sys, rust_byte_slice_to_bytea,
+    rust_str_to_text_p, PgBox, PgLogLevel, PgOid, SqlStateError,
 };
 
 /// Convert a Rust type into a `pg_sys::Datum`.
@@ -27,6 +28,30 @@ pub trait IntoDatum {
     }
 }
 
+/// for returning a `Result<T, E>` from a `#[pg_extern]` function, where an `Err` is raised as a
+/// Postgres `ERROR` with the error's `SqlStateError::sqlstate()` and `::detail()`
+impl<T, E> IntoDatum for Result<T, E>
+where
+    T: IntoDatum,
+    E: SqlStateError,
+{
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        match self {
+            Ok(t) => t.into_datum(),
+            Err(e) => {
+                let message = e.to_string();
+                let detail = e.detail();
+                ereport_with_detail(PgLogLevel::ERROR, e.sqlstate(), &message, detail.as_deref());
+                unreachable!("ereport() failed to raise an ERROR: {}", message);
+            }
+        }
+    }
+
+    fn type_oid() -> u32 {
+        T::type_oid()
+    }
+}
+
 /// for supporting NULL as the None value of an Option<T>
 impl<T> IntoDatum for Option<T>
 where
@@ -219,6 +244,20 @@ impl<'a> IntoDatum for &'a std::ffi::CStr {
     }
 }
 
+/// for cstring
+///
+/// The string is copied into the `CurrentMemoryContext`
+impl IntoDatum for std::ffi::CString {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(unsafe { pg_sys::pstrdup(self.as_ptr()) } as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::CSTRINGOID
+    }
+}
+
 /// for bytea
 impl<'a> IntoDatum for &'a [u8] {
     #[inline]
@@ -5,8 +5,12 @@ use crate::{
     direct_function_call, direct_function_call_as_datum, pg_sys, vardata_any, varsize_any_exhdr,
     void_mut_ptr, FromDatum, IntoDatum,
 };
+use serde::de::DeserializeOwned;
 use serde::{Serialize, Serializer};
 use serde_json::Value;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
 
 #[derive(Debug)]
 pub struct Json(pub Value);
@@ -17,6 +21,184 @@ pub struct JsonB(pub Value);
 #[derive(Debug)]
 pub struct JsonString(pub String);
 
+/// Any serde-serializable `T`, stored as `jsonb`.
+///
+/// As a `#[pg_extern]` argument or return type it's declared as `jsonb` in SQL, and it's
+/// (de)serialized directly to and from `T` without an intermediate `serde_json::Value`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Document {
+///     title: String,
+///     tags: Vec<String>,
+/// }
+///
+/// #[pg_extern]
+/// fn add_tag(mut doc: PgJsonB<Document>, tag: String) -> PgJsonB<Document> {
+///     doc.tags.push(tag);
+///     doc
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgJsonB<T>(pub T);
+
+/// The error returned when a `jsonb` value can't be deserialized into a `PgJsonB<T>`
+#[derive(Debug)]
+pub struct PgJsonBError {
+    type_name: &'static str,
+    error: serde_json::Error,
+}
+
+impl PgJsonBError {
+    fn new<T>(error: serde_json::Error) -> Self {
+        PgJsonBError {
+            type_name: std::any::type_name::<T>(),
+            error,
+        }
+    }
+
+    /// The underlying `serde_json` error, which describes where and why deserialization failed
+    pub fn serde_error(&self) -> &serde_json::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for PgJsonBError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot deserialize jsonb as {}: {}",
+            self.type_name, self.error
+        )
+    }
+}
+
+impl std::error::Error for PgJsonBError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<T> Deref for PgJsonB<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for PgJsonB<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned> PgJsonB<T> {
+    /// Like `FromDatum::from_datum()`, but returns a value that can't be deserialized as a `T`
+    /// as an `Err` rather than raising an ERROR
+    ///
+    /// ## Safety
+    ///
+    /// The datum must be a `jsonb` value
+    pub unsafe fn try_from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+    ) -> Result<Option<Self>, PgJsonBError> {
+        if is_null {
+            return Ok(None);
+        } else if datum == 0 {
+            panic!("a jsonb Datum was flagged as non-null but the datum is zero")
+        }
+
+        let cstr = direct_function_call::<&std::ffi::CStr>(pg_sys::jsonb_out, vec![Some(datum)])
+            .expect("failed to convert jsonb to a cstring");
+        let value = serde_json::from_slice::<T>(cstr.to_bytes());
+
+        // free the cstring returned from direct_function_call -- we don't need it anymore
+        pg_sys::pfree(cstr.as_ptr() as void_mut_ptr);
+
+        value
+            .map(|value| Some(PgJsonB(value)))
+            .map_err(PgJsonBError::new::<T>)
+    }
+}
+
+impl<T: DeserializeOwned> TryFrom<JsonB> for PgJsonB<T> {
+    type Error = PgJsonBError;
+
+    fn try_from(jsonb: JsonB) -> Result<Self, Self::Error> {
+        serde_json::from_value(jsonb.0)
+            .map(PgJsonB)
+            .map_err(PgJsonBError::new::<T>)
+    }
+}
+
+#[cfg(feature = "pg12")]
+impl JsonB {
+    /// Evaluate an SQL/JSON path expression against this value, returning every item it
+    /// produces, like `jsonb_path_query()`
+    ///
+    /// A malformed `jsonpath`, or an error evaluating it, is returned as an `Err` rather than
+    /// raised.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    /// use serde_json::json;
+    ///
+    /// let jsonb = JsonB(json!({"a": [1, 2, 3]}));
+    /// let values = jsonb.path_query("$.a[*] ? (@ > 1)").expect("invalid jsonpath");
+    /// assert_eq!(values, vec![json!(2), json!(3)]);
+    /// ```
+    pub fn path_query(&self, jsonpath: &str) -> Result<Vec<Value>, pg_sys::PgError> {
+        match self.call_path_function::<JsonB>(pg_sys::jsonb_path_query_array, jsonpath)? {
+            Some(JsonB(Value::Array(values))) => Ok(values),
+            other => panic!("jsonb_path_query_array() returned {:?}", other),
+        }
+    }
+
+    /// Does the SQL/JSON path expression produce any items for this value, like
+    /// `jsonb_path_exists()`?
+    ///
+    /// A malformed `jsonpath`, or an error evaluating it, is returned as an `Err` rather than
+    /// raised.
+    pub fn path_exists(&self, jsonpath: &str) -> Result<bool, pg_sys::PgError> {
+        Ok(self
+            .call_path_function::<bool>(pg_sys::jsonb_path_exists, jsonpath)?
+            .expect("jsonb_path_exists() returned NULL"))
+    }
+
+    fn call_path_function<R: FromDatum>(
+        &self,
+        func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+        jsonpath: &str,
+    ) -> Result<Option<R>, pg_sys::PgError> {
+        let jsonpath = std::ffi::CString::new(jsonpath).expect("jsonpath contained a null byte");
+        let target = JsonB(self.0.clone()).into_datum();
+        let vars = JsonB(Value::Object(Default::default())).into_datum();
+
+        // SAFETY:  parsing and evaluating a jsonpath doesn't acquire any resources that only
+        // transaction abort would release
+        unsafe {
+            pg_sys::guard_result(|| {
+                let path = direct_function_call_as_datum(
+                    pg_sys::jsonpath_in,
+                    vec![Some(jsonpath.as_ptr() as pg_sys::Datum)],
+                );
+
+                // (target, path, vars, silent)
+                direct_function_call::<R>(func, vec![target, path, vars, false.into_datum()])
+            })
+        }
+    }
+}
+
 /// for json
 impl FromDatum for Json {
     #[inline]
@@ -112,13 +294,32 @@ impl IntoDatum for Json {
 impl IntoDatum for JsonB {
     fn into_datum(self) -> Option<pg_sys::Datum> {
         let string = serde_json::to_string(&self.0).expect("failed to serialize JsonB value");
-        let cstring =
-            std::ffi::CString::new(string).expect("string version of jsonb is not valid UTF8");
+        string_to_jsonb(string)
+    }
 
-        direct_function_call_as_datum(
-            pg_sys::jsonb_in,
-            vec![Some(cstring.as_ptr() as pg_sys::Datum)],
-        )
+    fn type_oid() -> u32 {
+        pg_sys::JSONBOID
+    }
+}
+
+/// for `PgJsonB<T>`
+impl<T: DeserializeOwned> FromDatum for PgJsonB<T> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self> {
+        PgJsonB::try_from_datum(datum, is_null).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// for `PgJsonB<T>`
+impl<T: Serialize> IntoDatum for PgJsonB<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let string = serde_json::to_string(&self.0).unwrap_or_else(|e| {
+            panic!(
+                "cannot serialize {} as jsonb: {}",
+                std::any::type_name::<T>(),
+                e
+            )
+        });
+        string_to_jsonb(string)
     }
 
     fn type_oid() -> u32 {
@@ -126,6 +327,16 @@ impl IntoDatum for JsonB {
     }
 }
 
+fn string_to_jsonb(string: String) -> Option<pg_sys::Datum> {
+    let cstring =
+        std::ffi::CString::new(string).expect("string version of jsonb is not valid UTF8");
+
+    direct_function_call_as_datum(
+        pg_sys::jsonb_in,
+        vec![Some(cstring.as_ptr() as pg_sys::Datum)],
+    )
+}
+
 /// for jsonstring
 impl IntoDatum for JsonString {
     fn into_datum(self) -> Option<pg_sys::Datum> {
@@ -155,6 +366,15 @@ impl Serialize for JsonB {
     }
 }
 
+impl<T: Serialize> Serialize for PgJsonB<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
 impl Serialize for JsonString {
     fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
     where
@@ -0,0 +1,314 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, FromDatum, IntoDatum, Numeric};
+
+/// A `jsonb` value, converted directly to and from Postgres' binary `jsonb` representation
+/// rather than through `serde_json`.
+///
+/// Unlike `JsonB`, numbers are kept as Postgres `numeric`s, so they don't lose precision by
+/// passing through an `f64`, and objects keep their keys in the order `jsonb` stores them.
+/// `jsonb` itself doesn't preserve the order of an object's keys: it sorts them by length and
+/// then bytewise, and keeps only the last of any duplicates.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn with_total(amounts: Vec<Numeric>, total: Numeric) -> JsonBValue {
+///     JsonBValue::Object(vec![
+///         (
+///             "amounts".to_string(),
+///             JsonBValue::Array(amounts.into_iter().map(JsonBValue::Numeric).collect()),
+///         ),
+///         ("total".to_string(), JsonBValue::Numeric(total)),
+///     ])
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonBValue {
+    Null,
+    Bool(bool),
+    Numeric(Numeric),
+    String(String),
+    Array(Vec<JsonBValue>),
+    Object(Vec<(String, JsonBValue)>),
+}
+
+impl From<bool> for JsonBValue {
+    fn from(value: bool) -> Self {
+        JsonBValue::Bool(value)
+    }
+}
+
+impl From<Numeric> for JsonBValue {
+    fn from(value: Numeric) -> Self {
+        JsonBValue::Numeric(value)
+    }
+}
+
+impl From<String> for JsonBValue {
+    fn from(value: String) -> Self {
+        JsonBValue::String(value)
+    }
+}
+
+impl From<&str> for JsonBValue {
+    fn from(value: &str) -> Self {
+        JsonBValue::String(value.to_string())
+    }
+}
+
+impl From<Vec<JsonBValue>> for JsonBValue {
+    fn from(value: Vec<JsonBValue>) -> Self {
+        JsonBValue::Array(value)
+    }
+}
+
+/// A container that `FromDatum` is in the middle of reading
+enum Frame {
+    Array {
+        elements: Vec<JsonBValue>,
+        raw_scalar: bool,
+    },
+    Object {
+        pairs: Vec<(String, JsonBValue)>,
+        key: Option<String>,
+    },
+}
+
+impl Frame {
+    fn into_value(self) -> JsonBValue {
+        match self {
+            // Postgres stores a top-level scalar as the only element of a "raw scalar" array
+            Frame::Array {
+                mut elements,
+                raw_scalar: true,
+            } => elements.pop().expect("raw scalar jsonb array is empty"),
+            Frame::Array { elements, .. } => JsonBValue::Array(elements),
+            Frame::Object { pairs, .. } => JsonBValue::Object(pairs),
+        }
+    }
+}
+
+/// Add `value` to the container on the top of `stack`, or return it if `stack` is empty
+fn attach(stack: &mut Vec<Frame>, value: JsonBValue) -> Option<JsonBValue> {
+    match stack.last_mut() {
+        None => return Some(value),
+        Some(Frame::Array { elements, .. }) => elements.push(value),
+        Some(Frame::Object { pairs, key }) => {
+            let key = key.take().expect("jsonb object value has no key");
+            pairs.push((key, value));
+        }
+    }
+    None
+}
+
+unsafe fn scalar_from_jsonb_value(jbv: &pg_sys::JsonbValue) -> JsonBValue {
+    match jbv.type_ {
+        pg_sys::jbvType_jbvNull => JsonBValue::Null,
+        pg_sys::jbvType_jbvBool => JsonBValue::Bool(jbv.val.boolean),
+        pg_sys::jbvType_jbvNumeric => JsonBValue::Numeric(
+            Numeric::from_datum(jbv.val.numeric as pg_sys::Datum, false, pg_sys::NUMERICOID)
+                .expect("jsonb numeric is NULL"),
+        ),
+        pg_sys::jbvType_jbvString => {
+            let bytes = std::slice::from_raw_parts(
+                jbv.val.string.val as *const u8,
+                jbv.val.string.len as usize,
+            );
+            JsonBValue::String(
+                std::str::from_utf8(bytes)
+                    .expect("jsonb string is not valid UTF8")
+                    .to_string(),
+            )
+        }
+        unknown => panic!("unexpected jsonb scalar type: {}", unknown),
+    }
+}
+
+impl FromDatum for JsonBValue {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self> {
+        if is_null {
+            return None;
+        } else if datum == 0 {
+            panic!("a jsonb Datum was flagged as non-null but the datum is zero")
+        }
+
+        let jsonb = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as *mut pg_sys::Jsonb;
+        let mut iter = pg_sys::JsonbIteratorInit(&mut (*jsonb).root);
+        let mut jbv = std::mem::zeroed::<pg_sys::JsonbValue>();
+        let mut stack = Vec::new();
+
+        loop {
+            let value = match pg_sys::JsonbIteratorNext(&mut iter, &mut jbv, false) {
+                pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY => {
+                    stack.push(Frame::Array {
+                        elements: Vec::new(),
+                        raw_scalar: jbv.val.array.rawScalar,
+                    });
+                    continue;
+                }
+                pg_sys::JsonbIteratorToken_WJB_BEGIN_OBJECT => {
+                    stack.push(Frame::Object {
+                        pairs: Vec::new(),
+                        key: None,
+                    });
+                    continue;
+                }
+                pg_sys::JsonbIteratorToken_WJB_KEY => {
+                    if let (Some(Frame::Object { key, .. }), JsonBValue::String(name)) =
+                        (stack.last_mut(), scalar_from_jsonb_value(&jbv))
+                    {
+                        *key = Some(name);
+                    } else {
+                        panic!("jsonb object key is not a string");
+                    }
+                    continue;
+                }
+                pg_sys::JsonbIteratorToken_WJB_ELEM | pg_sys::JsonbIteratorToken_WJB_VALUE => {
+                    scalar_from_jsonb_value(&jbv)
+                }
+                pg_sys::JsonbIteratorToken_WJB_END_ARRAY
+                | pg_sys::JsonbIteratorToken_WJB_END_OBJECT => stack
+                    .pop()
+                    .expect("unbalanced jsonb container")
+                    .into_value(),
+                pg_sys::JsonbIteratorToken_WJB_DONE => panic!("jsonb ended unexpectedly"),
+                unknown => panic!("unexpected jsonb iterator token: {}", unknown),
+            };
+
+            if let Some(value) = attach(&mut stack, value) {
+                return Some(value);
+            }
+        }
+    }
+}
+
+/// A `pg_sys::JsonbValue` for the scalar `value`, which borrows any string from it
+unsafe fn scalar_to_jsonb_value(value: &JsonBValue) -> pg_sys::JsonbValue {
+    let mut jbv = std::mem::zeroed::<pg_sys::JsonbValue>();
+    match value {
+        JsonBValue::Null => jbv.type_ = pg_sys::jbvType_jbvNull,
+        JsonBValue::Bool(b) => {
+            jbv.type_ = pg_sys::jbvType_jbvBool;
+            jbv.val.boolean = *b;
+        }
+        JsonBValue::Numeric(n) => {
+            if n.0.trim().eq_ignore_ascii_case("nan") {
+                panic!("a jsonb numeric can't be NaN");
+            }
+            jbv.type_ = pg_sys::jbvType_jbvNumeric;
+            jbv.val.numeric =
+                n.clone().into_datum().expect("numeric_in() returned NULL") as pg_sys::Numeric;
+        }
+        JsonBValue::String(s) => string_to_jsonb_value(&mut jbv, s),
+        JsonBValue::Array(_) | JsonBValue::Object(_) => {
+            panic!("a jsonb array or object is not a scalar")
+        }
+    }
+    jbv
+}
+
+unsafe fn string_to_jsonb_value(jbv: &mut pg_sys::JsonbValue, s: &str) {
+    if s.contains('\0') {
+        panic!("a jsonb string can't contain a NUL character");
+    }
+    jbv.type_ = pg_sys::jbvType_jbvString;
+    jbv.val.string.len = s.len() as i32;
+    jbv.val.string.val = s.as_ptr() as *mut std::os::raw::c_char;
+}
+
+/// Push `value` onto `state` as a `token` (`WJB_ELEM` or `WJB_VALUE`), or as a whole container
+unsafe fn push_jsonb_value(
+    state: &mut *mut pg_sys::JsonbParseState,
+    token: pg_sys::JsonbIteratorToken,
+    value: &JsonBValue,
+) -> *mut pg_sys::JsonbValue {
+    match value {
+        JsonBValue::Array(elements) => {
+            pg_sys::pushJsonbValue(
+                state,
+                pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY,
+                std::ptr::null_mut(),
+            );
+            for element in elements {
+                push_jsonb_value(state, pg_sys::JsonbIteratorToken_WJB_ELEM, element);
+            }
+            pg_sys::pushJsonbValue(
+                state,
+                pg_sys::JsonbIteratorToken_WJB_END_ARRAY,
+                std::ptr::null_mut(),
+            )
+        }
+        JsonBValue::Object(pairs) => {
+            pg_sys::pushJsonbValue(
+                state,
+                pg_sys::JsonbIteratorToken_WJB_BEGIN_OBJECT,
+                std::ptr::null_mut(),
+            );
+            for (key, value) in pairs {
+                let mut jbv = std::mem::zeroed::<pg_sys::JsonbValue>();
+                string_to_jsonb_value(&mut jbv, key);
+                pg_sys::pushJsonbValue(state, pg_sys::JsonbIteratorToken_WJB_KEY, &mut jbv);
+                push_jsonb_value(state, pg_sys::JsonbIteratorToken_WJB_VALUE, value);
+            }
+            pg_sys::pushJsonbValue(
+                state,
+                pg_sys::JsonbIteratorToken_WJB_END_OBJECT,
+                std::ptr::null_mut(),
+            )
+        }
+        scalar => {
+            let mut jbv = scalar_to_jsonb_value(scalar);
+            pg_sys::pushJsonbValue(state, token, &mut jbv)
+        }
+    }
+}
+
+impl IntoDatum for JsonBValue {
+    /// ## Panics
+    ///
+    /// If a `JsonBValue::Numeric` isn't a valid `numeric`, or is `NaN`, or if a string contains
+    /// a NUL character
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let mut state = std::ptr::null_mut();
+
+        // strings are borrowed from `self` until `JsonbValueToJsonb()` copies them
+        unsafe {
+            let root = match &self {
+                JsonBValue::Array(_) | JsonBValue::Object(_) => {
+                    push_jsonb_value(&mut state, pg_sys::JsonbIteratorToken_WJB_ELEM, &self)
+                }
+                scalar => {
+                    // Postgres stores a top-level scalar as the only element of a "raw scalar"
+                    // array
+                    let mut array = std::mem::zeroed::<pg_sys::JsonbValue>();
+                    array.type_ = pg_sys::jbvType_jbvArray;
+                    array.val.array.nElems = 1;
+                    array.val.array.rawScalar = true;
+
+                    pg_sys::pushJsonbValue(
+                        &mut state,
+                        pg_sys::JsonbIteratorToken_WJB_BEGIN_ARRAY,
+                        &mut array,
+                    );
+                    push_jsonb_value(&mut state, pg_sys::JsonbIteratorToken_WJB_ELEM, scalar);
+                    pg_sys::pushJsonbValue(
+                        &mut state,
+                        pg_sys::JsonbIteratorToken_WJB_END_ARRAY,
+                        std::ptr::null_mut(),
+                    )
+                }
+            };
+
+            Some(pg_sys::JsonbValueToJsonb(root) as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::JSONBOID
+    }
+}
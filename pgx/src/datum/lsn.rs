@@ -0,0 +1,109 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, FromDatum, IntoDatum};
+use std::fmt;
+use std::str::FromStr;
+
+/// A Postgres `pg_lsn`, a position in the write-ahead log (an `XLogRecPtr`).
+///
+/// It's displayed, and parsed, in Postgres' `X/Y` form, where `X` and `Y` are the high and low
+/// 32 bits of the position in hexadecimal.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn lsn_lag(sent: Lsn, replayed: Lsn) -> i64 {
+///     (sent.0 - replayed.0) as i64
+/// }
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Lsn(pub pg_sys::XLogRecPtr);
+
+impl Lsn {
+    /// mimics the `XLogRecPtrIsInvalid()` macro from xlogdefs.h
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl From<pg_sys::XLogRecPtr> for Lsn {
+    fn from(lsn: pg_sys::XLogRecPtr) -> Self {
+        Lsn(lsn)
+    }
+}
+
+impl From<Lsn> for pg_sys::XLogRecPtr {
+    fn from(lsn: Lsn) -> Self {
+        lsn.0
+    }
+}
+
+impl fmt::Display for Lsn {
+    /// Formats the value the same way Postgres' `pg_lsn_out()` does, such as `16/B374D848`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 & 0xFFFF_FFFF)
+    }
+}
+
+/// The error returned when a string isn't a valid `pg_lsn`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LsnParseError(String);
+
+impl fmt::Display for LsnParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid input syntax for type pg_lsn: \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for LsnParseError {}
+
+impl FromStr for Lsn {
+    type Err = LsnParseError;
+
+    /// Parses the `X/Y` form, where each half is 1 to 8 hexadecimal digits, as Postgres'
+    /// `pg_lsn_in()` does
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let half = |half: &str| {
+            if half.is_empty() || half.len() > 8 || !half.bytes().all(|b| b.is_ascii_hexdigit()) {
+                None
+            } else {
+                u32::from_str_radix(half, 16).ok()
+            }
+        };
+
+        match s.split_once('/') {
+            Some((hi, lo)) => match (half(hi), half(lo)) {
+                (Some(hi), Some(lo)) => Ok(Lsn(((hi as u64) << 32) | lo as u64)),
+                _ => Err(LsnParseError(s.to_string())),
+            },
+            None => Err(LsnParseError(s.to_string())),
+        }
+    }
+}
+
+impl FromDatum for Lsn {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Lsn> {
+        if is_null {
+            None
+        } else {
+            Some(Lsn(datum as pg_sys::XLogRecPtr))
+        }
+    }
+}
+
+impl IntoDatum for Lsn {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0 as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::LSNOID
+    }
+}
@@ -6,35 +6,57 @@
 mod anyarray;
 mod anyelement;
 mod array;
+mod array_builder;
 mod date;
 mod from;
 mod geo;
 mod inet;
 mod internal;
+mod interval;
 mod into;
 mod item_pointer_data;
 mod json;
+mod jsonb_value;
+mod lsn;
+mod money;
+mod name;
 mod numeric;
+mod oidvector;
+mod record_builder;
+mod tid;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
 mod time_with_timezone;
 mod tuples;
+mod type_io;
+mod varbit;
 
 pub use self::time::*;
 pub use anyarray::*;
 pub use anyelement::*;
 pub use array::*;
+pub use array_builder::*;
 pub use date::*;
 pub use from::*;
 pub use geo::*;
 pub use inet::*;
 pub use internal::*;
+pub use interval::*;
 pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
+pub use jsonb_value::*;
+pub use lsn::*;
+pub use money::*;
+pub use name::*;
 pub use numeric::*;
+pub use oidvector::*;
+pub use record_builder::*;
+pub use tid::*;
 pub use time_stamp::*;
 pub use time_stamp_with_timezone::*;
 pub use time_with_timezone::*;
 pub use tuples::*;
+pub use type_io::*;
+pub use varbit::*;
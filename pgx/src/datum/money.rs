@@ -0,0 +1,93 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{direct_function_call, pg_sys, FromDatum, IntoDatum};
+use std::ffi::CStr;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// A Postgres `money` value.
+///
+/// The wrapped `i64` counts the smallest unit of the currency, which depends on the
+/// `lc_monetary` setting.  For most locales that's cents, so `Money(1234)` is `$12.34`.
+///
+/// Arithmetic panics, just as Postgres raises an ERROR, if the result is out of range.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn add_tax(price: Money, percent: i64) -> Money {
+///     price + Money(price.0 * percent / 100)
+/// }
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Money(pub i64);
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0.checked_add(rhs.0).expect("money out of range"))
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0.checked_sub(rhs.0).expect("money out of range"))
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        Money(self.0.checked_mul(rhs).expect("money out of range"))
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.checked_neg().expect("money out of range"))
+    }
+}
+
+impl fmt::Display for Money {
+    /// Formats the value the same way Postgres' `cash_out()` does, according to `lc_monetary`,
+    /// such as `$1,234.56`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cstr = unsafe {
+            direct_function_call::<&CStr>(pg_sys::cash_out, vec![self.into_datum()])
+                .expect("cash_out() returned NULL")
+        };
+        f.write_str(&cstr.to_string_lossy())
+    }
+}
+
+impl FromDatum for Money {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Money> {
+        if is_null {
+            None
+        } else {
+            Some(Money(datum as i64))
+        }
+    }
+}
+
+impl IntoDatum for Money {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0 as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::CASHOID
+    }
+}
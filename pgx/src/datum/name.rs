@@ -0,0 +1,106 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, warning, FromDatum, IntoDatum, PgBox};
+use std::ffi::CStr;
+use std::fmt;
+use std::ops::Deref;
+
+/// A Postgres `name`, the fixed-size type used for identifiers in the system catalogs.
+///
+/// A `name` holds at most `NAMEDATALEN - 1` (63) bytes.  Longer strings are truncated, with a
+/// WARNING, at the last UTF8 character boundary that fits, just like Postgres does.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Name(String);
+
+impl Name {
+    pub fn new(s: &str) -> Self {
+        let max_len = pg_sys::NAMEDATALEN as usize - 1;
+        if s.len() <= max_len {
+            Name(s.to_string())
+        } else {
+            let mut len = max_len;
+            while !s.is_char_boundary(len) {
+                len -= 1;
+            }
+
+            warning!(
+                "name \"{}\" is longer than {} bytes and has been truncated to \"{}\"",
+                s,
+                max_len,
+                &s[..len]
+            );
+            Name(s[..len].to_string())
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(s: &str) -> Self {
+        Name::new(s)
+    }
+}
+
+impl From<String> for Name {
+    fn from(s: String) -> Self {
+        Name::new(&s)
+    }
+}
+
+impl FromDatum for Name {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Name> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("name datum is declared non-null but Datum is zero");
+        } else {
+            let name = datum as *const pg_sys::NameData;
+            let cstr = CStr::from_ptr((*name).data.as_ptr());
+            Some(Name(
+                cstr.to_str()
+                    .expect("unable to convert name into &str")
+                    .to_owned(),
+            ))
+        }
+    }
+}
+
+impl IntoDatum for Name {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        // `Name::new()` guarantees we fit, with room for the trailing NULL
+        let mut src = pg_sys::NameData {
+            data: [0; pg_sys::NAMEDATALEN as usize],
+        };
+        for (dest, byte) in src.data.iter_mut().zip(self.0.as_bytes()) {
+            *dest = *byte as std::os::raw::c_char;
+        }
+
+        let dest = PgBox::<pg_sys::NameData>::alloc0();
+        unsafe {
+            pg_sys::namecpy(dest.as_ptr(), &mut src);
+        }
+        Some(dest.into_pg() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::NAMEOID
+    }
+}
@@ -10,7 +10,7 @@ use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Number;
 use std::fmt;
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Numeric(pub String);
 
 impl<'de> Deserialize<'de> for Numeric {
@@ -109,6 +109,12 @@ impl Into<Numeric> for i64 {
     }
 }
 
+impl Into<Numeric> for i128 {
+    fn into(self) -> Numeric {
+        Numeric(format!("{}", self))
+    }
+}
+
 impl Into<Numeric> for u8 {
     fn into(self) -> Numeric {
         Numeric(format!("{}", self))
@@ -180,3 +186,46 @@ impl IntoDatum for Numeric {
         pg_sys::NUMERICOID
     }
 }
+
+/// `i128` values are represented as Postgres `numeric`s
+impl FromDatum for i128 {
+    /// ## Panics
+    ///
+    /// If the `numeric` is `NaN`, has a non-zero fractional part, or is outside the range of an
+    /// `i128`
+    unsafe fn from_datum(datum: usize, is_null: bool, typoid: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else {
+            let numeric = Numeric::from_datum(datum, false, typoid).unwrap();
+            if numeric.0 == "NaN" {
+                panic!("numeric value NaN can't be converted into an i128");
+            }
+
+            // numeric_out() includes the value's scale, so "42.00" is still a valid i128
+            let integral = match numeric.0.find('.') {
+                Some(idx) if numeric.0[idx + 1..].chars().all(|c| c == '0') => &numeric.0[..idx],
+                Some(_) => panic!("numeric value {} has a fractional part", numeric.0),
+                None => &numeric.0,
+            };
+
+            Some(integral.parse::<i128>().unwrap_or_else(|_| {
+                panic!("numeric value {} is out of range for an i128", numeric.0)
+            }))
+        }
+    }
+}
+
+/// `i128` values are represented as Postgres `numeric`s
+impl IntoDatum for i128 {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Numeric(format!("{}", self)).into_datum()
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::NUMERICOID
+    }
+}
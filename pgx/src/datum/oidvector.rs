@@ -0,0 +1,162 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, FromDatum, IntoDatum};
+use std::ops::Deref;
+
+/// A Postgres `int2vector`, the one-dimensional `int2` array used by catalog columns such as
+/// `pg_index.indkey`.
+///
+/// `Vec<i16>` can also be read from an `int2vector`, but as `Vec<T>` converts into a Postgres
+/// array, returning an `int2vector` from a function requires this type.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let indkey = Spi::get_one::<Vec<i16>>(
+///     "SELECT indkey FROM pg_index WHERE indexrelid = 'pg_class_relname_nsp_index'::regclass",
+/// )
+/// .expect("indkey is NULL");
+/// info!("the index covers attribute numbers {:?}", indkey);
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct Int2Vector(pub Vec<i16>);
+
+/// A Postgres `oidvector`, the one-dimensional `oid` array used by catalog columns such as
+/// `pg_proc.proargtypes`.
+///
+/// `Vec<pg_sys::Oid>` can also be read from an `oidvector`, but as `Vec<T>` converts into a
+/// Postgres array, returning an `oidvector` from a function requires this type.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct OidVector(pub Vec<pg_sys::Oid>);
+
+impl Deref for Int2Vector {
+    type Target = Vec<i16>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for OidVector {
+    type Target = Vec<pg_sys::Oid>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<i16>> for Int2Vector {
+    fn from(values: Vec<i16>) -> Self {
+        Int2Vector(values)
+    }
+}
+
+impl From<Int2Vector> for Vec<i16> {
+    fn from(vector: Int2Vector) -> Self {
+        vector.0
+    }
+}
+
+impl From<Vec<pg_sys::Oid>> for OidVector {
+    fn from(values: Vec<pg_sys::Oid>) -> Self {
+        OidVector(values)
+    }
+}
+
+impl From<OidVector> for Vec<pg_sys::Oid> {
+    fn from(vector: OidVector) -> Self {
+        vector.0
+    }
+}
+
+/// `int2vector` and `oidvector` are laid out exactly like a one-dimensional array without a
+/// NULL bitmap, so this also reads `int2[]` and `oid[]` datums of that shape
+unsafe fn vector_values<T: Copy>(datum: pg_sys::Datum, element_type: pg_sys::Oid) -> Vec<T> {
+    // the fields we need are in the same place for both types, so `oidvector` stands in for
+    // `int2vector` too
+    let vector =
+        pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as *const pg_sys::oidvector;
+
+    if (*vector).ndim == 0 {
+        return Vec::new();
+    } else if (*vector).ndim != 1 || (*vector).dataoffset != 0 {
+        panic!("a vector datum must be one-dimensional and can't contain NULLs");
+    } else if (*vector).elemtype != element_type {
+        panic!(
+            "a vector datum with element type oid {} can't be read as type oid {}",
+            (*vector).elemtype,
+            element_type
+        );
+    }
+
+    std::slice::from_raw_parts(
+        (*vector).values.as_ptr() as *const T,
+        (*vector).dim1 as usize,
+    )
+    .to_vec()
+}
+
+impl FromDatum for Vec<i16> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Vec<i16>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("int2vector datum is declared non-null but Datum is zero");
+        } else {
+            Some(vector_values(datum, pg_sys::INT2OID))
+        }
+    }
+}
+
+impl FromDatum for Vec<pg_sys::Oid> {
+    unsafe fn from_datum(
+        datum: pg_sys::Datum,
+        is_null: bool,
+        _typoid: u32,
+    ) -> Option<Vec<pg_sys::Oid>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("oidvector datum is declared non-null but Datum is zero");
+        } else {
+            Some(vector_values(datum, pg_sys::OIDOID))
+        }
+    }
+}
+
+impl FromDatum for Int2Vector {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<Int2Vector> {
+        Vec::<i16>::from_datum(datum, is_null, typoid).map(Int2Vector)
+    }
+}
+
+impl FromDatum for OidVector {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<OidVector> {
+        Vec::<pg_sys::Oid>::from_datum(datum, is_null, typoid).map(OidVector)
+    }
+}
+
+impl IntoDatum for Int2Vector {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let vector = unsafe { pg_sys::buildint2vector(self.0.as_ptr(), self.0.len() as i32) };
+        Some(vector as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::INT2VECTOROID
+    }
+}
+
+impl IntoDatum for OidVector {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let vector = unsafe { pg_sys::buildoidvector(self.0.as_ptr(), self.0.len() as i32) };
+        Some(vector as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::OIDVECTOROID
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{heap_tuple_get_datum, pg_sys, IntoDatum, PgTupleDesc};
+use std::ffi::CString;
+
+/// Builds anonymous `RECORD`s whose columns are only known at runtime, such as for a function
+/// whose output columns depend on its input.
+///
+/// A function returning a `Record` is declared `RETURNS record` (or `RETURNS SETOF record` when
+/// returning an `impl Iterator<Item = Record>`), so callers must provide a column definition list
+/// that matches the builder's column types.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn powers(n: i32, count: i32) -> Record {
+///     let builder = RecordBuilder::new((1..=count).map(|i| (format!("p{}", i), pg_sys::INT8OID)));
+///     builder.build((1..=count).map(|i| (n as i64).pow(i as u32).into_datum()).collect())
+/// }
+/// ```
+///
+/// ```sql
+/// SELECT * FROM powers(2, 3) AS t(p1 bigint, p2 bigint, p3 bigint);
+/// ```
+pub struct RecordBuilder {
+    tupdesc: PgTupleDesc<'static>,
+}
+
+impl RecordBuilder {
+    /// Create a blessed tuple descriptor, in the `CurrentMemoryContext`, with a column for each
+    /// `(name, type oid)` pair
+    pub fn new<N: AsRef<str>, I: IntoIterator<Item = (N, pg_sys::Oid)>>(columns: I) -> Self {
+        let columns = columns
+            .into_iter()
+            .map(|(name, typoid)| {
+                (
+                    CString::new(name.as_ref()).expect("column name contained a null byte"),
+                    typoid,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            let tupdesc = pg_sys::CreateTemplateTupleDesc(columns.len() as i32);
+            for (i, (name, typoid)) in columns.iter().enumerate() {
+                pg_sys::TupleDescInitEntry(
+                    tupdesc,
+                    (i + 1) as pg_sys::AttrNumber,
+                    name.as_ptr(),
+                    *typoid,
+                    -1,
+                    0,
+                );
+            }
+
+            RecordBuilder {
+                tupdesc: PgTupleDesc::from_pg_is_copy(pg_sys::BlessTupleDesc(tupdesc)),
+            }
+        }
+    }
+
+    /// The blessed tuple descriptor each record is built with
+    pub fn tupdesc(&self) -> &PgTupleDesc<'static> {
+        &self.tupdesc
+    }
+
+    /// Build a record, in the `CurrentMemoryContext`, from a datum (or NULL) per column
+    ///
+    /// ## Panics
+    ///
+    /// If the number of values doesn't match the number of columns
+    pub fn build(&self, values: Vec<Option<pg_sys::Datum>>) -> Record {
+        if values.len() != self.tupdesc.len() {
+            panic!(
+                "expected {} values for a record, but got {}",
+                self.tupdesc.len(),
+                values.len()
+            );
+        }
+
+        let mut nulls = values.iter().map(|v| v.is_none()).collect::<Vec<_>>();
+        let mut datums = values
+            .into_iter()
+            .map(|v| v.unwrap_or(0))
+            .collect::<Vec<_>>();
+        let tuple = unsafe {
+            pg_sys::heap_form_tuple(
+                self.tupdesc.as_ptr(),
+                datums.as_mut_ptr(),
+                nulls.as_mut_ptr(),
+            )
+        };
+
+        Record(heap_tuple_get_datum(tuple))
+    }
+}
+
+/// An anonymous `RECORD` built by a `RecordBuilder`
+#[derive(Debug, Copy, Clone)]
+pub struct Record(pg_sys::Datum);
+
+impl IntoDatum for Record {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.0)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::RECORDOID
+    }
+}
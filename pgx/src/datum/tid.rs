@@ -0,0 +1,112 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{item_pointer_get_both, item_pointer_set_all, pg_sys, FromDatum, IntoDatum};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A Postgres `tid`, the physical location of a row as a (block number, offset number) pair,
+/// such as a table's `ctid` system column.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn next_block(tid: Tid) -> Tid {
+///     Tid::new(tid.block_number() + 1, 1)
+/// }
+/// ```
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Tid(pub pg_sys::ItemPointerData);
+
+impl Tid {
+    /// Create a `Tid` pointing at the row at `offset_number` (starting from 1) on page
+    /// `block_number`
+    pub fn new(block_number: pg_sys::BlockNumber, offset_number: pg_sys::OffsetNumber) -> Self {
+        let mut tid = pg_sys::ItemPointerData::default();
+        item_pointer_set_all(&mut tid, block_number, offset_number);
+        Tid(tid)
+    }
+
+    #[inline]
+    pub fn block_number(&self) -> pg_sys::BlockNumber {
+        item_pointer_get_both(self.0).0
+    }
+
+    #[inline]
+    pub fn offset_number(&self) -> pg_sys::OffsetNumber {
+        item_pointer_get_both(self.0).1
+    }
+
+    /// Is the offset number valid?  An invalid `Tid` doesn't point at any row
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.offset_number() != pg_sys::InvalidOffsetNumber
+    }
+}
+
+impl From<pg_sys::ItemPointerData> for Tid {
+    fn from(tid: pg_sys::ItemPointerData) -> Self {
+        Tid(tid)
+    }
+}
+
+impl From<Tid> for pg_sys::ItemPointerData {
+    fn from(tid: Tid) -> Self {
+        tid.0
+    }
+}
+
+impl PartialEq for Tid {
+    fn eq(&self, other: &Tid) -> bool {
+        item_pointer_get_both(self.0) == item_pointer_get_both(other.0)
+    }
+}
+
+impl Eq for Tid {}
+
+impl PartialOrd for Tid {
+    fn partial_cmp(&self, other: &Tid) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tid {
+    /// Orders by block number and then offset number, as Postgres' `tid` comparison operators do
+    fn cmp(&self, other: &Tid) -> Ordering {
+        item_pointer_get_both(self.0).cmp(&item_pointer_get_both(other.0))
+    }
+}
+
+impl std::hash::Hash for Tid {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        item_pointer_get_both(self.0).hash(state)
+    }
+}
+
+impl fmt::Display for Tid {
+    /// Formats the value the same way Postgres' `tidout()` does, such as `(0,1)`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{})", self.block_number(), self.offset_number())
+    }
+}
+
+impl FromDatum for Tid {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: u32) -> Option<Tid> {
+        pg_sys::ItemPointerData::from_datum(datum, is_null, typoid).map(Tid)
+    }
+}
+
+impl IntoDatum for Tid {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.0.into_datum()
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::TIDOID
+    }
+}
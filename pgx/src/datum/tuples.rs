@@ -1,106 +1,183 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+//! Conversions between tuples of `Option<T>`s, up to an arity of 8, and anonymous `RECORD` Datums
+//! such as `ROW(1, 'two')`.  `None` fields are NULL.
 
-use crate::{pg_sys, FromDatum, IntoDatum};
+use crate::{
+    heap_tuple_get_datum, heap_tuple_header_get_datum_length, heap_tuple_header_get_type_id,
+    heap_tuple_header_get_typmod, pg_sys, FromDatum, IntoDatum, PgBox, PgMemoryContexts,
+    PgTupleDesc,
+};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 
-impl<A, B> IntoDatum for (Option<A>, Option<B>)
-where
-    A: IntoDatum,
-    B: IntoDatum,
-{
-    fn into_datum(self) -> Option<pg_sys::Datum> {
-        let vec = vec![self.0.into_datum(), self.1.into_datum()];
-        vec.into_datum()
-    }
+/// Blessed `RECORD` tuple descriptors, keyed by their attribute types.  These, and the typmods
+/// `BlessTupleDesc()` assigns them, live for the life of the backend.
+static mut RECORD_TUPDESCS: Option<HashMap<Vec<pg_sys::Oid>, pg_sys::TupleDesc>> = None;
 
-    fn type_oid() -> pg_sys::Oid {
-        0
-    }
+macro_rules! record_tuple {
+    ($($t:ident $idx:tt),+) => {
+        impl<$($t),+> IntoDatum for ($(Option<$t>,)+)
+        where
+            $($t: IntoDatum,)+
+        {
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                let types = [$(<$t as IntoDatum>::type_oid()),+];
+                let values = vec![$(self.$idx.into_datum()),+];
+                Some(unsafe { form_record(&types, values) })
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::RECORDOID
+            }
+        }
+
+        impl<$($t),+> FromDatum for ($(Option<$t>,)+)
+        where
+            $($t: FromDatum + IntoDatum,)+
+        {
+            unsafe fn from_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                _typoid: pg_sys::Oid,
+            ) -> Option<Self> {
+                if is_null {
+                    None
+                } else if datum == 0 {
+                    panic!("a record Datum was flagged as non-null but the datum is zero");
+                } else {
+                    let types = [$(<$t as IntoDatum>::type_oid()),+];
+                    let values = deform_record(&types, datum);
+                    Some(($(
+                        match values[$idx] {
+                            Some(value) => $t::from_datum(value, false, types[$idx]),
+                            None => None,
+                        },
+                    )+))
+                }
+            }
+
+            unsafe fn from_datum_in_memory_context(
+                mut memory_context: PgMemoryContexts,
+                datum: pg_sys::Datum,
+                is_null: bool,
+                typoid: pg_sys::Oid,
+            ) -> Option<Self> {
+                if is_null {
+                    None
+                } else if datum == 0 {
+                    panic!("a record Datum was flagged as non-null but the datum is zero");
+                } else {
+                    memory_context.switch_to(|_| {
+                        // copy the record so that fields referencing it, like a `&str`, stay
+                        // valid for the life of this memory context
+                        let copy = pg_sys::pg_detoast_datum_copy(datum as *mut pg_sys::varlena);
+                        Self::from_datum(copy as pg_sys::Datum, false, typoid)
+                    })
+                }
+            }
+        }
+    };
 }
 
-impl<A, B, C> IntoDatum for (Option<A>, Option<B>, Option<C>)
-where
-    A: IntoDatum,
-    B: IntoDatum,
-    C: IntoDatum,
-{
-    fn into_datum(self) -> Option<pg_sys::Datum> {
-        let vec = vec![
-            self.0.into_datum(),
-            self.1.into_datum(),
-            self.2.into_datum(),
-        ];
-        vec.into_datum()
-    }
+record_tuple!(A 0, B 1);
+record_tuple!(A 0, B 1, C 2);
+record_tuple!(A 0, B 1, C 2, D 3);
+record_tuple!(A 0, B 1, C 2, D 3, E 4);
+record_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+record_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+record_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
 
-    fn type_oid() -> pg_sys::Oid {
-        0
-    }
+/// Build a record, in the `CurrentMemoryContext`, with fields of the specified types
+unsafe fn form_record(types: &[pg_sys::Oid], values: Vec<Option<pg_sys::Datum>>) -> pg_sys::Datum {
+    let tupdesc = record_tupdesc(types);
+    let mut nulls = values.iter().map(|v| v.is_none()).collect::<Vec<_>>();
+    let mut datums = values
+        .into_iter()
+        .map(|v| v.unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    let tuple = pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+    heap_tuple_get_datum(tuple)
 }
 
-impl<A, B> FromDatum for (Option<A>, Option<B>)
-where
-    A: FromDatum + IntoDatum,
-    B: FromDatum + IntoDatum,
-{
-    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        let mut vec = Vec::<Option<pg_sys::Datum>>::from_datum(datum, is_null, typoid).unwrap();
-        let b = vec.pop().unwrap();
-        let a = vec.pop().unwrap();
-
-        let a_datum = if a.is_some() {
-            A::from_datum(a.unwrap(), false, A::type_oid())
-        } else {
-            None
-        };
-
-        let b_datum = if b.is_some() {
-            B::from_datum(b.unwrap(), false, B::type_oid())
-        } else {
-            None
-        };
-
-        Some((a_datum, b_datum))
+/// Deform a record into its fields, after checking they're of the specified types
+unsafe fn deform_record(types: &[pg_sys::Oid], datum: pg_sys::Datum) -> Vec<Option<pg_sys::Datum>> {
+    let htup_header =
+        pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as pg_sys::HeapTupleHeader;
+    let tupdesc = PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(
+        heap_tuple_header_get_type_id(htup_header),
+        heap_tuple_header_get_typmod(htup_header),
+    ));
+
+    if tupdesc.len() != types.len() {
+        panic!(
+            "expected a record with {} fields, but it has {}",
+            types.len(),
+            tupdesc.len()
+        );
+    }
+    for (i, (att, expected)) in tupdesc.iter().zip(types).enumerate() {
+        if att.atttypid != *expected {
+            panic!(
+                "record field {} is of type {}, but expected {}",
+                i + 1,
+                type_name(att.atttypid),
+                type_name(*expected)
+            );
+        }
     }
+
+    let mut tuple = PgBox::<pg_sys::HeapTupleData>::alloc0();
+    tuple.t_len = heap_tuple_header_get_datum_length(htup_header) as u32;
+    tuple.t_data = htup_header;
+
+    let mut datums = vec![0 as pg_sys::Datum; types.len()];
+    let mut nulls = vec![false; types.len()];
+    pg_sys::heap_deform_tuple(
+        tuple.as_ptr(),
+        tupdesc.as_ptr(),
+        datums.as_mut_ptr(),
+        nulls.as_mut_ptr(),
+    );
+
+    datums
+        .into_iter()
+        .zip(nulls)
+        .map(|(datum, is_null)| if is_null { None } else { Some(datum) })
+        .collect()
 }
 
-impl<A, B, C> FromDatum for (Option<A>, Option<B>, Option<C>)
-where
-    A: FromDatum + IntoDatum,
-    B: FromDatum + IntoDatum,
-    C: FromDatum + IntoDatum,
-{
-    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        let mut vec = Vec::<Option<pg_sys::Datum>>::from_datum(datum, is_null, typoid).unwrap();
-        let c = vec.pop().unwrap();
-        let b = vec.pop().unwrap();
-        let a = vec.pop().unwrap();
-
-        let a_datum = if a.is_some() {
-            A::from_datum(a.unwrap(), false, A::type_oid())
-        } else {
-            None
-        };
-
-        let b_datum = if b.is_some() {
-            B::from_datum(b.unwrap(), false, B::type_oid())
-        } else {
-            None
-        };
-
-        let c_datum = if c.is_some() {
-            C::from_datum(c.unwrap(), false, C::type_oid())
-        } else {
-            None
-        };
-
-        Some((a_datum, b_datum, c_datum))
+/// Find (or create) the blessed `RECORD` tuple descriptor with fields of the specified types,
+/// named `f1`, `f2`, etc, just like `ROW()` does
+unsafe fn record_tupdesc(types: &[pg_sys::Oid]) -> pg_sys::TupleDesc {
+    let cache = RECORD_TUPDESCS.get_or_insert_with(HashMap::new);
+    if let Some(tupdesc) = cache.get(types) {
+        return *tupdesc;
     }
+
+    let tupdesc = PgMemoryContexts::TopMemoryContext.switch_to(|_| {
+        let tupdesc = pg_sys::CreateTemplateTupleDesc(types.len() as i32);
+        for (i, typoid) in types.iter().enumerate() {
+            let name = CString::new(format!("f{}", i + 1)).unwrap();
+            pg_sys::TupleDescInitEntry(
+                tupdesc,
+                (i + 1) as pg_sys::AttrNumber,
+                name.as_ptr(),
+                *typoid,
+                -1,
+                0,
+            );
+        }
+        pg_sys::BlessTupleDesc(tupdesc)
+    });
+    cache.insert(types.to_vec(), tupdesc);
+    tupdesc
+}
+
+fn type_name(typoid: pg_sys::Oid) -> String {
+    unsafe { CStr::from_ptr(pg_sys::format_type_be(typoid)) }
+        .to_string_lossy()
+        .into_owned()
 }
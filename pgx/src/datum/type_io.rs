@@ -0,0 +1,75 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::pg_sys;
+use std::ffi::{CStr, CString};
+
+/// Convert a datum of any type to its canonical text form, using the type's output function.
+///
+/// A NULL datum (`None`) is returned as `None`.
+///
+/// ## Safety
+///
+/// The datum must be a valid value of the type identified by `type_oid`
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let text = unsafe { datum_to_cstring(42i32.into_datum(), pg_sys::INT4OID) };
+/// assert_eq!(text, Some("42".to_string()));
+/// ```
+pub unsafe fn datum_to_cstring(
+    datum: Option<pg_sys::Datum>,
+    type_oid: pg_sys::Oid,
+) -> Option<String> {
+    let datum = datum?;
+    let mut output_func = pg_sys::InvalidOid;
+    let mut is_varlena = false;
+    pg_sys::getTypeOutputInfo(type_oid, &mut output_func, &mut is_varlena);
+
+    let cstr = pg_sys::OidOutputFunctionCall(output_func, datum);
+    let text = CStr::from_ptr(cstr)
+        .to_str()
+        .expect("type output function returned invalid UTF8")
+        .to_string();
+    pg_sys::pfree(cstr as crate::void_mut_ptr);
+    Some(text)
+}
+
+/// Convert text to a datum of any type, in the `CurrentMemoryContext`, using the type's input
+/// function.  Raises an ERROR if the text isn't valid input for the type.
+///
+/// `None` is converted to a NULL datum (`None`), although the input function is still called
+/// so that, for example, a domain's `NOT NULL` constraint is checked.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let datum = cstring_to_datum(Some("{1,2,NULL}"), pg_sys::INT4ARRAYOID);
+/// let array =
+///     unsafe { Vec::<Option<i32>>::from_datum(datum.unwrap(), false, pg_sys::INT4ARRAYOID) };
+/// assert_eq!(array, Some(vec![Some(1), Some(2), None]));
+/// ```
+pub fn cstring_to_datum(s: Option<&str>, type_oid: pg_sys::Oid) -> Option<pg_sys::Datum> {
+    let s = s.map(|s| CString::new(s).expect("input text contained a null byte"));
+    let mut input_func = pg_sys::InvalidOid;
+    let mut typioparam = pg_sys::InvalidOid;
+
+    unsafe {
+        pg_sys::getTypeInputInfo(type_oid, &mut input_func, &mut typioparam);
+        let datum = pg_sys::OidInputFunctionCall(
+            input_func,
+            s.as_ref().map_or(std::ptr::null_mut(), |s| {
+                s.as_ptr() as *mut std::os::raw::c_char
+            }),
+            typioparam,
+            -1,
+        );
+
+        s.map(|_| datum)
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, set_varsize, void_mut_ptr, FromDatum, IntoDatum};
+use std::fmt;
+use std::iter::FromIterator;
+
+/// A Postgres `bit varying` (or fixed-length `bit`) value: a string of bits.
+///
+/// Bits are numbered from zero, left-to-right, just as they're written in SQL, so the value
+/// `B'100'` has bit 0 set.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn feature_flags(enabled: Array<bool>) -> VarBit {
+///     enabled.iter().map(|bit| bit.unwrap_or(false)).collect()
+/// }
+///
+/// #[pg_extern]
+/// fn count_enabled(flags: VarBit) -> i32 {
+///     flags.iter().filter(|bit| *bit).count() as i32
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct VarBit {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl VarBit {
+    /// An empty bit string
+    pub fn new() -> Self {
+        VarBit::default()
+    }
+
+    /// A bit string of `len` unset bits
+    pub fn with_len(len: usize) -> Self {
+        VarBit {
+            bytes: vec![0; bytes_for(len)],
+            len,
+        }
+    }
+
+    /// How many bits are there?
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bit at `index`, or `None` if `index` is out of bounds
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(self.bytes[index / 8] & mask(index) != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Set the bit at `index` to `value`
+    ///
+    /// ## Panics
+    ///
+    /// If `index` is out of bounds
+    pub fn set(&mut self, index: usize, value: bool) {
+        if index >= self.len {
+            panic!(
+                "bit index {} is out of bounds for a bit string of length {}",
+                index, self.len
+            );
+        }
+
+        if value {
+            self.bytes[index / 8] |= mask(index);
+        } else {
+            self.bytes[index / 8] &= !mask(index);
+        }
+    }
+
+    /// Append a bit to the end
+    pub fn push(&mut self, value: bool) {
+        if self.len % 8 == 0 {
+            self.bytes.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Iterate over each bit, in order
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |index| self.bytes[index / 8] & mask(index) != 0)
+    }
+
+    /// The packed bits, with bit 0 as the high bit of the first byte.  Any unused bits in the
+    /// last byte are zero.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<Vec<bool>> for VarBit {
+    fn from(bits: Vec<bool>) -> Self {
+        bits.into_iter().collect()
+    }
+}
+
+impl From<&[bool]> for VarBit {
+    fn from(bits: &[bool]) -> Self {
+        bits.iter().copied().collect()
+    }
+}
+
+impl From<VarBit> for Vec<bool> {
+    fn from(bits: VarBit) -> Self {
+        bits.iter().collect()
+    }
+}
+
+impl FromIterator<bool> for VarBit {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = VarBit::new();
+        for bit in iter {
+            bits.push(bit);
+        }
+        bits
+    }
+}
+
+impl fmt::Display for VarBit {
+    /// Formats the bits the same way Postgres' `varbit_out()` does, such as `101`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromDatum for VarBit {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<VarBit> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("varbit datum is declared non-null but Datum is zero");
+        } else {
+            let original = datum as *mut pg_sys::varlena;
+            let varlena = pg_sys::pg_detoast_datum(original);
+
+            // mimics the VARBITLEN() and VARBITS() macros from varbit.h
+            let base = varlena as *const u8;
+            let len = (base.add(pg_sys::VARHDRSZ) as *const i32).read_unaligned() as usize;
+            let bits = base.add(pg_sys::VARHDRSZ + std::mem::size_of::<i32>());
+            let bytes = std::slice::from_raw_parts(bits, bytes_for(len)).to_vec();
+
+            if varlena != original {
+                pg_sys::pfree(varlena as void_mut_ptr);
+            }
+
+            Some(VarBit { bytes, len })
+        }
+    }
+}
+
+impl IntoDatum for VarBit {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let header = pg_sys::VARHDRSZ + std::mem::size_of::<i32>();
+        let total = header + self.bytes.len();
+
+        unsafe {
+            let varbit = pg_sys::palloc0(total) as *mut u8;
+            set_varsize(varbit as *mut pg_sys::varlena, total as i32);
+            (varbit.add(pg_sys::VARHDRSZ) as *mut i32).write_unaligned(self.len as i32);
+            std::ptr::copy_nonoverlapping(
+                self.bytes.as_ptr(),
+                varbit.add(header),
+                self.bytes.len(),
+            );
+
+            Some(varbit as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::VARBITOID
+    }
+}
+
+/// the number of bytes needed to hold `len` bits
+fn bytes_for(len: usize) -> usize {
+    (len + 7) / 8
+}
+
+/// the mask for the bit at `index` within its byte.  Postgres stores the first bit in the high
+/// bit of each byte
+fn mask(index: usize) -> u8 {
+    0x80 >> (index % 8) as u8
+}
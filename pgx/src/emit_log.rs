@@ -0,0 +1,157 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe wrapper around Postgres' `emit_log_hook`, for capturing or redirecting log
+//! messages
+use crate::{pg_guard, pg_sys, PgLogLevel};
+use std::borrow::Cow;
+use std::ffi::CStr;
+
+/// A message that Postgres is about to write to the server log
+pub struct LogMessage<'a>(&'a mut pg_sys::ErrorData);
+
+impl<'a> LogMessage<'a> {
+    /// The message's level, such as `PgLogLevel::WARNING`
+    pub fn level(&self) -> PgLogLevel {
+        match self.0.elevel as u32 {
+            pg_sys::DEBUG5 => PgLogLevel::DEBUG5,
+            pg_sys::DEBUG4 => PgLogLevel::DEBUG4,
+            pg_sys::DEBUG3 => PgLogLevel::DEBUG3,
+            pg_sys::DEBUG2 => PgLogLevel::DEBUG2,
+            pg_sys::DEBUG1 => PgLogLevel::DEBUG1,
+            pg_sys::LOG => PgLogLevel::LOG,
+            pg_sys::LOG_SERVER_ONLY => PgLogLevel::LOG_SERVER_ONLY,
+            pg_sys::INFO => PgLogLevel::INFO,
+            pg_sys::NOTICE => PgLogLevel::NOTICE,
+            pg_sys::WARNING => PgLogLevel::WARNING,
+            pg_sys::ERROR => PgLogLevel::ERROR,
+            pg_sys::FATAL => PgLogLevel::FATAL,
+            pg_sys::PANIC => PgLogLevel::PANIC,
+
+            // we can't panic here, so anything unknown is just a LOG
+            _ => PgLogLevel::LOG,
+        }
+    }
+
+    /// The primary message text
+    pub fn message(&self) -> Option<Cow<str>> {
+        cstr_to_str(self.0.message)
+    }
+
+    /// The optional `DETAIL` text
+    pub fn detail(&self) -> Option<Cow<str>> {
+        cstr_to_str(self.0.detail)
+    }
+
+    /// The optional `HINT` text
+    pub fn hint(&self) -> Option<Cow<str>> {
+        cstr_to_str(self.0.hint)
+    }
+
+    /// The five-character `SQLSTATE` code, such as `22012`
+    pub fn sqlstate(&self) -> String {
+        // mimics the `unpack_sql_state()` function from elog.c
+        let mut sqlstate = self.0.sqlerrcode;
+        let mut code = String::with_capacity(5);
+        for _ in 0..5 {
+            code.push((((sqlstate & 0x3F) as u8) + b'0') as char);
+            sqlstate >>= 6;
+        }
+        code
+    }
+
+    /// Will this message be written to the server log?
+    pub fn output_to_server(&self) -> bool {
+        self.0.output_to_server
+    }
+
+    /// Set to `false` to keep this message out of the server log, such as when it's been sent
+    /// elsewhere instead.  This doesn't affect what the client sees.
+    pub fn set_output_to_server(&mut self, output_to_server: bool) {
+        self.0.output_to_server = output_to_server
+    }
+
+    /// Will this message be sent to the client?
+    pub fn output_to_client(&self) -> bool {
+        self.0.output_to_client
+    }
+
+    /// Return the wrapped `pg_sys::ErrorData`
+    pub fn as_ptr(&self) -> *const pg_sys::ErrorData {
+        self.0 as *const pg_sys::ErrorData
+    }
+}
+
+static mut CALLBACKS: Vec<fn(&mut LogMessage)> = Vec::new();
+static mut PREV_EMIT_LOG_HOOK: pg_sys::emit_log_hook_type = None;
+static mut IN_EMIT_LOG_HOOK: bool = false;
+
+/// Register a `callback` that's handed every message before Postgres writes it to the server
+/// log.  Callbacks are called in the order they were registered, and then any `emit_log_hook`
+/// that was installed before the first registration is called.
+///
+/// Postgres only calls the hook for messages that are going to the server log, which by default
+/// is `WARNING` and above (see `log_min_messages`).
+///
+/// Any message raised while a callback is running, including by the callback itself, is not
+/// passed to the callbacks again.  A callback that panics raises an `ERROR` in the middle of
+/// reporting another message, so they really shouldn't.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// fn forward(message: &mut LogMessage) {
+///     if let Some(text) = message.message() {
+///         eprintln!("{}: {}", message.sqlstate(), text);
+///     }
+///     message.set_output_to_server(false);
+/// }
+///
+/// #[pg_guard]
+/// pub extern "C" fn _PG_init() {
+///     register_emit_log_hook(forward);
+/// }
+/// ```
+pub fn register_emit_log_hook(callback: fn(&mut LogMessage)) {
+    unsafe {
+        if CALLBACKS.is_empty() {
+            PREV_EMIT_LOG_HOOK = pg_sys::emit_log_hook.replace(pgx_emit_log_hook);
+        }
+        CALLBACKS.push(callback);
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_emit_log_hook(edata: *mut pg_sys::ErrorData) {
+    if !IN_EMIT_LOG_HOOK {
+        /// resets `IN_EMIT_LOG_HOOK`, even if a callback panics
+        struct Reentrancy;
+        impl Drop for Reentrancy {
+            fn drop(&mut self) {
+                unsafe { IN_EMIT_LOG_HOOK = false }
+            }
+        }
+
+        IN_EMIT_LOG_HOOK = true;
+        let _reentrancy = Reentrancy;
+        let mut message = LogMessage(&mut *edata);
+        for callback in CALLBACKS.iter() {
+            callback(&mut message);
+        }
+    }
+
+    if let Some(prev_hook) = PREV_EMIT_LOG_HOOK {
+        prev_hook(edata);
+    }
+}
+
+#[inline]
+fn cstr_to_str<'a>(ptr: *const std::os::raw::c_char) -> Option<Cow<'a, str>> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy())
+    }
+}
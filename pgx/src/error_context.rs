@@ -0,0 +1,112 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Add `CONTEXT` lines to the messages Postgres reports, like C's `errcontext()` callbacks
+use crate::{pg_guard, pg_sys, void_mut_ptr};
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::AssertUnwindSafe;
+
+struct ContextEntry<'a> {
+    callback: pg_sys::ErrorContextCallback,
+    message: Box<dyn Fn() -> String + 'a>,
+}
+
+/// Pushes an entry onto Postgres' `error_context_stack` and pops it when dropped.
+///
+/// While it's in scope, every message reported via `ereport()` -- including `warning!()`,
+/// `notice!()`, and ERRORs raised by Postgres itself -- gets the closure's message appended as a
+/// `CONTEXT` line.  The closure is called each time a message is reported, not when the guard is
+/// created, so it can describe the current state of the work.
+///
+/// `error!()` and `panic!()` aren't reported until they reach a `#[pg_guard]` boundary, by which
+/// point the guard has been dropped.  So when it's dropped while a panic unwinds, the guard
+/// instead hands its message to `pg_sys::add_panic_context()` for the eventual ERROR.  The closure
+/// must not panic then, as panicking while a panic unwinds aborts the process.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::cell::Cell;
+///
+/// let row = Cell::new(0);
+/// let _context = PgErrorContext::new(|| format!("while processing row {}", row.get()));
+/// for i in 1..=100 {
+///     row.set(i);
+///     if i == 42 {
+///         // CONTEXT:  while processing row 42
+///         ereport(
+///             PgLogLevel::ERROR,
+///             PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+///             "something went wrong",
+///             file!(),
+///             line!(),
+///             column!(),
+///         );
+///     }
+/// }
+/// ```
+pub struct PgErrorContext<'a> {
+    entry: Box<ContextEntry<'a>>,
+}
+
+impl<'a> PgErrorContext<'a> {
+    pub fn new<F: Fn() -> String + 'a>(message: F) -> Self {
+        let mut entry = Box::new(ContextEntry {
+            callback: pg_sys::ErrorContextCallback {
+                previous: unsafe { pg_sys::error_context_stack },
+                callback: Some(pgx_error_context_callback),
+                arg: std::ptr::null_mut(),
+            },
+            message: Box::new(message),
+        });
+
+        // the entry is boxed so that the pointers Postgres holds remain valid when we're moved
+        entry.callback.arg = entry.as_mut() as *mut ContextEntry as void_mut_ptr;
+        unsafe {
+            pg_sys::error_context_stack = &mut entry.callback;
+        }
+
+        PgErrorContext { entry }
+    }
+}
+
+impl Drop for PgErrorContext<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            pg_sys::add_panic_context((self.entry.message)());
+        }
+
+        let ours = &mut self.entry.callback as *mut pg_sys::ErrorContextCallback;
+
+        unsafe {
+            // we're normally on top, but if guards are dropped out of order, unlink ourselves
+            // from wherever we are so that Postgres never sees a dangling entry
+            let mut link = std::ptr::addr_of_mut!(pg_sys::error_context_stack);
+            while !(*link).is_null() {
+                if *link == ours {
+                    *link = (*ours).previous;
+                    break;
+                }
+                link = std::ptr::addr_of_mut!((**link).previous);
+            }
+        }
+    }
+}
+
+/// Called by Postgres while it's building a message's `ErrorData`.  If the closure panics, the
+/// context line is left out rather than raising an ERROR while reporting one.
+#[pg_guard]
+unsafe extern "C" fn pgx_error_context_callback(arg: void_mut_ptr) {
+    let entry = &*(arg as *const ContextEntry);
+    let message = match std::panic::catch_unwind(AssertUnwindSafe(|| (entry.message)())) {
+        Ok(message) => message,
+        Err(_) => return,
+    };
+
+    let message =
+        CString::new(message.replace('\0', "")).expect("message still contained a null byte");
+    pg_sys::set_errcontext_domain(std::ptr::null());
+    pg_sys::errcontext_msg(b"%s\0".as_ptr() as *const c_char, message.as_ptr());
+}
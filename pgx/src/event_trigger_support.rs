@@ -0,0 +1,69 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helper functions for working with custom Rust event trigger functions
+
+use crate::{is_a, pg_sys, PgBox};
+use std::ffi::CStr;
+
+#[inline]
+pub fn called_as_event_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let fcinfo = unsafe { fcinfo.as_ref() }.expect("fcinfo was null");
+    !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_EventTriggerData)
+}
+
+/// A safe wrapper around the `pg_sys::EventTriggerData` passed to an event trigger function.
+///
+/// Functions declared with `#[pg_eventtrigger]` are handed one of these.
+pub struct PgEventTrigger {
+    trigdata: PgBox<pg_sys::EventTriggerData>,
+}
+
+impl PgEventTrigger {
+    /// Wrap the `pg_sys::EventTriggerData` of a function called as an event trigger
+    ///
+    /// ## Panics
+    ///
+    /// If the function was not called as an event trigger
+    pub fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_event_trigger(fcinfo) {
+            panic!("function was not called as an event trigger");
+        }
+
+        let trigdata = unsafe { fcinfo.as_ref() }.unwrap().context as *mut pg_sys::EventTriggerData;
+        PgEventTrigger {
+            trigdata: PgBox::from_pg(trigdata),
+        }
+    }
+
+    /// The event that fired the trigger, such as `ddl_command_start` or `sql_drop`
+    pub fn event(&self) -> &str {
+        unsafe { CStr::from_ptr(self.trigdata.event) }
+            .to_str()
+            .expect("event trigger event name is not valid UTF-8")
+    }
+
+    /// The command tag of the statement that fired the trigger, such as `CREATE TABLE`
+    pub fn tag(&self) -> &str {
+        unsafe { CStr::from_ptr(self.trigdata.tag) }
+            .to_str()
+            .expect("event trigger command tag is not valid UTF-8")
+    }
+
+    /// The parse tree of the statement that fired the trigger.  Use `is_a()` to determine which
+    /// kind of statement it is before casting it.
+    ///
+    /// Postgres makes no promises about its structure, which can change between major versions.
+    pub fn parse_tree(&self) -> Option<PgBox<pg_sys::Node>> {
+        if self.trigdata.parsetree.is_null() {
+            None
+        } else {
+            Some(PgBox::from_pg(self.trigdata.parsetree))
+        }
+    }
+
+    /// Return the wrapped `pg_sys::EventTriggerData`
+    pub fn as_ptr(&self) -> *mut pg_sys::EventTriggerData {
+        self.trigdata.as_ptr()
+    }
+}
@@ -61,6 +61,8 @@ macro_rules! default {
 ///     vec![1, 2, 3].into_iter().zip(vec!["A", "B", "C"].into_iter())
 /// }
 /// ```
+///
+/// To return a single record instead of a set, wrap the tuple in a [`RecordType`].
 #[macro_export]
 macro_rules! name {
     ($name:tt, $ty:ty) => {
@@ -68,6 +70,26 @@ macro_rules! name {
     };
 }
 
+/// Wraps a tuple of `name!()` members so a `#[pg_extern]` function returns it as a single record,
+/// described with OUT parameters, rather than as a one-row `TABLE (...)`
+///
+/// ## Examples
+///
+/// This example will create a SQL function like so:
+///
+/// ```sql
+/// CREATE OR REPLACE FUNCTION get_a_row(OUT id integer, OUT title text) RETURNS record ...;
+/// ```
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// #[pg_extern]
+/// fn get_a_row() -> RecordType<(name!(id, i32), name!(title, &'static str))> {
+///     RecordType((1, "A"))
+/// }
+/// ```
+pub struct RecordType<T>(pub T);
+
 #[macro_export]
 macro_rules! variadic {
     ($ty:ty) => {
@@ -207,6 +229,209 @@ pub fn pg_getarg_cstr<'a>(
     }
 }
 
+/// A handle to the `FunctionCallInfo` of the current function call
+///
+/// A `#[pg_extern]` function can take an `FcInfo` as its last argument to read the collation
+/// it was called with and to inspect its arguments directly.  It isn't part of the function's
+/// SQL signature.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn collation_of(_input: &str, fcinfo: FcInfo) -> pg_sys::Oid {
+///     fcinfo.collation()
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FcInfo(pg_sys::FunctionCallInfo);
+
+impl FcInfo {
+    /// Wrap a `FunctionCallInfo`, which must outlive the returned `FcInfo`
+    pub fn from_ptr(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        FcInfo(fcinfo)
+    }
+
+    pub fn as_ptr(&self) -> pg_sys::FunctionCallInfo {
+        self.0
+    }
+
+    /// The collation the function was called with, or `pg_sys::InvalidOid` if there isn't one
+    pub fn collation(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref() }.unwrap().fncollation
+    }
+
+    /// The number of arguments the function was called with
+    pub fn nargs(&self) -> usize {
+        unsafe { self.0.as_ref() }.unwrap().nargs as usize
+    }
+
+    /// Is the 0-based argument `num` NULL?
+    ///
+    /// ## Panics
+    ///
+    /// If `num` is out of range
+    pub fn arg_is_null(&self, num: usize) -> bool {
+        self.check_arg(num);
+        pg_arg_is_null(self.0, num)
+    }
+
+    /// The type Oid of the 0-based argument `num`, as resolved by the function's call site
+    ///
+    /// ## Panics
+    ///
+    /// If `num` is out of range
+    pub fn arg_type(&self, num: usize) -> pg_sys::Oid {
+        self.check_arg(num);
+        get_getarg_type(self.0, num)
+    }
+
+    /// Convert the 0-based argument `num` into a `T`, or `None` if it's NULL
+    ///
+    /// ## Panics
+    ///
+    /// If `num` is out of range
+    pub fn get_arg<T: FromDatum>(&self, num: usize) -> Option<T> {
+        self.check_arg(num);
+        pg_getarg(self.0, num)
+    }
+
+    /// A value cached in the function's `flinfo->fn_extra`, which lives as long as the function's
+    /// `FmgrInfo`, usually for the duration of the query.  `init` creates the value on the first
+    /// call, and it's dropped when the `fn_mcxt` memory context is deleted.
+    ///
+    /// This can't be used by value-per-call set-returning functions, whose `fn_extra` holds their
+    /// `FuncCallContext`.  Use `PgBox<pg_sys::FuncCallContext>::user_state()` for those instead.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// #[pg_extern]
+    /// fn row_number_ish(fcinfo: FcInfo) -> i64 {
+    ///     let mut count = fcinfo.fn_extra(|| 0i64);
+    ///     *count += 1;
+    ///     *count
+    /// }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If `fn_extra` already holds a value of a different type, or something other than a value
+    /// cached by this function
+    pub fn fn_extra<T: 'static, F: FnOnce() -> T>(&self, init: F) -> PgBox<T> {
+        let flinfo = unsafe { self.0.as_ref() }.unwrap().flinfo;
+        let mut flinfo = PgBox::from_pg(flinfo);
+        let fn_mcxt = flinfo.fn_mcxt;
+        unsafe { cached_state(&mut flinfo.fn_extra, fn_mcxt, init, "fn_extra") }
+    }
+
+    fn check_arg(&self, num: usize) {
+        if num >= self.nargs() {
+            panic!(
+                "argument {} is out of range for a function called with {} arguments",
+                num,
+                self.nargs()
+            );
+        }
+    }
+}
+
+impl PgBox<pg_sys::FuncCallContext> {
+    /// Rust state for a value-per-call set-returning function, such as an iterator, that's kept
+    /// across calls in the `FuncCallContext`'s `user_fctx`.  `init` creates the state, and should
+    /// be called during the first call, between `srf_first_call_init()` and
+    /// `srf_per_call_setup()`.  The state is dropped when the `multi_call_memory_ctx` is deleted,
+    /// which happens in `srf_return_done()` or if the query is aborted.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// #[pg_extern(sql = "CREATE FUNCTION count_to(integer) RETURNS SETOF integer STRICT LANGUAGE c AS 'MODULE_PATHNAME', '@FUNCTION_NAME@';")]
+    /// fn count_to(n: i32, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    ///     if srf_is_first_call(fcinfo) {
+    ///         srf_first_call_init(fcinfo).user_state(|| 1..=n);
+    ///     }
+    ///
+    ///     let mut funcctx = srf_per_call_setup(fcinfo);
+    ///     let next = funcctx.user_state::<std::ops::RangeInclusive<i32>, _>(|| unreachable!()).next();
+    ///     match next {
+    ///         Some(i) => {
+    ///             srf_return_next(fcinfo, &mut funcctx);
+    ///             i.into_datum().unwrap()
+    ///         }
+    ///         None => {
+    ///             srf_return_done(fcinfo, &mut funcctx);
+    ///             pg_return_null(fcinfo)
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If `user_fctx` already holds state of a different type, or something other than state
+    /// created by this function
+    pub fn user_state<T: 'static, F: FnOnce() -> T>(&mut self, init: F) -> PgBox<T> {
+        let multi_call_memory_ctx = self.multi_call_memory_ctx;
+        unsafe {
+            cached_state(
+                &mut self.user_fctx,
+                multi_call_memory_ctx,
+                init,
+                "user_fctx",
+            )
+        }
+    }
+}
+
+/// Rust state kept behind one of Postgres' `void *` function state pointers
+struct CachedState {
+    magic: u32,
+    value: Box<dyn std::any::Any>,
+}
+
+const CACHED_STATE_MAGIC: u32 = 0x5047_5853; // "PGXS"
+
+/// Get the `T` that `slot` points to, creating it with `init` in `memcxt` if `slot` is null
+///
+/// ## Safety
+///
+/// `slot` must be null or have been set by this function, and `memcxt` must be the context that
+/// the state should be dropped with
+unsafe fn cached_state<T: 'static, F: FnOnce() -> T>(
+    slot: &mut void_mut_ptr,
+    memcxt: pg_sys::MemoryContext,
+    init: F,
+    slot_name: &str,
+) -> PgBox<T> {
+    if slot.is_null() {
+        *slot = PgMemoryContexts::For(memcxt).leak_and_drop_on_delete(CachedState {
+            magic: CACHED_STATE_MAGIC,
+            value: Box::new(init()),
+        }) as void_mut_ptr;
+    }
+
+    let state = &mut *(*slot as *mut CachedState);
+    if state.magic != CACHED_STATE_MAGIC {
+        panic!("{} is already in use by something else", slot_name);
+    }
+
+    match state.value.downcast_mut::<T>() {
+        Some(value) => PgBox::from_pg(value as *mut T),
+        None => panic!(
+            "{} holds a value of a different type than {}",
+            slot_name,
+            std::any::type_name::<T>()
+        ),
+    }
+}
+
 #[inline]
 pub fn pg_return_void() -> pg_sys::Datum {
     0 as pg_sys::Datum
@@ -304,6 +529,101 @@ pub fn direct_function_call_as_datum(
     }
 }
 
+/// Call any SQL-callable function, by its Oid, through the function manager.
+///
+/// This mimics the functionality of Postgres' `OidFunctionCallNColl` macros, however the called
+/// function is allowed to return a NULL datum.  If the function is `STRICT` and any argument is
+/// NULL, the function isn't called and `None` is returned.
+///
+/// ## Safety
+///
+/// This function is unsafe as we cannot validate that `args` are of the types the function
+/// expects, nor that its return type is `R`
+///
+/// ## Examples
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// fn compare(cmp_proc: pg_sys::Oid, a: i32, b: i32) -> i32 {
+///     unsafe {
+///         oid_function_call::<i32>(cmp_proc, vec![a.into_datum(), b.into_datum()])
+///             .expect("comparator returned null")
+///     }
+/// }
+/// ```
+pub unsafe fn oid_function_call<R: FromDatum>(
+    func_oid: pg_sys::Oid,
+    args: Vec<Option<pg_sys::Datum>>,
+) -> Option<R> {
+    oid_function_call_coll(func_oid, pg_sys::InvalidOid, args)
+}
+
+/// Same as [oid_function_call] but passes `collation` to the called function
+///
+/// ## Safety
+///
+/// This function is unsafe for the same reasons as [oid_function_call]
+pub unsafe fn oid_function_call_coll<R: FromDatum>(
+    func_oid: pg_sys::Oid,
+    collation: pg_sys::Oid,
+    args: Vec<Option<pg_sys::Datum>>,
+) -> Option<R> {
+    match oid_function_call_as_datum(func_oid, collation, args) {
+        Some(datum) => R::from_datum(datum, false, pg_sys::InvalidOid),
+        None => None,
+    }
+}
+
+/// Same as [oid_function_call_coll] but instead returns the direct `Option<pg_sys::Datum>`
+/// instead of converting it to a value
+///
+/// ## Panics
+///
+/// If the number of arguments provided isn't the number the function accepts.  Parameter
+/// defaults aren't applied when calling a function through the function manager, so every
+/// argument must be provided.
+pub fn oid_function_call_as_datum(
+    func_oid: pg_sys::Oid,
+    collation: pg_sys::Oid,
+    args: Vec<Option<pg_sys::Datum>>,
+) -> Option<pg_sys::Datum> {
+    let mut flinfo = unsafe { std::mem::zeroed::<pg_sys::FmgrInfo>() };
+    unsafe { pg_sys::fmgr_info(func_oid, &mut flinfo) };
+
+    if args.len() != flinfo.fn_nargs as usize {
+        panic!(
+            "function {} accepts {} arguments, but {} were provided",
+            func_oid,
+            flinfo.fn_nargs,
+            args.len()
+        );
+    } else if flinfo.fn_strict && args.iter().any(|arg| arg.is_none()) {
+        return None;
+    }
+
+    let mut null_array = [false; 100usize];
+    let mut arg_array = [0 as pg_sys::Datum; 100usize];
+    let nargs = args.len();
+
+    for (i, datum) in args.into_iter().enumerate() {
+        null_array[i] = datum.is_none();
+        arg_array[i] = datum.unwrap_or(0);
+    }
+
+    let mut fcid = make_function_call_info(nargs, arg_array, null_array);
+    fcid.flinfo = &mut flinfo;
+    fcid.fncollation = collation;
+
+    let func = flinfo.fn_addr.expect("function has no fn_addr");
+    let datum = unsafe { func(fcid.as_ptr()) };
+
+    if fcid.isnull {
+        None
+    } else {
+        Some(datum)
+    }
+}
+
 #[cfg(feature = "pg10")]
 fn make_function_call_info(
     nargs: usize,
@@ -0,0 +1,191 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Call SQL functions by name, for extensions that dispatch to functions chosen at runtime, such
+//! as callbacks named in a configuration table
+use crate::{
+    oid_function_call_coll, pg_sys, FromDatum, IntoDatum, PgOid, PgSqlErrorCode, SqlStateError,
+};
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+/// Why `fn_call()` couldn't call a function.  Each variant holds the signature that was looked
+/// up, such as `"public.my_func(integer, text)"`.
+///
+/// Returning it as the `Err` of a `#[pg_extern]` function's `Result` raises an `ERROR` with the
+/// same SQLSTATE Postgres would use.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FnCallError {
+    /// No function with the name accepts the argument types
+    NotFound(String),
+
+    /// More than one function with the name accepts the argument types equally well
+    Ambiguous(String),
+
+    /// The function is an aggregate, window function, or procedure, or it returns a set
+    NotCallable(String),
+
+    /// The function's arguments are declared with types the provided ones would need to be
+    /// converted to, as with `int8pl()` for `int4` arguments
+    ArgumentTypes(String),
+
+    /// The function doesn't return the requested type
+    ReturnType(String),
+}
+
+impl fmt::Display for FnCallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FnCallError::NotFound(func) => write!(f, "function {} does not exist", func),
+            FnCallError::Ambiguous(func) => write!(f, "function {} is not unique", func),
+            FnCallError::NotCallable(func) => write!(
+                f,
+                "function {} is an aggregate, window function, procedure, or returns a set",
+                func
+            ),
+            FnCallError::ArgumentTypes(func) => write!(
+                f,
+                "function {} requires its arguments to be converted to other types",
+                func
+            ),
+            FnCallError::ReturnType(func) => {
+                write!(f, "function {} doesn't return the requested type", func)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FnCallError {}
+
+impl SqlStateError for FnCallError {
+    fn sqlstate(&self) -> PgSqlErrorCode {
+        match self {
+            FnCallError::NotFound(_) => PgSqlErrorCode::ERRCODE_UNDEFINED_FUNCTION,
+            FnCallError::Ambiguous(_) => PgSqlErrorCode::ERRCODE_AMBIGUOUS_FUNCTION,
+            FnCallError::NotCallable(_) => PgSqlErrorCode::ERRCODE_WRONG_OBJECT_TYPE,
+            FnCallError::ArgumentTypes(_) | FnCallError::ReturnType(_) => {
+                PgSqlErrorCode::ERRCODE_DATATYPE_MISMATCH
+            }
+        }
+    }
+}
+
+/// Call the SQL function `name`, optionally schema-qualified such as `"public.my_func"`, with
+/// `args`, and convert its result into an `R`.
+///
+/// The function is resolved the same way a SQL function call is, in the `search_path` when the
+/// name isn't qualified, except that an argument is only accepted for a parameter declared with
+/// a type it's binary-coercible to, such as `varchar` for `text`.  Likewise, the function must
+/// return a type that's binary-coercible to `R`'s.  Polymorphic functions aren't supported.
+///
+/// If the function is `STRICT` and any argument is NULL, the function isn't called and
+/// `Ok(None)` is returned.  If its return type or any argument's type is collatable, such as
+/// `text`, it's called with the database's default collation.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn run_callback(callback: &str, value: i64) -> Result<Option<bool>, FnCallError> {
+///     fn_call::<bool>(callback, args![value])
+/// }
+/// ```
+///
+/// ## Panics
+///
+/// Postgres raises an `ERROR` if `name` isn't a valid, possibly qualified, name, and the
+/// function itself may raise one
+pub fn fn_call<R: FromDatum + IntoDatum>(
+    name: &str,
+    args: Vec<(PgOid, Option<pg_sys::Datum>)>,
+) -> Result<Option<R>, FnCallError> {
+    let argtypes = args.iter().map(|(oid, _)| oid.value()).collect::<Vec<_>>();
+    let collation = if std::iter::once(R::type_oid())
+        .chain(argtypes.iter().copied())
+        .any(|typoid| unsafe { pg_sys::type_is_collatable(typoid) })
+    {
+        pg_sys::DEFAULT_COLLATION_OID
+    } else {
+        pg_sys::InvalidOid
+    };
+    let funcid = lookup_function(name, argtypes, R::type_oid())?;
+    let args = args.into_iter().map(|(_, datum)| datum).collect();
+
+    // SAFETY:  we've checked that the function accepts the argument types, and that it returns
+    // an `R`
+    Ok(unsafe { oid_function_call_coll::<R>(funcid, collation, args) })
+}
+
+fn lookup_function(
+    name: &str,
+    mut argtypes: Vec<pg_sys::Oid>,
+    rettype: pg_sys::Oid,
+) -> Result<pg_sys::Oid, FnCallError> {
+    let name = CString::new(name).expect("function name contained a null byte");
+    let nargs = argtypes.len();
+
+    unsafe {
+        let funcname = pg_sys::stringToQualifiedNameList(name.as_ptr());
+        let signature = |argtypes: &[pg_sys::Oid]| {
+            let signature = pg_sys::func_signature_string(
+                funcname,
+                nargs as i32,
+                std::ptr::null_mut(),
+                argtypes.as_ptr(),
+            );
+            CStr::from_ptr(signature).to_string_lossy().into_owned()
+        };
+
+        let mut funcid = pg_sys::InvalidOid;
+        let mut func_rettype = pg_sys::InvalidOid;
+        let mut retset = false;
+        let mut nvargs = 0;
+        let mut vatype = pg_sys::InvalidOid;
+        let mut true_typeids = std::ptr::null_mut();
+
+        match pg_sys::func_get_detail(
+            funcname,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            nargs as i32,
+            argtypes.as_mut_ptr(),
+            false,
+            false,
+            &mut funcid,
+            &mut func_rettype,
+            &mut retset,
+            &mut nvargs,
+            &mut vatype,
+            &mut true_typeids,
+            std::ptr::null_mut(),
+        ) {
+            pg_sys::FuncDetailCode_FUNCDETAIL_NORMAL if !retset => {}
+            pg_sys::FuncDetailCode_FUNCDETAIL_NOTFOUND => {
+                return Err(FnCallError::NotFound(signature(&argtypes)))
+            }
+            pg_sys::FuncDetailCode_FUNCDETAIL_MULTIPLE => {
+                return Err(FnCallError::Ambiguous(signature(&argtypes)))
+            }
+            _ => return Err(FnCallError::NotCallable(signature(&argtypes))),
+        }
+
+        if nargs > 0 {
+            let declared = std::slice::from_raw_parts(true_typeids, nargs);
+            if argtypes
+                .iter()
+                .zip(declared)
+                .any(|(&argtype, &declared)| !pg_sys::IsBinaryCoercible(argtype, declared))
+            {
+                return Err(FnCallError::ArgumentTypes(signature(declared)));
+            }
+        }
+
+        if !pg_sys::IsBinaryCoercible(func_rettype, rettype) {
+            return Err(FnCallError::ReturnType(signature(&argtypes)));
+        }
+
+        Ok(funcid)
+    }
+}
@@ -138,6 +138,84 @@ where
     }
 }
 
+/// Hooks for a string GUC defined with `GucRegistry::define_string_guc_with_hooks()`
+///
+/// Both have default implementations that do nothing, so implement only the ones you need.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// static MODE: GucSetting<Option<&'static str>> = GucSetting::new(Some("fast"));
+///
+/// struct ModeHooks;
+/// impl GucStringHooks for ModeHooks {
+///     fn check(value: Option<&str>) -> Result<(), String> {
+///         match value {
+///             Some("fast") | Some("slow") => Ok(()),
+///             _ => Err("mode must be 'fast' or 'slow'".to_string()),
+///         }
+///     }
+///
+///     fn assign(_value: Option<&str>) {
+///         // invalidate whatever was cached for the previous mode
+///     }
+/// }
+///
+/// GucRegistry::define_string_guc_with_hooks::<ModeHooks>(
+///     "myext.mode",
+///     "the mode",
+///     "the mode",
+///     &MODE,
+///     GucContext::Userset,
+/// );
+/// ```
+pub trait GucStringHooks {
+    /// Validate a proposed new value.  Returning `Err` rejects it, and the message becomes the
+    /// error message of the rejected `SET`.
+    fn check(_value: Option<&str>) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when the setting changes to `value`, just before the `GucSetting` itself is updated
+    fn assign(_value: Option<&str>) {}
+}
+
+unsafe fn guc_string_value<'a>(value: *const std::os::raw::c_char) -> Option<&'a str> {
+    if value.is_null() {
+        None
+    } else {
+        Some(
+            CStr::from_ptr(value)
+                .to_str()
+                .expect("GUC value is not valid UTF8"),
+        )
+    }
+}
+
+unsafe extern "C" fn guc_string_check_hook<H: GucStringHooks>(
+    newval: *mut *mut std::os::raw::c_char,
+    _extra: *mut *mut std::os::raw::c_void,
+    _source: pg_sys::GucSource,
+) -> bool {
+    pg_sys::guard(|| match H::check(guc_string_value(*newval)) {
+        Ok(()) => true,
+        Err(message) => {
+            pg_sys::GUC_check_errmsg_string =
+                PgMemoryContexts::CurrentMemoryContext.pstrdup(&message);
+            false
+        }
+    })
+}
+
+unsafe extern "C" fn guc_string_assign_hook<H: GucStringHooks>(
+    newval: *const std::os::raw::c_char,
+    _extra: *mut std::os::raw::c_void,
+) {
+    pg_sys::guard(|| H::assign(guc_string_value(newval)))
+}
+
 pub struct GucRegistry {}
 impl GucRegistry {
     pub fn define_bool_guc(
@@ -196,6 +274,47 @@ impl GucRegistry {
         long_description: &str,
         setting: &GucSetting<Option<&'static str>>,
         context: GucContext,
+    ) {
+        GucRegistry::define_string_guc_internal(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            None,
+            None,
+        )
+    }
+
+    /// Like `::define_string_guc()`, but with the check and assign hooks implemented by `H`
+    ///
+    /// Postgres calls the hooks for the boot value too, while the GUC is being defined.
+    pub fn define_string_guc_with_hooks<H: GucStringHooks>(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<Option<&'static str>>,
+        context: GucContext,
+    ) {
+        GucRegistry::define_string_guc_internal(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            Some(guc_string_check_hook::<H>),
+            Some(guc_string_assign_hook::<H>),
+        )
+    }
+
+    fn define_string_guc_internal(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<Option<&'static str>>,
+        context: GucContext,
+        check_hook: pg_sys::GucStringCheckHook,
+        assign_hook: pg_sys::GucStringAssignHook,
     ) {
         unsafe {
             let boot_value = match setting.value.get() {
@@ -211,8 +330,8 @@ impl GucRegistry {
                 boot_value,
                 context as isize as u32,
                 0,
-                None,
-                None,
+                check_hook,
+                assign_hook,
                 None,
             )
         }
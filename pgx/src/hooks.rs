@@ -129,6 +129,24 @@ pub trait PgHooks {
         prev_hook(parse, cursor_options, bound_params)
     }
 
+    /// Hook for plugins to add paths, such as a `pg_sys::CustomPath`, to a base relation after
+    /// the planner has added its own, via `PgBox<pg_sys::RelOptInfo>::add_path()`
+    fn set_rel_pathlist(
+        &mut self,
+        root: PgBox<pg_sys::PlannerInfo>,
+        rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        rte: PgBox<pg_sys::RangeTblEntry>,
+        prev_hook: fn(
+            root: PgBox<pg_sys::PlannerInfo>,
+            rel: PgBox<pg_sys::RelOptInfo>,
+            rti: pg_sys::Index,
+            rte: PgBox<pg_sys::RangeTblEntry>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(root, rel, rti, rte)
+    }
+
     /// Called when the transaction aborts
     fn abort(&mut self) {}
 
@@ -145,6 +163,7 @@ struct Hooks {
     prev_executor_check_perms_hook: pg_sys::ExecutorCheckPerms_hook_type,
     prev_process_utility_hook: pg_sys::ProcessUtility_hook_type,
     prev_planner_hook: pg_sys::planner_hook_type,
+    prev_set_rel_pathlist_hook: pg_sys::set_rel_pathlist_hook_type,
 }
 
 static mut HOOKS: Option<Hooks> = None;
@@ -177,6 +196,9 @@ pub unsafe fn register_hook(hook: &'static mut (dyn PgHooks)) {
         prev_planner_hook: pg_sys::planner_hook
             .replace(pgx_planner)
             .or(Some(pgx_standard_planner_wrapper)),
+        prev_set_rel_pathlist_hook: pg_sys::set_rel_pathlist_hook
+            .replace(pgx_set_rel_pathlist)
+            .or(Some(pgx_standard_set_rel_pathlist_wrapper)),
     });
 
     unsafe extern "C" fn xact_callback(event: pg_sys::XactEvent, _: void_mut_ptr) {
@@ -381,6 +403,39 @@ unsafe extern "C" fn pgx_planner(
     .inner
 }
 
+#[pg_guard]
+unsafe extern "C" fn pgx_set_rel_pathlist(
+    root: *mut pg_sys::PlannerInfo,
+    rel: *mut pg_sys::RelOptInfo,
+    rti: pg_sys::Index,
+    rte: *mut pg_sys::RangeTblEntry,
+) {
+    fn prev(
+        root: PgBox<pg_sys::PlannerInfo>,
+        rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        rte: PgBox<pg_sys::RangeTblEntry>,
+    ) -> HookResult<()> {
+        unsafe {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_set_rel_pathlist_hook
+                .as_ref()
+                .unwrap())(root.into_pg(), rel.into_pg(), rti, rte.into_pg())
+        }
+        HookResult::new(())
+    }
+    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
+    hook.set_rel_pathlist(
+        PgBox::from_pg(root),
+        PgBox::from_pg(rel),
+        rti,
+        PgBox::from_pg(rte),
+        prev,
+    );
+}
+
 #[pg_guard]
 unsafe extern "C" fn pgx_standard_executor_start_wrapper(
     query_desc: *mut pg_sys::QueryDesc,
@@ -446,3 +501,13 @@ unsafe extern "C" fn pgx_standard_planner_wrapper(
 ) -> *mut pg_sys::PlannedStmt {
     pg_sys::standard_planner(parse, cursor_options, bound_params)
 }
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_set_rel_pathlist_wrapper(
+    _root: *mut pg_sys::PlannerInfo,
+    _rel: *mut pg_sys::RelOptInfo,
+    _rti: pg_sys::Index,
+    _rte: *mut pg_sys::RangeTblEntry,
+) {
+    // there's no standard implementation of this hook
+}
@@ -4,6 +4,139 @@
 //! Utility functions for working with `pg_sys::HeapTuple` and `pg_sys::HeapTupleHeader` structs
 use crate::*;
 
+/// A `pg_sys::HeapTupleData` along with the `PgTupleDesc` that describes its attributes
+pub struct PgHeapTuple<'a> {
+    tuple: PgBox<pg_sys::HeapTupleData>,
+    tupdesc: PgTupleDesc<'a>,
+}
+
+impl<'a> PgHeapTuple<'a> {
+    /// Wrap a `pg_sys::HeapTupleData` whose attributes are described by `tupdesc`
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that the provided tuple is actually described
+    /// by the provided `PgTupleDesc`
+    pub unsafe fn from_heap_tuple(
+        tuple: PgBox<pg_sys::HeapTupleData>,
+        tupdesc: PgTupleDesc<'a>,
+    ) -> Self {
+        PgHeapTuple { tuple, tupdesc }
+    }
+
+    /// Return the wrapped `pg_sys::HeapTuple`
+    pub fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple.as_ptr()
+    }
+
+    /// Return the `PgTupleDesc` describing this tuple's attributes
+    pub fn tupdesc(&self) -> &PgTupleDesc<'a> {
+        &self.tupdesc
+    }
+
+    /// How many attributes does this tuple have?
+    pub fn len(&self) -> usize {
+        self.tupdesc.len()
+    }
+
+    /// Does this tuple have attributes?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find the 1-based attribute number of the (non-dropped) attribute named `name`
+    pub fn attno(&self, name: &str) -> Option<usize> {
+        self.tupdesc.attno(name)
+    }
+
+    /// Get a typed attribute value by its 1-based attribute number
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of bounds
+    pub fn get_by_index<T: FromDatum>(&self, attno: usize) -> Option<T> {
+        if attno < 1 || attno > self.len() {
+            panic!(
+                "attribute number {} is out of bounds for a tuple with {} attributes",
+                attno,
+                self.len()
+            );
+        }
+
+        heap_getattr(&self.tuple, attno, &self.tupdesc)
+    }
+
+    /// Get a typed attribute value by its name
+    ///
+    /// ## Panics
+    ///
+    /// If this tuple has no attribute with the specified name
+    pub fn get_by_name<T: FromDatum>(&self, name: &str) -> Option<T> {
+        match self.attno(name) {
+            Some(attno) => self.get_by_index(attno),
+            None => panic!("tuple has no attribute named {}", name),
+        }
+    }
+}
+
+/// A composite (row-typed) Datum, such as `SELECT t FROM t` returns, as a `PgHeapTuple` whose
+/// `PgTupleDesc` is a copy of the row type's
+impl<'a> FromDatum for PgHeapTuple<'a> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a composite Datum was flagged as non-null but the datum is zero");
+        } else {
+            let htup_header =
+                pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as pg_sys::HeapTupleHeader;
+            let mut tuple = PgBox::<pg_sys::HeapTupleData>::alloc0();
+            tuple.t_len = heap_tuple_header_get_datum_length(htup_header) as u32;
+            tuple.t_data = htup_header;
+
+            let tupdesc = pg_sys::lookup_rowtype_tupdesc_copy(
+                heap_tuple_header_get_type_id(htup_header),
+                heap_tuple_header_get_typmod(htup_header),
+            );
+
+            Some(PgHeapTuple::from_heap_tuple(
+                tuple,
+                PgTupleDesc::from_pg_is_copy(tupdesc),
+            ))
+        }
+    }
+
+    unsafe fn from_datum_in_memory_context(
+        mut memory_context: PgMemoryContexts,
+        datum: pg_sys::Datum,
+        is_null: bool,
+        typoid: u32,
+    ) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a composite Datum was flagged as non-null but the datum is zero");
+        } else {
+            memory_context.switch_to(|_| {
+                // copy the tuple so that it, along with the copy of its TupleDesc, lives in
+                // this memory context
+                let copy = pg_sys::pg_detoast_datum_copy(datum as *mut pg_sys::varlena);
+                Self::from_datum(copy as pg_sys::Datum, false, typoid)
+            })
+        }
+    }
+}
+
+impl<'a> IntoDatum for PgHeapTuple<'a> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(unsafe { pg_sys::heap_copy_tuple_as_datum(self.as_ptr(), self.tupdesc.as_ptr()) })
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::RECORDOID
+    }
+}
+
 /// Given a `pg_sys::Datum` representing a composite row type, return a boxed `HeapTupleData`,
 /// which can be used by the various `heap_getattr` methods
 ///
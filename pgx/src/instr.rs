@@ -0,0 +1,47 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! A timer built on Postgres' `instr_time`, the same clock `EXPLAIN ANALYZE` and
+//! `pg_stat_statements` use
+use crate::pg_sys;
+use std::time::Duration;
+
+extern "C" {
+    fn pgx_INSTR_TIME_SET_CURRENT(time: *mut pg_sys::instr_time);
+    fn pgx_instr_time_elapsed(start: *mut pg_sys::instr_time) -> f64;
+}
+
+/// Measures elapsed time the way Postgres' own instrumentation does, for timing code inside an
+/// extension, such as a hook, consistently with what Postgres reports.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let timer = InstrTimer::start();
+/// Spi::run("SELECT pg_sleep(0.1)");
+/// info!("took {:?}", timer.elapsed());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct InstrTimer {
+    start: pg_sys::instr_time,
+}
+
+impl InstrTimer {
+    /// Start a new timer, via `INSTR_TIME_SET_CURRENT()`
+    pub fn start() -> Self {
+        let mut start = pg_sys::instr_time::default();
+        unsafe { pgx_INSTR_TIME_SET_CURRENT(&mut start) };
+        InstrTimer { start }
+    }
+
+    /// The time elapsed since this timer was started
+    pub fn elapsed(&self) -> Duration {
+        let mut start = self.start;
+        let seconds = unsafe { pgx_instr_time_elapsed(&mut start) };
+
+        // `Duration` can't be negative, which a clock that isn't monotonic could produce
+        Duration::from_secs_f64(seconds.max(0.0))
+    }
+}
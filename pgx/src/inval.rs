@@ -0,0 +1,126 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides safe wrappers around Postgres' relcache and syscache invalidation callbacks, so that
+//! extensions can keep their own caches of catalog-derived data in sync with DDL
+use crate::callbacks::{registered_hooks, BackendHook};
+use crate::{pg_guard, pg_sys, BackendCallbackReceipt};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type RelcacheHooks = Vec<Rc<BackendHook<dyn FnMut(pg_sys::Oid)>>>;
+type SyscacheHooks = HashMap<pg_sys::SysCacheIdentifier, Vec<Rc<BackendHook<dyn FnMut(u32)>>>>;
+
+static mut RELCACHE_HOOKS: Option<RelcacheHooks> = None;
+static mut SYSCACHE_HOOKS: Option<SyscacheHooks> = None;
+
+/// Register a closure to be called, for the life of the backend, whenever a relcache entry is
+/// invalidated, just like `pg_sys::CacheRegisterRelcacheCallback()`.  This is usually done in
+/// `_PG_init()`.
+///
+/// The closure is passed the Oid of the invalidated relation, or `pg_sys::InvalidOid` when every
+/// relation should be considered invalid.  Closures are called in the order in which they were
+/// registered, under `#[pg_guard]`.
+///
+/// Postgres only allows a handful of relcache callbacks per backend, so pgx registers a single
+/// one and dispatches to every closure registered here.
+///
+/// Invalidations are processed at unpredictable times, including while Postgres is in the
+/// middle of building a relcache entry, so the closure must not access the catalogs or do
+/// anything else that could raise an error.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+/// use std::rc::Rc;
+///
+/// let cache = Rc::new(RefCell::new(HashMap::<pg_sys::Oid, String>::new()));
+/// let for_inval = Rc::clone(&cache);
+///
+/// register_relcache_callback(move |relid| {
+///     if relid == pg_sys::InvalidOid {
+///         for_inval.borrow_mut().clear();
+///     } else {
+///         for_inval.borrow_mut().remove(&relid);
+///     }
+/// });
+/// ```
+pub fn register_relcache_callback<F>(f: F) -> BackendCallbackReceipt
+where
+    F: FnMut(pg_sys::Oid) + 'static,
+{
+    #[pg_guard]
+    unsafe extern "C" fn pgx_relcache_callback(_arg: pg_sys::Datum, relid: pg_sys::Oid) {
+        for hook in registered_hooks(RELCACHE_HOOKS.as_mut()) {
+            hook.call(|func| func(relid));
+        }
+    }
+
+    let hooks = unsafe {
+        RELCACHE_HOOKS.get_or_insert_with(|| {
+            pg_sys::CacheRegisterRelcacheCallback(Some(pgx_relcache_callback), 0);
+            Vec::new()
+        })
+    };
+
+    let (hook, receipt) = BackendHook::new(Box::new(f) as Box<dyn FnMut(pg_sys::Oid)>);
+    hooks.push(hook);
+    receipt
+}
+
+/// Register a closure to be called, for the life of the backend, whenever an entry in the
+/// system cache identified by `cache_id` is invalidated, just like
+/// `pg_sys::CacheRegisterSyscacheCallback()`.
+///
+/// The closure is passed the hash value of the invalidated entry's keys, or zero when every
+/// entry of the cache should be considered invalid.  See `register_relcache_callback()` for
+/// details.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// register_syscache_callback(pg_sys::SysCacheIdentifier_PROCOID, |_hashvalue| {
+///     // forget anything cached about functions
+/// });
+/// ```
+pub fn register_syscache_callback<F>(
+    cache_id: pg_sys::SysCacheIdentifier,
+    f: F,
+) -> BackendCallbackReceipt
+where
+    F: FnMut(u32) + 'static,
+{
+    #[pg_guard]
+    unsafe extern "C" fn pgx_syscache_callback(
+        _arg: pg_sys::Datum,
+        cacheid: std::os::raw::c_int,
+        hashvalue: u32,
+    ) {
+        let hooks = registered_hooks(
+            SYSCACHE_HOOKS
+                .as_mut()
+                .and_then(|hooks| hooks.get_mut(&(cacheid as pg_sys::SysCacheIdentifier))),
+        );
+
+        for hook in hooks {
+            hook.call(|func| func(hashvalue));
+        }
+    }
+
+    let hooks = unsafe { SYSCACHE_HOOKS.get_or_insert_with(HashMap::new) };
+    let hooks = hooks.entry(cache_id).or_insert_with(|| {
+        unsafe {
+            pg_sys::CacheRegisterSyscacheCallback(cache_id as i32, Some(pgx_syscache_callback), 0);
+        }
+        Vec::new()
+    });
+
+    let (hook, receipt) = BackendHook::new(Box::new(f) as Box<dyn FnMut(u32)>);
+    hooks.push(hook);
+    receipt
+}
@@ -29,14 +29,23 @@ extern crate bitflags;
 // expose our various derive macros
 pub use pgx_macros::*;
 
+pub mod acl;
 pub mod callbacks;
+pub mod catalog;
+pub mod copy;
 pub mod datum;
+pub mod emit_log;
 pub mod enum_helper;
+pub mod error_context;
+pub mod event_trigger_support;
 pub mod fcinfo;
+pub mod fn_call;
 pub mod guc;
 pub mod hooks;
 pub mod htup;
 pub mod inoutfuncs;
+pub mod instr;
+pub mod inval;
 pub mod itemptr;
 pub mod list;
 #[macro_use]
@@ -45,37 +54,77 @@ pub mod bgworkers;
 pub mod memcxt;
 pub mod namespace;
 pub mod nodes;
+pub mod notify;
+pub mod oids;
 pub mod pgbox;
+pub mod pgstat;
+pub mod planner;
+pub mod query;
 pub mod rel;
+pub mod reloptions;
+pub mod scankey;
+pub mod shmem;
 pub mod spi;
 pub mod stringinfo;
+pub mod syscache;
+#[cfg(feature = "pg12")]
+pub mod tableam;
 pub mod trigger_support;
 pub mod tupdesc;
+pub mod tupleslot;
+pub mod tuplestore;
+pub mod typecache;
 pub mod varlena;
+pub mod version;
+pub mod xact;
 pub mod xid;
+pub mod xlog;
 
 pub use callbacks::*;
+pub use catalog::*;
+pub use copy::*;
 pub use datum::*;
+pub use emit_log::*;
 pub use enum_helper::*;
+pub use error_context::*;
+pub use event_trigger_support::*;
 pub use fcinfo::*;
+pub use fn_call::*;
 pub use guc::*;
 pub use hooks::*;
 pub use htup::*;
 pub use inoutfuncs::*;
+pub use instr::*;
+pub use inval::*;
 pub use itemptr::*;
 pub use list::*;
 pub use log::*;
 pub use memcxt::*;
 pub use namespace::*;
 pub use nodes::{is_a, PgNode, PgNodeFactory}; // be specific since we have multiple versions of these things behind feature gates
+pub use notify::*;
+pub use oids::*;
 pub use pgbox::*;
+pub use pgstat::*;
+pub use query::*;
 pub use rel::*;
+pub use reloptions::*;
+pub use scankey::*;
+pub use shmem::*;
 pub use spi::*;
 pub use stringinfo::*;
+pub use syscache::*;
+#[cfg(feature = "pg12")]
+pub use tableam::*;
 pub use trigger_support::*;
 pub use tupdesc::*;
+pub use tupleslot::*;
+pub use tuplestore::*;
 pub use varlena::*;
+pub use version::*;
+pub use xact::*;
 pub use xid::*;
+pub use xlog::*;
 
 pub use pgx_pg_sys as pg_sys; // the module only, not its contents
 pub use pgx_pg_sys::submodules::*;
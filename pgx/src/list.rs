@@ -6,7 +6,7 @@
 //! It functions similarly to a Rust `Vec`, including Iterator support, but provides separate
 //! understandings of Lists of Oids, Integers, and Pointers.
 
-use crate::{is_a, pg_sys, void_mut_ptr};
+use crate::{is_a, pg_sys, void_mut_ptr, void_ptr, PgMemoryContexts};
 use serde::export::PhantomData;
 
 pub struct PgList<T> {
@@ -98,9 +98,7 @@ impl<T> PgList<T> {
 
     #[inline]
     pub fn get_ptr(&self, i: usize) -> Option<*mut T> {
-        if !self.is_empty() && !is_a(self.list as *mut pg_sys::Node, pg_sys::NodeTag_T_List) {
-            panic!("PgList does not contain pointers")
-        }
+        self.assert_tag(pg_sys::NodeTag_T_List, "pointers");
         if self.list.is_null() || i >= self.len() {
             None
         } else {
@@ -110,10 +108,7 @@ impl<T> PgList<T> {
 
     #[inline]
     pub fn get_int(&self, i: usize) -> Option<i32> {
-        if !self.is_empty() && !is_a(self.list as *mut pg_sys::Node, pg_sys::NodeTag_T_IntList) {
-            panic!("PgList does not contain ints")
-        }
-
+        self.assert_tag(pg_sys::NodeTag_T_IntList, "ints");
         if self.list.is_null() || i >= self.len() {
             None
         } else {
@@ -123,10 +118,7 @@ impl<T> PgList<T> {
 
     #[inline]
     pub fn get_oid(&self, i: usize) -> Option<pg_sys::Oid> {
-        if !self.is_empty() && !is_a(self.list as *mut pg_sys::Node, pg_sys::NodeTag_T_OidList) {
-            panic!("PgList does not contain oids")
-        }
-
+        self.assert_tag(pg_sys::NodeTag_T_OidList, "oids");
         if self.list.is_null() || i >= self.len() {
             None
         } else {
@@ -160,31 +152,67 @@ impl<T> PgList<T> {
 
     #[inline]
     pub fn iter_ptr(&self) -> impl Iterator<Item = *mut T> + '_ {
+        self.assert_tag(pg_sys::NodeTag_T_List, "pointers");
         PgListIteratorPtr {
-            list: &self,
-            pos: 0,
+            cell: self.head_cell(),
+            _list: &self,
         }
     }
 
     #[inline]
     pub fn iter_oid(&self) -> impl Iterator<Item = pg_sys::Oid> + '_ {
+        self.assert_tag(pg_sys::NodeTag_T_OidList, "oids");
         PgListIteratorOid {
-            list: &self,
-            pos: 0,
+            cell: self.head_cell(),
+            _list: &self,
         }
     }
 
     #[inline]
     pub fn iter_int(&self) -> impl Iterator<Item = i32> + '_ {
+        self.assert_tag(pg_sys::NodeTag_T_IntList, "ints");
         PgListIteratorInt {
-            list: &self,
-            pos: 0,
+            cell: self.head_cell(),
+            _list: &self,
         }
     }
 
+    /// Append a pointer to the end of this list.
+    ///
+    /// If the list already exists, the new cell is allocated in the same `MemoryContext` that
+    /// owns the list.  Otherwise a new list is created in `CurrentMemoryContext`
     #[inline]
     pub fn push(&mut self, ptr: *mut T) {
-        self.list = unsafe { pg_sys::lappend(self.list, ptr as void_mut_ptr) };
+        self.assert_tag(pg_sys::NodeTag_T_List, "pointers");
+        let list = self.list;
+        let ptr = ptr as void_mut_ptr;
+        self.list = self
+            .list_context()
+            .switch_to(|_| unsafe { pg_sys::lappend(list, ptr) });
+    }
+
+    /// Append an integer to the end of this list.
+    ///
+    /// Memory is allocated in the same manner as `::push()`
+    #[inline]
+    pub fn push_int(&mut self, value: i32) {
+        self.assert_tag(pg_sys::NodeTag_T_IntList, "ints");
+        let list = self.list;
+        self.list = self
+            .list_context()
+            .switch_to(|_| unsafe { pg_sys::lappend_int(list, value) });
+    }
+
+    /// Append an oid to the end of this list.
+    ///
+    /// Memory is allocated in the same manner as `::push()`
+    #[inline]
+    pub fn push_oid(&mut self, value: pg_sys::Oid) {
+        self.assert_tag(pg_sys::NodeTag_T_OidList, "oids");
+        let list = self.list;
+        self.list = self
+            .list_context()
+            .switch_to(|_| unsafe { pg_sys::lappend_oid(list, value) });
     }
 
     #[inline]
@@ -197,30 +225,62 @@ impl<T> PgList<T> {
 
         tail
     }
+
+    /// The `MemoryContext` in which this list was allocated, or `CurrentMemoryContext` if
+    /// the list is still NIL
+    #[inline]
+    fn list_context(&self) -> PgMemoryContexts {
+        if self.list.is_null() {
+            PgMemoryContexts::CurrentMemoryContext
+        } else {
+            PgMemoryContexts::Of(self.list as void_ptr)
+        }
+    }
+
+    #[inline]
+    fn head_cell(&self) -> *mut pg_sys::ListCell {
+        if self.list.is_null() {
+            std::ptr::null_mut()
+        } else {
+            unsafe { self.list.as_ref() }.unwrap().head
+        }
+    }
+
+    /// panic if this list is not NIL and its NodeTag isn't the one we expect
+    #[inline]
+    fn assert_tag(&self, tag: pg_sys::NodeTag, what: &str) {
+        if !self.is_empty() && !is_a(self.list as *mut pg_sys::Node, tag) {
+            panic!("PgList does not contain {}", what)
+        }
+    }
 }
 
 struct PgListIteratorPtr<'a, T> {
-    list: &'a PgList<T>,
-    pos: usize,
+    cell: *mut pg_sys::ListCell,
+    _list: &'a PgList<T>,
 }
 
 struct PgListIteratorOid<'a, T> {
-    list: &'a PgList<T>,
-    pos: usize,
+    cell: *mut pg_sys::ListCell,
+    _list: &'a PgList<T>,
 }
 
 struct PgListIteratorInt<'a, T> {
-    list: &'a PgList<T>,
-    pos: usize,
+    cell: *mut pg_sys::ListCell,
+    _list: &'a PgList<T>,
 }
 
 impl<'a, T> Iterator for PgListIteratorPtr<'a, T> {
     type Item = *mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = self.list.get_ptr(self.pos);
-        self.pos += 1;
-        result
+        match unsafe { self.cell.as_ref() } {
+            Some(cell) => {
+                self.cell = cell.next;
+                Some(unsafe { cell.data.ptr_value } as *mut T)
+            }
+            None => None,
+        }
     }
 }
 
@@ -228,9 +288,13 @@ impl<'a, T> Iterator for PgListIteratorOid<'a, T> {
     type Item = pg_sys::Oid;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = self.list.get_oid(self.pos);
-        self.pos += 1;
-        result
+        match unsafe { self.cell.as_ref() } {
+            Some(cell) => {
+                self.cell = cell.next;
+                Some(unsafe { cell.data.oid_value })
+            }
+            None => None,
+        }
     }
 }
 
@@ -238,9 +302,13 @@ impl<'a, T> Iterator for PgListIteratorInt<'a, T> {
     type Item = i32;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = self.list.get_int(self.pos);
-        self.pos += 1;
-        result
+        match unsafe { self.cell.as_ref() } {
+            Some(cell) => {
+                self.cell = cell.next;
+                Some(unsafe { cell.data.int_value })
+            }
+            None => None,
+        }
     }
 }
 
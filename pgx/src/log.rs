@@ -5,7 +5,7 @@
 
 /// Postgres' various logging levels
 #[allow(dead_code)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PgLogLevel {
     /// Debugging messages, in categories of decreasing detail
     DEBUG5 = crate::pg_sys::DEBUG5 as isize,
@@ -427,6 +427,12 @@ pub enum PgSqlErrorCode {
     ERRCODE_INDEX_CORRUPTED = MAKE_SQLSTATE('X', 'X', '0', '0', '2') as isize,
 }
 
+impl From<PgSqlErrorCode> for i32 {
+    fn from(code: PgSqlErrorCode) -> Self {
+        code as i32
+    }
+}
+
 #[allow(non_snake_case)]
 #[inline]
 const fn PGSIXBIT(ch: i32) -> i32 {
@@ -525,6 +531,112 @@ pub fn ereport(
     }
 }
 
+/// Emit a Postgres `ereport` message with an optional `DETAIL` line
+///
+/// Messages of level `pg_sys::ERROR` will cause the current transaction to abort
+pub fn ereport_with_detail(
+    level: PgLogLevel,
+    code: PgSqlErrorCode,
+    message: &str,
+    detail: Option<&str>,
+) {
+    use std::ffi::CStr;
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+
+    extern "C" {
+        fn pgx_ereport_detail(level: i32, code: i32, message: *const c_char, detail: *const c_char);
+    }
+
+    let message = match CString::new(message) {
+        Ok(s) => s,
+        Err(_) => CString::from(
+            CStr::from_bytes_with_nul(b"error message was null\0")
+                .expect("hardcoded error message failed"),
+        ),
+    };
+    let detail = detail.map(|detail| {
+        CString::new(detail).unwrap_or_else(|_| {
+            CString::from(
+                CStr::from_bytes_with_nul(b"error detail was null\0")
+                    .expect("hardcoded error message failed"),
+            )
+        })
+    });
+
+    unsafe {
+        crate::guard(|| {
+            pgx_ereport_detail(
+                level as i32,
+                code as i32,
+                message.as_ptr(),
+                detail
+                    .as_ref()
+                    .map_or(std::ptr::null(), |detail| detail.as_ptr()),
+            );
+        });
+    }
+}
+
+/// An error that a `#[pg_extern]` function can return as the `Err` of a `Result<T, E>`, which is
+/// raised as a Postgres `ERROR` with the error's SQLSTATE so that clients can tell errors apart
+/// by their code.  The error's `Display` form is the `ERROR`'s message.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[derive(Debug)]
+/// enum ParseError {
+///     Empty,
+///     Invalid(String),
+/// }
+///
+/// impl std::fmt::Display for ParseError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         match self {
+///             ParseError::Empty => write!(f, "input is empty"),
+///             ParseError::Invalid(input) => write!(f, "invalid input: {}", input),
+///         }
+///     }
+/// }
+///
+/// impl SqlStateError for ParseError {
+///     fn sqlstate(&self) -> PgSqlErrorCode {
+///         match self {
+///             ParseError::Empty => PgSqlErrorCode::ERRCODE_ZERO_LENGTH_CHARACTER_STRING,
+///             ParseError::Invalid(_) => PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+///         }
+///     }
+/// }
+///
+/// #[pg_extern]
+/// fn parse_flag(input: &str) -> Result<bool, ParseError> {
+///     match input {
+///         "" => Err(ParseError::Empty),
+///         "yes" => Ok(true),
+///         "no" => Ok(false),
+///         other => Err(ParseError::Invalid(other.to_string())),
+///     }
+/// }
+/// ```
+pub trait SqlStateError: std::fmt::Display {
+    /// The SQLSTATE to raise this error with
+    fn sqlstate(&self) -> PgSqlErrorCode {
+        PgSqlErrorCode::ERRCODE_INTERNAL_ERROR
+    }
+
+    /// An optional `DETAIL` line for this error
+    fn detail(&self) -> Option<String> {
+        None
+    }
+}
+
+impl SqlStateError for String {}
+
+impl<'a> SqlStateError for &'a str {}
+
 /// Log to Postgres' `debug5` log level
 #[macro_export]
 macro_rules! debug5 {
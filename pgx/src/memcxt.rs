@@ -159,6 +159,38 @@ pub enum PgMemoryContexts {
     },
 }
 
+/// How much memory a `MemoryContext` holds, as reported by its `stats` method.  See
+/// [PgMemoryContexts::mem_allocated()]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct MemoryContextStats {
+    /// The number of blocks the context has allocated from the operating system
+    pub nblocks: usize,
+
+    /// The number of chunks on the context's freelists
+    pub free_chunks: usize,
+
+    /// The total bytes held by the context, including its own overhead
+    pub total_bytes: usize,
+
+    /// Of `total_bytes`, how many are free for reuse by the context
+    pub free_bytes: usize,
+
+    /// `total_bytes` minus `free_bytes`
+    pub used_bytes: usize,
+}
+
+impl From<pg_sys::MemoryContextCounters> for MemoryContextStats {
+    fn from(counters: pg_sys::MemoryContextCounters) -> Self {
+        MemoryContextStats {
+            nblocks: counters.nblocks,
+            free_chunks: counters.freechunks,
+            total_bytes: counters.totalspace,
+            free_bytes: counters.freespace,
+            used_bytes: counters.totalspace - counters.freespace,
+        }
+    }
+}
+
 impl PgMemoryContexts {
     /// Retrieve the underlying Postgres `*mut MemoryContextData`
     ///
@@ -182,6 +214,53 @@ impl PgMemoryContexts {
         }
     }
 
+    /// How much memory this context holds, not counting its children.
+    ///
+    /// Useful for asserting that code doesn't leak, by comparing `used_bytes` before and after.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let stats = PgMemoryContexts::CurrentMemoryContext.mem_allocated();
+    /// info!("{} of {} bytes in use", stats.used_bytes, stats.total_bytes);
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If this is a `::Transient` context, same as `::value()`
+    pub fn mem_allocated(&self) -> MemoryContextStats {
+        let mut totals = pg_sys::MemoryContextCounters::default();
+        unsafe { context_stats(self.value(), &mut totals) };
+        totals.into()
+    }
+
+    /// Like `::mem_allocated()`, but also sums the memory held by every descendant of this
+    /// context
+    ///
+    /// ## Panics
+    ///
+    /// If this is a `::Transient` context, same as `::value()`
+    pub fn mem_allocated_recursive(&self) -> MemoryContextStats {
+        unsafe fn recurse(
+            context: pg_sys::MemoryContext,
+            totals: &mut pg_sys::MemoryContextCounters,
+        ) {
+            context_stats(context, totals);
+
+            let mut child = (*context).firstchild;
+            while !child.is_null() {
+                recurse(child, totals);
+                child = (*child).nextchild;
+            }
+        }
+
+        let mut totals = pg_sys::MemoryContextCounters::default();
+        unsafe { recurse(self.value(), &mut totals) };
+        totals.into()
+    }
+
     /// Run the specified function "within" the `MemoryContext` represented by this enum.
     ///
     /// The important implementation detail is that Postgres' `CurrentMemoryContext` is changed
@@ -244,6 +323,104 @@ impl PgMemoryContexts {
         }
     }
 
+    /// Reset this `MemoryContext`, freeing everything allocated in it (and in its children) and
+    /// running any drop callbacks registered through `::leak_and_drop_on_delete()`
+    ///
+    /// ## Safety
+    ///
+    /// Every `PgBox` and raw pointer into this context is left dangling.  The caller must ensure
+    /// none of them are used again.
+    ///
+    /// ## Panics
+    ///
+    /// If this is a `::Transient` context, same as `::value()`
+    pub unsafe fn reset(&mut self) {
+        pg_sys::MemoryContextReset(self.value());
+    }
+
+    /// Run `f` once for every item of `iter` with this `MemoryContext` as the
+    /// `CurrentMemoryContext`, resetting the context after each call.
+    ///
+    /// This is the "allocate-use-reset" loop Postgres uses for its per-tuple contexts: whatever
+    /// `f` allocates only lives until the end of its iteration, so memory use stays flat no matter
+    /// how many items there are.  `f` returns nothing, so anything it needs to keep must be copied
+    /// into Rust-owned values or into another, longer-lived context.
+    ///
+    /// The context is reset, and the previous `CurrentMemoryContext` restored, after every call,
+    /// even one that panics or raises an ERROR.  Resetting runs the drop callbacks of anything
+    /// `f` leaked into the context with `::leak_and_drop_on_delete()`.
+    ///
+    /// ## Safety
+    ///
+    /// Every `PgBox` and raw pointer `f` allocates in the context dangles once its iteration ends.
+    /// The caller must ensure none of them escape `f`, such as by being pushed onto a `Vec` it
+    /// captured, and that nothing else still points into the context when this is called.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// # let per_row_context: pg_sys::MemoryContext = unimplemented!();
+    /// let mut per_row = PgMemoryContexts::For(per_row_context);
+    /// let mut total = 0;
+    /// unsafe {
+    ///     per_row.reset_each(0..1_000_000, |context, i| {
+    ///         // `copy` is freed after this iteration, so only its length is kept
+    ///         let copy = context.pstrdup(&i.to_string());
+    ///         total += std::ffi::CStr::from_ptr(copy).to_bytes().len();
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If this is a `::Transient` context, same as `::value()`, or if it's the
+    /// `CurrentMemoryContext` or one of its ancestors, as resetting it would free memory the
+    /// caller is still using
+    pub unsafe fn reset_each<I, F>(&mut self, iter: I, mut f: F)
+    where
+        I: IntoIterator,
+        F: FnMut(&mut PgMemoryContexts, I::Item),
+    {
+        /// Restores the previous `CurrentMemoryContext` and resets the per-item context when an
+        /// iteration ends, including when `f` unwinds
+        struct ResetOnDrop {
+            context: pg_sys::MemoryContext,
+            prev_context: pg_sys::MemoryContext,
+        }
+
+        impl Drop for ResetOnDrop {
+            fn drop(&mut self) {
+                unsafe {
+                    pg_sys::CurrentMemoryContext = self.prev_context;
+                    pg_sys::MemoryContextReset(self.context);
+                }
+            }
+        }
+
+        let context = self.value();
+        let mut current = pg_sys::CurrentMemoryContext;
+        while !current.is_null() {
+            if current == context {
+                panic!("cannot reset_each() the CurrentMemoryContext or one of its parents");
+            }
+            current = (*current).parent;
+        }
+
+        for item in iter {
+            let _reset = ResetOnDrop {
+                context,
+                prev_context: pg_sys::CurrentMemoryContext,
+            };
+            pg_sys::CurrentMemoryContext = context;
+
+            guard::guard(std::panic::AssertUnwindSafe(|| {
+                f(&mut PgMemoryContexts::For(context), item)
+            }));
+        }
+    }
+
     /// Duplicate a Rust `&str` into a Postgres-allocated "char *"
     ///
     /// ## Examples
@@ -276,25 +453,71 @@ impl PgMemoryContexts {
     }
 
     pub fn palloc_struct<T>(&mut self) -> *mut T {
-        self.palloc(std::mem::size_of::<T>()) as *mut T
+        self.palloc(slice_size::<T>(1)) as *mut T
     }
 
     pub fn palloc0_struct<T>(&mut self) -> *mut T {
-        self.palloc0(std::mem::size_of::<T>()) as *mut T
+        self.palloc0(slice_size::<T>(1)) as *mut T
     }
 
     /// Allocate a slice in this context, which will be free'd whenever Postgres deletes this MeoryContext
+    ///
+    /// ## Panics
+    ///
+    /// If the slice would exceed Postgres' `MaxAllocSize` (1GB), or if `T` requires a greater
+    /// alignment than Postgres provides (`MAXIMUM_ALIGNOF`)
     pub fn palloc_slice<'a, T>(&mut self, len: usize) -> &'a mut [T] {
-        let buffer = self.palloc(std::mem::size_of::<T>() * len) as *mut T;
+        let buffer = self.palloc(slice_size::<T>(len)) as *mut T;
         unsafe { std::slice::from_raw_parts_mut(buffer, len) }
     }
 
     /// Allocate a slice in this context, where the memory is zero'd, which will be free'd whenever Postgres deletes this MeoryContext
+    ///
+    /// ## Panics
+    ///
+    /// If the slice would exceed Postgres' `MaxAllocSize` (1GB), or if `T` requires a greater
+    /// alignment than Postgres provides (`MAXIMUM_ALIGNOF`)
     pub fn palloc0_slice<'a, T>(&mut self, len: usize) -> &'a mut [T] {
-        let buffer = self.palloc0(std::mem::size_of::<T>() * len) as *mut T;
+        let buffer = self.palloc0(slice_size::<T>(len)) as *mut T;
         unsafe { std::slice::from_raw_parts_mut(buffer, len) }
     }
 
+    /// Resize a slice allocated by `::palloc_slice()` or `::palloc0_slice()` to `len` elements,
+    /// by way of `repalloc()`.  Existing elements are preserved, up to the new length, and any
+    /// added elements are zero'd.
+    ///
+    /// The slice stays in the MemoryContext it was originally allocated in, and `slice` must not
+    /// be used afterwards, as it may have been free'd.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let slice = PgMemoryContexts::CurrentMemoryContext.palloc0_slice::<i32>(2);
+    /// slice[1] = 42;
+    /// let slice = unsafe { PgMemoryContexts::repalloc_slice(slice, 4) };
+    /// assert_eq!(slice, &[0, 42, 0, 0]);
+    /// ```
+    ///
+    /// ## Safety
+    ///
+    /// `slice` must have been allocated by Postgres, and its MemoryContext must still be valid
+    ///
+    /// ## Panics
+    ///
+    /// If the resized slice would exceed Postgres' `MaxAllocSize` (1GB)
+    pub unsafe fn repalloc_slice<'a, T>(slice: &mut [T], len: usize) -> &'a mut [T] {
+        let old_len = slice.len();
+        let buffer =
+            pg_sys::repalloc(slice.as_mut_ptr() as void_mut_ptr, slice_size::<T>(len)) as *mut T;
+
+        if len > old_len {
+            std::ptr::write_bytes(buffer.add(old_len), 0, len - old_len);
+        }
+        std::slice::from_raw_parts_mut(buffer, len)
+    }
+
     /// Allocate memory in this context, which will be free'd whenever Postgres deletes this MeoryContext
     ///
     /// The allocated memory is zero'd
@@ -302,6 +525,22 @@ impl PgMemoryContexts {
         unsafe { pg_sys::MemoryContextAllocZero(self.value(), len) }
     }
 
+    /// Move a Rust value onto the heap and arrange for it to be dropped when this `MemoryContext`
+    /// is reset or deleted, returning a raw pointer to it.
+    ///
+    /// This ties the lifetime of Rust-allocated state (a `HashMap` cache, an iterator, etc) to a
+    /// Postgres `MemoryContext`, by registering a `MemoryContextCallback` that runs `v`'s
+    /// destructor.  The returned pointer is valid until the context is reset or deleted.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let cache = PgMemoryContexts::TopTransactionContext
+    ///     .leak_and_drop_on_delete(HashMap::<i32, String>::new());
+    /// ```
     pub fn leak_and_drop_on_delete<T>(&mut self, v: T) -> *mut T {
         unsafe extern "C" fn drop_on_delete<T>(ptr: void_mut_ptr) {
             let boxed = Box::from_raw(ptr as *mut T);
@@ -319,6 +558,16 @@ impl PgMemoryContexts {
         leaked_ptr
     }
 
+    /// Like `::leak_and_drop_on_delete()`, but returns a mutable reference to the leaked value
+    ///
+    /// ## Safety
+    ///
+    /// The caller must choose a lifetime `'a` that doesn't outlive this `MemoryContext`.  Using the
+    /// reference after the context has been reset or deleted is a use-after-free
+    pub unsafe fn leak_and_drop_on_delete_ref<'a, T>(&mut self, v: T) -> &'a mut T {
+        &mut *self.leak_and_drop_on_delete(v)
+    }
+
     /// helper function
     fn exec_in_context<
         R,
@@ -408,3 +657,52 @@ impl PgMemoryContexts {
         //        context
     }
 }
+
+/// Add the memory held by `context`, but not its children, to `totals`, without printing anything
+#[cfg(feature = "pg10")]
+unsafe fn context_stats(
+    context: pg_sys::MemoryContext,
+    totals: &mut pg_sys::MemoryContextCounters,
+) {
+    if let Some(stats) = (*(*context).methods).stats {
+        stats(context, 0, false, totals);
+    }
+}
+
+/// Add the memory held by `context`, but not its children, to `totals`, without printing anything
+#[cfg(any(feature = "pg11", feature = "pg12"))]
+unsafe fn context_stats(
+    context: pg_sys::MemoryContext,
+    totals: &mut pg_sys::MemoryContextCounters,
+) {
+    if let Some(stats) = (*(*context).methods).stats {
+        stats(context, None, std::ptr::null_mut(), totals);
+    }
+}
+
+/// `MaxAllocSize` from memutils.h, the largest allocation `palloc()` will make
+const MAX_ALLOC_SIZE: usize = 0x3fff_ffff;
+
+/// The number of bytes needed for `len` `T`s, after checking that `palloc()` can provide them
+/// with the proper alignment
+fn slice_size<T>(len: usize) -> usize {
+    let align = std::mem::align_of::<T>();
+    if align > pg_sys::MAXIMUM_ALIGNOF as usize {
+        panic!(
+            "{} requires an alignment of {} bytes, but Postgres only aligns to {}",
+            std::any::type_name::<T>(),
+            align,
+            pg_sys::MAXIMUM_ALIGNOF
+        );
+    }
+
+    match std::mem::size_of::<T>().checked_mul(len) {
+        Some(size) if size <= MAX_ALLOC_SIZE => size,
+        _ => panic!(
+            "{} elements of {} exceed the maximum allocation size of {} bytes",
+            len,
+            std::any::type_name::<T>(),
+            MAX_ALLOC_SIZE
+        ),
+    }
+}
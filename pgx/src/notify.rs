@@ -0,0 +1,91 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Rate-limited log messages, for code that would otherwise report the same problem once per
+//! row and flood the client
+use crate::{elog, PgLogLevel};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+struct RateLimit {
+    emitted_at: Instant,
+    suppressed: u64,
+}
+
+impl RateLimit {
+    fn new(emitted_at: Instant) -> Self {
+        RateLimit {
+            emitted_at,
+            suppressed: 0,
+        }
+    }
+}
+
+static mut RATE_LIMITS: Option<HashMap<&'static str, RateLimit>> = None;
+
+/// Emit `message` at `level`, unless a message with the same `key` was already emitted within
+/// the last `window`.  Returns whether the message was emitted.
+///
+/// When a message is emitted after others with its `key` were suppressed, it says how many
+/// were.  Keys are tracked for the life of the backend, and aren't shared with other backends.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::time::Duration;
+///
+/// #[pg_extern]
+/// fn check_price(price: f64) -> f64 {
+///     if price < 0.0 {
+///         warn_once_per(Duration::from_secs(10), "negative price", "found a negative price");
+///     }
+///     price
+/// }
+/// ```
+///
+/// ## Panics
+///
+/// If `level` is `ERROR` or higher, as those messages abort the transaction and can't flood
+/// anything
+pub fn log_once_per(level: PgLogLevel, window: Duration, key: &'static str, message: &str) -> bool {
+    if level as isize >= PgLogLevel::ERROR as isize {
+        panic!("log_once_per() can't rate-limit {:?} messages", level);
+    }
+
+    let now = Instant::now();
+    let limits = unsafe { RATE_LIMITS.get_or_insert_with(HashMap::new) };
+    let suppressed = match limits.get_mut(key) {
+        Some(limit) if now.duration_since(limit.emitted_at) < window => {
+            limit.suppressed += 1;
+            return false;
+        }
+        Some(limit) => std::mem::replace(limit, RateLimit::new(now)).suppressed,
+        None => {
+            limits.insert(key, RateLimit::new(now));
+            0
+        }
+    };
+
+    if suppressed == 0 {
+        elog(level, message);
+    } else {
+        elog(
+            level,
+            &format!("{} ({} similar messages suppressed)", message, suppressed),
+        );
+    }
+    true
+}
+
+/// Emit `message` as a `NOTICE`, at most once per `window` for each `key`.  See
+/// `log_once_per()` for details.
+pub fn notice_once_per(window: Duration, key: &'static str, message: &str) -> bool {
+    log_once_per(PgLogLevel::NOTICE, window, key, message)
+}
+
+/// Emit `message` as a `WARNING`, at most once per `window` for each `key`.  See
+/// `log_once_per()` for details.
+pub fn warn_once_per(window: Duration, key: &'static str, message: &str) -> bool {
+    log_once_per(PgLogLevel::WARNING, window, key, message)
+}
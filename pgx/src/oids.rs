@@ -0,0 +1,123 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helpers for resolving catalog object names to a [PgOid]
+use crate::{
+    direct_function_call, pg_sys, register_syscache_callback, IntoDatum, PgBuiltInOids, PgOid, Spi,
+};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Resolve a relation name, optionally schema-qualified such as `"public.users"`, to its
+/// [PgOid], by way of Postgres' `to_regclass()` function.  Unqualified names are looked up in
+/// the `search_path`.
+///
+/// Returns `PgOid::InvalidOid` if no such relation exists.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let relid = regclass("pg_catalog.pg_class");
+/// assert_eq!(relid, PgOid::Custom(pg_sys::RelationRelationId));
+/// ```
+pub fn regclass(name: &str) -> PgOid {
+    lookup(pg_sys::to_regclass, name)
+}
+
+/// Resolve a type name, such as `"int4"` or `"varchar(255)"`, to its [PgOid], by way of
+/// Postgres' `to_regtype()` function.  Any type modifiers are ignored.
+///
+/// Returns `PgOid::InvalidOid` if no such type exists.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let typoid = regtype("int4");
+/// assert_eq!(typoid, PgOid::BuiltIn(PgBuiltInOids::INT4OID));
+/// ```
+pub fn regtype(name: &str) -> PgOid {
+    lookup(pg_sys::to_regtype, name)
+}
+
+/// The Oid `extension_type_oid()` resolved a type name to, kept for the life of the backend
+pub struct TypeOidCache {
+    oid: AtomicU32,
+    registered: AtomicBool,
+}
+
+impl Default for TypeOidCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeOidCache {
+    pub const fn new() -> Self {
+        TypeOidCache {
+            oid: AtomicU32::new(pg_sys::InvalidOid),
+            registered: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Resolve the name of a type this extension created, such as a `#[derive(PostgresType)]`, to
+/// its Oid, caching it in `cache` until `pg_type` changes.
+///
+/// A schema-qualified name is resolved as is.  An unqualified name is resolved to the type of
+/// that name which belongs to an extension, wherever it lives, so it's found even when the
+/// extension's schema isn't on the `search_path`.  Only if there's no such type, or more than
+/// one, is it looked up in the `search_path`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// static MY_TYPE: TypeOidCache = TypeOidCache::new();
+/// let typoid = extension_type_oid("mytype", &MY_TYPE);
+/// ```
+pub fn extension_type_oid(name: &str, cache: &'static TypeOidCache) -> pg_sys::Oid {
+    let oid = cache.oid.load(Ordering::Relaxed);
+    if oid != pg_sys::InvalidOid {
+        return oid;
+    }
+
+    if !cache.registered.swap(true, Ordering::Relaxed) {
+        register_syscache_callback(pg_sys::SysCacheIdentifier_TYPEOID, move |_| {
+            cache.oid.store(pg_sys::InvalidOid, Ordering::Relaxed)
+        });
+    }
+
+    let owned = if name.contains('.') {
+        None
+    } else {
+        Spi::get_one_with_args::<pg_sys::Oid>(
+            "SELECT CASE WHEN count(*) = 1 THEN min(t.oid) END \
+               FROM pg_catalog.pg_type t \
+               JOIN pg_catalog.pg_depend d \
+                 ON d.classid = 'pg_catalog.pg_type'::regclass AND d.objid = t.oid AND d.deptype = 'e' \
+              WHERE t.typname = $1",
+            vec![(PgBuiltInOids::TEXTOID.oid(), name.into_datum())],
+        )
+    };
+
+    let oid = match owned {
+        Some(oid) => oid,
+        None => unsafe {
+            direct_function_call::<pg_sys::Oid>(pg_sys::regtypein, vec![name.into_datum()])
+                .expect("failed to lookup typeoid")
+        },
+    };
+    cache.oid.store(oid, Ordering::Relaxed);
+    oid
+}
+
+fn lookup(func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum, name: &str) -> PgOid {
+    match unsafe { direct_function_call::<pg_sys::Oid>(func, vec![name.into_datum()]) } {
+        Some(oid) => PgOid::from(oid),
+        None => PgOid::InvalidOid,
+    }
+}
@@ -267,6 +267,9 @@ impl<T> PgBox<T> {
     }
 
     /// Return the boxed pointer, so that it can be passed back into a Postgres function
+    ///
+    /// This only borrows the pointer.  Ownership of the backing memory is unchanged, so if this
+    /// `PgBox<T>` is owned by Rust, the pointer becomes invalid as soon as the box is dropped
     pub fn as_ptr(&self) -> *mut T {
         let ptr = self.inner.ptr;
         match ptr {
@@ -275,8 +278,24 @@ impl<T> PgBox<T> {
         }
     }
 
+    /// Return the boxed pointer for a Postgres function that intends to modify it in place
+    ///
+    /// Like `::as_ptr()`, this only borrows the pointer and ownership of the backing memory
+    /// is unchanged
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_ptr()
+    }
+
+    /// Is the backing memory owned by Postgres (and therefore not freed on Drop)?
+    pub fn is_owned_by_pg(&self) -> bool {
+        self.inner.allocated_by_pg
+    }
+
     /// Useful for returning the boxed pointer back to Postgres (as a return value, for example).
     ///
+    /// This consumes the `PgBox<T>` and transfers ownership of the backing memory to Postgres,
+    /// where it'll be freed when its owning `MemoryContext` is reset or deleted.
+    ///
     /// The boxed pointer is **not** free'd by Rust
     #[inline]
     pub fn into_pg(mut self) -> *mut T {
@@ -0,0 +1,82 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helpers for surfacing a backend's activity and progress in `pg_stat_activity` and the
+//! `pg_stat_progress_*` views.
+//!
+//! All of these functions silently do nothing when `track_activities` is off.
+use crate::pg_sys;
+
+/// Report `activity` as this backend's current query, and its state as "active", in
+/// `pg_stat_activity`.
+///
+/// Postgres clips the activity to `track_activity_query_size`, at a character boundary.
+///
+/// ## Panics
+///
+/// If `activity` contains a null byte
+pub fn report_activity(activity: &str) {
+    report_activity_state(pg_sys::BackendState_STATE_RUNNING, activity)
+}
+
+/// Same as [report_activity], but with an explicit `pg_sys::BackendState`
+///
+/// ## Panics
+///
+/// If `activity` contains a null byte
+pub fn report_activity_state(state: pg_sys::BackendState, activity: &str) {
+    let activity = std::ffi::CString::new(activity).expect("activity contained a null byte");
+    unsafe { pg_sys::pgstat_report_activity(state, activity.as_ptr()) }
+}
+
+/// Begin reporting progress for a command of type `cmdtype` against the relation `relid`.  Its
+/// progress parameters are reset to zero.
+pub fn start_progress(cmdtype: pg_sys::ProgressCommandType, relid: pg_sys::Oid) {
+    unsafe { pg_sys::pgstat_progress_start_command(cmdtype, relid) }
+}
+
+/// Set the progress parameter at (0-based) `index` to `value`
+///
+/// ## Panics
+///
+/// If `index` is beyond the number of progress parameters Postgres tracks
+pub fn report_progress(index: usize, value: i64) {
+    check_progress_index(index);
+    unsafe { pg_sys::pgstat_progress_update_param(index as i32, value) }
+}
+
+/// Set multiple progress parameters at once, as `(index, value)` pairs, so readers never see a
+/// partial update
+///
+/// ## Panics
+///
+/// If any index is beyond the number of progress parameters Postgres tracks
+pub fn report_progress_multi(params: &[(usize, i64)]) {
+    let indexes = params
+        .iter()
+        .map(|(index, _)| {
+            check_progress_index(*index);
+            *index as i32
+        })
+        .collect::<Vec<_>>();
+    let values = params.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+
+    unsafe {
+        pg_sys::pgstat_progress_update_multi_param(
+            params.len() as i32,
+            indexes.as_ptr(),
+            values.as_ptr(),
+        )
+    }
+}
+
+/// Stop reporting progress for the command started with [start_progress]
+pub fn end_progress() {
+    unsafe { pg_sys::pgstat_progress_end_command() }
+}
+
+fn check_progress_index(index: usize) {
+    if index >= pg_sys::PGSTAT_NUM_PROGRESS_PARAM as usize {
+        panic!("progress parameter index {} is out of bounds", index);
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Accessors for the planner's `pg_sys::PlannerInfo` and `pg_sys::RelOptInfo`, as passed to
+//! `PgHooks::set_rel_pathlist()`
+use crate::{pg_sys, PgBox, PgList};
+
+impl PgBox<pg_sys::PlannerInfo> {
+    /// The query being planned
+    pub fn parse(&self) -> PgBox<pg_sys::Query> {
+        PgBox::from_pg(self.parse)
+    }
+
+    /// The relation for the range table entry numbered `rti`, if it's a base relation that has
+    /// been set up for planning
+    pub fn rel(&self, rti: pg_sys::Index) -> Option<PgBox<pg_sys::RelOptInfo>> {
+        self.simple_array_entry(self.simple_rel_array, rti)
+    }
+
+    /// The range table entry numbered `rti`
+    pub fn rte(&self, rti: pg_sys::Index) -> Option<PgBox<pg_sys::RangeTblEntry>> {
+        self.simple_array_entry(self.simple_rte_array, rti)
+    }
+
+    /// The range table indexes of all the query's base relations
+    pub fn all_baserels(&self) -> Vec<pg_sys::Index> {
+        bitmapset_members(self.all_baserels)
+    }
+
+    fn simple_array_entry<T>(&self, array: *mut *mut T, rti: pg_sys::Index) -> Option<PgBox<T>> {
+        if array.is_null() || rti == 0 || rti as i32 >= self.simple_rel_array_size {
+            return None;
+        }
+
+        let entry = unsafe { *array.add(rti as usize) };
+        if entry.is_null() {
+            None
+        } else {
+            Some(PgBox::from_pg(entry))
+        }
+    }
+}
+
+impl PgBox<pg_sys::RelOptInfo> {
+    /// The range table indexes of the base relations this relation is made of
+    pub fn relids(&self) -> Vec<pg_sys::Index> {
+        bitmapset_members(self.relids)
+    }
+
+    /// The restriction clauses from the query's `WHERE` clause that only reference this relation
+    pub fn baserestrictinfo(&self) -> PgList<pg_sys::RestrictInfo> {
+        PgList::from_pg(self.baserestrictinfo)
+    }
+
+    /// The paths that have been added to this relation so far
+    pub fn pathlist(&self) -> PgList<pg_sys::Path> {
+        PgList::from_pg(self.pathlist)
+    }
+
+    /// Offer `path` as a way to scan this relation, via Postgres' `add_path()`, which takes
+    /// ownership of it.  The path is kept only if it's not dominated by an existing path, and
+    /// it's `pfree()`'d otherwise.
+    ///
+    /// For a `pg_sys::CustomPath`, or any other kind of path, pass a pointer to its leading
+    /// `pg_sys::Path` field.  Paths must be allocated in the planner's memory context, which is
+    /// the `CurrentMemoryContext` when `PgHooks::set_rel_pathlist()` is called.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// # let mut rel: PgBox<pg_sys::RelOptInfo> = unimplemented!();
+    /// let mut cpath = PgBox::<pg_sys::CustomPath>::alloc_node(PgNode::CustomPath);
+    /// cpath.path.pathtype = pg_sys::NodeTag_T_CustomScan;
+    /// cpath.path.parent = rel.as_ptr();
+    /// cpath.path.pathtarget = rel.reltarget;
+    /// // ... set the costs and `cpath.methods` ...
+    /// rel.add_path(PgBox::from_pg(cpath.into_pg() as *mut pg_sys::Path));
+    /// ```
+    pub fn add_path(&mut self, path: PgBox<pg_sys::Path>) {
+        unsafe { pg_sys::add_path(self.as_ptr(), path.into_pg()) }
+    }
+}
+
+fn bitmapset_members(bms: *mut pg_sys::Bitmapset) -> Vec<pg_sys::Index> {
+    let mut members = Vec::new();
+    let mut member = -1;
+    loop {
+        member = unsafe { pg_sys::bms_next_member(bms, member) };
+        if member < 0 {
+            break;
+        }
+        members.push(member as pg_sys::Index);
+    }
+    members
+}
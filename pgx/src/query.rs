@@ -0,0 +1,83 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Access to the text and command tag of the statement the backend is currently executing
+use crate::{pg_sys, void_mut_ptr};
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+
+thread_local! {
+    /// The copy of the query string made by the last call to `set_current_query()`
+    static QUERY_COPY: Cell<*mut std::os::raw::c_char> = Cell::new(std::ptr::null_mut());
+}
+
+/// The text of the query the client sent, which is what Postgres logs and reports as the query
+/// in `pg_stat_activity`.  This is `debug_query_string`, or if that isn't set, the source text
+/// of the active portal.
+///
+/// When called from within a function, this is the entire statement that called it, even if the
+/// function was called through SPI.  It's `None` when no query is being executed, such as in a
+/// background worker that hasn't set one.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// if let Some(query) = current_query() {
+///     info!("running on behalf of: {}", query);
+/// }
+/// ```
+pub fn current_query() -> Option<String> {
+    unsafe {
+        let mut query = pg_sys::debug_query_string;
+        if query.is_null() && !pg_sys::ActivePortal.is_null() {
+            query = (*pg_sys::ActivePortal).sourceText;
+        }
+        cstr_to_string(query)
+    }
+}
+
+/// The command tag of the active portal's statement, such as `SELECT` or `INSERT`, or `None` when
+/// no portal is running
+pub fn current_command_tag() -> Option<String> {
+    unsafe {
+        if pg_sys::ActivePortal.is_null() {
+            None
+        } else {
+            cstr_to_string((*pg_sys::ActivePortal).commandTag)
+        }
+    }
+}
+
+/// Set the query string that Postgres reports for this backend, such as in log messages.  This
+/// is mostly useful for background workers, which otherwise have no query.
+///
+/// The string is copied into the `TopMemoryContext`, and the previous copy made by this function
+/// is freed.  Postgres replaces the query string when the backend starts executing its next
+/// statement.
+///
+/// ## Panics
+///
+/// If `query` contains a null byte
+pub fn set_current_query(query: &str) {
+    let query = CString::new(query).expect("query string contained a null byte");
+
+    unsafe {
+        let copy = pg_sys::MemoryContextStrdup(pg_sys::TopMemoryContext, query.as_ptr());
+        pg_sys::debug_query_string = copy;
+
+        let previous = QUERY_COPY.with(|c| c.replace(copy));
+        if !previous.is_null() {
+            pg_sys::pfree(previous as void_mut_ptr);
+        }
+    }
+}
+
+unsafe fn cstr_to_string(s: *const std::os::raw::c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
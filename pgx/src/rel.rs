@@ -4,8 +4,8 @@
 
 //! Provides a safe wrapper around Postgres' `pg_sys::RelationData` struct
 use crate::{
-    direct_function_call, name_data_to_str, pg_sys, FromDatum, IntoDatum, PgBox, PgList,
-    PgTupleDesc,
+    direct_function_call, name_data_to_str, pg_sys, FromDatum, IntoDatum, PgBox, PgHeapTuple,
+    PgList, PgSysCache, PgTupleDesc,
 };
 use std::ops::Deref;
 
@@ -174,6 +174,29 @@ impl PgRelation {
             .into_iter()
     }
 
+    /// Return an iterator of this relation's indices, opened with `lockmode`, along with their
+    /// `pg_index` details.
+    ///
+    /// Indices that are being built or dropped concurrently are included, and can be skipped
+    /// by filtering on `PgIndex::is_valid()` or `PgIndex::is_ready()`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// # let relid: pg_sys::Oid = unimplemented!();
+    /// let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+    /// for index in relation.indices(pg_sys::AccessShareLock as pg_sys::LOCKMODE) {
+    ///     if index.is_valid() && index.is_unique() {
+    ///         info!("{} is unique on columns {:?}", index.name(), index.key_attnums());
+    ///     }
+    /// }
+    /// ```
+    pub fn indices(&self, lockmode: pg_sys::LOCKMODE) -> impl std::iter::Iterator<Item = PgIndex> {
+        self.indicies(lockmode).map(|relation| PgIndex { relation })
+    }
+
     /// Returned a wrapped `PgTupleDesc`
     ///
     /// The returned `PgTupleDesc` is tied to the lifetime of this `PgRelation` instance.
@@ -191,6 +214,80 @@ impl PgRelation {
         PgTupleDesc::from_relation(&self)
     }
 
+    /// Sequentially scan this relation's tuples, as visible to the current transaction
+    /// snapshot, without going through SPI or the planner.
+    ///
+    /// Each tuple is copied into the `CurrentMemoryContext`.  The scan is ended when the returned
+    /// iterator is dropped.  The caller should already hold at least `AccessShareLock` on the
+    /// relation, such as by opening it with `PgRelation::with_lock()`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// # let relid: pg_sys::Oid = unimplemented!();
+    /// let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+    /// for tuple in relation.scan() {
+    ///     let id = tuple.get_by_name::<i32>("id");
+    /// }
+    /// ```
+    pub fn scan(&self) -> PgHeapScan {
+        self.scan_with_keys(Vec::new())
+    }
+
+    /// Like `::scan()`, but only returns tuples that satisfy every one of `keys`, which are
+    /// usually built with `ScanKeys`
+    pub fn scan_with_keys(&self, mut keys: Vec<pg_sys::ScanKeyData>) -> PgHeapScan {
+        let nkeys = keys.len() as i32;
+        let keys_ptr = if keys.is_empty() {
+            std::ptr::null_mut()
+        } else {
+            keys.as_mut_ptr()
+        };
+
+        unsafe {
+            let snapshot = pg_sys::RegisterSnapshot(pg_sys::GetTransactionSnapshot());
+
+            #[cfg(any(feature = "pg10", feature = "pg11"))]
+            let scan = PgHeapScan {
+                relation: self,
+                scan: pg_sys::heap_beginscan(self.as_ptr(), snapshot, nkeys, keys_ptr),
+                snapshot,
+                _keys: keys,
+            };
+
+            #[cfg(feature = "pg12")]
+            let scan = {
+                // this is what the static inline table_beginscan() does
+                let flags = pg_sys::ScanOptions_SO_TYPE_SEQSCAN
+                    | pg_sys::ScanOptions_SO_ALLOW_STRAT
+                    | pg_sys::ScanOptions_SO_ALLOW_SYNC
+                    | pg_sys::ScanOptions_SO_ALLOW_PAGEMODE;
+                let scan_begin = (*self.rd_tableam)
+                    .scan_begin
+                    .expect("table access method has no scan_begin");
+
+                PgHeapScan {
+                    relation: self,
+                    scan: scan_begin(
+                        self.as_ptr(),
+                        snapshot,
+                        nkeys,
+                        keys_ptr,
+                        std::ptr::null_mut(),
+                        flags,
+                    ),
+                    slot: pg_sys::table_slot_create(self.as_ptr(), std::ptr::null_mut()),
+                    snapshot,
+                    _keys: keys,
+                }
+            };
+
+            scan
+        }
+    }
+
     /// Number of tuples in this relation (not always up-to-date)
     pub fn reltuples(&self) -> Option<f32> {
         let reltuples = unsafe { self.boxed.rd_rel.as_ref() }
@@ -265,6 +362,80 @@ impl PgRelation {
     }
 }
 
+/// An index relation, as returned by `PgRelation::indices()`.  It dereferences to its `PgRelation`
+pub struct PgIndex {
+    relation: PgRelation,
+}
+
+impl PgIndex {
+    pub fn into_relation(self) -> PgRelation {
+        self.relation
+    }
+
+    /// The index's `pg_index` catalog entry
+    pub fn form(&self) -> &pg_sys::FormData_pg_index {
+        unsafe { self.relation.rd_index.as_ref() }.expect("rd_index is NULL")
+    }
+
+    /// Can the index be used for queries?
+    pub fn is_valid(&self) -> bool {
+        self.form().indisvalid
+    }
+
+    /// Is the index ready for inserts?
+    pub fn is_ready(&self) -> bool {
+        self.form().indisready
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.form().indisunique
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.form().indisprimary
+    }
+
+    /// The heap attribute numbers of the index's key columns, in order.  An expression column's
+    /// attribute number is zero.  Non-key `INCLUDE` columns aren't returned.
+    pub fn key_attnums(&self) -> Vec<pg_sys::AttrNumber> {
+        let form = self.form();
+        unsafe { form.indkey.values.as_slice(index_key_count(form)) }.to_vec()
+    }
+
+    /// The operator class of each of the index's key columns, in order
+    pub fn opclasses(&self) -> Vec<pg_sys::Oid> {
+        let form = self.form();
+        let tuple = PgSysCache::search_form::<pg_sys::FormData_pg_index>(form.indexrelid)
+            .expect("index not found in the system cache");
+
+        // indclass is an oidvector, which isn't ever toasted
+        let indclass = unsafe {
+            tuple.get_attr::<pg_sys::Datum>(pg_sys::Anum_pg_index_indclass as pg_sys::AttrNumber)
+        }
+        .expect("indclass is NULL") as *const pg_sys::oidvector;
+
+        unsafe { (*indclass).values.as_slice(index_key_count(form)) }.to_vec()
+    }
+}
+
+#[cfg(feature = "pg10")]
+fn index_key_count(form: &pg_sys::FormData_pg_index) -> usize {
+    form.indnatts as usize
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12"))]
+fn index_key_count(form: &pg_sys::FormData_pg_index) -> usize {
+    form.indnkeyatts as usize
+}
+
+impl Deref for PgIndex {
+    type Target = PgRelation;
+
+    fn deref(&self) -> &Self::Target {
+        &self.relation
+    }
+}
+
 impl Clone for PgRelation {
     /// Same as calling `PgRelation::with_lock(AccessShareLock)` on the underlying relation id
     fn clone(&self) -> Self {
@@ -317,3 +488,87 @@ impl Drop for PgRelation {
         }
     }
 }
+
+/// An iterator over the tuples of a `PgRelation`, returned by `PgRelation::scan()`
+pub struct PgHeapScan<'a> {
+    relation: &'a PgRelation,
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    scan: pg_sys::HeapScanDesc,
+    #[cfg(feature = "pg12")]
+    scan: pg_sys::TableScanDesc,
+    #[cfg(feature = "pg12")]
+    slot: *mut pg_sys::TupleTableSlot,
+    snapshot: pg_sys::Snapshot,
+
+    // the scan refers to these for as long as it's open
+    _keys: Vec<pg_sys::ScanKeyData>,
+}
+
+impl<'a> Iterator for PgHeapScan<'a> {
+    type Item = PgHeapTuple<'a>;
+
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let tuple = pg_sys::heap_getnext(self.scan, pg_sys::ScanDirection_ForwardScanDirection);
+            if tuple.is_null() {
+                None
+            } else {
+                Some(PgHeapTuple::from_heap_tuple(
+                    PgBox::from_pg(pg_sys::heap_copytuple(tuple)),
+                    PgTupleDesc::from_relation(self.relation),
+                ))
+            }
+        }
+    }
+
+    #[cfg(feature = "pg12")]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let getnextslot = (*self.relation.rd_tableam)
+                .scan_getnextslot
+                .expect("table access method has no scan_getnextslot");
+
+            if !getnextslot(
+                self.scan,
+                pg_sys::ScanDirection_ForwardScanDirection,
+                self.slot,
+            ) {
+                return None;
+            }
+
+            let mut should_free = false;
+            let tuple = pg_sys::ExecFetchSlotHeapTuple(self.slot, false, &mut should_free);
+            let copy = pg_sys::heap_copytuple(tuple);
+            if should_free {
+                pg_sys::heap_freetuple(tuple);
+            }
+
+            Some(PgHeapTuple::from_heap_tuple(
+                PgBox::from_pg(copy),
+                PgTupleDesc::from_relation(self.relation),
+            ))
+        }
+    }
+}
+
+impl<'a> Drop for PgHeapScan<'a> {
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::heap_endscan(self.scan);
+            pg_sys::UnregisterSnapshot(self.snapshot);
+        }
+    }
+
+    #[cfg(feature = "pg12")]
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::ExecDropSingleTupleTableSlot(self.slot);
+            if let Some(scan_end) = (*self.relation.rd_tableam).scan_end {
+                scan_end(self.scan);
+            }
+            pg_sys::UnregisterSnapshot(self.snapshot);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Declare and parse the `WITH (...)` options (reloptions) of a custom access method's relations
+
+use crate::{pg_sys, void_mut_ptr};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// The value of an option that isn't set in a relation's reloptions
+#[derive(Debug, Clone, PartialEq)]
+enum RelOptionDefault {
+    Bool(bool),
+    Int(i32),
+    Real(f64),
+    String(Option<String>),
+}
+
+#[derive(Debug)]
+struct RelOption {
+    name: CString,
+    opttype: pg_sys::relopt_type,
+    offset: usize,
+    default: RelOptionDefault,
+}
+
+/// Declares the options an access method accepts, as a new `relopt_kind`, and lays them out in the
+/// `bytea` that Postgres caches in `RelationData.rd_options`.
+///
+/// Options must be declared once per backend, usually in `_PG_init()`, and the builder kept for
+/// the life of the backend so the AM's `amoptions` callback can call `::parse()`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// static mut OPTIONS: Option<RelOptionsBuilder> = None;
+///
+/// #[pg_guard]
+/// pub extern "C" fn _PG_init() {
+///     let options = RelOptionsBuilder::new()
+///         .add_int("pages_per_range", "Pages summarized by each index entry", 128, 1, 131072)
+///         .add_bool("autosummarize", "Summarize new ranges automatically", false);
+///     unsafe { OPTIONS = Some(options) }
+/// }
+///
+/// // the IndexAmRoutine's amoptions callback
+/// #[pg_guard]
+/// extern "C" fn amoptions(reloptions: pg_sys::Datum, validate: bool) -> *mut pg_sys::bytea {
+///     unsafe { OPTIONS.as_ref().unwrap() }.parse(reloptions, validate)
+/// }
+///
+/// fn pages_per_range(index: &PgRelation) -> i32 {
+///     unsafe { OPTIONS.as_ref().unwrap() }
+///         .options(index.rd_options)
+///         .get_int("pages_per_range")
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RelOptionsBuilder {
+    kind: pg_sys::relopt_kind,
+    options: Vec<RelOption>,
+
+    // the options start after the bytea's 4-byte varlena header
+    size: usize,
+}
+
+impl Default for RelOptionsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RelOptionsBuilder {
+    /// Allocate a new `relopt_kind` to declare options for
+    pub fn new() -> Self {
+        RelOptionsBuilder {
+            kind: unsafe { pg_sys::add_reloption_kind() },
+            options: Vec::new(),
+            size: std::mem::size_of::<i32>(),
+        }
+    }
+
+    /// The `relopt_kind` these options are declared as
+    pub fn kind(&self) -> pg_sys::relopt_kind {
+        self.kind
+    }
+
+    pub fn add_bool(self, name: &str, desc: &str, default: bool) -> Self {
+        let (name, desc) = names(name, desc);
+        unsafe {
+            pg_sys::add_bool_reloption(
+                self.kind,
+                name.as_ptr() as *mut c_char,
+                desc.as_ptr() as *mut c_char,
+                default,
+            )
+        }
+        self.push(
+            name,
+            pg_sys::relopt_type_RELOPT_TYPE_BOOL,
+            std::mem::size_of::<bool>(),
+            RelOptionDefault::Bool(default),
+        )
+    }
+
+    /// Declare an integer option, which must be between `min` and `max`, inclusive
+    pub fn add_int(self, name: &str, desc: &str, default: i32, min: i32, max: i32) -> Self {
+        let (name, desc) = names(name, desc);
+        unsafe {
+            pg_sys::add_int_reloption(
+                self.kind,
+                name.as_ptr() as *mut c_char,
+                desc.as_ptr() as *mut c_char,
+                default,
+                min,
+                max,
+            )
+        }
+        self.push(
+            name,
+            pg_sys::relopt_type_RELOPT_TYPE_INT,
+            std::mem::size_of::<i32>(),
+            RelOptionDefault::Int(default),
+        )
+    }
+
+    /// Declare a floating point option, which must be between `min` and `max`, inclusive
+    pub fn add_real(self, name: &str, desc: &str, default: f64, min: f64, max: f64) -> Self {
+        let (name, desc) = names(name, desc);
+        unsafe {
+            pg_sys::add_real_reloption(
+                self.kind,
+                name.as_ptr() as *mut c_char,
+                desc.as_ptr() as *mut c_char,
+                default,
+                min,
+                max,
+            )
+        }
+        self.push(
+            name,
+            pg_sys::relopt_type_RELOPT_TYPE_REAL,
+            std::mem::size_of::<f64>(),
+            RelOptionDefault::Real(default),
+        )
+    }
+
+    pub fn add_string(self, name: &str, desc: &str, default: Option<&str>) -> Self {
+        let (name, desc) = names(name, desc);
+        let default_cstr =
+            default.map(|default| CString::new(default).expect("default contained a null byte"));
+        unsafe {
+            pg_sys::add_string_reloption(
+                self.kind,
+                name.as_ptr() as *mut c_char,
+                desc.as_ptr() as *mut c_char,
+                default_cstr
+                    .as_ref()
+                    .map_or(std::ptr::null_mut(), |d| d.as_ptr() as *mut c_char),
+                None,
+            )
+        }
+
+        // the option's value is stored as the offset of the string within the bytea
+        self.push(
+            name,
+            pg_sys::relopt_type_RELOPT_TYPE_STRING,
+            std::mem::size_of::<i32>(),
+            RelOptionDefault::String(default.map(|d| d.to_string())),
+        )
+    }
+
+    fn push(
+        mut self,
+        name: CString,
+        opttype: pg_sys::relopt_type,
+        size: usize,
+        default: RelOptionDefault,
+    ) -> Self {
+        if self.options.iter().any(|option| option.name == name) {
+            panic!("reloption {:?} is already declared", name);
+        }
+
+        // each value is aligned to its own size, as it would be in a C struct
+        let offset = (self.size + size - 1) / size * size;
+        self.size = offset + size;
+        self.options.push(RelOption {
+            name,
+            opttype,
+            offset,
+            default,
+        });
+        self
+    }
+
+    /// Parse a relation's `reloptions` text array, as the AM's `amoptions` callback should, into
+    /// a `bytea` for Postgres to cache in `RelationData.rd_options`.
+    ///
+    /// When `validate` is true, unrecognized options and invalid values raise an ERROR.  Options
+    /// that aren't set have their default values.
+    pub fn parse(&self, reloptions: pg_sys::Datum, validate: bool) -> *mut pg_sys::bytea {
+        let elems = self
+            .options
+            .iter()
+            .map(|option| pg_sys::relopt_parse_elt {
+                optname: option.name.as_ptr(),
+                opttype: option.opttype,
+                offset: option.offset as i32,
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            let mut numoptions = 0;
+            let options = pg_sys::parseRelOptions(reloptions, validate, self.kind, &mut numoptions);
+            if numoptions == 0 {
+                return std::ptr::null_mut();
+            }
+
+            let rdopts = pg_sys::allocateReloptStruct(self.size, options, numoptions);
+            pg_sys::fillRelOptions(
+                rdopts,
+                self.size,
+                options,
+                numoptions,
+                validate,
+                elems.as_ptr(),
+                elems.len() as i32,
+            );
+            pg_sys::pfree(options as void_mut_ptr);
+
+            rdopts as *mut pg_sys::bytea
+        }
+    }
+
+    /// Read option values from a `bytea` returned by `::parse()`, usually a relation's
+    /// `rd_options`.  If it's NULL, every option has its default value.
+    pub fn options(&self, rd_options: *mut pg_sys::bytea) -> RelOptions {
+        RelOptions {
+            builder: self,
+            data: rd_options as *const c_char,
+        }
+    }
+}
+
+fn names(name: &str, desc: &str) -> (CString, CString) {
+    (
+        CString::new(name).expect("reloption name contained a null byte"),
+        CString::new(desc).expect("reloption description contained a null byte"),
+    )
+}
+
+/// The parsed options of a relation, as declared by a `RelOptionsBuilder`
+pub struct RelOptions<'a> {
+    builder: &'a RelOptionsBuilder,
+    data: *const c_char,
+}
+
+impl<'a> RelOptions<'a> {
+    fn find(&self, name: &str, opttype: pg_sys::relopt_type) -> &RelOption {
+        match self
+            .builder
+            .options
+            .iter()
+            .find(|option| option.name.as_bytes() == name.as_bytes())
+        {
+            Some(option) if option.opttype == opttype => option,
+            Some(_) => panic!("reloption \"{}\" is of a different type", name),
+            None => panic!("reloption \"{}\" is not declared", name),
+        }
+    }
+
+    /// The value at `option`'s offset, or `None` if there are no parsed options
+    unsafe fn value<T: Copy>(&self, option: &RelOption) -> Option<T> {
+        if self.data.is_null() {
+            None
+        } else {
+            Some((self.data.add(option.offset) as *const T).read_unaligned())
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> bool {
+        let option = self.find(name, pg_sys::relopt_type_RELOPT_TYPE_BOOL);
+        match (unsafe { self.value(option) }, &option.default) {
+            (Some(value), _) => value,
+            (None, RelOptionDefault::Bool(default)) => *default,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> i32 {
+        let option = self.find(name, pg_sys::relopt_type_RELOPT_TYPE_INT);
+        match (unsafe { self.value(option) }, &option.default) {
+            (Some(value), _) => value,
+            (None, RelOptionDefault::Int(default)) => *default,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn get_real(&self, name: &str) -> f64 {
+        let option = self.find(name, pg_sys::relopt_type_RELOPT_TYPE_REAL);
+        match (unsafe { self.value(option) }, &option.default) {
+            (Some(value), _) => value,
+            (None, RelOptionDefault::Real(default)) => *default,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns `None` if the option isn't set and has no default
+    pub fn get_string(&self, name: &str) -> Option<String> {
+        let option = self.find(name, pg_sys::relopt_type_RELOPT_TYPE_STRING);
+        match (unsafe { self.value::<i32>(option) }, &option.default) {
+            // fillRelOptions() stores 0 for a NULL string
+            (Some(0), _) => None,
+            (Some(offset), _) => Some(
+                unsafe { CStr::from_ptr(self.data.add(offset as usize)) }
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            (None, RelOptionDefault::String(default)) => default.clone(),
+            _ => unreachable!(),
+        }
+    }
+}
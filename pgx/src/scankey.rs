@@ -0,0 +1,165 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! A typed builder for the `ScanKeyData` used by heap and index scans
+use crate::{name_data_to_str, pg_sys, IntoDatum, PgRelation};
+use std::ffi::CStr;
+
+/// Builds the `ScanKeyData` for a scan of a relation, resolving each key's comparison function
+/// from the btree operator family of the column being compared.
+///
+/// For an index, attribute numbers are the index's column numbers and the operator family is the
+/// column's opclass.  For any other relation, they're the relation's column numbers and the
+/// operator family is the column type's default btree opclass.  Either way, a value of a
+/// different type may be used if the operator family has a cross-type operator for it, such as
+/// comparing an `int4` column to an `i64`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// # let relid: pg_sys::Oid = unimplemented!();
+/// let relation = PgRelation::with_lock(relid, pg_sys::AccessShareLock as pg_sys::LOCKMODE);
+/// let keys = ScanKeys::new(&relation)
+///     .greater_than(1, 10i32)
+///     .equals(2, "active");
+/// for tuple in relation.scan_with_keys(keys.into_keys()) {
+///     let id = tuple.get_by_name::<i32>("id");
+/// }
+/// ```
+pub struct ScanKeys<'a> {
+    relation: &'a PgRelation,
+    keys: Vec<pg_sys::ScanKeyData>,
+}
+
+impl<'a> ScanKeys<'a> {
+    pub fn new(relation: &'a PgRelation) -> Self {
+        ScanKeys {
+            relation,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Only match tuples where the attribute `= value`
+    pub fn equals<T: IntoDatum>(self, attno: pg_sys::AttrNumber, value: T) -> Self {
+        self.key(attno, pg_sys::BTEqualStrategyNumber, value)
+    }
+
+    /// Only match tuples where the attribute `< value`
+    pub fn less_than<T: IntoDatum>(self, attno: pg_sys::AttrNumber, value: T) -> Self {
+        self.key(attno, pg_sys::BTLessStrategyNumber, value)
+    }
+
+    /// Only match tuples where the attribute `<= value`
+    pub fn less_or_equal<T: IntoDatum>(self, attno: pg_sys::AttrNumber, value: T) -> Self {
+        self.key(attno, pg_sys::BTLessEqualStrategyNumber, value)
+    }
+
+    /// Only match tuples where the attribute `>= value`
+    pub fn greater_or_equal<T: IntoDatum>(self, attno: pg_sys::AttrNumber, value: T) -> Self {
+        self.key(attno, pg_sys::BTGreaterEqualStrategyNumber, value)
+    }
+
+    /// Only match tuples where the attribute `> value`
+    pub fn greater_than<T: IntoDatum>(self, attno: pg_sys::AttrNumber, value: T) -> Self {
+        self.key(attno, pg_sys::BTGreaterStrategyNumber, value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The keys, ready for `PgRelation::scan_with_keys()`, or `pg_sys::index_rescan()`
+    pub fn into_keys(self) -> Vec<pg_sys::ScanKeyData> {
+        self.keys
+    }
+
+    /// ## Panics
+    ///
+    /// If `attno` isn't one of the relation's columns, if the value is NULL, or if the column's
+    /// operator family has no operator comparing the column's type to the value's type
+    fn key<T: IntoDatum>(mut self, attno: pg_sys::AttrNumber, strategy: u32, value: T) -> Self {
+        let tupdesc = self.relation.tuple_desc();
+        let att = match tupdesc.get((attno as usize).wrapping_sub(1)) {
+            Some(att) if attno > 0 && !att.attisdropped => att,
+            _ => panic!(
+                "attribute number {} is not a column of \"{}\"",
+                attno,
+                self.relation.name()
+            ),
+        };
+        let attname = name_data_to_str(&att.attname).to_string();
+        let value_type = T::type_oid();
+        let argument = value.into_datum().unwrap_or_else(|| {
+            panic!(
+                "cannot compare column \"{}\" to NULL in a scan key",
+                attname
+            )
+        });
+
+        unsafe {
+            let index = attno as usize - 1;
+            let (opfamily, lefttype, collation) = if self.relation.rd_index.is_null() {
+                let typentry = pg_sys::lookup_type_cache(
+                    att.atttypid,
+                    pg_sys::TYPECACHE_BTREE_OPFAMILY as i32,
+                );
+                (
+                    (*typentry).btree_opf,
+                    (*typentry).btree_opintype,
+                    att.attcollation,
+                )
+            } else {
+                (
+                    *self.relation.rd_opfamily.add(index),
+                    *self.relation.rd_opcintype.add(index),
+                    *self.relation.rd_indcollation.add(index),
+                )
+            };
+
+            let operator = if opfamily == pg_sys::InvalidOid {
+                pg_sys::InvalidOid
+            } else {
+                pg_sys::get_opfamily_member(opfamily, lefttype, value_type, strategy as i16)
+            };
+            if operator == pg_sys::InvalidOid {
+                panic!(
+                    "column \"{}\" is of type {}, which cannot be compared to a value of type {}",
+                    attname,
+                    type_name(att.atttypid),
+                    type_name(value_type)
+                );
+            }
+
+            let mut key = std::mem::zeroed::<pg_sys::ScanKeyData>();
+            pg_sys::ScanKeyEntryInitialize(
+                &mut key,
+                0,
+                attno,
+                strategy as pg_sys::StrategyNumber,
+                if value_type == lefttype {
+                    pg_sys::InvalidOid
+                } else {
+                    value_type
+                },
+                collation,
+                pg_sys::get_opcode(operator),
+                argument,
+            );
+            self.keys.push(key);
+        }
+
+        self
+    }
+}
+
+fn type_name(oid: pg_sys::Oid) -> String {
+    unsafe { CStr::from_ptr(pg_sys::format_type_be(oid)) }
+        .to_string_lossy()
+        .into_owned()
+}
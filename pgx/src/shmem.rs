@@ -0,0 +1,68 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for reserving Postgres shared memory from `_PG_init()`
+use crate::pg_sys;
+
+static mut REQUESTED_SHMEM_SPACE: usize = 0;
+
+/// Ask Postgres to reserve `size` bytes of shared memory for this extension.
+///
+/// Shared memory can only be requested while Postgres is loading `shared_preload_libraries`, so
+/// this must be called from `_PG_init()`, and the extension's library must be preloaded.  The
+/// space can later be carved up with `pg_sys::ShmemInitStruct()` from a `shmem_startup_hook`.
+///
+/// Use the `pg_shmem_request!()` macro rather than calling this directly.
+///
+/// ## Panics
+///
+/// If the library isn't being loaded via `shared_preload_libraries`
+pub fn request_shmem_space(size: usize) {
+    unsafe {
+        if !pg_sys::process_shared_preload_libraries_in_progress {
+            panic!(
+                "shared memory can only be requested from _PG_init() when the extension is loaded via shared_preload_libraries"
+            );
+        }
+
+        pg_sys::RequestAddinShmemSpace(size);
+        REQUESTED_SHMEM_SPACE += size;
+    }
+}
+
+/// The total number of bytes of shared memory this extension has requested
+pub fn requested_shmem_space() -> usize {
+    unsafe { REQUESTED_SHMEM_SPACE }
+}
+
+/// Reserve the given number of bytes of shared memory for this extension.
+///
+/// Call this from `_PG_init()`.  The total requested is available from `pgx::requested_shmem_space()`.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[repr(C)]
+/// struct SharedCounters {
+///     hits: u64,
+///     misses: u64,
+/// }
+///
+/// #[allow(non_snake_case)]
+/// #[pg_guard]
+/// pub extern "C" fn _PG_init() {
+///     pg_shmem_request!(std::mem::size_of::<SharedCounters>());
+/// }
+/// ```
+///
+/// ## Panics
+///
+/// If the extension's library isn't listed in `shared_preload_libraries`
+#[macro_export]
+macro_rules! pg_shmem_request {
+    ($size:expr) => {
+        $crate::request_shmem_space($size)
+    };
+}
@@ -3,10 +3,17 @@
 
 //! Safe access to Postgres' *Server Programming Interface* (SPI).
 
-use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid};
+use crate::copy::quote_identifier;
+#[cfg(any(feature = "pg11", feature = "pg12"))]
+use crate::is_a;
+use crate::{
+    pg_sys, void_mut_ptr, void_ptr, FromDatum, IntoDatum, Json, PgBox, PgHeapTuple,
+    PgMemoryContexts, PgOid, PgTupleDesc,
+};
 use enum_primitive_derive::*;
 use num_traits::FromPrimitive;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 
 #[derive(Debug, Primitive)]
 pub enum SpiOk {
@@ -29,49 +36,257 @@ pub enum SpiOk {
     TdRegister = 17,
 }
 
-#[derive(Debug, Primitive)]
+#[derive(Debug, PartialEq)]
 pub enum SpiError {
-    // NB:  These are #define'd as negative, but we redefine them as positive so that
-    // #[derive(Primitive)] will work.  We just need to negate result codes from the
-    // various SPI_xxx functions when looking for errors
-    Connect = 1,
-    Copy = 2,
-    Opunknown = 3,
-    Unconnected = 4,
+    // NB:  These are #define'd as negative, and `FromPrimitive` maps them from their positive
+    // values.  We just need to negate result codes from the various SPI_xxx functions when
+    // looking for errors
+    Connect,
+    Copy,
+    Opunknown,
+    Unconnected,
     #[allow(dead_code)]
-    Cursor = 5, /* not used anymore */
-    Argument = 6,
-    Param = 7,
-    Transaction = 8,
-    Noattribute = 9,
-    Nooutfunc = 10,
-    Typunknown = 11,
-    RelDuplicate = 12,
-    RelNotFound = 13,
+    Cursor, /* not used anymore */
+    Argument,
+    Param,
+    Transaction,
+    Noattribute,
+    Nooutfunc,
+    Typunknown,
+    RelDuplicate,
+    RelNotFound,
+
+    /// A Postgres ERROR raised while executing a query via `SpiClient::try_select()` or
+    /// `SpiClient::try_update()`
+    Postgres(pg_sys::PgError),
+}
+
+impl FromPrimitive for SpiError {
+    fn from_i64(n: i64) -> Option<Self> {
+        match n {
+            1 => Some(SpiError::Connect),
+            2 => Some(SpiError::Copy),
+            3 => Some(SpiError::Opunknown),
+            4 => Some(SpiError::Unconnected),
+            5 => Some(SpiError::Cursor),
+            6 => Some(SpiError::Argument),
+            7 => Some(SpiError::Param),
+            8 => Some(SpiError::Transaction),
+            9 => Some(SpiError::Noattribute),
+            10 => Some(SpiError::Nooutfunc),
+            11 => Some(SpiError::Typunknown),
+            12 => Some(SpiError::RelDuplicate),
+            13 => Some(SpiError::RelNotFound),
+            _ => None,
+        }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        SpiError::from_i64(n as i64)
+    }
+}
+
+/// Converts a value into an argument for a parameterized SPI query, whose type is the value's
+/// `IntoDatum::type_oid()`.  `None` is a NULL of the `Option`'s inner type.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let count = Spi::get_one_with_args::<i64>(
+///     "SELECT count(*) FROM users WHERE id = $1 OR name = $2 OR email = $3",
+///     vec![42.into_spi_arg(), "alice".into_spi_arg(), None::<&str>.into_spi_arg()],
+/// );
+/// ```
+pub trait IntoSpiArg {
+    fn into_spi_arg(self) -> (PgOid, Option<pg_sys::Datum>);
+}
+
+impl<T: IntoDatum> IntoSpiArg for T {
+    fn into_spi_arg(self) -> (PgOid, Option<pg_sys::Datum>) {
+        (PgOid::from(T::type_oid()), self.into_datum())
+    }
+}
+
+/// Build the arguments for a parameterized SPI query from a list of values of any type that
+/// implements `IntoDatum`, converting each with `IntoSpiArg::into_spi_arg()`.
+///
+/// Each argument's type is inferred from its value, so `None` needs a type annotation.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let count = Spi::get_one_with_args::<i64>(
+///     "SELECT count(*) FROM users WHERE id = $1 OR name = $2 OR score = $3",
+///     args![42, "alice", None::<f64>],
+/// );
+/// ```
+#[macro_export]
+macro_rules! args {
+    ($($arg:expr),* $(,)?) => (
+        vec![$($crate::IntoSpiArg::into_spi_arg($arg)),*]
+    );
+}
+
+/// A value that can be returned from `Spi::connect_and_return()`'s closure.
+///
+/// SPI allocates everything in its own memory context, which is freed when the connection closes,
+/// so `::copy_into()` copies any Postgres-allocated memory the value refers to into `context`,
+/// which outlives the connection.  Values that Rust owns entirely, such as a `String` or an
+/// `i32`, are returned as-is.
+///
+/// There's deliberately no implementation for `usize`, because `pg_sys::Datum` is a `usize` and
+/// a by-reference `Datum` would point into SPI's memory context.  Return an owned Rust value
+/// instead.
+pub trait SpiReturn {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self;
+}
+
+macro_rules! spi_return_as_is {
+    ($($t:ty),*) => {
+        $(
+            impl SpiReturn for $t {
+                #[inline]
+                fn copy_into(self, _context: &mut PgMemoryContexts) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+spi_return_as_is!(
+    (),
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    isize,
+    f32,
+    f64,
+    String,
+    Json
+);
+
+impl<'a> SpiReturn for &'a str {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        let copy = context.palloc_slice::<u8>(self.len());
+        copy.copy_from_slice(self.as_bytes());
+        unsafe { std::str::from_utf8_unchecked(copy) }
+    }
+}
+
+impl<'a> SpiReturn for PgHeapTuple<'a> {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        let tuple = self.as_ptr();
+        let tupdesc = self.tupdesc().as_ptr();
+        context.switch_to(|_| unsafe {
+            PgHeapTuple::from_heap_tuple(
+                PgBox::from_pg(pg_sys::heap_copytuple(tuple)),
+                PgTupleDesc::from_pg_copy(tupdesc),
+            )
+        })
+    }
+}
+
+impl<T: SpiReturn> SpiReturn for Option<T> {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        self.map(|value| value.copy_into(context))
+    }
+}
+
+impl<T: SpiReturn> SpiReturn for Vec<T> {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        self.into_iter()
+            .map(|value| value.copy_into(context))
+            .collect()
+    }
+}
+
+impl<T: SpiReturn, E: SpiReturn> SpiReturn for std::result::Result<T, E> {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        match self {
+            Ok(value) => Ok(value.copy_into(context)),
+            Err(e) => Err(e.copy_into(context)),
+        }
+    }
+}
+
+impl<A: SpiReturn, B: SpiReturn> SpiReturn for (A, B) {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        (self.0.copy_into(context), self.1.copy_into(context))
+    }
+}
+
+impl<A: SpiReturn, B: SpiReturn, C: SpiReturn> SpiReturn for (A, B, C) {
+    fn copy_into(self, context: &mut PgMemoryContexts) -> Self {
+        (
+            self.0.copy_into(context),
+            self.1.copy_into(context),
+            self.2.copy_into(context),
+        )
+    }
 }
 
 pub struct Spi();
 
 pub struct SpiClient();
 
+/// The result of a query executed through a `SpiClient`.
+///
+/// It's also an `Iterator` over the result's rows.  Both it and its rows borrow the `SpiClient`
+/// that executed the query, so they can't outlive the SPI connection, which frees them.  Use
+/// `::into_heap_tuples()`, or `SpiHeapTupleData::copy_datum()`, to copy values out of it.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let names = Spi::connect(|client| {
+///     let names = client
+///         .select("SELECT relname::text, relpages FROM pg_class", None, None)
+///         .filter(|row| row.get_datum::<i32>(2).unwrap_or(0) > 10)
+///         .map(|row| row.get_datum::<String>(1))
+///         .collect::<Vec<Option<String>>>();
+///     Ok(Some(names))
+/// });
+/// ```
 #[derive(Debug)]
-pub struct SpiTupleTable {
+pub struct SpiTupleTable<'conn> {
     status_code: SpiOk,
     table: *mut pg_sys::SPITupleTable,
     size: usize,
     tupdesc: Option<pg_sys::TupleDesc>,
     current: isize,
+    upper_context: pg_sys::MemoryContext,
+    _client: PhantomData<&'conn SpiClient>,
 }
 
-pub struct SpiHeapTupleData {
+/// A row of an `SpiTupleTable`, which can't outlive the SPI connection either
+pub struct SpiHeapTupleData<'conn> {
     data: *mut pg_sys::HeapTupleData,
     tupdesc: pg_sys::TupleDesc,
+    upper_context: pg_sys::MemoryContext,
+    _client: PhantomData<&'conn SpiClient>,
 }
 
 impl Spi {
+    // NB:  the `::get_xxx()` functions execute their queries read/write so that they see changes
+    // made by earlier statements in the same transaction
+
     pub fn get_one<A: FromDatum + IntoDatum>(query: &str) -> Option<A> {
-        Spi::connect(|client| {
-            let result = client.select(query, Some(1), None).first().get_one();
+        Spi::connect(|mut client| {
+            let result = client.update(query, Some(1), None).first().get_one();
             Ok(result)
         })
     }
@@ -79,9 +294,9 @@ impl Spi {
     pub fn get_two<A: FromDatum + IntoDatum, B: FromDatum + IntoDatum>(
         query: &str,
     ) -> (Option<A>, Option<B>) {
-        Spi::connect(|client| {
+        Spi::connect(|mut client| {
             let (a, b) = client
-                .select(query, Some(1), None)
+                .update(query, Some(1), None)
                 .first()
                 .get_two::<A, B>();
             Ok(Some((a, b)))
@@ -96,9 +311,9 @@ impl Spi {
     >(
         query: &str,
     ) -> (Option<A>, Option<B>, Option<C>) {
-        Spi::connect(|client| {
+        Spi::connect(|mut client| {
             let (a, b, c) = client
-                .select(query, Some(1), None)
+                .update(query, Some(1), None)
                 .first()
                 .get_three::<A, B, C>();
             Ok(Some((a, b, c)))
@@ -106,20 +321,39 @@ impl Spi {
         .unwrap()
     }
 
+    /// Run a parameterized query and return the first column of its first row, or `None` if
+    /// there are no rows or the value is NULL.
+    ///
+    /// A row-typed column can be returned as a `PgHeapTuple`, which is copied into the
+    /// `CurrentMemoryContext`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let user = Spi::get_one_with_args::<PgHeapTuple>(
+    ///     "SELECT users FROM users WHERE id = $1",
+    ///     vec![42.into_spi_arg()],
+    /// );
+    /// if let Some(user) = user {
+    ///     let name = user.get_by_name::<String>("name");
+    /// }
+    /// ```
     pub fn get_one_with_args<A: FromDatum + IntoDatum>(
         query: &str,
         args: Vec<(PgOid, Option<pg_sys::Datum>)>,
     ) -> Option<A> {
-        Spi::connect(|client| Ok(client.select(query, Some(1), Some(args)).first().get_one()))
+        Spi::connect(|mut client| Ok(client.update(query, Some(1), Some(args)).first().get_one()))
     }
 
     pub fn get_two_with_args<A: FromDatum + IntoDatum, B: FromDatum + IntoDatum>(
         query: &str,
         args: Vec<(PgOid, Option<pg_sys::Datum>)>,
     ) -> (Option<A>, Option<B>) {
-        Spi::connect(|client| {
+        Spi::connect(|mut client| {
             let (a, b) = client
-                .select(query, Some(1), Some(args))
+                .update(query, Some(1), Some(args))
                 .first()
                 .get_two::<A, B>();
             Ok(Some((a, b)))
@@ -135,9 +369,9 @@ impl Spi {
         query: &str,
         args: Vec<(PgOid, Option<pg_sys::Datum>)>,
     ) -> (Option<A>, Option<B>, Option<C>) {
-        Spi::connect(|client| {
+        Spi::connect(|mut client| {
             let (a, b, c) = client
-                .select(query, Some(1), Some(args))
+                .update(query, Some(1), Some(args))
                 .first()
                 .get_three::<A, B, C>();
             Ok(Some((a, b, c)))
@@ -158,9 +392,22 @@ impl Spi {
 
     /// explain a query, returning its result in json form
     pub fn explain(query: &str) -> Json {
+        Spi::explain_with_options(query, "format json")
+    }
+
+    /// explain a query with `ANALYZE`, returning its result in json form
+    ///
+    /// ## Safety
+    ///
+    /// `EXPLAIN ANALYZE` actually executes the query, so any changes it makes are applied
+    pub fn explain_analyze(query: &str) -> Json {
+        Spi::explain_with_options(query, "analyze, format json")
+    }
+
+    fn explain_with_options(query: &str, options: &str) -> Json {
         Spi::connect(|mut client| {
             let table = client
-                .update(&format!("EXPLAIN (format json) {}", query), None, None)
+                .update(&format!("EXPLAIN ({}) {}", options, query), None, None)
                 .first();
             Ok(Some(
                 table
@@ -181,6 +428,8 @@ impl Spi {
 
     /// execute SPI commands via the provided `SpiClient` and return a value from SPI which is
     /// automatically copied into the `CurrentMemoryContext` at the time of this function call
+    ///
+    /// See `::connect_and_return()` for returning values that aren't Datums
     pub fn connect<
         R: FromDatum + IntoDatum,
         F: FnOnce(SpiClient) -> std::result::Result<Option<R>, SpiError>,
@@ -190,25 +439,6 @@ impl Spi {
         let outer_memory_context =
             PgMemoryContexts::For(PgMemoryContexts::CurrentMemoryContext.value());
 
-        /// a struct to manage our SPI connection lifetime
-        struct SpiConnection;
-        impl SpiConnection {
-            /// Connect to Postgres' SPI system
-            fn connect() -> Self {
-                // connect to SPI
-                Spi::check_status(unsafe { pg_sys::SPI_connect() });
-                SpiConnection
-            }
-        }
-
-        impl Drop for SpiConnection {
-            /// when SpiConnection is dropped, we make sure to disconnect from SPI
-            fn drop(&mut self) {
-                // disconnect from SPI
-                Spi::check_status(unsafe { pg_sys::SPI_finish() });
-            }
-        }
-
         // connect to SPI
         let _connection = SpiConnection::connect();
 
@@ -246,10 +476,83 @@ impl Spi {
             }
 
             // closure returned an error
+            Err(SpiError::Postgres(e)) => e.rethrow(),
             Err(e) => panic!(e),
         }
     }
 
+    /// execute SPI commands via the provided `SpiClient` and return whatever the closure returns.
+    ///
+    /// While connected, the `CurrentMemoryContext` is SPI's own context, which is freed when
+    /// the connection closes, along with the results of every query.  So, before disconnecting,
+    /// the returned value is copied into the `CurrentMemoryContext` at the time of this function
+    /// call via `SpiReturn::copy_into()`.  Values read with `SpiHeapTupleData::copy_datum()` and
+    /// `SpiTupleTable::into_heap_tuples()` are already copied there, but anything else allocated
+    /// while connected, such as a `PgHeapTuple` or a `&str` built from a function call's result,
+    /// would otherwise be freed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let (count, names) = Spi::connect_and_return(|client| {
+    ///     let names = client
+    ///         .select("SELECT relname::text FROM pg_class", None, None)
+    ///         .map(|row| row.get_datum::<String>(1))
+    ///         .collect::<Vec<_>>();
+    ///     (names.len() as i64, names)
+    /// });
+    /// ```
+    pub fn connect_and_return<R: SpiReturn, F: FnOnce(SpiClient) -> R>(f: F) -> R {
+        let mut outer_memory_context =
+            PgMemoryContexts::For(PgMemoryContexts::CurrentMemoryContext.value());
+
+        // as with `::connect()`, Postgres disconnects us if `f` raises an ERROR
+        let _connection = SpiConnection::connect();
+        f(SpiClient()).copy_into(&mut outer_memory_context)
+    }
+
+    /// execute SPI commands via the provided `SpiClient`, allowing it to `::commit()` and
+    /// `::rollback()` if `fcinfo` belongs to a procedure invoked by a non-atomic `CALL`
+    ///
+    /// In any other context (a function, or a `CALL` inside a transaction block) SPI connects in
+    /// atomic mode, as usual, and committing or rolling back raises an ERROR.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// #[pg_extern]
+    /// fn insert_in_batches(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    ///     Spi::connect_nonatomic(fcinfo, |client| {
+    ///         for batch in 0..10 {
+    ///             client.update(&format!("INSERT INTO t SELECT {} FROM generate_series(1, 1000)", batch), None, None);
+    ///             client.commit();
+    ///         }
+    ///     });
+    ///     0
+    /// }
+    ///
+    /// extension_sql! { r#"
+    /// CREATE PROCEDURE insert_in_batches() LANGUAGE c AS 'MODULE_PATHNAME', 'insert_in_batches_wrapper';
+    /// "#}
+    /// ```
+    #[cfg(any(feature = "pg11", feature = "pg12"))]
+    pub fn connect_nonatomic<F: FnOnce(SpiClient)>(fcinfo: pg_sys::FunctionCallInfo, f: F) {
+        let options = if unsafe { is_nonatomic_call(fcinfo) } {
+            pg_sys::SPI_OPT_NONATOMIC as i32
+        } else {
+            0
+        };
+
+        // if `f` raises an ERROR, Postgres cleans up our SPI connection for us
+        Spi::check_status(unsafe { pg_sys::SPI_connect_ext(options) });
+        f(SpiClient());
+        Spi::check_status(unsafe { pg_sys::SPI_finish() });
+    }
+
     pub fn check_status(status_code: i32) -> SpiOk {
         if status_code > 0 {
             let status_enum = SpiOk::from_i32(status_code);
@@ -267,44 +570,318 @@ impl Spi {
     }
 }
 
+/// a struct to manage our SPI connection lifetime
+struct SpiConnection;
+
+impl SpiConnection {
+    /// Connect to Postgres' SPI system
+    fn connect() -> Self {
+        // connect to SPI
+        Spi::check_status(unsafe { pg_sys::SPI_connect() });
+        SpiConnection
+    }
+}
+
+impl Drop for SpiConnection {
+    /// when SpiConnection is dropped, we make sure to disconnect from SPI
+    fn drop(&mut self) {
+        // disconnect from SPI
+        Spi::check_status(unsafe { pg_sys::SPI_finish() });
+    }
+}
+
+/// Was the function called by a `CALL` statement that allows transaction control?
+#[cfg(any(feature = "pg11", feature = "pg12"))]
+unsafe fn is_nonatomic_call(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let context = fcinfo.as_ref().expect("fcinfo is NULL").context;
+    is_a(context, pg_sys::NodeTag_T_CallContext) && !(*(context as *mut pg_sys::CallContext)).atomic
+}
+
 impl SpiClient {
+    /// commit the current transaction and start a new one
+    ///
+    /// Subsequent statements run with a fresh snapshot.  This is only allowed for clients created
+    /// by `Spi::connect_nonatomic()` in a non-atomic context, otherwise Postgres raises an ERROR
+    #[cfg(any(feature = "pg11", feature = "pg12"))]
+    pub fn commit(&self) {
+        unsafe {
+            pg_sys::SPI_commit();
+            pg_sys::SPI_start_transaction();
+        }
+    }
+
+    /// roll back the current transaction and start a new one
+    ///
+    /// Subsequent statements run with a fresh snapshot.  This is only allowed for clients created
+    /// by `Spi::connect_nonatomic()` in a non-atomic context, otherwise Postgres raises an ERROR
+    #[cfg(any(feature = "pg11", feature = "pg12"))]
+    pub fn rollback(&self) {
+        unsafe {
+            pg_sys::SPI_rollback();
+            pg_sys::SPI_start_transaction();
+        }
+    }
+
     /// perform a SELECT statement
+    ///
+    /// The query is executed read/write, like `::update()`, so it sees the changes made by earlier
+    /// statements in the same transaction.  Postgres docs say it's generally unwise to mix
+    /// read-only and read/write commands, as the read-only ones won't see the others' changes.
+    /// Use `::select_read_only()` for queries that don't need to.
     pub fn select(
         &self,
         query: &str,
         limit: Option<i64>,
         args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
-    ) -> SpiTupleTable {
-        // Postgres docs say:
-        //
-        //    It is generally unwise to mix read-only and read-write commands within a single function
-        //    using SPI; that could result in very confusing behavior, since the read-only queries
-        //    would not see the results of any database updates done by the read-write queries.
-        //
-        // As such, we don't actually set read-only to true here
-
-        // TODO:  can we detect if the command counter (or something?) has incremented and if yes
-        //        then we set read_only=false, else we can set it to true?
-        //        Is this even a good idea?
+    ) -> SpiTupleTable<'_> {
         SpiClient::execute(query, false, limit, args)
     }
 
+    /// perform a read-only SELECT statement
+    ///
+    /// The query is planned and executed read-only, which avoids taking a new snapshot and
+    /// incrementing the command counter.  Postgres raises an ERROR if the query attempts to
+    /// modify the database.
+    ///
+    /// Note that, as the query runs with the current snapshot, it won't see changes made by
+    /// earlier statements in the same transaction that were executed via `::select()` or
+    /// `::update()`.
+    pub fn select_read_only(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> SpiTupleTable<'_> {
+        SpiClient::execute(query, true, limit, args)
+    }
+
     /// perform any query (including utility statements) that modify the database in some way
     pub fn update(
         &mut self,
         query: &str,
         limit: Option<i64>,
         args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
-    ) -> SpiTupleTable {
+    ) -> SpiTupleTable<'_> {
         SpiClient::execute(query, false, limit, args)
     }
 
-    fn execute(
+    /// perform an `INSERT`, `UPDATE`, or `DELETE` that has a `RETURNING` clause, returning the
+    /// rows it returned, such as the values generated for a `serial` or identity column
+    ///
+    /// The statement is executed like `::update()`, but without a row limit, as a limit would
+    /// stop the statement itself after that many rows.  The rows are copied as with
+    /// `SpiTupleTable::into_heap_tuples()`, so they remain valid after the connection is closed.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let mut ids = Vec::new();
+    /// Spi::connect(|mut client| {
+    ///     ids = client
+    ///         .update_returning(
+    ///             "INSERT INTO users (name) VALUES ($1), ($2) RETURNING id",
+    ///             Some(args!["alice", "bob"]),
+    ///         )
+    ///         .into_iter()
+    ///         .map(|row| row.get_by_name::<i64>("id").unwrap())
+    ///         .collect();
+    ///     Ok(Some(()))
+    /// });
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If the query isn't an `INSERT`, `UPDATE`, or `DELETE` with a `RETURNING` clause
+    pub fn update_returning(
+        &mut self,
+        query: &str,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> Vec<PgHeapTuple<'static>> {
+        let table = SpiClient::execute(query, false, None, args);
+        match table.status_code {
+            SpiOk::InsertReturning | SpiOk::UpdateReturning | SpiOk::DeleteReturning => {
+                table.into_heap_tuples()
+            }
+            other => panic!(
+                "update_returning() requires an INSERT, UPDATE, or DELETE with a RETURNING clause, but the query's result was {:?}",
+                other
+            ),
+        }
+    }
+
+    /// insert each of `rows`, instances of a `#[derive(PostgresType)] #[composite]` type, into
+    /// `table`, returning the number of rows inserted
+    ///
+    /// The `INSERT` names a column for each of the composite type's attributes, so the table's
+    /// columns must have the same names, and any it has besides them get their defaults.  It's
+    /// planned once, and then executed for each row, with a `None` field inserted as NULL.
+    /// `table` is used in the statement as written, so it may be schema-qualified, and must be
+    /// quoted if it's not a plain lowercase identifier.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// #[derive(PostgresType)]
+    /// #[composite]
+    /// pub struct User {
+    ///     id: i64,
+    ///     name: Option<String>,
+    /// }
+    ///
+    /// let users = vec![
+    ///     User { id: 1, name: Some("alice".to_string()) },
+    ///     User { id: 2, name: None },
+    /// ];
+    /// Spi::connect(|mut client| {
+    ///     client.insert_all("public.users", users);
+    ///     Ok(Some(()))
+    /// });
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Postgres raises an ERROR if `T` isn't a composite type, or if the table doesn't have one
+    /// of its columns
+    pub fn insert_all<T: IntoDatum, I: IntoIterator<Item = T>>(
+        &mut self,
+        table: &str,
+        rows: I,
+    ) -> u64 {
+        let mut typoid = T::type_oid();
+        let columns = unsafe {
+            PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(typoid, -1))
+                .attributes()
+                .map(|att| quote_identifier(att.name()))
+                .collect::<Vec<_>>()
+        };
+        let insert = std::ffi::CString::new(format!(
+            "INSERT INTO {} ({}) SELECT ($1).*",
+            table,
+            columns.join(", ")
+        ))
+        .expect("table name contained a null byte");
+
+        unsafe {
+            let plan = pg_sys::SPI_prepare(insert.as_ptr(), 1, &mut typoid);
+            if plan.is_null() {
+                Spi::check_status(pg_sys::SPI_result);
+            }
+
+            // each row's datum only needs to live until it's inserted
+            let per_row = pg_sys::AllocSetContextCreateExtended(
+                pg_sys::CurrentMemoryContext,
+                "insert_all per-row context\0".as_ptr() as *const std::os::raw::c_char,
+                pg_sys::ALLOCSET_DEFAULT_MINSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_INITSIZE as usize,
+                pg_sys::ALLOCSET_DEFAULT_MAXSIZE as usize,
+            );
+            let mut count = 0u64;
+
+            for row in rows {
+                // `::switch_to()` takes an `Fn`, so `row` is moved out of a `Cell` to be consumed
+                let row = std::panic::AssertUnwindSafe(std::cell::Cell::new(Some(row)));
+                let datum = PgMemoryContexts::For(per_row)
+                    .switch_to(|_| row.take().expect("row was already converted").into_datum());
+
+                let mut value = datum.unwrap_or(0);
+                let null = if datum.is_none() { 'n' } else { ' ' } as std::os::raw::c_char;
+                Spi::check_status(pg_sys::SPI_execute_plan(plan, &mut value, &null, false, 0));
+                count += pg_sys::SPI_processed as u64;
+                pg_sys::MemoryContextReset(per_row);
+            }
+
+            pg_sys::MemoryContextDelete(per_row);
+            pg_sys::SPI_freeplan(plan);
+            count
+        }
+    }
+
+    /// perform a SELECT statement, like `::select()`, returning a Postgres ERROR as
+    /// `Err(SpiError::Postgres)` rather than raising it
+    ///
+    /// The query runs in its own subtransaction, which is rolled back if it fails, so the
+    /// connection remains usable afterwards.  Returning the error from the `Spi::connect()`
+    /// closure raises it again, with its original SQLSTATE.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// Spi::connect(|client| {
+    ///     match client.try_select("SELECT * FROM no_such_table", None, None) {
+    ///         Ok(table) => { /* use the results */ }
+    ///         Err(SpiError::Postgres(e)) if e.sqlstate() == "42P01" => { /* table is missing */ }
+    ///         Err(e) => return Err(e),
+    ///     }
+    ///     Ok(Some(()))
+    /// });
+    /// ```
+    pub fn try_select(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> std::result::Result<SpiTupleTable<'_>, SpiError> {
+        SpiClient::try_execute(query, false, limit, args)
+    }
+
+    /// perform any query (including utility statements) that modify the database in some way,
+    /// returning a Postgres ERROR as `Err(SpiError::Postgres)` rather than raising it
+    ///
+    /// See `::try_select()` for details.
+    pub fn try_update(
+        &mut self,
+        query: &str,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> std::result::Result<SpiTupleTable<'_>, SpiError> {
+        SpiClient::try_execute(query, false, limit, args)
+    }
+
+    fn try_execute<'conn>(
+        query: &str,
+        read_only: bool,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> std::result::Result<SpiTupleTable<'conn>, SpiError> {
+        let (memcxt, owner) =
+            unsafe { (pg_sys::CurrentMemoryContext, pg_sys::CurrentResourceOwner) };
+
+        unsafe {
+            pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+
+            // BeginInternalSubTransaction() leaves us in the subtransaction's memory context
+            pg_sys::CurrentMemoryContext = memcxt;
+        }
+
+        // SAFETY:  if the query fails, rolling back the subtransaction releases whatever
+        // resources it held
+        let result =
+            unsafe { pg_sys::guard_result(|| SpiClient::execute(query, read_only, limit, args)) };
+
+        unsafe {
+            match result {
+                Ok(_) => pg_sys::ReleaseCurrentSubTransaction(),
+                Err(_) => pg_sys::RollbackAndReleaseCurrentSubTransaction(),
+            }
+            pg_sys::CurrentMemoryContext = memcxt;
+            pg_sys::CurrentResourceOwner = owner;
+        }
+
+        result.map_err(SpiError::Postgres)
+    }
+
+    fn execute<'conn>(
         query: &str,
         read_only: bool,
         limit: Option<i64>,
         args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
-    ) -> SpiTupleTable {
+    ) -> SpiTupleTable<'conn> {
         unsafe {
             pg_sys::SPI_tuptable = std::ptr::null_mut();
         }
@@ -358,11 +935,13 @@ impl SpiClient {
                 Some(unsafe { (*pg_sys::SPI_tuptable).tupdesc })
             },
             current: -1,
+            upper_context: spi_upper_context(),
+            _client: PhantomData,
         }
     }
 }
 
-impl SpiTupleTable {
+impl<'conn> SpiTupleTable<'conn> {
     /// `SpiTupleTable`s are positioned before the start, for iteration purposes.
     ///
     /// This method moves the position to the first row.  If there are no rows, this
@@ -381,6 +960,51 @@ impl SpiTupleTable {
         self.len() == 0
     }
 
+    /// Copy every row into an owned `PgHeapTuple`, regardless of the current position.
+    ///
+    /// The tuples, and a copy of the result's `TupleDesc` for each one, are allocated in the
+    /// memory context that was current when SPI was connected, so they remain valid after the
+    /// SPI connection is closed.  This is intended for small result sets.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let mut rows = Vec::new();
+    /// Spi::connect(|client| {
+    ///     rows = client
+    ///         .select("SELECT name, setting FROM pg_settings", None, None)
+    ///         .into_heap_tuples();
+    ///     Ok(Some(()))
+    /// });
+    ///
+    /// for row in rows {
+    ///     let name = row.get_by_name::<String>("name");
+    ///     let setting = row.get_by_name::<String>("setting");
+    /// }
+    /// ```
+    pub fn into_heap_tuples(self) -> Vec<PgHeapTuple<'static>> {
+        let tupdesc = match self.tupdesc {
+            Some(tupdesc) => tupdesc,
+            None => return Vec::new(),
+        };
+
+        let tuples = unsafe { std::slice::from_raw_parts((*self.table).vals, self.size) };
+        tuples
+            .iter()
+            .map(|tuple| unsafe {
+                // SPI_copytuple() copies into SPI's "upper executor context", which is the
+                // context that was current when we connected to SPI
+                let copy = pg_sys::SPI_copytuple(*tuple);
+                let tupdesc = PgMemoryContexts::Of(copy as void_ptr)
+                    .switch_to(|_| PgTupleDesc::from_pg_copy(tupdesc));
+
+                PgHeapTuple::from_heap_tuple(PgBox::from_pg(copy), tupdesc)
+            })
+            .collect()
+    }
+
     pub fn get_one<A: FromDatum>(&self) -> Option<A> {
         self.get_datum(1)
     }
@@ -400,11 +1024,11 @@ impl SpiTupleTable {
         (a, b, c)
     }
 
-    pub fn get_heap_tuple(&self) -> Option<SpiHeapTupleData> {
+    pub fn get_heap_tuple(&self) -> Option<SpiHeapTupleData<'conn>> {
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -414,6 +1038,8 @@ impl SpiTupleTable {
                     Some(SpiHeapTupleData {
                         data: heap_tuple,
                         tupdesc,
+                        upper_context: self.upper_context,
+                        _client: PhantomData,
                     })
                 },
                 None => panic!("TupDesc is NULL"),
@@ -421,11 +1047,55 @@ impl SpiTupleTable {
         }
     }
 
+    /// Get the current row's value for the column with the specified name
+    ///
+    /// Returns `Err(SpiError::Noattribute)` if the result has no column with that name
+    pub fn get_by_name<T: FromDatum>(
+        &self,
+        name: &str,
+    ) -> std::result::Result<Option<T>, SpiError> {
+        match self.tupdesc {
+            Some(tupdesc) => Ok(self.get_datum(column_ordinal(tupdesc, name)?)),
+            None => Err(SpiError::Noattribute),
+        }
+    }
+
+    /// Get the current row's value for the column at the specified 1-based ordinal position.
+    ///
+    /// By-reference values such as an `Array<T>` or `&str` point into SPI's memory, so they're
+    /// only valid until the SPI connection is closed.  Use `::copy_datum()` for values that
+    /// need to outlive it.
     pub fn get_datum<T: FromDatum>(&self, ordinal: i32) -> Option<T> {
+        self.read_datum(ordinal, |datum, is_null, typoid| unsafe {
+            T::from_datum(datum, is_null, typoid)
+        })
+    }
+
+    /// Like `::get_datum()`, but the value is copied into the memory context that was current
+    /// when SPI was connected, so it remains valid after the SPI connection is closed.
+    ///
+    /// The copy lives as long as that context, so avoid calling this for every row of a large
+    /// result.
+    pub fn copy_datum<T: FromDatum>(&self, ordinal: i32) -> Option<T> {
+        self.read_datum(ordinal, |datum, is_null, typoid| unsafe {
+            T::from_datum_in_memory_context(
+                PgMemoryContexts::For(self.upper_context),
+                datum,
+                is_null,
+                typoid,
+            )
+        })
+    }
+
+    fn read_datum<T, F: FnOnce(pg_sys::Datum, bool, pg_sys::Oid) -> Option<T>>(
+        &self,
+        ordinal: i32,
+        f: F,
+    ) -> Option<T> {
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -441,7 +1111,7 @@ impl SpiTupleTable {
                         let datum =
                             pg_sys::SPI_getbinval(heap_tuple, tupdesc, ordinal, &mut is_null);
 
-                        T::from_datum(datum, is_null, pg_sys::SPI_gettypeid(tupdesc, ordinal))
+                        f(datum, is_null, pg_sys::SPI_gettypeid(tupdesc, ordinal))
                     }
                 },
                 None => panic!("TupDesc is NULL"),
@@ -450,8 +1120,50 @@ impl SpiTupleTable {
     }
 }
 
-impl SpiHeapTupleData {
+impl SpiHeapTupleData<'_> {
+    /// Get a column's value by name rather than by ordinal position, so that it's unaffected
+    /// by changes to the order of a query's output columns
+    ///
+    /// Returns `Err(SpiError::Noattribute)` if the row has no column with that name
+    pub fn get_by_name<T: FromDatum>(
+        &self,
+        name: &str,
+    ) -> std::result::Result<Option<T>, SpiError> {
+        Ok(self.get_datum(column_ordinal(self.tupdesc, name)?))
+    }
+
+    /// Get this row's value for the column at the specified 1-based ordinal position.
+    ///
+    /// By-reference values such as an `Array<T>` or `&str` point into SPI's memory, so they're
+    /// only valid until the SPI connection is closed.  Use `::copy_datum()` for values that
+    /// need to outlive it.
     pub fn get_datum<T: FromDatum>(&self, ordinal: i32) -> Option<T> {
+        self.read_datum(ordinal, |datum, is_null, typoid| unsafe {
+            T::from_datum(datum, is_null, typoid)
+        })
+    }
+
+    /// Like `::get_datum()`, but the value is copied into the memory context that was current
+    /// when SPI was connected, so it remains valid after the SPI connection is closed.
+    ///
+    /// The copy lives as long as that context, so avoid calling this for every row of a large
+    /// result.
+    pub fn copy_datum<T: FromDatum>(&self, ordinal: i32) -> Option<T> {
+        self.read_datum(ordinal, |datum, is_null, typoid| unsafe {
+            T::from_datum_in_memory_context(
+                PgMemoryContexts::For(self.upper_context),
+                datum,
+                is_null,
+                typoid,
+            )
+        })
+    }
+
+    fn read_datum<T, F: FnOnce(pg_sys::Datum, bool, pg_sys::Oid) -> Option<T>>(
+        &self,
+        ordinal: i32,
+        f: F,
+    ) -> Option<T> {
         unsafe {
             let natts = (*self.tupdesc).natts;
 
@@ -461,39 +1173,85 @@ impl SpiHeapTupleData {
                 let mut is_null = false;
                 let datum = pg_sys::SPI_getbinval(self.data, self.tupdesc, ordinal, &mut is_null);
 
-                T::from_datum(datum, is_null, pg_sys::SPI_gettypeid(self.tupdesc, ordinal))
+                f(datum, is_null, pg_sys::SPI_gettypeid(self.tupdesc, ordinal))
             }
         }
     }
+
+    /// Get the text representation of this row's value for the column at the specified 1-based
+    /// ordinal position, as produced by the column type's output function via `SPI_getvalue()`.
+    ///
+    /// This works for any column type, even those without a Rust wrapper.  Returns `None` if
+    /// the value is NULL or the ordinal is out of range.
+    pub fn get_datum_as_string(&self, ordinal: i32) -> Option<String> {
+        unsafe {
+            let natts = (*self.tupdesc).natts;
+
+            if ordinal < 1 || ordinal > natts {
+                None
+            } else {
+                let value = pg_sys::SPI_getvalue(self.data, self.tupdesc, ordinal);
+                if value.is_null() {
+                    None
+                } else {
+                    let string = std::ffi::CStr::from_ptr(value)
+                        .to_str()
+                        .expect("SPI_getvalue() returned invalid UTF8")
+                        .to_string();
+                    pg_sys::pfree(value as void_mut_ptr);
+                    Some(string)
+                }
+            }
+        }
+    }
+}
+
+/// The "upper executor context", which was current when SPI was connected.  Values read from
+/// an SPI result with `::copy_datum()` are copied into it so they remain valid after the SPI
+/// connection is closed
+fn spi_upper_context() -> pg_sys::MemoryContext {
+    unsafe {
+        // SPI_palloc() allocates in the upper executor context, and every chunk knows its context
+        let ptr = pg_sys::SPI_palloc(1);
+        let context = PgMemoryContexts::Of(ptr as void_ptr).value();
+        pg_sys::pfree(ptr);
+        context
+    }
+}
+
+/// Resolve a column name to its 1-based ordinal position via `SPI_fnumber()`
+fn column_ordinal(tupdesc: pg_sys::TupleDesc, name: &str) -> std::result::Result<i32, SpiError> {
+    let name = std::ffi::CString::new(name).expect("column name contained a null byte");
+    let ordinal = unsafe { pg_sys::SPI_fnumber(tupdesc, name.as_ptr()) };
+
+    // system columns have negative attribute numbers, but they're never part of an SPI result
+    if ordinal > 0 {
+        Ok(ordinal)
+    } else {
+        Err(SpiError::Noattribute)
+    }
 }
 
-impl Iterator for SpiTupleTable {
-    type Item = SpiHeapTupleData;
+impl<'conn> Iterator for SpiTupleTable<'conn> {
+    type Item = SpiHeapTupleData<'conn>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current >= self.size as isize {
-            self.current = -1;
+        if self.current + 1 >= self.size as isize {
+            // stay positioned after the end, so that we keep returning `None`
+            self.current = self.size as isize;
             None
         } else {
             self.current += 1;
-            assert!(self.current >= 0);
             self.get_heap_tuple()
         }
     }
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.size))
-    }
 
     #[inline]
-    fn count(self) -> usize
-    where
-        Self: Sized,
-    {
-        self.size
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.size as isize - self.current - 1).max(0) as usize;
+        (remaining, Some(remaining))
     }
-
-    // Removed this function as it comes with an iterator
-    //fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
 }
+
+impl std::iter::FusedIterator for SpiTupleTable<'_> {}
@@ -0,0 +1,159 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe wrapper around Postgres' system catalog cache (`SearchSysCache()` and friends)
+use crate::{pg_sys, FromDatum, IntoDatum};
+use std::marker::PhantomData;
+
+/// The key (or keys) used to search a system cache.  A single `Oid`, integer, `bool`, or
+/// `&CStr` (for `name` columns) is a one-key search, and tuples of two to four `IntoDatum`
+/// values are multi-key searches.
+///
+/// The datums produced must match the types the cache is keyed on (ie, an `Oid` for `RELOID`)
+pub trait SysCacheKeys {
+    fn into_keys(self) -> [pg_sys::Datum; 4];
+}
+
+macro_rules! single_key {
+    ($($t:ty),*) => {
+        $(
+            impl SysCacheKeys for $t {
+                fn into_keys(self) -> [pg_sys::Datum; 4] {
+                    [key(self), 0, 0, 0]
+                }
+            }
+        )*
+    };
+}
+
+single_key!(bool, i8, i16, i32, i64, u32, &std::ffi::CStr);
+
+impl<A: IntoDatum, B: IntoDatum> SysCacheKeys for (A, B) {
+    fn into_keys(self) -> [pg_sys::Datum; 4] {
+        [key(self.0), key(self.1), 0, 0]
+    }
+}
+
+impl<A: IntoDatum, B: IntoDatum, C: IntoDatum> SysCacheKeys for (A, B, C) {
+    fn into_keys(self) -> [pg_sys::Datum; 4] {
+        [key(self.0), key(self.1), key(self.2), 0]
+    }
+}
+
+impl<A: IntoDatum, B: IntoDatum, C: IntoDatum, D: IntoDatum> SysCacheKeys for (A, B, C, D) {
+    fn into_keys(self) -> [pg_sys::Datum; 4] {
+        [key(self.0), key(self.1), key(self.2), key(self.3)]
+    }
+}
+
+fn key<T: IntoDatum>(value: T) -> pg_sys::Datum {
+    value
+        .into_datum()
+        .expect("system cache keys cannot be NULL")
+}
+
+/// A catalog `Form_pg_xxx` struct that can be looked up by its Oid through a known system cache
+pub trait SysCacheForm {
+    const CACHE_ID: pg_sys::SysCacheIdentifier;
+}
+
+impl SysCacheForm for pg_sys::FormData_pg_class {
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_RELOID;
+}
+
+impl SysCacheForm for pg_sys::FormData_pg_index {
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_INDEXRELID;
+}
+
+impl SysCacheForm for pg_sys::FormData_pg_type {
+    const CACHE_ID: pg_sys::SysCacheIdentifier = pg_sys::SysCacheIdentifier_TYPEOID;
+}
+
+/// Entry point for system cache lookups
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// # let relid: pg_sys::Oid = unimplemented!();
+/// let tuple = PgSysCache::search_form::<pg_sys::FormData_pg_class>(relid).expect("no such relation");
+/// let relname = name_data_to_str(&tuple.get_struct().relname);
+/// ```
+pub struct PgSysCache;
+
+impl PgSysCache {
+    /// Search the system cache identified by `cache_id`, returning the cached tuple, if found,
+    /// viewed as a `T`
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that `T` is the `FormData_pg_xxx` struct
+    /// stored in the catalog backing `cache_id`, nor that `keys` are of the types the cache is
+    /// keyed on
+    pub unsafe fn search<T, K: SysCacheKeys>(
+        cache_id: pg_sys::SysCacheIdentifier,
+        keys: K,
+    ) -> Option<SysCacheTuple<T>> {
+        let keys = keys.into_keys();
+        let tuple = pg_sys::SearchSysCache(cache_id as i32, keys[0], keys[1], keys[2], keys[3]);
+
+        if tuple.is_null() {
+            None
+        } else {
+            Some(SysCacheTuple {
+                tuple,
+                cache_id,
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    /// Look up a catalog tuple by its Oid in the system cache that `T` knows how to search
+    pub fn search_form<T: SysCacheForm>(oid: pg_sys::Oid) -> Option<SysCacheTuple<T>> {
+        unsafe { PgSysCache::search(T::CACHE_ID, oid) }
+    }
+}
+
+/// A tuple found in a system cache.  The tuple is released via `pg_sys::ReleaseSysCache()` when
+/// this instance is dropped.
+pub struct SysCacheTuple<T> {
+    tuple: pg_sys::HeapTuple,
+    cache_id: pg_sys::SysCacheIdentifier,
+    _marker: PhantomData<T>,
+}
+
+impl<T> SysCacheTuple<T> {
+    /// Return the wrapped `pg_sys::HeapTuple` pointer
+    pub fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple
+    }
+
+    /// The fixed-size portion of the tuple, as its catalog `FormData_pg_xxx` struct.
+    ///
+    /// mimics the `GETSTRUCT()` macro from htup_details.h
+    pub fn get_struct(&self) -> &T {
+        unsafe {
+            let data = (*self.tuple).t_data;
+            &*((data as *mut std::os::raw::c_char).add((*data).t_hoff as usize) as *const T)
+        }
+    }
+
+    /// Get a (possibly variable-length or nullable) attribute that isn't accessible through
+    /// `::get_struct()`, via `pg_sys::SysCacheGetAttr()`
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that `attno` is of type `A`
+    pub unsafe fn get_attr<A: FromDatum>(&self, attno: pg_sys::AttrNumber) -> Option<A> {
+        let mut is_null = false;
+        let datum = pg_sys::SysCacheGetAttr(self.cache_id as i32, self.tuple, attno, &mut is_null);
+        A::from_datum(datum, is_null, pg_sys::InvalidOid)
+    }
+}
+
+impl<T> Drop for SysCacheTuple<T> {
+    fn drop(&mut self) {
+        unsafe { pg_sys::ReleaseSysCache(self.tuple) }
+    }
+}
@@ -0,0 +1,644 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! A scaffold for implementing Postgres 12+ table access methods in Rust
+use crate::{guard, pg_guard, pg_sys, PgRelation, PgTupleTableSlot};
+
+/// The essential callbacks of a Postgres `TableAmRoutine`.
+///
+/// Implementations are turned into a `TableAmRoutine` by `make_table_am_routine()`, usually via
+/// the `pg_table_am!()` macro.  Every callback is wrapped in a shim that converts Rust panics into
+/// Postgres ERRORs.
+///
+/// Only the callbacks below are provided, and `COPY FROM` inserts through `::tuple_insert()`.  The
+/// remaining `TableAmRoutine` callbacks (index fetches, parallel scans, VACUUM, ANALYZE, CLUSTER,
+/// sample scans, etc) raise an ERROR saying the table access method doesn't support them, so such
+/// tables only support plain sequential scans, INSERT, UPDATE, DELETE, COPY, and TRUNCATE.
+pub trait TableAccessMethod {
+    /// Which kind of `pg_sys::TupleTableSlot` should be used for this AM's tuples?
+    fn slot_callbacks(_rel: &PgRelation) -> *const pg_sys::TupleTableSlotOps {
+        unsafe { &pg_sys::TTSOpsVirtual }
+    }
+
+    /// Start a scan.  The returned scan descriptor must be allocated by the implementation and
+    /// (usually) embed a `pg_sys::TableScanDescData` as its first field
+    fn scan_begin(
+        rel: &PgRelation,
+        snapshot: pg_sys::Snapshot,
+        nkeys: i32,
+        key: *mut pg_sys::ScanKeyData,
+        pscan: pg_sys::ParallelTableScanDesc,
+        flags: u32,
+    ) -> pg_sys::TableScanDesc;
+
+    /// Restart the scan
+    fn scan_rescan(
+        _scan: pg_sys::TableScanDesc,
+        _key: *mut pg_sys::ScanKeyData,
+        _set_params: bool,
+        _allow_strat: bool,
+        _allow_sync: bool,
+        _allow_pagemode: bool,
+    ) {
+        panic!("this table access method does not support rescans")
+    }
+
+    /// Store the next tuple in `slot`, returning false when the scan is exhausted
+    fn scan_getnextslot(
+        scan: pg_sys::TableScanDesc,
+        direction: pg_sys::ScanDirection,
+        slot: &mut PgTupleTableSlot,
+    ) -> bool;
+
+    /// Finish the scan and release its resources
+    fn scan_end(scan: pg_sys::TableScanDesc);
+
+    /// Insert the tuple in `slot`, which should also be updated with the new tuple's tid
+    fn tuple_insert(
+        rel: &PgRelation,
+        slot: &mut PgTupleTableSlot,
+        cid: pg_sys::CommandId,
+        options: i32,
+        bistate: *mut pg_sys::BulkInsertStateData,
+    );
+
+    /// Delete the tuple identified by `tid`
+    #[allow(clippy::too_many_arguments)]
+    fn tuple_delete(
+        rel: &PgRelation,
+        tid: pg_sys::ItemPointer,
+        cid: pg_sys::CommandId,
+        snapshot: pg_sys::Snapshot,
+        crosscheck: pg_sys::Snapshot,
+        wait: bool,
+        tmfd: *mut pg_sys::TM_FailureData,
+        changing_part: bool,
+    ) -> pg_sys::TM_Result;
+
+    /// Replace the tuple identified by `otid` with the tuple in `slot`
+    #[allow(clippy::too_many_arguments)]
+    fn tuple_update(
+        rel: &PgRelation,
+        otid: pg_sys::ItemPointer,
+        slot: &mut PgTupleTableSlot,
+        cid: pg_sys::CommandId,
+        snapshot: pg_sys::Snapshot,
+        crosscheck: pg_sys::Snapshot,
+        wait: bool,
+        tmfd: *mut pg_sys::TM_FailureData,
+        lockmode: *mut pg_sys::LockTupleMode,
+        update_indexes: *mut bool,
+    ) -> pg_sys::TM_Result;
+
+    /// Called after a bulk insert (such as COPY) has finished
+    fn finish_bulk_insert(_rel: &PgRelation, _options: i32) {}
+
+    /// Create the storage for a new relation (or a new relfilenode for an existing one, such as
+    /// during TRUNCATE)
+    fn relation_set_new_filenode(
+        rel: &PgRelation,
+        newrnode: &pg_sys::RelFileNode,
+        persistence: std::os::raw::c_char,
+        freeze_xid: &mut pg_sys::TransactionId,
+        minmulti: &mut pg_sys::MultiXactId,
+    );
+
+    /// Remove all of the relation's data outside of transactional control
+    fn relation_nontransactional_truncate(_rel: &PgRelation) {
+        panic!("this table access method does not support non-transactional truncation")
+    }
+
+    /// The size, in bytes, of the given fork
+    fn relation_size(rel: &PgRelation, fork_number: pg_sys::ForkNumber) -> u64;
+
+    /// Does this relation need a TOAST table?
+    fn relation_needs_toast_table(_rel: &PgRelation) -> bool {
+        false
+    }
+
+    /// Provide the planner with size estimates.  The default estimates an empty relation
+    fn relation_estimate_size(
+        _rel: &PgRelation,
+        _attr_widths: *mut i32,
+        pages: &mut pg_sys::BlockNumber,
+        tuples: &mut f64,
+        allvisfrac: &mut f64,
+    ) {
+        *pages = 0;
+        *tuples = 0.0;
+        *allvisfrac = 0.0;
+    }
+}
+
+/// Build a `pg_sys::TableAmRoutine` whose callbacks dispatch to `T`.
+///
+/// Postgres keeps a pointer to the routine returned by a table AM handler, so the result must be
+/// stored somewhere that lives for the life of the backend.  The `pg_table_am!()` macro does this.
+pub fn make_table_am_routine<T: TableAccessMethod>() -> pg_sys::TableAmRoutine {
+    // all of TableAmRoutine's fields are Option<fn> (or the NodeTag), so zeroed means "all NULL"
+    let mut routine: pg_sys::TableAmRoutine = unsafe { std::mem::zeroed() };
+
+    routine.type_ = pg_sys::NodeTag_T_TableAmRoutine;
+    routine.slot_callbacks = Some(slot_callbacks::<T>);
+    routine.scan_begin = Some(scan_begin::<T>);
+    routine.scan_end = Some(scan_end::<T>);
+    routine.scan_rescan = Some(scan_rescan::<T>);
+    routine.scan_getnextslot = Some(scan_getnextslot::<T>);
+    routine.tuple_insert = Some(tuple_insert::<T>);
+    routine.multi_insert = Some(multi_insert::<T>);
+    routine.tuple_delete = Some(tuple_delete::<T>);
+    routine.tuple_update = Some(tuple_update::<T>);
+    routine.finish_bulk_insert = Some(finish_bulk_insert::<T>);
+    routine.relation_set_new_filenode = Some(relation_set_new_filenode::<T>);
+    routine.relation_nontransactional_truncate = Some(relation_nontransactional_truncate::<T>);
+    routine.relation_size = Some(relation_size::<T>);
+    routine.relation_needs_toast_table = Some(relation_needs_toast_table::<T>);
+    routine.relation_estimate_size = Some(relation_estimate_size::<T>);
+
+    // Postgres requires these, but `TableAccessMethod` doesn't support them.  The bitmap scan
+    // callbacks are optional, and bitmap scans need an index anyway
+    routine.parallelscan_estimate = Some(parallelscan_estimate);
+    routine.parallelscan_initialize = Some(parallelscan_initialize);
+    routine.parallelscan_reinitialize = Some(parallelscan_reinitialize);
+    routine.index_fetch_begin = Some(index_fetch_begin);
+    routine.index_fetch_reset = Some(index_fetch_reset);
+    routine.index_fetch_end = Some(index_fetch_end);
+    routine.index_fetch_tuple = Some(index_fetch_tuple);
+    routine.tuple_fetch_row_version = Some(tuple_fetch_row_version);
+    routine.tuple_tid_valid = Some(tuple_tid_valid);
+    routine.tuple_get_latest_tid = Some(tuple_get_latest_tid);
+    routine.tuple_satisfies_snapshot = Some(tuple_satisfies_snapshot);
+    routine.compute_xid_horizon_for_tuples = Some(compute_xid_horizon_for_tuples);
+    routine.tuple_insert_speculative = Some(tuple_insert_speculative);
+    routine.tuple_complete_speculative = Some(tuple_complete_speculative);
+    routine.tuple_lock = Some(tuple_lock);
+    routine.relation_copy_data = Some(relation_copy_data);
+    routine.relation_copy_for_cluster = Some(relation_copy_for_cluster);
+    routine.relation_vacuum = Some(relation_vacuum);
+    routine.scan_analyze_next_block = Some(scan_analyze_next_block);
+    routine.scan_analyze_next_tuple = Some(scan_analyze_next_tuple);
+    routine.index_build_range_scan = Some(index_build_range_scan);
+    routine.index_validate_scan = Some(index_validate_scan);
+    routine.scan_sample_next_block = Some(scan_sample_next_block);
+    routine.scan_sample_next_tuple = Some(scan_sample_next_tuple);
+
+    routine
+}
+
+/// Create the handler function for a table access method.
+///
+/// The handler returns a `TableAmRoutine` built from the given `TableAccessMethod` implementation.
+/// Its SQL must be declared manually, along with the access method itself:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// struct MyAm;
+/// # impl TableAccessMethod for MyAm {
+/// #    fn scan_begin(rel: &PgRelation, snapshot: pg_sys::Snapshot, nkeys: i32, key: *mut pg_sys::ScanKeyData, pscan: pg_sys::ParallelTableScanDesc, flags: u32) -> pg_sys::TableScanDesc { unimplemented!() }
+/// #    fn scan_getnextslot(scan: pg_sys::TableScanDesc, direction: pg_sys::ScanDirection, slot: &mut PgTupleTableSlot) -> bool { unimplemented!() }
+/// #    fn scan_end(scan: pg_sys::TableScanDesc) { unimplemented!() }
+/// #    fn tuple_insert(rel: &PgRelation, slot: &mut PgTupleTableSlot, cid: pg_sys::CommandId, options: i32, bistate: *mut pg_sys::BulkInsertStateData) { unimplemented!() }
+/// #    fn tuple_delete(rel: &PgRelation, tid: pg_sys::ItemPointer, cid: pg_sys::CommandId, snapshot: pg_sys::Snapshot, crosscheck: pg_sys::Snapshot, wait: bool, tmfd: *mut pg_sys::TM_FailureData, changing_part: bool) -> pg_sys::TM_Result { unimplemented!() }
+/// #    fn tuple_update(rel: &PgRelation, otid: pg_sys::ItemPointer, slot: &mut PgTupleTableSlot, cid: pg_sys::CommandId, snapshot: pg_sys::Snapshot, crosscheck: pg_sys::Snapshot, wait: bool, tmfd: *mut pg_sys::TM_FailureData, lockmode: *mut pg_sys::LockTupleMode, update_indexes: *mut bool) -> pg_sys::TM_Result { unimplemented!() }
+/// #    fn relation_set_new_filenode(rel: &PgRelation, newrnode: &pg_sys::RelFileNode, persistence: std::os::raw::c_char, freeze_xid: &mut pg_sys::TransactionId, minmulti: &mut pg_sys::MultiXactId) { unimplemented!() }
+/// #    fn relation_size(rel: &PgRelation, fork_number: pg_sys::ForkNumber) -> u64 { unimplemented!() }
+/// # }
+///
+/// pg_table_am!(my_am_handler, MyAm);
+///
+/// extension_sql! { r#"
+/// CREATE FUNCTION my_am_handler(internal) RETURNS table_am_handler LANGUAGE c AS 'MODULE_PATHNAME', 'my_am_handler_wrapper';
+/// CREATE ACCESS METHOD my_am TYPE TABLE HANDLER my_am_handler;
+/// "#}
+/// ```
+#[macro_export]
+macro_rules! pg_table_am {
+    ($handler:ident, $am:ty) => {
+        // `#[pg_extern]` only recognizes the argument as `pg_sys::FunctionCallInfo`, and its
+        // wrapper refers to `pg_sys` too, so bring it into scope without clashing with the caller's
+        const _: () = {
+            use $crate::pg_sys;
+
+            #[$crate::pg_extern]
+            fn $handler(_fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+                static mut ROUTINE: Option<pg_sys::TableAmRoutine> = None;
+
+                unsafe {
+                    ROUTINE.get_or_insert_with($crate::make_table_am_routine::<$am>)
+                        as *const pg_sys::TableAmRoutine as pg_sys::Datum
+                }
+            }
+        };
+    };
+}
+
+unsafe extern "C" fn slot_callbacks<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+) -> *const pg_sys::TupleTableSlotOps {
+    guard(|| T::slot_callbacks(&PgRelation::from_pg(rel)))
+}
+
+unsafe extern "C" fn scan_begin<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    snapshot: pg_sys::Snapshot,
+    nkeys: std::os::raw::c_int,
+    key: *mut pg_sys::ScanKeyData,
+    pscan: pg_sys::ParallelTableScanDesc,
+    flags: u32,
+) -> pg_sys::TableScanDesc {
+    guard(|| {
+        T::scan_begin(
+            &PgRelation::from_pg(rel),
+            snapshot,
+            nkeys,
+            key,
+            pscan,
+            flags,
+        )
+    })
+}
+
+unsafe extern "C" fn scan_end<T: TableAccessMethod>(scan: pg_sys::TableScanDesc) {
+    guard(|| T::scan_end(scan))
+}
+
+unsafe extern "C" fn scan_rescan<T: TableAccessMethod>(
+    scan: pg_sys::TableScanDesc,
+    key: *mut pg_sys::ScanKeyData,
+    set_params: bool,
+    allow_strat: bool,
+    allow_sync: bool,
+    allow_pagemode: bool,
+) {
+    guard(|| {
+        T::scan_rescan(
+            scan,
+            key,
+            set_params,
+            allow_strat,
+            allow_sync,
+            allow_pagemode,
+        )
+    })
+}
+
+unsafe extern "C" fn scan_getnextslot<T: TableAccessMethod>(
+    scan: pg_sys::TableScanDesc,
+    direction: pg_sys::ScanDirection,
+    slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    guard(|| T::scan_getnextslot(scan, direction, &mut PgTupleTableSlot::from_pg(slot)))
+}
+
+unsafe extern "C" fn tuple_insert<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    slot: *mut pg_sys::TupleTableSlot,
+    cid: pg_sys::CommandId,
+    options: std::os::raw::c_int,
+    bistate: *mut pg_sys::BulkInsertStateData,
+) {
+    guard(|| {
+        T::tuple_insert(
+            &PgRelation::from_pg(rel),
+            &mut PgTupleTableSlot::from_pg(slot),
+            cid,
+            options,
+            bistate,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn tuple_delete<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    tid: pg_sys::ItemPointer,
+    cid: pg_sys::CommandId,
+    snapshot: pg_sys::Snapshot,
+    crosscheck: pg_sys::Snapshot,
+    wait: bool,
+    tmfd: *mut pg_sys::TM_FailureData,
+    changing_part: bool,
+) -> pg_sys::TM_Result {
+    guard(|| {
+        T::tuple_delete(
+            &PgRelation::from_pg(rel),
+            tid,
+            cid,
+            snapshot,
+            crosscheck,
+            wait,
+            tmfd,
+            changing_part,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn tuple_update<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    otid: pg_sys::ItemPointer,
+    slot: *mut pg_sys::TupleTableSlot,
+    cid: pg_sys::CommandId,
+    snapshot: pg_sys::Snapshot,
+    crosscheck: pg_sys::Snapshot,
+    wait: bool,
+    tmfd: *mut pg_sys::TM_FailureData,
+    lockmode: *mut pg_sys::LockTupleMode,
+    update_indexes: *mut bool,
+) -> pg_sys::TM_Result {
+    guard(|| {
+        T::tuple_update(
+            &PgRelation::from_pg(rel),
+            otid,
+            &mut PgTupleTableSlot::from_pg(slot),
+            cid,
+            snapshot,
+            crosscheck,
+            wait,
+            tmfd,
+            lockmode,
+            update_indexes,
+        )
+    })
+}
+
+unsafe extern "C" fn finish_bulk_insert<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    options: std::os::raw::c_int,
+) {
+    guard(|| T::finish_bulk_insert(&PgRelation::from_pg(rel), options))
+}
+
+unsafe extern "C" fn relation_set_new_filenode<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    newrnode: *const pg_sys::RelFileNode,
+    persistence: std::os::raw::c_char,
+    freeze_xid: *mut pg_sys::TransactionId,
+    minmulti: *mut pg_sys::MultiXactId,
+) {
+    guard(|| {
+        T::relation_set_new_filenode(
+            &PgRelation::from_pg(rel),
+            &*newrnode,
+            persistence,
+            &mut *freeze_xid,
+            &mut *minmulti,
+        )
+    })
+}
+
+unsafe extern "C" fn relation_nontransactional_truncate<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+) {
+    guard(|| T::relation_nontransactional_truncate(&PgRelation::from_pg(rel)))
+}
+
+unsafe extern "C" fn relation_size<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    fork_number: pg_sys::ForkNumber,
+) -> u64 {
+    guard(|| T::relation_size(&PgRelation::from_pg(rel), fork_number))
+}
+
+unsafe extern "C" fn relation_needs_toast_table<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+) -> bool {
+    guard(|| T::relation_needs_toast_table(&PgRelation::from_pg(rel)))
+}
+
+unsafe extern "C" fn relation_estimate_size<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    attr_widths: *mut i32,
+    pages: *mut pg_sys::BlockNumber,
+    tuples: *mut f64,
+    allvisfrac: *mut f64,
+) {
+    guard(|| {
+        T::relation_estimate_size(
+            &PgRelation::from_pg(rel),
+            attr_widths,
+            &mut *pages,
+            &mut *tuples,
+            &mut *allvisfrac,
+        )
+    })
+}
+
+/// `COPY FROM` inserts its rows in batches, which are inserted one at a time
+unsafe extern "C" fn multi_insert<T: TableAccessMethod>(
+    rel: pg_sys::Relation,
+    slots: *mut *mut pg_sys::TupleTableSlot,
+    nslots: std::os::raw::c_int,
+    cid: pg_sys::CommandId,
+    options: std::os::raw::c_int,
+    bistate: *mut pg_sys::BulkInsertStateData,
+) {
+    for i in 0..nslots as usize {
+        tuple_insert::<T>(rel, *slots.add(i), cid, options, bistate);
+    }
+}
+
+/// Define a callback for a `TableAmRoutine` field that `TableAccessMethod` doesn't provide, which
+/// raises an ERROR saying the table access method doesn't support `$what`
+macro_rules! unsupported {
+    ($name:ident, $what:literal, ($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?) => {
+        #[pg_guard]
+        unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            panic!(concat!("this table access method does not support ", $what))
+        }
+    };
+}
+
+unsupported!(parallelscan_estimate, "parallel scans", (_rel: pg_sys::Relation) -> pg_sys::Size);
+unsupported!(
+    parallelscan_initialize,
+    "parallel scans",
+    (_rel: pg_sys::Relation, _pscan: pg_sys::ParallelTableScanDesc) -> pg_sys::Size
+);
+unsupported!(
+    parallelscan_reinitialize,
+    "parallel scans",
+    (_rel: pg_sys::Relation, _pscan: pg_sys::ParallelTableScanDesc)
+);
+unsupported!(
+    index_fetch_begin,
+    "index scans",
+    (_rel: pg_sys::Relation) -> *mut pg_sys::IndexFetchTableData
+);
+unsupported!(index_fetch_reset, "index scans", (_data: *mut pg_sys::IndexFetchTableData));
+unsupported!(index_fetch_end, "index scans", (_data: *mut pg_sys::IndexFetchTableData));
+unsupported!(
+    index_fetch_tuple,
+    "index scans",
+    (
+        _scan: *mut pg_sys::IndexFetchTableData,
+        _tid: pg_sys::ItemPointer,
+        _snapshot: pg_sys::Snapshot,
+        _slot: *mut pg_sys::TupleTableSlot,
+        _call_again: *mut bool,
+        _all_dead: *mut bool,
+    ) -> bool
+);
+unsupported!(
+    tuple_fetch_row_version,
+    "fetching tuples by TID",
+    (
+        _rel: pg_sys::Relation,
+        _tid: pg_sys::ItemPointer,
+        _snapshot: pg_sys::Snapshot,
+        _slot: *mut pg_sys::TupleTableSlot,
+    ) -> bool
+);
+unsupported!(
+    tuple_tid_valid,
+    "TID scans",
+    (_scan: pg_sys::TableScanDesc, _tid: pg_sys::ItemPointer) -> bool
+);
+unsupported!(
+    tuple_get_latest_tid,
+    "fetching the latest version of a tuple",
+    (_scan: pg_sys::TableScanDesc, _tid: pg_sys::ItemPointer)
+);
+unsupported!(
+    tuple_satisfies_snapshot,
+    "checking tuple visibility",
+    (
+        _rel: pg_sys::Relation,
+        _slot: *mut pg_sys::TupleTableSlot,
+        _snapshot: pg_sys::Snapshot,
+    ) -> bool
+);
+unsupported!(
+    compute_xid_horizon_for_tuples,
+    "index tuple deletion",
+    (
+        _rel: pg_sys::Relation,
+        _items: *mut pg_sys::ItemPointerData,
+        _nitems: std::os::raw::c_int,
+    ) -> pg_sys::TransactionId
+);
+unsupported!(
+    tuple_insert_speculative,
+    "INSERT ... ON CONFLICT",
+    (
+        _rel: pg_sys::Relation,
+        _slot: *mut pg_sys::TupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _options: std::os::raw::c_int,
+        _bistate: *mut pg_sys::BulkInsertStateData,
+        _spec_token: u32,
+    )
+);
+unsupported!(
+    tuple_complete_speculative,
+    "INSERT ... ON CONFLICT",
+    (_rel: pg_sys::Relation, _slot: *mut pg_sys::TupleTableSlot, _spec_token: u32, _succeeded: bool)
+);
+unsupported!(
+    tuple_lock,
+    "row locking",
+    (
+        _rel: pg_sys::Relation,
+        _tid: pg_sys::ItemPointer,
+        _snapshot: pg_sys::Snapshot,
+        _slot: *mut pg_sys::TupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _mode: pg_sys::LockTupleMode,
+        _wait_policy: pg_sys::LockWaitPolicy,
+        _flags: u8,
+        _tmfd: *mut pg_sys::TM_FailureData,
+    ) -> pg_sys::TM_Result
+);
+unsupported!(
+    relation_copy_data,
+    "changing tablespaces",
+    (_rel: pg_sys::Relation, _newrnode: *const pg_sys::RelFileNode)
+);
+unsupported!(
+    relation_copy_for_cluster,
+    "CLUSTER or VACUUM FULL",
+    (
+        _new_table: pg_sys::Relation,
+        _old_table: pg_sys::Relation,
+        _old_index: pg_sys::Relation,
+        _use_sort: bool,
+        _oldest_xmin: pg_sys::TransactionId,
+        _xid_cutoff: *mut pg_sys::TransactionId,
+        _multi_cutoff: *mut pg_sys::MultiXactId,
+        _num_tuples: *mut f64,
+        _tups_vacuumed: *mut f64,
+        _tups_recently_dead: *mut f64,
+    )
+);
+unsupported!(
+    relation_vacuum,
+    "VACUUM",
+    (
+        _rel: pg_sys::Relation,
+        _params: *mut pg_sys::VacuumParams,
+        _bstrategy: pg_sys::BufferAccessStrategy,
+    )
+);
+unsupported!(
+    scan_analyze_next_block,
+    "ANALYZE",
+    (
+        _scan: pg_sys::TableScanDesc,
+        _blockno: pg_sys::BlockNumber,
+        _bstrategy: pg_sys::BufferAccessStrategy,
+    ) -> bool
+);
+unsupported!(
+    scan_analyze_next_tuple,
+    "ANALYZE",
+    (
+        _scan: pg_sys::TableScanDesc,
+        _oldest_xmin: pg_sys::TransactionId,
+        _liverows: *mut f64,
+        _deadrows: *mut f64,
+        _slot: *mut pg_sys::TupleTableSlot,
+    ) -> bool
+);
+unsupported!(
+    index_build_range_scan,
+    "indexes",
+    (
+        _table_rel: pg_sys::Relation,
+        _index_rel: pg_sys::Relation,
+        _index_info: *mut pg_sys::IndexInfo,
+        _allow_sync: bool,
+        _anyvisible: bool,
+        _progress: bool,
+        _start_blockno: pg_sys::BlockNumber,
+        _numblocks: pg_sys::BlockNumber,
+        _callback: pg_sys::IndexBuildCallback,
+        _callback_state: *mut std::os::raw::c_void,
+        _scan: pg_sys::TableScanDesc,
+    ) -> f64
+);
+unsupported!(
+    index_validate_scan,
+    "indexes",
+    (
+        _table_rel: pg_sys::Relation,
+        _index_rel: pg_sys::Relation,
+        _index_info: *mut pg_sys::IndexInfo,
+        _snapshot: pg_sys::Snapshot,
+        _state: *mut pg_sys::ValidateIndexState,
+    )
+);
+unsupported!(
+    scan_sample_next_block,
+    "TABLESAMPLE",
+    (_scan: pg_sys::TableScanDesc, _scanstate: *mut pg_sys::SampleScanState) -> bool
+);
+unsupported!(
+    scan_sample_next_tuple,
+    "TABLESAMPLE",
+    (
+        _scan: pg_sys::TableScanDesc,
+        _scanstate: *mut pg_sys::SampleScanState,
+        _slot: *mut pg_sys::TupleTableSlot,
+    ) -> bool
+);
@@ -3,7 +3,7 @@
 
 //! Helper functions for working with custom Rust trigger functions
 
-use crate::{is_a, pg_sys};
+use crate::{is_a, pg_sys, PgBox, PgHeapTuple, PgRelation, PgTupleDesc};
 
 #[inline]
 pub fn called_as_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
@@ -55,3 +55,131 @@ pub fn trigger_fired_after(event: u32) -> bool {
 pub fn trigger_fired_instead(event: u32) -> bool {
     event & pg_sys::TRIGGER_EVENT_TIMINGMASK == pg_sys::TRIGGER_EVENT_INSTEAD
 }
+
+/// A safe wrapper around the `pg_sys::TriggerData` passed to a trigger function
+pub struct PgTrigger {
+    trigdata: PgBox<pg_sys::TriggerData>,
+}
+
+impl PgTrigger {
+    /// Wrap the `pg_sys::TriggerData` of a function called as a trigger
+    ///
+    /// ## Panics
+    ///
+    /// If the function was not called as a trigger
+    pub fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_trigger(fcinfo) {
+            panic!("function was not called as a trigger");
+        }
+
+        let trigdata = unsafe { fcinfo.as_ref() }.unwrap().context as *mut pg_sys::TriggerData;
+        PgTrigger {
+            trigdata: PgBox::from_pg(trigdata),
+        }
+    }
+
+    /// The `TRIGGER_EVENT_xxx` flags describing why the trigger fired
+    pub fn event(&self) -> u32 {
+        self.trigdata.tg_event
+    }
+
+    /// The relation the trigger fired on
+    pub fn relation(&self) -> PgRelation {
+        unsafe { PgRelation::from_pg(self.trigdata.tg_relation) }
+    }
+
+    /// Iterate the rows of the `REFERENCING OLD TABLE` transition table, if the trigger has one
+    pub fn old_table(&self) -> Option<TransitionTableIterator> {
+        self.transition_table(self.trigdata.tg_oldtable)
+    }
+
+    /// Iterate the rows of the `REFERENCING NEW TABLE` transition table, if the trigger has one
+    pub fn new_table(&self) -> Option<TransitionTableIterator> {
+        self.transition_table(self.trigdata.tg_newtable)
+    }
+
+    fn transition_table(
+        &self,
+        tuplestore: *mut pg_sys::Tuplestorestate,
+    ) -> Option<TransitionTableIterator> {
+        if tuplestore.is_null() {
+            return None;
+        }
+
+        let tupdesc = unsafe { (*self.trigdata.tg_relation).rd_att };
+        unsafe {
+            // start from the beginning, even if something else already read the tuplestore
+            pg_sys::tuplestore_rescan(tuplestore);
+        }
+
+        Some(TransitionTableIterator {
+            tuplestore,
+            slot: make_tuple_table_slot(tupdesc),
+            tupdesc,
+            _trigger: std::marker::PhantomData,
+        })
+    }
+}
+
+/// Iterates the rows of a trigger's transition table as `PgHeapTuple`s copied into the
+/// `CurrentMemoryContext`
+///
+/// The transition table is rewound when this iterator is dropped
+pub struct TransitionTableIterator<'a> {
+    tuplestore: *mut pg_sys::Tuplestorestate,
+    slot: *mut pg_sys::TupleTableSlot,
+    tupdesc: pg_sys::TupleDesc,
+    _trigger: std::marker::PhantomData<&'a PgTrigger>,
+}
+
+impl<'a> Iterator for TransitionTableIterator<'a> {
+    type Item = PgHeapTuple<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            if !pg_sys::tuplestore_gettupleslot(self.tuplestore, true, false, self.slot) {
+                return None;
+            }
+
+            Some(PgHeapTuple::from_heap_tuple(
+                PgBox::from_rust(copy_slot_heap_tuple(self.slot)),
+                PgTupleDesc::from_pg_unchecked(self.tupdesc),
+            ))
+        }
+    }
+}
+
+impl<'a> Drop for TransitionTableIterator<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::ExecDropSingleTupleTableSlot(self.slot);
+            pg_sys::tuplestore_rescan(self.tuplestore);
+        }
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+fn make_tuple_table_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    unsafe { pg_sys::MakeSingleTupleTableSlot(tupdesc) }
+}
+
+#[cfg(feature = "pg12")]
+fn make_tuple_table_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    unsafe { pg_sys::MakeSingleTupleTableSlot(tupdesc, &pg_sys::TTSOpsMinimalTuple) }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn copy_slot_heap_tuple(slot: *mut pg_sys::TupleTableSlot) -> pg_sys::HeapTuple {
+    pg_sys::ExecCopySlotTuple(slot)
+}
+
+#[cfg(feature = "pg12")]
+unsafe fn copy_slot_heap_tuple(slot: *mut pg_sys::TupleTableSlot) -> pg_sys::HeapTuple {
+    let mut should_free = false;
+    let tuple = pg_sys::ExecFetchSlotHeapTuple(slot, false, &mut should_free);
+    if should_free {
+        tuple
+    } else {
+        pg_sys::heap_copytuple(tuple)
+    }
+}
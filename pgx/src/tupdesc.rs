@@ -2,7 +2,7 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 //! Provides a safe wrapper around Postgres' `pg_sys::TupleDescData` struct
-use crate::{pg_sys, void_mut_ptr, FromDatum, PgBox, PgRelation};
+use crate::{pg_sys, void_mut_ptr, FromDatum, PgBox, PgHeapTuple, PgRelation};
 
 use std::ops::Deref;
 
@@ -66,6 +66,23 @@ impl<'a> PgTupleDesc<'a> {
         }
     }
 
+    /// Wrap a Postgres-provided `pg_sys::TupleDescData` that is neither reference counted nor
+    /// free'd when this `PgTupleDesc` is dropped, such as a relation's `rd_att`
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that the provided `pg_sys::TupleDesc` is valid
+    /// and will outlive this `PgTupleDesc`
+    pub unsafe fn from_pg_unchecked<'b>(ptr: pg_sys::TupleDesc) -> PgTupleDesc<'b> {
+        PgTupleDesc {
+            tupdesc: PgBox::from_pg(ptr),
+            parent: None,
+            data: None,
+            need_release: false,
+            need_pfree: false,
+        }
+    }
+
     /// Wrap a copy of a `pg_sys::TupleDesc`.  This form is not reference counted and the copy is
     /// allocated in the `CurrentMemoryContext`
     ///
@@ -208,6 +225,129 @@ impl<'a> PgTupleDesc<'a> {
             curr: 0,
         }
     }
+
+    /// Iterate over our attributes, skipping any that have been dropped
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let relation = unsafe { PgRelation::open_with_name("pg_class") }.unwrap();
+    /// for att in relation.tuple_desc().attributes() {
+    ///     info!("{} {:?} not null={}", att.name(), att.type_oid(), att.is_not_null());
+    /// }
+    /// ```
+    pub fn attributes(&self) -> impl Iterator<Item = &pg_sys::FormData_pg_attribute> {
+        self.iter().filter(|att| !att.is_dropped())
+    }
+
+    /// Find the 1-based attribute number of the (non-dropped) attribute named `name`
+    pub fn attno(&self, name: &str) -> Option<usize> {
+        self.iter()
+            .position(|att| !att.is_dropped() && att.name() == name)
+            .map(|idx| idx + 1)
+    }
+
+    /// Form a new tuple, in the `CurrentMemoryContext`, from a `(datum, is_null)` pair per
+    /// attribute, via Postgres' `heap_form_tuple()`.  The returned `PgHeapTuple` is described by
+    /// a copy of this `PgTupleDesc`.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,no_run
+    /// use pgx::*;
+    ///
+    /// let relation = unsafe { PgRelation::open_with_name("users") }.unwrap();
+    /// let tuple = relation.tuple_desc().form_tuple(&[
+    ///     (42.into_datum().unwrap(), false),
+    ///     (0, true), // a NULL "name"
+    /// ]);
+    /// assert_eq!(tuple.get_by_name::<i32>("id"), Some(42));
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If the number of values doesn't match the number of attributes
+    pub fn form_tuple<'b>(&self, values: &[(pg_sys::Datum, bool)]) -> PgHeapTuple<'b> {
+        if values.len() != self.len() {
+            panic!(
+                "expected {} values for a tuple, but got {}",
+                self.len(),
+                values.len()
+            );
+        }
+
+        let mut datums = values.iter().map(|(datum, _)| *datum).collect::<Vec<_>>();
+        let mut nulls = values
+            .iter()
+            .map(|(_, is_null)| *is_null)
+            .collect::<Vec<_>>();
+        unsafe {
+            let tuple =
+                pg_sys::heap_form_tuple(self.as_ptr(), datums.as_mut_ptr(), nulls.as_mut_ptr());
+            PgHeapTuple::from_heap_tuple(
+                PgBox::from_pg(tuple),
+                PgTupleDesc::from_pg_copy(self.as_ptr()),
+            )
+        }
+    }
+
+    /// Form a copy of `existing`, in the `CurrentMemoryContext`, with the attributes in `changes`
+    /// replaced, via Postgres' `heap_modify_tuple()`.  Each change is a
+    /// `(1-based attribute number, datum, is_null)` triple, and the attributes that aren't
+    /// changed keep their values from `existing`.
+    ///
+    /// This is how a `BEFORE` row trigger edits the row it returns.
+    ///
+    /// ## Panics
+    ///
+    /// If `existing` doesn't have the same number of attributes as this `PgTupleDesc`, or if an
+    /// attribute number is out of bounds
+    pub fn modify_tuple<'b>(
+        &self,
+        existing: &PgHeapTuple,
+        changes: &[(usize, pg_sys::Datum, bool)],
+    ) -> PgHeapTuple<'b> {
+        if existing.len() != self.len() {
+            panic!(
+                "cannot modify a tuple with {} attributes using a tuple descriptor with {} attributes",
+                existing.len(),
+                self.len()
+            );
+        }
+
+        let mut datums = vec![0 as pg_sys::Datum; self.len()];
+        let mut nulls = vec![false; self.len()];
+        let mut replace = vec![false; self.len()];
+        for (attno, datum, is_null) in changes {
+            if *attno < 1 || *attno > self.len() {
+                panic!(
+                    "attribute number {} is out of bounds for a tuple with {} attributes",
+                    attno,
+                    self.len()
+                );
+            }
+
+            datums[attno - 1] = *datum;
+            nulls[attno - 1] = *is_null;
+            replace[attno - 1] = true;
+        }
+
+        unsafe {
+            let tuple = pg_sys::heap_modify_tuple(
+                existing.as_ptr(),
+                self.as_ptr(),
+                datums.as_mut_ptr(),
+                nulls.as_mut_ptr(),
+                replace.as_mut_ptr(),
+            );
+            PgHeapTuple::from_heap_tuple(
+                PgBox::from_pg(tuple),
+                PgTupleDesc::from_pg_copy(self.as_ptr()),
+            )
+        }
+    }
 }
 
 impl<'a> Deref for PgTupleDesc<'a> {
@@ -0,0 +1,225 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe wrapper around Postgres' `pg_sys::TupleTableSlot` struct
+use crate::{pg_sys, FromDatum, IntoDatum, PgBox, PgTupleDesc};
+
+/// A safe wrapper around a `pg_sys::TupleTableSlot`, providing typed access to its attributes.
+///
+/// Attribute numbers are 1-based, like everywhere else in Postgres, and are validated against
+/// the slot's tuple descriptor.
+///
+/// ## Examples
+///
+/// Building a virtual tuple:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// # let tupdesc: PgTupleDesc = unimplemented!();
+/// let mut slot = PgTupleTableSlot::new(&tupdesc);
+/// slot.clear();
+/// slot.set(1, 42i32);
+/// slot.set(2, Option::<i32>::None);
+/// slot.store_virtual();
+///
+/// assert_eq!(slot.get::<i32>(1), Some(42));
+/// ```
+pub struct PgTupleTableSlot {
+    slot: PgBox<pg_sys::TupleTableSlot>,
+    need_drop: bool,
+}
+
+impl PgTupleTableSlot {
+    /// Create a new, empty, "virtual" slot for tuples described by `tupdesc`, allocated in the
+    /// `CurrentMemoryContext`.
+    ///
+    /// The slot is dropped via `pg_sys::ExecDropSingleTupleTableSlot()` when this instance is
+    /// dropped, so `tupdesc` must outlive it
+    pub fn new(tupdesc: &PgTupleDesc) -> Self {
+        PgTupleTableSlot {
+            slot: PgBox::from_pg(make_virtual_slot(tupdesc.as_ptr())),
+            need_drop: true,
+        }
+    }
+
+    /// Wrap a Postgres-provided `pg_sys::TupleTableSlot`, such as one handed to a table access
+    /// method or scan node.  It is not dropped when this instance is dropped.
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that the provided slot is valid
+    pub unsafe fn from_pg(ptr: *mut pg_sys::TupleTableSlot) -> Self {
+        PgTupleTableSlot {
+            slot: PgBox::from_pg(ptr),
+            need_drop: false,
+        }
+    }
+
+    /// Return the wrapped `pg_sys::TupleTableSlot` pointer
+    pub fn as_ptr(&self) -> *mut pg_sys::TupleTableSlot {
+        self.slot.as_ptr()
+    }
+
+    /// How many attributes does this slot's tuple descriptor have?
+    pub fn natts(&self) -> usize {
+        unsafe { (*self.slot.tts_tupleDescriptor).natts as usize }
+    }
+
+    /// Does this slot currently hold a tuple?
+    pub fn is_empty(&self) -> bool {
+        slot_is_empty(&self.slot)
+    }
+
+    /// Get a typed attribute value, deforming the slot's tuple as far as `attno` if necessary
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of bounds, or the slot is empty
+    pub fn get<T: FromDatum>(&self, attno: usize) -> Option<T> {
+        self.check_attno(attno);
+        if self.is_empty() {
+            panic!("cannot get an attribute from an empty TupleTableSlot");
+        }
+
+        unsafe {
+            getsomeattrs(self.slot.as_ptr(), attno as i32);
+
+            let idx = attno - 1;
+            let datum = *self.slot.tts_values.add(idx);
+            let is_null = *self.slot.tts_isnull.add(idx);
+            let typoid = self.atttypid(idx);
+
+            T::from_datum(datum, is_null, typoid)
+        }
+    }
+
+    /// Set an attribute value while building a virtual tuple.  Call `::clear()` before setting
+    /// the first attribute and `::store_virtual()` after setting the last.
+    ///
+    /// Pass-by-reference values must remain valid for as long as the slot holds the tuple, which
+    /// generally means they should be allocated in (or copied to) a Postgres `MemoryContext`.
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of bounds
+    pub fn set<T: IntoDatum>(&mut self, attno: usize, value: T) {
+        self.check_attno(attno);
+
+        let idx = attno - 1;
+        let datum = value.into_datum();
+        unsafe {
+            *self.slot.tts_values.add(idx) = datum.unwrap_or(0);
+            *self.slot.tts_isnull.add(idx) = datum.is_none();
+        }
+    }
+
+    /// Clear the slot's contents, releasing whatever tuple it might hold
+    pub fn clear(&mut self) {
+        unsafe {
+            clear_slot(self.slot.as_ptr());
+        }
+    }
+
+    /// Mark the values set via `::set()` as the slot's (virtual) tuple
+    pub fn store_virtual(&mut self) {
+        unsafe {
+            pg_sys::ExecStoreVirtualTuple(self.slot.as_ptr());
+        }
+    }
+
+    /// Force the slot to hold a local copy of its tuple that no longer depends on any external
+    /// storage such as a buffer or pass-by-reference values set by the caller
+    pub fn materialize(&mut self) {
+        unsafe {
+            materialize_slot(self.slot.as_ptr());
+        }
+    }
+
+    fn check_attno(&self, attno: usize) {
+        if attno < 1 || attno > self.natts() {
+            panic!(
+                "attribute number {} is out of bounds for a TupleTableSlot with {} attributes",
+                attno,
+                self.natts()
+            );
+        }
+    }
+
+    /// `idx` is 0-based
+    unsafe fn atttypid(&self, idx: usize) -> pg_sys::Oid {
+        PgTupleDesc::from_pg_unchecked(self.slot.tts_tupleDescriptor)
+            .get(idx)
+            .expect("no attribute")
+            .atttypid
+    }
+}
+
+impl Drop for PgTupleTableSlot {
+    fn drop(&mut self) {
+        if self.need_drop {
+            unsafe { pg_sys::ExecDropSingleTupleTableSlot(self.slot.as_ptr()) }
+        }
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+fn make_virtual_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    unsafe { pg_sys::MakeSingleTupleTableSlot(tupdesc) }
+}
+
+#[cfg(feature = "pg12")]
+fn make_virtual_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    unsafe { pg_sys::MakeSingleTupleTableSlot(tupdesc, &pg_sys::TTSOpsVirtual) }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+fn slot_is_empty(slot: &PgBox<pg_sys::TupleTableSlot>) -> bool {
+    slot.tts_isempty
+}
+
+#[cfg(feature = "pg12")]
+fn slot_is_empty(slot: &PgBox<pg_sys::TupleTableSlot>) -> bool {
+    slot.tts_flags as u32 & pg_sys::TTS_FLAG_EMPTY != 0
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn getsomeattrs(slot: *mut pg_sys::TupleTableSlot, attnum: i32) {
+    pg_sys::slot_getsomeattrs(slot, attnum)
+}
+
+/// mimics the static inline `slot_getsomeattrs()` from tuptable.h
+#[cfg(feature = "pg12")]
+unsafe fn getsomeattrs(slot: *mut pg_sys::TupleTableSlot, attnum: i32) {
+    if ((*slot).tts_nvalid as i32) < attnum {
+        pg_sys::slot_getsomeattrs_int(slot, attnum)
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn clear_slot(slot: *mut pg_sys::TupleTableSlot) {
+    pg_sys::ExecClearTuple(slot);
+}
+
+/// mimics the static inline `ExecClearTuple()` from tuptable.h
+#[cfg(feature = "pg12")]
+unsafe fn clear_slot(slot: *mut pg_sys::TupleTableSlot) {
+    let clear = (*(*slot).tts_ops)
+        .clear
+        .expect("TupleTableSlotOps has no clear function");
+    clear(slot);
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn materialize_slot(slot: *mut pg_sys::TupleTableSlot) {
+    pg_sys::ExecMaterializeSlot(slot);
+}
+
+/// mimics the static inline `ExecMaterializeSlot()` from tuptable.h
+#[cfg(feature = "pg12")]
+unsafe fn materialize_slot(slot: *mut pg_sys::TupleTableSlot) {
+    let materialize = (*(*slot).tts_ops)
+        .materialize
+        .expect("TupleTableSlotOps has no materialize function");
+    materialize(slot);
+}
@@ -0,0 +1,151 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for set-returning functions that return their results in "materialize" mode
+use crate::{error, is_a, pg_sys, PgBox, PgHeapTuple, PgMemoryContexts, PgTupleDesc};
+
+/// A Postgres `Tuplestorestate` that a `Materialize` function fills with its result rows
+pub struct PgTupleStore {
+    store: *mut pg_sys::Tuplestorestate,
+    tupdesc: PgTupleDesc<'static>,
+}
+
+impl PgTupleStore {
+    /// The tuple descriptor each row must match.  For functions declared `RETURNS SETOF record`
+    /// this comes from the column definition list in the calling query.
+    pub fn tupdesc(&self) -> &PgTupleDesc<'static> {
+        &self.tupdesc
+    }
+
+    /// Add a row built from a datum (or NULL) per attribute
+    ///
+    /// ## Panics
+    ///
+    /// If the number of values doesn't match the number of attributes in `::tupdesc()`
+    pub fn put_values(&mut self, values: Vec<Option<pg_sys::Datum>>) {
+        if values.len() != self.tupdesc.len() {
+            panic!(
+                "expected {} values for a row, but got {}",
+                self.tupdesc.len(),
+                values.len()
+            );
+        }
+
+        let mut nulls = values.iter().map(|v| v.is_none()).collect::<Vec<_>>();
+        let mut datums = values
+            .into_iter()
+            .map(|v| v.unwrap_or(0))
+            .collect::<Vec<_>>();
+        unsafe {
+            pg_sys::tuplestore_putvalues(
+                self.store,
+                self.tupdesc.as_ptr(),
+                datums.as_mut_ptr(),
+                nulls.as_mut_ptr(),
+            )
+        }
+    }
+
+    /// Add a row that is already a `PgHeapTuple`.  The tuple is copied into the tuplestore.
+    ///
+    /// ## Panics
+    ///
+    /// If the tuple's number of attributes doesn't match the number of attributes in `::tupdesc()`
+    pub fn put_tuple(&mut self, tuple: &PgHeapTuple) {
+        if tuple.len() != self.tupdesc.len() {
+            panic!(
+                "expected a tuple with {} attributes, but got {}",
+                self.tupdesc.len(),
+                tuple.len()
+            );
+        }
+
+        unsafe { pg_sys::tuplestore_puttuple(self.store, tuple.as_ptr()) }
+    }
+}
+
+/// The return type for a `#[pg_extern]` set-returning function that builds its whole result at
+/// once, in a tuplestore, rather than returning one row per call like an `impl Iterator` does.
+///
+/// The function is declared as `RETURNS SETOF record`, so callers must provide a column
+/// definition list.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn squares(n: i32) -> Materialize {
+///     Materialize::new(move |store| {
+///         for i in 1..=n {
+///             store.put_values(vec![i.into_datum(), (i * i).into_datum()]);
+///         }
+///     })
+/// }
+/// ```
+///
+/// ```sql
+/// SELECT * FROM squares(10) AS t(i int, square int);
+/// ```
+pub struct Materialize(Box<dyn FnOnce(&mut PgTupleStore)>);
+
+impl Materialize {
+    /// `f` is called with the tuplestore to fill once the function returns
+    pub fn new<F: FnOnce(&mut PgTupleStore) + 'static>(f: F) -> Self {
+        Materialize(Box::new(f))
+    }
+
+    /// Fill a new tuplestore, allocated in the query's per-query memory context, and hand it to
+    /// Postgres via the function's `pg_sys::ReturnSetInfo`.
+    ///
+    /// This is called by the code `#[pg_extern]` generates for functions returning `Materialize`.
+    pub fn materialize(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+        let fcinfo = PgBox::from_pg(fcinfo);
+        let rsinfo = fcinfo.resultinfo as *mut pg_sys::ReturnSetInfo;
+
+        if !is_a(rsinfo as *mut pg_sys::Node, pg_sys::NodeTag_T_ReturnSetInfo) {
+            error!("set-valued function called in context that cannot accept a set");
+        }
+
+        let mut rsinfo = PgBox::from_pg(rsinfo);
+        if rsinfo.allowedModes & pg_sys::SetFunctionReturnMode_SFRM_Materialize as i32 == 0 {
+            error!("materialize mode required, but it is not allowed in this context");
+        }
+
+        let random_access =
+            rsinfo.allowedModes & pg_sys::SetFunctionReturnMode_SFRM_Materialize_Random as i32 != 0;
+        let per_query_memory = unsafe { (*rsinfo.econtext).ecxt_per_query_memory };
+        let (store, tupdesc) = PgMemoryContexts::For(per_query_memory).switch_to(|_| unsafe {
+            let mut tupdesc: pg_sys::TupleDesc = std::ptr::null_mut();
+            if pg_sys::get_call_result_type(fcinfo.as_ptr(), std::ptr::null_mut(), &mut tupdesc)
+                != pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE
+            {
+                // it's a `SETOF record` function, so use the caller's column definition list
+                if rsinfo.expectedDesc.is_null() {
+                    error!(
+                        "a column definition list is required for functions returning \"record\""
+                    );
+                }
+                tupdesc = pg_sys::CreateTupleDescCopy(rsinfo.expectedDesc);
+            }
+
+            (
+                pg_sys::tuplestore_begin_heap(random_access, false, pg_sys::work_mem),
+                tupdesc,
+            )
+        });
+
+        let mut tuplestore = PgTupleStore {
+            store,
+            tupdesc: unsafe { PgTupleDesc::from_pg_unchecked(tupdesc) },
+        };
+        (self.0)(&mut tuplestore);
+
+        rsinfo.returnMode = pg_sys::SetFunctionReturnMode_SFRM_Materialize;
+        rsinfo.setResult = store;
+        rsinfo.setDesc = tupdesc;
+
+        0 as pg_sys::Datum
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Access to Postgres' type cache, which remembers the storage details, default operators, and
+//! support functions of each type used by the backend
+use crate::{pg_sys, PgTupleDesc};
+
+bitflags! {
+    /// The optional parts of a `TypeCacheEntry` that `lookup()` should fill in, mirroring the
+    /// `TYPECACHE_xxx` flags from typcache.h
+    pub struct TypeCacheFlags: i32 {
+        const EQ_OPR                   = pg_sys::TYPECACHE_EQ_OPR as i32;
+        const LT_OPR                   = pg_sys::TYPECACHE_LT_OPR as i32;
+        const GT_OPR                   = pg_sys::TYPECACHE_GT_OPR as i32;
+        const CMP_PROC                 = pg_sys::TYPECACHE_CMP_PROC as i32;
+        const HASH_PROC                = pg_sys::TYPECACHE_HASH_PROC as i32;
+        const EQ_OPR_FINFO             = pg_sys::TYPECACHE_EQ_OPR_FINFO as i32;
+        const CMP_PROC_FINFO           = pg_sys::TYPECACHE_CMP_PROC_FINFO as i32;
+        const HASH_PROC_FINFO          = pg_sys::TYPECACHE_HASH_PROC_FINFO as i32;
+        const TUPDESC                  = pg_sys::TYPECACHE_TUPDESC as i32;
+        const BTREE_OPFAMILY           = pg_sys::TYPECACHE_BTREE_OPFAMILY as i32;
+        const HASH_OPFAMILY            = pg_sys::TYPECACHE_HASH_OPFAMILY as i32;
+        const RANGE_INFO               = pg_sys::TYPECACHE_RANGE_INFO as i32;
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        const HASH_EXTENDED_PROC       = pg_sys::TYPECACHE_HASH_EXTENDED_PROC as i32;
+        #[cfg(any(feature = "pg11", feature = "pg12"))]
+        const HASH_EXTENDED_PROC_FINFO = pg_sys::TYPECACHE_HASH_EXTENDED_PROC_FINFO as i32;
+    }
+}
+
+/// Look up a type in the type cache, filling in the parts of its entry requested by `flags`.
+///
+/// Entries live for the life of the backend, although the optional parts are recomputed after
+/// catalog changes invalidate them, so the returned entry is only current until the next
+/// `lookup()` of the same type.  The storage details (`typlen`, `typbyval`, etc) are always
+/// available.  Raises an ERROR if the type doesn't exist.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use pgx::typecache::TypeCacheFlags;
+///
+/// let entry = pgx::typecache::lookup(pg_sys::INT4OID, TypeCacheFlags::HASH_PROC_FINFO);
+/// let finfo = entry.hash_proc_finfo().expect("int4 is not hashable");
+/// let hash = unsafe {
+///     pg_sys::FunctionCall1Coll(
+///         finfo as *const _ as *mut _,
+///         pg_sys::InvalidOid,
+///         42i32.into_datum().unwrap(),
+///     )
+/// };
+/// ```
+pub fn lookup(type_oid: pg_sys::Oid, flags: TypeCacheFlags) -> &'static TypeCacheEntry {
+    unsafe {
+        let entry = pg_sys::lookup_type_cache(type_oid, flags.bits());
+        &*(entry as *const TypeCacheEntry)
+    }
+}
+
+/// A type's entry in the type cache, as returned by `lookup()`.
+///
+/// It dereferences to the underlying `pg_sys::TypeCacheEntry` for anything without an accessor.
+#[repr(transparent)]
+pub struct TypeCacheEntry(pg_sys::TypeCacheEntry);
+
+impl TypeCacheEntry {
+    pub fn type_oid(&self) -> pg_sys::Oid {
+        self.0.type_id
+    }
+
+    /// The type's length in bytes, or -1 for varlena types and -2 for cstrings
+    pub fn typlen(&self) -> i16 {
+        self.0.typlen
+    }
+
+    pub fn typbyval(&self) -> bool {
+        self.0.typbyval
+    }
+
+    /// One of `b'c'`, `b's'`, `b'i'`, or `b'd'`, for char, short, int, or double alignment
+    pub fn typalign(&self) -> u8 {
+        self.0.typalign as u8
+    }
+
+    /// One of `b'p'`, `b'e'`, `b'm'`, or `b'x'`, for plain, external, main, or extended storage
+    pub fn typstorage(&self) -> u8 {
+        self.0.typstorage as u8
+    }
+
+    /// One of `b'b'`, `b'c'`, `b'd'`, `b'e'`, `b'p'`, or `b'r'`, for a base, composite, domain,
+    /// enum, pseudo, or range type
+    pub fn typtype(&self) -> u8 {
+        self.0.typtype as u8
+    }
+
+    /// The default equality operator, if requested with `TypeCacheFlags::EQ_OPR` and the type
+    /// has one
+    pub fn eq_opr(&self) -> Option<pg_sys::Oid> {
+        valid_oid(self.0.eq_opr)
+    }
+
+    /// The default less-than operator, if requested with `TypeCacheFlags::LT_OPR` and the type
+    /// has one
+    pub fn lt_opr(&self) -> Option<pg_sys::Oid> {
+        valid_oid(self.0.lt_opr)
+    }
+
+    /// The default greater-than operator, if requested with `TypeCacheFlags::GT_OPR` and the
+    /// type has one
+    pub fn gt_opr(&self) -> Option<pg_sys::Oid> {
+        valid_oid(self.0.gt_opr)
+    }
+
+    /// The default btree comparison function, if requested with `TypeCacheFlags::CMP_PROC` and
+    /// the type has one
+    pub fn cmp_proc(&self) -> Option<pg_sys::Oid> {
+        valid_oid(self.0.cmp_proc)
+    }
+
+    /// The default hash function, if requested with `TypeCacheFlags::HASH_PROC` and the type
+    /// has one
+    pub fn hash_proc(&self) -> Option<pg_sys::Oid> {
+        valid_oid(self.0.hash_proc)
+    }
+
+    /// The equality operator's underlying function, ready to call, if requested with
+    /// `TypeCacheFlags::EQ_OPR_FINFO` and the type has one
+    pub fn eq_opr_finfo(&self) -> Option<&pg_sys::FmgrInfo> {
+        valid_finfo(&self.0.eq_opr_finfo)
+    }
+
+    /// The btree comparison function, ready to call, if requested with
+    /// `TypeCacheFlags::CMP_PROC_FINFO` and the type has one
+    pub fn cmp_proc_finfo(&self) -> Option<&pg_sys::FmgrInfo> {
+        valid_finfo(&self.0.cmp_proc_finfo)
+    }
+
+    /// The hash function, ready to call, if requested with `TypeCacheFlags::HASH_PROC_FINFO`
+    /// and the type has one
+    pub fn hash_proc_finfo(&self) -> Option<&pg_sys::FmgrInfo> {
+        valid_finfo(&self.0.hash_proc_finfo)
+    }
+
+    /// A composite type's `TupleDesc`, if requested with `TypeCacheFlags::TUPDESC`.
+    ///
+    /// The `TupleDesc` is pinned until the returned `PgTupleDesc` is dropped, so it remains valid
+    /// even if the type is altered in the meantime.
+    pub fn tuple_desc(&self) -> Option<PgTupleDesc<'static>> {
+        let tupdesc = self.0.tupDesc;
+        if tupdesc.is_null() {
+            None
+        } else {
+            unsafe {
+                pg_sys::IncrTupleDescRefCount(tupdesc);
+                Some(PgTupleDesc::from_pg(tupdesc))
+            }
+        }
+    }
+}
+
+impl std::ops::Deref for TypeCacheEntry {
+    type Target = pg_sys::TypeCacheEntry;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+fn valid_oid(oid: pg_sys::Oid) -> Option<pg_sys::Oid> {
+    if oid == pg_sys::InvalidOid {
+        None
+    } else {
+        Some(oid)
+    }
+}
+
+fn valid_finfo(finfo: &pg_sys::FmgrInfo) -> Option<&pg_sys::FmgrInfo> {
+    if finfo.fn_oid == pg_sys::InvalidOid {
+        None
+    } else {
+        Some(finfo)
+    }
+}
@@ -3,7 +3,8 @@
 
 //! Helper functions to work with Postgres `varlena *` structures
 
-use crate::{pg_sys, PgBox};
+use crate::{pg_sys, void_mut_ptr, FromDatum, PgBox};
+use std::ops::Deref;
 
 pub unsafe fn set_varsize(ptr: *mut pg_sys::varlena, len: i32) {
     extern "C" {
@@ -270,6 +271,22 @@ pub unsafe fn varlena_size(t: *const pg_sys::varlena) -> usize {
     std::mem::size_of_val(&(*t).vl_len_) + varsize_any_exhdr(t)
 }
 
+/// Convert a Postgres `varlena *` (or `text *`) into a Rust `&str`, returning an error if it
+/// isn't valid UTF-8, such as when the database's encoding is `SQL_ASCII`.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided varlena pointer is non-null.
+///
+/// Note also that this function is zero-copy and the underlying Rust &str is backed by Postgres-allocated
+/// memory.  As such, the return value will become invalid the moment Postgres frees the varlena
+#[inline]
+pub unsafe fn text_to_rust_str<'a>(
+    varlena: *const pg_sys::varlena,
+) -> Result<&'a str, std::str::Utf8Error> {
+    std::str::from_utf8(varlena_to_byte_slice(varlena))
+}
+
 /// Convert a Postgres `varlena *` (or `text *`) into a Rust `&str`.
 ///
 /// ## Safety
@@ -325,3 +342,85 @@ pub fn rust_byte_slice_to_bytea(slice: &[u8]) -> PgBox<pg_sys::bytea> {
         pg_sys::cstring_to_text_with_len(ptr as *const std::os::raw::c_char, len as i32)
     })
 }
+
+/// A detoasted Postgres varlena, viewed as the `#[repr(C)]` type `T` whose first field is the
+/// varlena header.
+///
+/// The datum is detoasted (decompressed and/or fetched out-of-line) on construction.  If that
+/// required a fresh allocation, the copy is `pfree()`'d when this instance is dropped, so hot
+/// paths don't leak one detoasted copy per call.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[repr(C)]
+/// struct MyVarlenaType {
+///     vl_len_: i32,
+///     value: i64,
+/// }
+///
+/// #[pg_extern]
+/// fn my_value(input: PgVarlena<MyVarlenaType>) -> i64 {
+///     input.value
+/// }
+/// ```
+pub struct PgVarlena<T> {
+    detoasted: *mut T,
+    original: *mut pg_sys::varlena,
+}
+
+impl<T> PgVarlena<T> {
+    /// Detoast the provided varlena datum
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure the datum is a non-null varlena whose contents
+    /// are laid out as a `T`
+    pub unsafe fn detoast(datum: pg_sys::Datum) -> Self {
+        let original = datum as *mut pg_sys::varlena;
+        PgVarlena {
+            detoasted: pg_sys::pg_detoast_datum(original) as *mut T,
+            original,
+        }
+    }
+
+    /// The detoasted varlena pointer
+    pub fn as_ptr(&self) -> *mut T {
+        self.detoasted
+    }
+
+    /// Was detoasting required to allocate a copy of the original datum?
+    pub fn is_copy(&self) -> bool {
+        self.detoasted as *mut pg_sys::varlena != self.original
+    }
+}
+
+impl<T> Deref for PgVarlena<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.detoasted }
+    }
+}
+
+impl<T> Drop for PgVarlena<T> {
+    fn drop(&mut self) {
+        if self.is_copy() {
+            unsafe { pg_sys::pfree(self.detoasted as void_mut_ptr) }
+        }
+    }
+}
+
+impl<T> FromDatum for PgVarlena<T> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a varlena Datum was flagged as non-null but the datum is zero");
+        } else {
+            Some(PgVarlena::detoast(datum))
+        }
+    }
+}
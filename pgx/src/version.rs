@@ -0,0 +1,47 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! The version of Postgres an extension was compiled for, and the one it's running in.
+//!
+//! Prefer these to `#[cfg(feature = "pg12")]` when code only needs to behave differently across
+//! versions.  Bindings whose signatures changed between versions, such as
+//! `pg_sys::CreateTemplateTupleDesc()`, are declared in `pg_sys` with the same signature for
+//! every version, so calling them doesn't need a `cfg` either.
+use crate::pg_sys;
+use std::ffi::CStr;
+
+/// The `PG_VERSION_NUM` of the Postgres headers this extension was compiled against, such as
+/// `120003` for Postgres 12.3
+pub const PG_VERSION_NUM: u32 = pg_sys::PG_VERSION_NUM;
+
+/// The major version of Postgres this extension was compiled against, such as `12`
+pub const PG_MAJOR_VERSION: u32 = PG_VERSION_NUM / 10000;
+
+/// The version of the running Postgres server, in the same format as `PG_VERSION_NUM`.
+///
+/// It has the same major version as `PG_VERSION_NUM`, but may be of another minor release.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// if pg_version() < 120004 {
+///     warning!("upgrade to Postgres 12.4 or later to avoid a known bug");
+/// }
+/// ```
+pub fn pg_version() -> u32 {
+    let version = unsafe {
+        CStr::from_ptr(pg_sys::GetConfigOption(
+            "server_version_num\0".as_ptr() as *const std::os::raw::c_char,
+            false,
+            false,
+        ))
+    };
+
+    version
+        .to_str()
+        .ok()
+        .and_then(|version| version.parse().ok())
+        .expect("server_version_num is not a number")
+}
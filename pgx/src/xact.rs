@@ -0,0 +1,88 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helpers for working with Postgres (sub)transactions
+
+use crate::pg_sys;
+
+/// Run `f` inside an internal subtransaction that is always rolled back once `f` returns, undoing
+/// any changes `f` made to the database.
+///
+/// `f` runs in the caller's `CurrentMemoryContext`, so its return value survives the rollback.
+/// If `f` raises an ERROR (or panics), it's propagated as usual and the enclosing transaction's
+/// abort also discards the subtransaction.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let count = in_rolled_back_subtransaction(|| {
+///     Spi::run("CREATE TABLE scratch AS SELECT generate_series(1, 10) x");
+///     Spi::get_one::<i64>("SELECT count(*) FROM scratch")
+/// });
+/// assert_eq!(count, Some(10));
+/// ```
+pub fn in_rolled_back_subtransaction<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _subxact = RolledBackSubtransaction::begin();
+    f()
+}
+
+/// An internal subtransaction that is rolled back when dropped, undoing any changes made to the
+/// database while it was in scope.
+///
+/// Unlike `in_rolled_back_subtransaction()`, this doesn't need a closure, so `return` and `?`
+/// in the guarded code behave as usual.  This is what backs `#[pg_test(transactional)]`.
+///
+/// Code runs in the `CurrentMemoryContext` that was current at `::begin()`, so values it
+/// allocates survive the rollback.  If it raises an ERROR (or panics), the subtransaction isn't
+/// rolled back here, and the enclosing transaction's abort discards it instead.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// fn count_scratch() -> Option<i64> {
+///     let _subxact = RolledBackSubtransaction::begin();
+///     Spi::run("CREATE TABLE scratch AS SELECT generate_series(1, 10) x");
+///     Spi::get_one::<i64>("SELECT count(*) FROM scratch")
+/// }
+/// ```
+pub struct RolledBackSubtransaction {
+    memcxt: pg_sys::MemoryContext,
+    owner: pg_sys::ResourceOwner,
+}
+
+impl RolledBackSubtransaction {
+    pub fn begin() -> Self {
+        let (memcxt, owner) =
+            unsafe { (pg_sys::CurrentMemoryContext, pg_sys::CurrentResourceOwner) };
+
+        unsafe {
+            pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+
+            // BeginInternalSubTransaction() leaves us in the subtransaction's memory context
+            pg_sys::CurrentMemoryContext = memcxt;
+        }
+
+        RolledBackSubtransaction { memcxt, owner }
+    }
+}
+
+impl Drop for RolledBackSubtransaction {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+
+        unsafe {
+            pg_sys::RollbackAndReleaseCurrentSubTransaction();
+            pg_sys::CurrentMemoryContext = self.memcxt;
+            pg_sys::CurrentResourceOwner = self.owner;
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Write-ahead logging of changes to relation pages, so extensions with their own storage can be
+//! crash-safe and replicated
+use crate::{pg_sys, Lsn, PgRelation, StringInfo};
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+
+/// WAL-logs changes to up to `pg_sys::MAX_GENERIC_XLOG_PAGES` pages of a relation using Postgres'
+/// generic WAL records, which replay without the extension being loaded.
+///
+/// Register each buffer, which must already be pinned and exclusively locked, and modify the
+/// working copy of its page that's returned rather than the page itself.  `::finish()` then
+/// applies the changes to the buffers, marks them dirty, and writes a WAL record of the
+/// differences.  If dropped before then, the changes are discarded.
+///
+/// Nothing is logged for a relation that doesn't need WAL, such as a temporary or unlogged one,
+/// although the changes are still applied.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// # let relation: PgRelation = unimplemented!();
+/// unsafe {
+///     let buffer = pg_sys::ReadBuffer(relation.as_ptr(), 0);
+///     pg_sys::LockBuffer(buffer, pg_sys::BUFFER_LOCK_EXCLUSIVE as i32);
+///
+///     let mut xlog = GenericXLog::start(&relation);
+///     let page = xlog.register_buffer(buffer, false);
+///     // ... modify `page` ...
+///     let lsn = xlog.finish();
+///
+///     pg_sys::UnlockReleaseBuffer(buffer);
+/// }
+/// ```
+pub struct GenericXLog<'a> {
+    state: *mut pg_sys::GenericXLogState,
+    _relation: PhantomData<&'a PgRelation>,
+}
+
+impl<'a> GenericXLog<'a> {
+    pub fn start(relation: &'a PgRelation) -> Self {
+        GenericXLog {
+            state: unsafe { pg_sys::GenericXLogStart(relation.as_ptr()) },
+            _relation: PhantomData,
+        }
+    }
+
+    /// Register a buffer to be modified, returning a working copy of its page to modify in its
+    /// place.  The copy is valid until this `GenericXLog` is finished or dropped.
+    ///
+    /// When `full_image` is true, the whole page is logged rather than just the changes to it,
+    /// which is required when the page is being initialized.
+    ///
+    /// ## Safety
+    ///
+    /// The buffer must belong to the relation, and the caller must hold a pin and an exclusive
+    /// lock on it until after `::finish()`
+    pub unsafe fn register_buffer(
+        &mut self,
+        buffer: pg_sys::Buffer,
+        full_image: bool,
+    ) -> pg_sys::Page {
+        let flags = if full_image {
+            pg_sys::GENERIC_XLOG_FULL_IMAGE as c_int
+        } else {
+            0
+        };
+        pg_sys::GenericXLogRegisterBuffer(self.state, buffer, flags)
+    }
+
+    /// Apply the changes to the registered buffers, mark them dirty, and WAL-log them, returning
+    /// the position of the WAL record, which is invalid if the relation doesn't need WAL
+    pub fn finish(mut self) -> Lsn {
+        let state = std::mem::replace(&mut self.state, std::ptr::null_mut());
+        Lsn(unsafe { pg_sys::GenericXLogFinish(state) })
+    }
+}
+
+impl Drop for GenericXLog<'_> {
+    fn drop(&mut self) {
+        if !self.state.is_null() {
+            unsafe { pg_sys::GenericXLogAbort(self.state) }
+        }
+    }
+}
+
+/// Builds and inserts a WAL record for one of Postgres' own resource managers, via
+/// `XLogBeginInsert()`, `XLogRegisterData()` and friends, and `XLogInsert()`.
+///
+/// Only one record can be under construction at a time, and the registered data must remain valid
+/// until it's inserted.  If dropped without being inserted, the record is discarded.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let payload = b"hello";
+/// let lsn = unsafe {
+///     XLogRecordBuilder::begin()
+///         .register_data(payload)
+///         .insert(pg_sys::RmgrIds_RM_XLOG_ID as pg_sys::RmgrId, pg_sys::XLOG_NOOP as u8)
+/// };
+/// ```
+pub struct XLogRecordBuilder<'a> {
+    inserted: bool,
+    _data: PhantomData<&'a [u8]>,
+}
+
+impl<'a> XLogRecordBuilder<'a> {
+    pub fn begin() -> Self {
+        unsafe { pg_sys::XLogBeginInsert() }
+        XLogRecordBuilder {
+            inserted: false,
+            _data: PhantomData,
+        }
+    }
+
+    /// Add data to the record's main data
+    pub fn register_data(self, data: &'a [u8]) -> Self {
+        unsafe { pg_sys::XLogRegisterData(data.as_ptr() as *mut c_char, data.len() as c_int) }
+        self
+    }
+
+    /// Add a buffer to the record as `block_id`, with `pg_sys::REGBUF_xxx` `flags`
+    ///
+    /// ## Safety
+    ///
+    /// The caller must hold a pin and an exclusive lock on the buffer, and have already modified
+    /// it and marked it dirty, all within a critical section
+    pub unsafe fn register_buffer(self, block_id: u8, buffer: pg_sys::Buffer, flags: u8) -> Self {
+        pg_sys::XLogRegisterBuffer(block_id, buffer, flags);
+        self
+    }
+
+    /// Add data associated with the buffer registered as `block_id`
+    pub fn register_buffer_data(self, block_id: u8, data: &'a [u8]) -> Self {
+        unsafe {
+            pg_sys::XLogRegisterBufData(block_id, data.as_ptr() as *mut c_char, data.len() as c_int)
+        }
+        self
+    }
+
+    /// Insert the record, returning the position of its end
+    ///
+    /// ## Safety
+    ///
+    /// The record is replayed by the resource manager `rmid` during recovery and on replicas, so
+    /// it must be a record that resource manager understands
+    pub unsafe fn insert(mut self, rmid: pg_sys::RmgrId, info: u8) -> Lsn {
+        self.inserted = true;
+        Lsn(pg_sys::XLogInsert(rmid, info))
+    }
+}
+
+impl Drop for XLogRecordBuilder<'_> {
+    fn drop(&mut self) {
+        if !self.inserted {
+            unsafe { pg_sys::XLogResetInsertion() }
+        }
+    }
+}
+
+/// A WAL resource manager, which replays and describes its own kind of WAL records, such as
+/// those inserted with `XLogRecordBuilder::insert()` using its id.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+/// use std::ffi::CStr;
+///
+/// struct MyRmgr;
+///
+/// impl CustomRmgr for MyRmgr {
+///     const NAME: &'static str = "my_rmgr";
+///
+///     fn redo(record: &XLogRecordRef) {
+///         // apply `record.data()` to the pages of `record.info()`'s kind of change
+///     }
+///
+///     fn desc(buf: &mut StringInfo, record: &XLogRecordRef) {
+///         buf.push_str(&format!("{} bytes", record.data().len()));
+///     }
+///
+///     fn identify(info: u8) -> Option<&'static CStr> {
+///         match info & !(pg_sys::XLR_INFO_MASK as u8) {
+///             0x00 => Some(CStr::from_bytes_with_nul(b"INSERT\0").unwrap()),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait CustomRmgr {
+    /// The resource manager's name, as shown by `pg_waldump`
+    const NAME: &'static str;
+
+    /// Replay a record during crash recovery or on a replica
+    fn redo(record: &XLogRecordRef);
+
+    /// Describe a record's contents, for `pg_waldump` and WAL debugging output
+    fn desc(buf: &mut StringInfo, record: &XLogRecordRef);
+
+    /// Name the kind of record with the `info` bits, such as `INSERT`, or `None` if unknown
+    fn identify(info: u8) -> Option<&'static CStr>;
+}
+
+/// A WAL record being replayed or described by a `CustomRmgr`
+pub struct XLogRecordRef<'a> {
+    reader: &'a pg_sys::XLogReaderState,
+}
+
+impl<'a> XLogRecordRef<'a> {
+    /// ## Safety
+    ///
+    /// The reader must have a decoded record, as it does when resource manager callbacks are
+    /// called
+    pub unsafe fn from_pg(reader: *mut pg_sys::XLogReaderState) -> Self {
+        XLogRecordRef {
+            reader: reader.as_ref().expect("XLogReaderState is NULL"),
+        }
+    }
+
+    /// The record's `info` bits, including the low `pg_sys::XLR_INFO_MASK` bits reserved for
+    /// Postgres
+    pub fn info(&self) -> u8 {
+        unsafe { (*self.reader.decoded_record).xl_info }
+    }
+
+    /// The transaction that inserted the record
+    pub fn xid(&self) -> pg_sys::TransactionId {
+        unsafe { (*self.reader.decoded_record).xl_xid }
+    }
+
+    /// The position of the record's start
+    pub fn lsn(&self) -> Lsn {
+        Lsn(self.reader.ReadRecPtr)
+    }
+
+    /// The position of the record's end
+    pub fn end_lsn(&self) -> Lsn {
+        Lsn(self.reader.EndRecPtr)
+    }
+
+    /// The record's main data, as registered with `XLogRecordBuilder::register_data()`
+    pub fn data(&self) -> &'a [u8] {
+        if self.reader.main_data.is_null() {
+            &[]
+        } else {
+            unsafe {
+                std::slice::from_raw_parts(
+                    self.reader.main_data as *const u8,
+                    self.reader.main_data_len as usize,
+                )
+            }
+        }
+    }
+}
+
+/// The C callbacks for a `CustomRmgr`, which raise any panic as an ERROR, in the shape of
+/// Postgres' `RmgrData` functions, such as `clog_redo()`, `clog_desc()` and `clog_identify()`
+pub struct RmgrCallbacks {
+    pub redo: unsafe extern "C" fn(record: *mut pg_sys::XLogReaderState),
+    pub desc: unsafe extern "C" fn(buf: pg_sys::StringInfo, record: *mut pg_sys::XLogReaderState),
+    pub identify: unsafe extern "C" fn(info: u8) -> *const c_char,
+}
+
+impl RmgrCallbacks {
+    pub fn of<R: CustomRmgr>() -> Self {
+        RmgrCallbacks {
+            redo: rmgr_redo::<R>,
+            desc: rmgr_desc::<R>,
+            identify: rmgr_identify::<R>,
+        }
+    }
+}
+
+/// The first resource manager id Postgres reserves for custom resource managers
+pub const RM_MIN_CUSTOM_ID: pg_sys::RmgrId = 128;
+
+/// Register a `CustomRmgr` as the resource manager for records with the id `rmid`, from
+/// `_PG_init()` while `shared_preload_libraries` are being loaded, returning whether it was
+/// registered.
+///
+/// Custom resource managers require Postgres 15 or later, which pgx doesn't yet support, so for
+/// now this does nothing and returns `false`.  Records must not be inserted with `rmid` unless it
+/// returns `true`, as they couldn't be replayed.  `RmgrCallbacks::of()` provides the callbacks
+/// for when it does.
+///
+/// ## Panics
+///
+/// If `rmid` is less than `RM_MIN_CUSTOM_ID`, as those ids belong to Postgres' own resource
+/// managers
+pub fn register_custom_rmgr<R: CustomRmgr>(rmid: pg_sys::RmgrId) -> bool {
+    if rmid < RM_MIN_CUSTOM_ID {
+        panic!(
+            "cannot register WAL resource manager \"{}\" with id {}: custom resource manager ids start at {}",
+            R::NAME,
+            rmid,
+            RM_MIN_CUSTOM_ID
+        );
+    }
+
+    false
+}
+
+unsafe extern "C" fn rmgr_redo<R: CustomRmgr>(record: *mut pg_sys::XLogReaderState) {
+    crate::guard(|| R::redo(&XLogRecordRef::from_pg(record)))
+}
+
+unsafe extern "C" fn rmgr_desc<R: CustomRmgr>(
+    buf: pg_sys::StringInfo,
+    record: *mut pg_sys::XLogReaderState,
+) {
+    crate::guard(|| {
+        let mut buf = StringInfo::from_pg(buf).expect("StringInfo is NULL");
+        R::desc(&mut buf, &XLogRecordRef::from_pg(record))
+    })
+}
+
+unsafe extern "C" fn rmgr_identify<R: CustomRmgr>(info: u8) -> *const c_char {
+    crate::guard(|| R::identify(info).map_or(std::ptr::null(), |name| name.as_ptr()))
+}